@@ -0,0 +1,652 @@
+//! The audit module checks every test implementation's `config.toml` for the
+//! problems which would otherwise only surface once its tests are run: parse
+//! errors and missing fields, missing dockerfiles, and settings which refer to
+//! test types the test does not have.
+//!
+//! It also measures the source tree of each framework, the context uploaded
+//! to the Docker daemon by every build of its images, flagging those grown
+//! beyond `MAX_SOURCE_BYTES` (typically by accidentally committed binaries).
+//!
+//! Finally, it scores how completely each framework is documented for its
+//! maintainers; an incomplete framework is not a problem, merely reported.
+//!
+//! Every finding is also written to `audit.json`, by framework, with the id of
+//! the rule it breaks; ids are dotted by category (e.g. `test.dockerfile`), so
+//! that CI can gate on the categories it cares about.
+
+use crate::config::{normalize_name, Framework, Named, Test};
+use crate::error::ToolsetResult;
+use crate::io::Logger;
+use crate::{config, io, metadata};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// The size beyond which a framework's source tree is a problem.
+const MAX_SOURCE_BYTES: u64 = 50 * 1024 * 1024;
+/// How many of the largest files of each source tree are recorded.
+const LARGEST_FILES: usize = 5;
+
+/// A problem found with the test implementation configured by `config_file`.
+#[derive(Clone, Debug)]
+pub struct Problem {
+    pub config_file: PathBuf,
+    /// The id of the rule broken; see `Finding`.
+    pub rule: &'static str,
+    /// The test with the problem, unless it is with the whole framework.
+    pub test: Option<String>,
+    pub description: String,
+}
+
+/// A problem (an error) or a lack of completeness (a warning) found with a
+/// framework, as written to `audit.json`.
+#[derive(Serialize, Clone, Debug)]
+pub struct Finding {
+    pub severity: &'static str,
+    /// The id of the rule broken, e.g. `test.dockerfile`; its prefix up to
+    /// the first `.` is its category (`config`, `framework`, or `test`).
+    pub rule: &'static str,
+    pub message: String,
+    /// The file with the finding, relative to the FrameworkBenchmarks dir.
+    pub path: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub test: Option<String>,
+}
+
+/// The size of the source tree of a framework, with its largest files.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceSize {
+    pub framework_dir: PathBuf,
+    pub bytes: u64,
+    /// The largest files, by path relative to `framework_dir`, and size.
+    pub largest_files: Vec<(PathBuf, u64)>,
+}
+
+/// How completely a framework is documented: whether it has a README, its
+/// config declares `github` and `authors`, and each `versus` of its tests
+/// names a framework (or test) which exists.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Completeness {
+    pub framework_dir: PathBuf,
+    /// The percentage of the checks passed.
+    pub score: u32,
+    /// What the framework lacks, by check failed.
+    pub missing: Vec<String>,
+}
+
+/// Audits every test implementation, logging the problems found with each;
+/// returns the number of problems found.
+///
+/// The size of every framework's source tree is written to the
+/// `source_sizes.json` of a new results directory, so that their growth can
+/// be tracked across audits, the completeness of each framework to its
+/// `completeness.json`, and every finding to its `audit.json`.
+pub fn run() -> ToolsetResult<usize> {
+    let logger = Logger::default();
+    let tfb_dir = io::get_tfb_dir()?;
+    let mut source_sizes = measure_source_sizes()?;
+    for source_size in &mut source_sizes {
+        if let Ok(framework_dir) = source_size.framework_dir.strip_prefix(&tfb_dir) {
+            source_size.framework_dir = framework_dir.to_path_buf();
+        }
+    }
+    let mut problems = audit_all_config_files()?;
+    problems.append(&mut audit_source_sizes(&source_sizes, &tfb_dir));
+    problems.sort_by(|a, b| a.config_file.cmp(&b.config_file));
+    let mut previous = None;
+    for problem in &problems {
+        let config_file = problem
+            .config_file
+            .strip_prefix(&tfb_dir)
+            .unwrap_or(&problem.config_file);
+        if previous != Some(config_file) {
+            logger.log(config_file.display())?;
+            previous = Some(config_file);
+        }
+        logger.error(format!("  {}", problem.description))?;
+    }
+    let results_dir = PathBuf::from(io::create_results_dir()?);
+    let sizes_file = results_dir.join("source_sizes.json");
+    std::fs::write(&sizes_file, serde_json::to_vec_pretty(&source_sizes)?)?;
+    logger.log(format!(
+        "Measured {} framework source trees, {} MB in all; see {}",
+        source_sizes.len(),
+        source_sizes.iter().map(|size| size.bytes).sum::<u64>() / (1024 * 1024),
+        sizes_file.display()
+    ))?;
+    let mut completeness = measure_completeness()?;
+    for framework in &mut completeness {
+        if let Ok(framework_dir) = framework.framework_dir.strip_prefix(&tfb_dir) {
+            framework.framework_dir = framework_dir.to_path_buf();
+        }
+        if framework.score < 100 {
+            logger.log(format!(
+                "{} is {}% complete; it lacks {}",
+                framework.framework_dir.display(),
+                framework.score,
+                framework.missing.join(", ")
+            ))?;
+        }
+    }
+    let completeness_file = results_dir.join("completeness.json");
+    std::fs::write(
+        &completeness_file,
+        serde_json::to_vec_pretty(&completeness)?,
+    )?;
+    logger.log(format!(
+        "Scored the completeness of {} frameworks, {}% on average; see {}",
+        completeness.len(),
+        completeness
+            .iter()
+            .map(|framework| framework.score)
+            .sum::<u32>()
+            / completeness.len().max(1) as u32,
+        completeness_file.display()
+    ))?;
+    let audit_file = results_dir.join("audit.json");
+    std::fs::write(
+        &audit_file,
+        serde_json::to_vec_pretty(&get_findings(&problems, &completeness, &tfb_dir))?,
+    )?;
+    logger.log(format!("Wrote every finding to {}", audit_file.display()))?;
+    if problems.is_empty() {
+        logger.log("Audit found no problems.")?;
+    } else {
+        logger.error(format!("Audit found {} problem(s).", problems.len()))?;
+    }
+
+    Ok(problems.len())
+}
+
+/// Audits the `config.toml` of every test implementation, along with the
+/// framework names they declare between them.
+pub fn audit_all_config_files() -> ToolsetResult<Vec<Problem>> {
+    let mut problems = Vec::new();
+    let mut config_files_by_framework: HashMap<String, Vec<PathBuf>> = HashMap::new();
+    for config_file in metadata::list_config_files()? {
+        problems.append(&mut audit_config_file(&config_file));
+        if let Ok(framework) = config::get_framework_by_config_file(&config_file) {
+            config_files_by_framework
+                .entry(framework.get_normalized_name())
+                .or_default()
+                .push(config_file);
+        }
+    }
+
+    // Tests are named (and their images tagged) after their framework, so
+    // two frameworks of the same name would clobber one another.
+    for (framework, config_files) in config_files_by_framework {
+        if config_files.len() > 1 {
+            for config_file in &config_files {
+                problems.push(Problem {
+                    config_file: config_file.clone(),
+                    rule: "framework.name.duplicate",
+                    test: None,
+                    description: format!(
+                        "framework name \"{}\" is declared by {} config files",
+                        framework,
+                        config_files.len()
+                    ),
+                });
+            }
+        }
+    }
+    problems.sort_by(|a, b| a.config_file.cmp(&b.config_file));
+
+    Ok(problems)
+}
+
+/// Measures the source tree of every framework with a `config.toml`, in the
+/// order of their directories.
+pub fn measure_source_sizes() -> ToolsetResult<Vec<SourceSize>> {
+    let mut source_sizes = Vec::new();
+    for config_file in metadata::list_config_files()? {
+        let framework_dir = config_file.parent().unwrap();
+        let mut files = Vec::new();
+        list_files(framework_dir, &mut files)?;
+        files.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        source_sizes.push(SourceSize {
+            framework_dir: framework_dir.to_path_buf(),
+            bytes: files.iter().map(|(_, bytes)| bytes).sum(),
+            largest_files: files
+                .into_iter()
+                .take(LARGEST_FILES)
+                .map(|(file, bytes)| {
+                    let file = file.strip_prefix(framework_dir).unwrap_or(&file);
+                    (file.to_path_buf(), bytes)
+                })
+                .collect(),
+        });
+    }
+
+    Ok(source_sizes)
+}
+
+/// Scores the completeness of every framework, in the order of their
+/// directories.
+pub fn measure_completeness() -> ToolsetResult<Vec<Completeness>> {
+    let tfb_dir = io::get_tfb_dir()?;
+    let projects = metadata::list_all_projects()?;
+    let mut names = HashSet::new();
+    for project in &projects {
+        names.insert(project.framework.get_normalized_name());
+        names.extend(project.tests.iter().map(|test| test.get_normalized_name()));
+    }
+    let mut completeness: Vec<Completeness> = projects
+        .iter()
+        .map(|project| {
+            let framework_dir = tfb_dir
+                .join("frameworks")
+                .join(&project.language)
+                .join(&project.name);
+            get_completeness(&framework_dir, &project.framework, &project.tests, &names)
+        })
+        .collect();
+    completeness.sort_by(|a, b| a.framework_dir.cmp(&b.framework_dir));
+
+    Ok(completeness)
+}
+
+//
+// PRIVATES
+//
+
+/// Scores the completeness of the given `framework`, whose `tests` are in
+/// `framework_dir`; `names` are those of every framework and test, which
+/// `versus` may name.
+fn get_completeness(
+    framework_dir: &Path,
+    framework: &Framework,
+    tests: &[Test],
+    names: &HashSet<String>,
+) -> Completeness {
+    let mut checks = vec![
+        (has_readme(framework_dir), "a README".to_string()),
+        (framework.github.is_some(), "github".to_string()),
+        (
+            matches!(&framework.authors, Some(authors) if !authors.is_empty()),
+            "authors".to_string(),
+        ),
+    ];
+    let mut versus: Vec<&String> = tests.iter().map(|test| &test.versus).collect();
+    versus.sort();
+    versus.dedup();
+    for versus in versus {
+        if versus.is_empty() {
+            checks.push((false, "a versus".to_string()));
+        } else {
+            checks.push((
+                names.contains(&normalize_name(versus)),
+                format!("a framework named by versus \"{}\"", versus),
+            ));
+        }
+    }
+    let passed = checks.iter().filter(|(passed, _)| *passed).count();
+
+    Completeness {
+        framework_dir: framework_dir.to_path_buf(),
+        score: (passed * 100 / checks.len()) as u32,
+        missing: checks
+            .into_iter()
+            .filter(|(passed, _)| !passed)
+            .map(|(_, check)| check)
+            .collect(),
+    }
+}
+
+/// Returns whether `dir` contains a README (of any extension).
+fn has_readme(dir: &Path) -> bool {
+    match std::fs::read_dir(dir) {
+        Ok(entries) => entries.filter_map(Result::ok).any(|entry| {
+            entry.path().is_file()
+                && entry
+                    .file_name()
+                    .to_string_lossy()
+                    .to_lowercase()
+                    .starts_with("readme")
+        }),
+        Err(_) => false,
+    }
+}
+
+/// Lists each file beneath `dir`, with its size, into `files`; symbolic
+/// links are not followed.
+fn list_files(dir: &Path, files: &mut Vec<(PathBuf, u64)>) -> ToolsetResult<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            list_files(&entry.path(), files)?;
+        } else if file_type.is_file() {
+            files.push((entry.path(), entry.metadata()?.len()));
+        }
+    }
+
+    Ok(())
+}
+
+/// Gets a problem with the `config.toml` of each framework in `tfb_dir` whose
+/// source tree is larger than `MAX_SOURCE_BYTES`, naming its largest files.
+fn audit_source_sizes(source_sizes: &[SourceSize], tfb_dir: &Path) -> Vec<Problem> {
+    source_sizes
+        .iter()
+        .filter(|source_size| source_size.bytes > MAX_SOURCE_BYTES)
+        .map(|source_size| {
+            let largest_files: Vec<String> = source_size
+                .largest_files
+                .iter()
+                .map(|(file, bytes)| format!("{} ({} KB)", file.display(), bytes / 1024))
+                .collect();
+            Problem {
+                config_file: tfb_dir.join(&source_size.framework_dir).join("config.toml"),
+                rule: "framework.source_size",
+                test: None,
+                description: format!(
+                    "source tree is {:.1} MB, more than {} MB; its largest files are {}",
+                    source_size.bytes as f64 / (1024 * 1024) as f64,
+                    MAX_SOURCE_BYTES / (1024 * 1024),
+                    largest_files.join(", ")
+                ),
+            }
+        })
+        .collect()
+}
+
+/// Gets the findings of the given `problems` and `completeness`, by the
+/// framework directory, relative to `tfb_dir`, in which they were found.
+fn get_findings(
+    problems: &[Problem],
+    completeness: &[Completeness],
+    tfb_dir: &Path,
+) -> BTreeMap<PathBuf, Vec<Finding>> {
+    let mut findings: BTreeMap<PathBuf, Vec<Finding>> = BTreeMap::new();
+    for problem in problems {
+        let path = problem
+            .config_file
+            .strip_prefix(tfb_dir)
+            .unwrap_or(&problem.config_file);
+        findings
+            .entry(path.parent().unwrap_or(path).to_path_buf())
+            .or_default()
+            .push(Finding {
+                severity: "error",
+                rule: problem.rule,
+                message: problem.description.clone(),
+                path: path.to_path_buf(),
+                test: problem.test.clone(),
+            });
+    }
+    for framework in completeness {
+        for missing in &framework.missing {
+            findings
+                .entry(framework.framework_dir.clone())
+                .or_default()
+                .push(Finding {
+                    severity: "warning",
+                    rule: "framework.completeness",
+                    message: format!("lacks {}", missing),
+                    path: framework.framework_dir.clone(),
+                    test: None,
+                });
+        }
+    }
+
+    findings
+}
+
+/// Audits the given `config_file`, returning each problem.
+fn audit_config_file(config_file: &PathBuf) -> Vec<Problem> {
+    let problem = |rule, test, description| Problem {
+        config_file: config_file.clone(),
+        rule,
+        test,
+        description,
+    };
+    let framework = match config::get_framework_by_config_file(config_file) {
+        Ok(framework) => framework,
+        Err(error) => return vec![problem("config.parse", None, error.to_string())],
+    };
+    let mut problems = Vec::new();
+    let framework_dir = config_file.parent().unwrap();
+    if let Some(dir_name) = framework_dir.file_name() {
+        if dir_name.to_string_lossy() != framework.get_normalized_name() {
+            problems.push(problem(
+                "framework.name.directory",
+                None,
+                format!(
+                    "framework name \"{}\" does not match its directory, \"{}\"",
+                    framework.name,
+                    dir_name.to_string_lossy()
+                ),
+            ));
+        }
+    }
+    match config::get_test_implementations_by_config_file(config_file) {
+        Ok(tests) => {
+            for test in &tests {
+                for (rule, description) in audit_test(test, framework_dir) {
+                    problems.push(problem(rule, Some(test.get_name()), description));
+                }
+            }
+        }
+        Err(error) => problems.push(problem("config.parse", None, error.to_string())),
+    }
+
+    problems
+}
+
+/// Audits the given `test`, whose dockerfile should be in `framework_dir`,
+/// returning the rule broken and a description of each problem.
+fn audit_test(test: &Test, framework_dir: &Path) -> Vec<(&'static str, String)> {
+    let name = test.get_name();
+    let mut problems = Vec::new();
+    if test.urls.is_empty() {
+        problems.push(("test.urls", format!("{} has no urls", name)));
+    }
+    let mut test_types: Vec<&String> = test.urls.keys().collect();
+    test_types.sort();
+    for test_type in test_types {
+        if !test.urls[test_type].starts_with('/') {
+            problems.push((
+                "test.urls",
+                format!(
+                    "{} has a {} url which does not begin with \"/\"",
+                    name, test_type
+                ),
+            ));
+        }
+    }
+    let dockerfile = test.get_dockerfile();
+    if !framework_dir.join(&dockerfile).is_file() {
+        problems.push((
+            "test.dockerfile",
+            format!("{} has no dockerfile, {}", name, dockerfile),
+        ));
+    }
+    if test.database.is_some() && test.database_os.is_none() {
+        problems.push((
+            "test.database_os",
+            format!("{} has a database but no database_os", name),
+        ));
+    }
+
+    let mut settings: Vec<(&str, Vec<&String>)> = Vec::new();
+    if let Some(custom) = &test.custom {
+        settings.push(("custom", custom.iter().collect()));
+    }
+    if let Some(protocols) = &test.protocols {
+        settings.push(("protocols", protocols.keys().collect()));
+    }
+    if let Some(command_overrides) = &test.command_overrides {
+        settings.push(("command_overrides", command_overrides.keys().collect()));
+    }
+    for (setting, mut test_types) in settings {
+        test_types.sort();
+        for test_type in test_types {
+            if !test.urls.contains_key(test_type) {
+                problems.push((
+                    "test.settings",
+                    format!(
+                        "{} lists {} in {}, but has no url for it",
+                        name, test_type, setting
+                    ),
+                ));
+            }
+        }
+    }
+
+    problems
+}
+
+//
+// TESTS
+//
+
+#[cfg(test)]
+mod tests {
+    use crate::audit::{
+        audit_all_config_files, audit_source_sizes, audit_test, get_findings, measure_completeness,
+        measure_source_sizes, Completeness, Problem, SourceSize, LARGEST_FILES, MAX_SOURCE_BYTES,
+    };
+    use crate::config::{Named, Protocol};
+    use crate::metadata::list_tests_for_framework;
+    use std::collections::HashMap;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn it_finds_no_problems_with_valid_configs() {
+        match audit_all_config_files() {
+            Ok(problems) => assert!(problems.is_empty(), "{:?}", problems),
+            Err(e) => panic!("audit_all_config_files failed. error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn it_can_find_problems_with_a_test() {
+        let mut test = list_tests_for_framework("gemini")
+            .unwrap()
+            .into_iter()
+            .find(|test| test.get_name() == "gemini")
+            .unwrap();
+        test.dockerfile = Some("missing.dockerfile".to_string());
+        test.urls.insert("custom".to_string(), "custom".to_string());
+        test.custom = Some(vec!["custom".to_string(), "other".to_string()]);
+        let mut protocols = HashMap::new();
+        protocols.insert("json".to_string(), Protocol::Http);
+        test.protocols = Some(protocols);
+
+        let problems = audit_test(&test, Path::new("frameworks/Java/gemini"));
+
+        assert_eq!(
+            problems,
+            vec![
+                (
+                    "test.urls",
+                    "gemini has a custom url which does not begin with \"/\"".to_string()
+                ),
+                (
+                    "test.dockerfile",
+                    "gemini has no dockerfile, missing.dockerfile".to_string()
+                ),
+                (
+                    "test.settings",
+                    "gemini lists other in custom, but has no url for it".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_can_flag_oversized_source_trees() {
+        let gemini = measure_source_sizes()
+            .unwrap()
+            .into_iter()
+            .find(|source_size| source_size.framework_dir.ends_with("Java/gemini"))
+            .unwrap();
+        assert!(gemini.bytes > 0);
+        assert!(gemini.largest_files.len() <= LARGEST_FILES);
+        assert!(gemini
+            .largest_files
+            .windows(2)
+            .all(|files| files[0].1 >= files[1].1));
+
+        let oversized = SourceSize {
+            framework_dir: PathBuf::from("frameworks/Java/gemini"),
+            bytes: MAX_SOURCE_BYTES + 10 * 1024 * 1024,
+            largest_files: vec![(PathBuf::from("lib/app.jar"), 40 * 1024 * 1024)],
+        };
+        let problems = audit_source_sizes(&[gemini, oversized], Path::new("/tfb"));
+
+        assert_eq!(problems.len(), 1);
+        assert_eq!(
+            problems[0].config_file,
+            PathBuf::from("/tfb/frameworks/Java/gemini/config.toml")
+        );
+        assert_eq!(
+            problems[0].description,
+            "source tree is 60.0 MB, more than 50 MB; its largest files are lib/app.jar (40960 KB)"
+        );
+    }
+
+    #[test]
+    fn it_can_score_framework_completeness() {
+        let completeness = measure_completeness().unwrap();
+        let get = |framework_dir: &str| {
+            completeness
+                .iter()
+                .find(|framework| framework.framework_dir.ends_with(framework_dir))
+                .unwrap()
+        };
+
+        let gemini = get("Java/gemini");
+        assert_eq!(gemini.score, 75);
+        assert_eq!(
+            gemini.missing,
+            vec!["a framework named by versus \"servlet\""]
+        );
+        let nodejs = get("JavaScript/nodejs");
+        assert_eq!(nodejs.score, 50);
+        assert_eq!(nodejs.missing, vec!["github", "authors"]);
+        let actix = get("Rust/actix");
+        assert_eq!(actix.score, 25);
+        assert_eq!(actix.missing, vec!["github", "authors", "a versus"]);
+    }
+
+    #[test]
+    fn it_can_map_findings_by_framework() {
+        let problems = vec![Problem {
+            config_file: PathBuf::from("/tfb/frameworks/Java/gemini/config.toml"),
+            rule: "test.dockerfile",
+            test: Some("gemini-mysql".to_string()),
+            description: "gemini-mysql has no dockerfile, gemini-mysql.dockerfile".to_string(),
+        }];
+        let completeness = vec![Completeness {
+            framework_dir: PathBuf::from("frameworks/Rust/actix"),
+            score: 67,
+            missing: vec!["github".to_string()],
+        }];
+
+        let findings = get_findings(&problems, &completeness, Path::new("/tfb"));
+
+        assert_eq!(
+            serde_json::to_value(&findings).unwrap(),
+            serde_json::json!({
+                "frameworks/Java/gemini": [{
+                    "severity": "error",
+                    "rule": "test.dockerfile",
+                    "message": "gemini-mysql has no dockerfile, gemini-mysql.dockerfile",
+                    "path": "frameworks/Java/gemini/config.toml",
+                    "test": "gemini-mysql",
+                }],
+                "frameworks/Rust/actix": [{
+                    "severity": "warning",
+                    "rule": "framework.completeness",
+                    "message": "lacks github",
+                    "path": "frameworks/Rust/actix",
+                }],
+            })
+        );
+    }
+}
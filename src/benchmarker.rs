@@ -1,34 +1,60 @@
 use crate::benchmarker::modes::CICD;
-use crate::config::{Framework, Named, Project, Test};
+use crate::config::{Named, Project, Protocol, Test};
+use crate::docker::api::DockerApi;
+use crate::docker::connections::ConnectionSampler;
 use crate::docker::container::{
-    block_until_database_is_ready, create_benchmarker_container, create_container,
-    create_database_verifier_container, create_verifier_container, get_port_bindings_for_container,
-    start_benchmark_command_retrieval_container, start_benchmarker_container, start_container,
-    start_verification_container, stop_docker_container_future,
+    block_until_database_is_ready, configure_core_pattern, create_benchmarker_container,
+    create_container, create_database_verifier_container, create_verifier_container,
+    force_remove_docker_container_future, get_core_dump_mount, get_database_data_dir,
+    get_load_generator_image, get_port_bindings_for_container, get_source_mount, get_tmpfs_mount,
+    remove_container, restore_core_pattern, start_benchmark_command_retrieval_container,
+    start_benchmarker_container, start_container, start_verification_container,
+    stop_docker_container_future,
 };
+use crate::docker::daemon::check_daemons;
 use crate::docker::docker_config::DockerConfig;
-use crate::docker::image::{build_image, pull_image};
+use crate::docker::events::{ContainerDeath, ContainerEvents};
+use crate::docker::image::{build_image, pull_image, BuildPipeline};
 use crate::docker::listener::benchmarker::BenchmarkResults;
 use crate::docker::listener::simple::Simple;
-use crate::docker::listener::verifier::Error;
-use crate::docker::network::connect_container_to_network;
+use crate::docker::listener::verifier::{Error, Warning};
+use crate::docker::network::{
+    connect_container_to_network, create_isolated_network, get_isolated_network_name,
+    get_network_id, get_tfb_network_id, remove_network, shape_container_network,
+};
+use crate::docker::network_check::check_network;
+use crate::docker::preflight::check_server_is_reachable;
+use crate::docker::profile::Profiler;
+use crate::docker::queries::{appears_cached, count_statements, get_expected_queries_per_request};
+use crate::docker::stats::StatsCollector;
 use crate::docker::{
-    BenchmarkCommands, DockerContainerIdFuture, DockerOrchestration, Verification,
+    BenchmarkCommands, DockerContainerIdFuture, DockerOrchestration, Topology, Verification,
 };
 use crate::error::ToolsetError::{
-    AppServerContainerShutDownError, DebugFailedException, NoResponseFromDockerContainerError,
+    AppServerContainerShutDownError, ContainerDiedError, DebugFailedException,
+    FailedBenchmarkCommandRetrievalError, NoResponseFromDockerContainerError, PortConflictError,
     VerificationFailedException,
 };
 use crate::error::{ToolsetError, ToolsetResult};
-use crate::io::{report_verifications, Logger};
-use crate::results::{BenchmarkData, Results};
+use crate::io::{report_verifications, upload_results, LogPath, Logger};
+use crate::metadata::interleave_projects;
+use crate::metrics::{get_metric_segment, Metrics};
+use crate::report::write_report;
+use crate::results::{
+    Baseline, BenchmarkData, Calibration, ContainerIncidents, Failure, Results, SocketErrorSummary,
+    TunedData, TuningProbe, STATUS_FAILED, STATUS_SUCCEEDED,
+};
+use crate::status::{phases, Status};
+use crate::trace::Tracer;
+use chrono::Local;
 use colored::Colorize;
 use curl::easy::Easy2;
-use dockurl::container::inspect_container;
-use std::collections::HashMap;
+use dockurl::network::NetworkMode::{Bridge, Host};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use std::{thread, time};
 
 pub mod modes {
@@ -36,13 +62,50 @@ pub mod modes {
     pub const VERIFY: &str = "verify";
     pub const CICD: &str = "cicd";
     pub const DEBUG: &str = "debug";
+    pub const TUNE: &str = "tune";
 }
 
+/// The relative improvement in throughput required to keep doubling the
+/// concurrency while tuning.
+const TUNING_TOLERANCE: f32 = 0.02;
+/// The maximum number of concurrency levels measured while tuning a single
+/// test type.
+const MAX_TUNING_PROBES: usize = 12;
+/// The test types which do not exercise the database; connections are not
+/// sampled while benchmarking them.
+const DATABASE_FREE_TEST_TYPES: [&str; 2] = ["json", "plaintext"];
+/// The reference server benchmarked by `--calibrate`.
+const CALIBRATION_IMAGE: &str = "nginx:alpine";
+/// The port on which `CALIBRATION_IMAGE` serves its static index page.
+const CALIBRATION_PORT: u16 = 80;
+/// The most `wrk` threads used by the calibration command.
+const CALIBRATION_THREADS: u32 = 8;
+/// The drift in percent from the first baseline of a run (see
+/// `--baseline-interval`) beyond which a baseline is reported as an error.
+const BASELINE_DRIFT_TOLERANCE: f32 = 10f32;
+
 pub enum Mode {
     Verify,
     Benchmark,
 }
 
+/// A network created for a single test by `--isolate-networks`, along with
+/// the network ids it temporarily replaced.
+struct IsolatedNetwork {
+    id: String,
+    server_network_id: String,
+    database_network_id: String,
+    client_network_id: String,
+}
+
+/// The database host, Docker host, and network id temporarily replaced by
+/// `relocate_database` for a test whose database was given its own host.
+struct RelocatedDatabase<'a> {
+    database_host: &'a str,
+    database_docker_host: String,
+    database_network_id: String,
+}
+
 /// Benchmarker supports three different functions which all perform the
 /// underlying Docker orchestration of getting a `Test` implementation running
 /// in a Container and accepting requests on their exposed port. The three
@@ -57,6 +120,9 @@ pub enum Mode {
 ///              if the verification of the `URL` passes, runs the
 ///              `TFBBenchmarker` against it, captures the results, parses
 ///              them, and writes them to the results file.
+/// 4. `tune` - like `benchmark`, but rather than sweeping the configured
+///              concurrency levels, searches for the concurrency at which the
+///              `Test` reaches its peak throughput.
 #[derive(Debug)]
 pub struct Benchmarker<'a> {
     docker_config: DockerConfig<'a>,
@@ -65,7 +131,25 @@ pub struct Benchmarker<'a> {
     database_container_id: Arc<Mutex<DockerContainerIdFuture>>,
     verifier_container_id: Arc<Mutex<DockerContainerIdFuture>>,
     benchmarker_container_id: Arc<Mutex<DockerContainerIdFuture>>,
+    container_events: ContainerEvents,
+    run_uuid: String,
+    tracer: Tracer,
+    metrics: Metrics,
     ctrlc_received: Arc<AtomicBool>,
+    // The server host's `core_pattern` replaced for `--core-dumps`, which is
+    // put back when the run ends.
+    core_pattern: Arc<Mutex<Option<String>>>,
+    // When the current test started; only core dumps since are attributed
+    // to its failures.
+    test_started_at: SystemTime,
+    // The database whose connections are sampled during each benchmark
+    // command with `--sample-connections`; set per test type.
+    sampled_database: Option<String>,
+    // Whether the test type being benchmarked queries the database, whose
+    // container's stats are then collected during each benchmark command.
+    benchmarking_database: bool,
+    build_pipeline: BuildPipeline,
+    status: Status,
 }
 
 impl<'a> Benchmarker<'a> {
@@ -83,6 +167,36 @@ impl<'a> Benchmarker<'a> {
             &docker_config.client_docker_host,
         )));
 
+        let container_events = ContainerEvents::default();
+        container_events.subscribe(
+            &docker_config.server_docker_host,
+            docker_config.use_unix_socket,
+        );
+        container_events.subscribe(
+            &docker_config.database_docker_host,
+            docker_config.use_unix_socket,
+        );
+
+        let run_uuid = docker_config.run_uuid.clone();
+        let mut tracer = Tracer::new(docker_config.otlp_endpoint, "run");
+        tracer.set_attribute("mode", mode);
+        tracer.set_attribute("results.name", docker_config.results_name);
+        tracer.set_attribute("run.uuid", run_uuid.as_str());
+        let metrics = Metrics::new(
+            docker_config.statsd_address,
+            docker_config.statsd_prefix,
+            &docker_config.logger,
+        );
+        let build_pipeline = BuildPipeline::new(docker_config.build_jobs);
+
+        let mut docker_config = docker_config;
+        docker_config.image_prefix = docker_config.image_prefix.replace("{run}", &run_uuid);
+        docker_config.manifest.set_projects(&projects);
+        docker_config
+            .logger
+            .write_manifest(&docker_config.manifest)
+            .unwrap();
+
         let benchmarker = Self {
             docker_config,
             projects,
@@ -90,24 +204,63 @@ impl<'a> Benchmarker<'a> {
             database_container_id,
             verifier_container_id,
             benchmarker_container_id,
+            container_events,
+            run_uuid,
+            tracer,
+            metrics,
             ctrlc_received: Arc::new(AtomicBool::new(false)),
+            core_pattern: Arc::new(Mutex::new(None)),
+            test_started_at: SystemTime::now(),
+            sampled_database: None,
+            benchmarking_database: false,
+            build_pipeline,
+            status: Status::new(mode),
         };
 
+        if let Some(pause_file) = &benchmarker.docker_config.pause_file {
+            benchmarker
+                .docker_config
+                .logger
+                .log(format!(
+                    "To pause after the current test, create {}",
+                    pause_file.to_string_lossy()
+                ))
+                .unwrap();
+        }
         if mode != CICD {
-            let use_unix_socket = benchmarker.docker_config.use_unix_socket;
+            let docker_api = Arc::clone(&benchmarker.docker_config.docker_api);
             let docker_cleanup = benchmarker.docker_config.clean_up;
             let application_container_id = Arc::clone(&benchmarker.application_container_id);
             let database_container_id = Arc::clone(&benchmarker.database_container_id);
             let verifier_container_id = Arc::clone(&benchmarker.verifier_container_id);
             let benchmarker_container_id = Arc::clone(&benchmarker.benchmarker_container_id);
             let ctrlc_received = Arc::clone(&benchmarker.ctrlc_received);
+            let core_pattern = Arc::clone(&benchmarker.core_pattern);
+            let server_docker_host = benchmarker.docker_config.server_docker_host.clone();
+            let logger = benchmarker.docker_config.logger.clone();
             ctrlc::set_handler(move || {
-                let logger = Logger::default();
+                let logger = logger.clone();
                 logger.log("Shutting down (may take a moment)").unwrap();
                 if ctrlc_received.load(Ordering::Acquire) {
-                    logger
-                        .log("Exiting immediately (there may still be running containers to stop)")
-                        .unwrap();
+                    logger.log("Force removing containers and exiting").unwrap();
+                    for container_id in &[
+                        &verifier_container_id,
+                        &benchmarker_container_id,
+                        &application_container_id,
+                        &database_container_id,
+                    ] {
+                        force_remove_docker_container_future(
+                            docker_api.as_ref(),
+                            container_id,
+                            &logger,
+                        );
+                    }
+                    put_back_core_pattern(
+                        docker_api.as_ref(),
+                        &server_docker_host,
+                        &core_pattern,
+                        &logger,
+                    );
                     std::process::exit(0);
                 } else {
                     let application_container_id = Arc::clone(&application_container_id);
@@ -115,33 +268,47 @@ impl<'a> Benchmarker<'a> {
                     let verifier_container_id = Arc::clone(&verifier_container_id);
                     let benchmarker_container_id = Arc::clone(&benchmarker_container_id);
                     let ctrlc_received = Arc::clone(&ctrlc_received);
+                    let core_pattern = Arc::clone(&core_pattern);
+                    let server_docker_host = server_docker_host.clone();
+                    let logger = logger.clone();
+                    let docker_api = Arc::clone(&docker_api);
                     thread::spawn(move || {
                         ctrlc_received.store(true, Ordering::Release);
                         stop_docker_container_future(
-                            use_unix_socket,
+                            docker_api.as_ref(),
                             docker_cleanup,
                             &verifier_container_id,
+                            &logger,
                         );
                         stop_docker_container_future(
-                            use_unix_socket,
+                            docker_api.as_ref(),
                             docker_cleanup,
                             &benchmarker_container_id,
+                            &logger,
                         );
                         stop_docker_container_future(
-                            use_unix_socket,
+                            docker_api.as_ref(),
                             docker_cleanup,
                             &application_container_id,
+                            &logger,
                         );
                         stop_docker_container_future(
-                            use_unix_socket,
+                            docker_api.as_ref(),
                             docker_cleanup,
                             &database_container_id,
+                            &logger,
+                        );
+                        put_back_core_pattern(
+                            docker_api.as_ref(),
+                            &server_docker_host,
+                            &core_pattern,
+                            &logger,
                         );
                         std::process::exit(0);
                     });
                 }
             })
-                .unwrap();
+            .unwrap();
         }
 
         benchmarker
@@ -154,54 +321,305 @@ impl<'a> Benchmarker<'a> {
     /// benchmarking completes, the results are parsed and stored in the
     /// results directory for this benchmark.
     pub fn benchmark(&mut self) -> ToolsetResult<()> {
-        let mut benchmark_results = Results::new(&self.docker_config)?;
+        let mut benchmark_results = match &self.docker_config.resume {
+            Some(results_dir) => Results::load(results_dir)?,
+            None => Results::new(&self.docker_config)?,
+        };
         let logger = self.docker_config.logger.clone();
-        logger.log("Pulling verifier; this may take some time.")?;
-        // todo - how should we version this?
-        pull_image(
-            &self.docker_config,
-            &self.docker_config.client_docker_host,
-            "techempower/tfb.verifier",
-        )?;
-        let projects = &self.projects.clone();
+        self.pull_verifier(&logger)?;
+        self.prepare_core_dumps(&logger)?;
+        benchmark_results.daemons = check_daemons(&self.docker_config, &logger)?;
+        // Multi-machine setups communicate with Docker over TCP.
+        if !self.docker_config.use_unix_socket {
+            benchmark_results.network_checks = check_network(&self.docker_config, &logger)?;
+            logger.write_results(&benchmark_results)?;
+        }
+        if self.docker_config.calibrate {
+            benchmark_results.calibration = Some(self.calibrate(&logger)?);
+            logger.write_results(&benchmark_results)?;
+        }
+        let projects = &if self.docker_config.interleave {
+            interleave_projects(&self.projects)
+        } else {
+            self.projects.clone()
+        };
+        let projects = &skip_completed_projects(projects, &benchmark_results, &logger)?;
+        let mut protocols = HashSet::new();
+        for project in projects {
+            for test in &project.tests {
+                for test_type in test.urls.keys() {
+                    protocols.insert(test.get_protocol(test_type));
+                }
+            }
+        }
+        let mut image_names: HashSet<&str> = protocols
+            .into_iter()
+            .map(|protocol| get_load_generator_image(&self.docker_config, protocol))
+            .collect();
+        // The verifier, which doubles as the wrk load generator, is pulled
+        // above.
+        image_names.remove("techempower/tfb.verifier");
+        for image_name in image_names {
+            logger.log(format!("Pulling {}; this may take some time.", image_name))?;
+            pull_image(
+                &self.docker_config,
+                &self.docker_config.client_docker_host,
+                image_name,
+            )?;
+        }
+        // The tests in the order they are run, from which `--build-jobs`
+        // builds ahead.
+        let queue: Vec<(&Project, &Test)> = projects
+            .iter()
+            .flat_map(|project| project.tests.iter().map(move |test| (project, test)))
+            .collect();
+        self.status.tests_total = queue.len();
+        logger.write_status(&self.status)?;
+        let mut tests_run = 0;
         for project in projects {
             for test in &project.tests {
                 let mut logger = logger.clone();
                 logger.set_test(test);
                 self.trip();
+                self.wait_while_paused(&logger)?;
+                self.wait_for_schedule(&logger)?;
+                if let Some(baseline_interval) = self.docker_config.baseline_interval {
+                    if tests_run % baseline_interval == 0 {
+                        self.measure_baseline(&mut benchmark_results, tests_run, &logger)?;
+                    }
+                }
+                self.status.start_test(test);
+                logger.write_status(&self.status)?;
+                self.build_pipeline
+                    .prebuild(&self.docker_config, &queue[tests_run as usize + 1..])?;
+                let isolated_network = self.isolate_network(test, &logger)?;
+                let relocated_database = self.relocate_database(test, &logger)?;
+                self.tracer.start_span("test");
+                self.tracer
+                    .set_attribute("framework", project.framework.get_name());
+                self.tracer.set_attribute("test", test.get_name());
                 match self.start_test_orchestration(project, test, &logger) {
-                    Ok(orchestration) => {
-                        for test_type in &test.urls {
+                    Ok(mut orchestration) => {
+                        // The application container is replaced when restarted.
+                        let mut container_ids = vec![orchestration.host_container_id.clone()];
+                        container_ids.extend(orchestration.db_container_id.clone());
+                        for (index, test_type) in test.urls.iter().enumerate() {
                             logger.log(format!("Benchmarking: {}", test_type.0))?;
-                            match self.run_benchmarks(&orchestration, &test_type, &logger) {
-                                Ok(results) => self.report_benchmark_success(
-                                    &mut benchmark_results,
-                                    results,
-                                    &project.framework,
-                                    test_type.0,
-                                    &logger,
-                                ),
-                                Err(e) => self.report_benchmark_error(
-                                    &mut benchmark_results,
-                                    &test,
-                                    test_type.0,
-                                    &e,
-                                    &logger,
-                                ),
+                            self.status
+                                .set_phase(phases::BENCHMARKING, Some(test_type.0));
+                            logger.write_status(&self.status)?;
+                            self.tracer.start_span("benchmark");
+                            self.tracer.set_attribute("test.type", test_type.0.as_str());
+                            let started = Instant::now();
+                            let restarted = if index > 0 && self.docker_config.restart_between_types
+                            {
+                                logger.log("Restarting application server")?;
+                                self.restart_application(&orchestration, test, &logger)
+                                    .map(|restarted| orchestration = restarted)
+                            } else {
+                                Ok(())
+                            };
+                            let results = restarted.and_then(|_| {
+                                self.run_benchmarks(&mut orchestration, test, &test_type, &logger)
+                            });
+                            if !container_ids.contains(&orchestration.host_container_id) {
+                                container_ids.push(orchestration.host_container_id.clone());
+                            }
+                            self.metrics.timing("phase.benchmark", started.elapsed());
+                            if let Ok((warmups, results)) = &results {
+                                self.tracer.set_attribute("warmups", *warmups);
+                                let rps = results
+                                    .iter()
+                                    .map(|result| result.requests_per_second)
+                                    .fold(0f32, f32::max);
+                                self.tracer.set_attribute("rps", rps);
+                            }
+                            self.tracer.end_span_with(&results);
+                            self.status.complete_test_type(results.is_err());
+                            match results {
+                                Ok((warmups, results)) => {
+                                    if self.docker_config.warmup_tolerance.is_some() {
+                                        benchmark_results
+                                            .warmups
+                                            .entry(test_type.0.to_string())
+                                            .or_default()
+                                            .insert(
+                                                project.framework.get_normalized_name(),
+                                                warmups,
+                                            );
+                                    }
+                                    self.report_benchmark_success(
+                                        &mut benchmark_results,
+                                        results,
+                                        project,
+                                        test,
+                                        test_type.0,
+                                        &logger,
+                                    )
+                                }
+                                Err(e) => {
+                                    logger.error(&e)?;
+                                    self.report_benchmark_error(
+                                        &mut benchmark_results,
+                                        project,
+                                        &test,
+                                        test_type.0,
+                                        &e,
+                                        &logger,
+                                    );
+                                }
                             }
 
                             logger.write_results(&benchmark_results)?;
+                            self.upload_results(&benchmark_results, &logger)?;
                             logger.log(format!("Completed benchmarking: {}", test_type.0))?;
                         }
+                        self.report_container_incidents(
+                            &mut benchmark_results,
+                            test,
+                            &container_ids,
+                            &logger,
+                        )?;
                     }
                     Err(e) => {
                         logger.error(&e)?;
+                        self.status.fail_test();
                         // We could not start this implementation's docker
                         // container(s); all of its test implementations must
                         // fail.
                         for test_type in &test.urls {
                             self.report_benchmark_error(
                                 &mut benchmark_results,
+                                project,
+                                &test,
+                                test_type.0,
+                                &e,
+                                &logger,
+                            );
+                        }
+                    }
+                }
+
+                self.trip();
+                self.stop_containers();
+                self.tracer.end_span();
+                self.metrics.increment("tests.completed");
+                self.restore_database(relocated_database);
+                self.remove_isolated_network(isolated_network, &logger)?;
+                self.status.complete_test();
+                logger.write_status(&self.status)?;
+                tests_run += 1;
+            }
+        }
+        if self.docker_config.baseline_interval.is_some() && tests_run > 0 {
+            self.measure_baseline(&mut benchmark_results, tests_run, &logger)?;
+        }
+        if let Some(socket_errors) = &benchmark_results.socket_errors {
+            report_socket_errors(socket_errors, &logger)?;
+        }
+        logger.write_results(&benchmark_results)?;
+        self.upload_results(&benchmark_results, &logger)?;
+        self.write_html_report(&benchmark_results, &logger)?;
+        self.status.finish();
+        logger.write_status(&self.status)?;
+        if let Err(e) = self.tracer.export() {
+            logger.error(&e)?;
+        }
+
+        Ok(())
+    }
+
+    /// Searches, for each test type of the specified test implementation(s),
+    /// for the concurrency at which throughput peaks and stores it, along
+    /// with its latency profile, in the results file.
+    ///
+    /// Only HTTP test types, whose `wrk` commands can be rewritten, are tuned.
+    pub fn tune(&mut self) -> ToolsetResult<()> {
+        let mut benchmark_results = Results::new(&self.docker_config)?;
+        let logger = self.docker_config.logger.clone();
+        self.pull_verifier(&logger)?;
+        benchmark_results.daemons = check_daemons(&self.docker_config, &logger)?;
+        let projects = &self.projects.clone();
+        self.status.tests_total = projects.iter().map(|project| project.tests.len()).sum();
+        logger.write_status(&self.status)?;
+        for project in projects {
+            for test in &project.tests {
+                let mut logger = logger.clone();
+                logger.set_test(test);
+                self.trip();
+                self.wait_while_paused(&logger)?;
+                self.wait_for_schedule(&logger)?;
+                self.status.start_test(test);
+                logger.write_status(&self.status)?;
+                let isolated_network = self.isolate_network(test, &logger)?;
+                let relocated_database = self.relocate_database(test, &logger)?;
+                self.tracer.start_span("test");
+                self.tracer
+                    .set_attribute("framework", project.framework.get_name());
+                self.tracer.set_attribute("test", test.get_name());
+                match self.start_test_orchestration(project, test, &logger) {
+                    Ok(orchestration) => {
+                        for test_type in &test.urls {
+                            if test.get_protocol(test_type.0) != Protocol::Http {
+                                logger.log(format!(
+                                    "Skipping tuning: {} is not benchmarked over HTTP",
+                                    test_type.0
+                                ))?;
+                                continue;
+                            }
+                            logger.log(format!("Tuning: {}", test_type.0))?;
+                            self.status.set_phase(phases::TUNING, Some(test_type.0));
+                            logger.write_status(&self.status)?;
+                            self.tracer.start_span("tune");
+                            self.tracer.set_attribute("test.type", test_type.0.as_str());
+                            let started = Instant::now();
+                            let tuned = self.run_tuning(&orchestration, test, &test_type, &logger);
+                            self.metrics.timing("phase.tune", started.elapsed());
+                            if let Ok(tuned) = &tuned {
+                                self.tracer.set_attribute("rps", tuned.requests_per_second);
+                                self.tracer.set_attribute("concurrency", tuned.concurrency);
+                            }
+                            self.tracer.end_span_with(&tuned);
+                            self.status.complete_test_type(tuned.is_err());
+                            match tuned {
+                                Ok(tuned) => {
+                                    logger.log(format!(
+                                        "Peak throughput for {}: {:.2} requests/sec at {} connections",
+                                        test_type.0, tuned.requests_per_second, tuned.concurrency
+                                    ))?;
+                                    self.report_tuning_success(
+                                        &mut benchmark_results,
+                                        tuned,
+                                        project,
+                                        test,
+                                        test_type.0,
+                                    );
+                                }
+                                Err(e) => {
+                                    logger.error(&e)?;
+                                    self.report_benchmark_error(
+                                        &mut benchmark_results,
+                                        project,
+                                        &test,
+                                        test_type.0,
+                                        &e,
+                                        &logger,
+                                    );
+                                }
+                            }
+
+                            logger.write_results(&benchmark_results)?;
+                            self.upload_results(&benchmark_results, &logger)?;
+                            logger.log(format!("Completed tuning: {}", test_type.0))?;
+                        }
+                    }
+                    Err(e) => {
+                        logger.error(&e)?;
+                        self.status.fail_test();
+                        for test_type in &test.urls {
+                            self.report_benchmark_error(
+                                &mut benchmark_results,
+                                project,
                                 &test,
                                 test_type.0,
                                 &e,
@@ -213,6 +631,74 @@ impl<'a> Benchmarker<'a> {
 
                 self.trip();
                 self.stop_containers();
+                self.tracer.end_span();
+                self.metrics.increment("tests.completed");
+                self.restore_database(relocated_database);
+                self.remove_isolated_network(isolated_network, &logger)?;
+                self.status.complete_test();
+                logger.write_status(&self.status)?;
+            }
+        }
+        logger.write_results(&benchmark_results)?;
+        self.upload_results(&benchmark_results, &logger)?;
+        self.status.finish();
+        logger.write_status(&self.status)?;
+        if let Err(e) = self.tracer.export() {
+            logger.error(&e)?;
+        }
+
+        Ok(())
+    }
+
+    /// Starts the specified test implementation(s) and prints the primer,
+    /// warmup, and benchmark commands for each test type without running
+    /// them, so that a measurement can be reproduced by hand.
+    pub fn print_commands(&mut self) -> ToolsetResult<()> {
+        let logger = self.docker_config.logger.clone();
+        self.pull_verifier(&logger)?;
+        let projects = &self.projects.clone();
+        for project in projects {
+            for test in &project.tests {
+                let mut logger = logger.clone();
+                logger.set_test(test);
+                self.trip();
+                let isolated_network = self.isolate_network(test, &logger)?;
+                let relocated_database = self.relocate_database(test, &logger)?;
+                match self.start_test_orchestration(project, test, &logger) {
+                    Ok(orchestration) => {
+                        for test_type in &test.urls {
+                            match self.get_benchmark_commands(
+                                &orchestration,
+                                test,
+                                &test_type,
+                                &logger,
+                            ) {
+                                Ok(commands) => {
+                                    logger.log(format!("{}:", test_type.0))?;
+                                    logger.log(format!(
+                                        "  primer: {}",
+                                        commands.primer_command.join(" ")
+                                    ))?;
+                                    logger.log(format!(
+                                        "  warmup: {}",
+                                        commands.warmup_command.join(" ")
+                                    ))?;
+                                    for command in &commands.benchmark_commands {
+                                        logger
+                                            .log(format!("  benchmark: {}", command.join(" ")))?;
+                                    }
+                                }
+                                Err(e) => logger.error(&e)?,
+                            }
+                        }
+                    }
+                    Err(e) => logger.error(&e)?,
+                }
+
+                self.trip();
+                self.stop_containers();
+                self.restore_database(relocated_database);
+                self.remove_isolated_network(isolated_network, &logger)?;
             }
         }
 
@@ -265,34 +751,64 @@ impl<'a> Benchmarker<'a> {
             succeeded = false;
         } else {
             let logger = self.docker_config.logger.clone();
-            logger.log("Pulling verifier; this may take some time.")?;
-            // todo - how should we version this?
-            pull_image(
-                &self.docker_config,
-                &self.docker_config.client_docker_host,
-                "techempower/tfb.verifier",
-            )?;
+            self.pull_verifier(&logger)?;
+            self.prepare_core_dumps(&logger)?;
+            self.status.tests_total = projects.iter().map(|project| project.tests.len()).sum();
+            logger.write_status(&self.status)?;
             for project in projects {
                 for test in &project.tests {
                     let mut logger = logger.clone();
                     logger.set_test(test);
                     self.trip();
+                    self.wait_while_paused(&logger)?;
+                    self.status.start_test(test);
+                    logger.write_status(&self.status)?;
+                    let isolated_network = self.isolate_network(test, &logger)?;
+                    let relocated_database = self.relocate_database(test, &logger)?;
+                    self.tracer.start_span("test");
+                    self.tracer
+                        .set_attribute("framework", project.framework.get_name());
+                    self.tracer.set_attribute("test", test.get_name());
                     match self.start_test_orchestration(project, test, &logger) {
                         Ok(orchestration) => {
                             for test_type in &test.urls {
                                 self.trip();
-                                match self.run_verification(
+                                self.status.set_phase(phases::VERIFYING, Some(test_type.0));
+                                logger.write_status(&self.status)?;
+                                self.tracer.start_span("verify");
+                                self.tracer.set_attribute("test.type", test_type.0.as_str());
+                                let started = Instant::now();
+                                let verification = self.run_verification(
                                     &project,
                                     &test,
                                     &orchestration,
                                     &test_type,
                                     &logger,
-                                ) {
+                                );
+                                self.metrics.timing("phase.verify", started.elapsed());
+                                match &verification {
+                                    Ok(verification) if verification.errors.is_empty() => {
+                                        self.status.complete_test_type(false);
+                                    }
+                                    _ => {
+                                        self.metrics.increment("verifications.failed");
+                                        self.status.complete_test_type(true);
+                                    }
+                                }
+                                if let Ok(verification) = &verification {
+                                    self.tracer.set_attribute(
+                                        "verification.errors",
+                                        verification.errors.len() as u32,
+                                    );
+                                }
+                                self.tracer.end_span_with(&verification);
+                                match verification {
                                     Ok(verification) => {
                                         succeeded &= verification.errors.is_empty();
                                         verifications.push(verification);
                                     }
                                     Err(e) => {
+                                        self.write_failure_summary(&e, &logger);
                                         verifications.push(Verification {
                                             framework_name: project.framework.get_name(),
                                             test_name: test.get_name(),
@@ -312,6 +828,8 @@ impl<'a> Benchmarker<'a> {
                         }
                         Err(e) => {
                             logger.error(&e)?;
+                            self.write_failure_summary(&e, &logger);
+                            self.status.fail_test();
                             verifications.push(Verification {
                                 framework_name: project.framework.get_name(),
                                 test_name: test.get_name(),
@@ -330,12 +848,28 @@ impl<'a> Benchmarker<'a> {
 
                     self.trip();
                     self.stop_containers();
+                    self.tracer.end_span();
+                    self.metrics.increment("tests.completed");
+                    self.restore_database(relocated_database);
+                    self.remove_isolated_network(isolated_network, &logger)?;
+                    self.status.complete_test();
+                    logger.write_status(&self.status)?;
                 }
             }
 
             self.trip();
             self.stop_containers();
-            report_verifications(verifications, logger)?;
+            self.status.finish();
+            logger.write_status(&self.status)?;
+            if let Err(e) = self.tracer.export() {
+                logger.error(&e)?;
+            }
+            report_verifications(
+                verifications,
+                &self.projects,
+                self.docker_config.junit_report.as_deref(),
+                logger,
+            )?;
         }
 
         if succeeded {
@@ -344,6 +878,17 @@ impl<'a> Benchmarker<'a> {
             Err(VerificationFailedException)
         }
     }
+
+    /// Puts back the server host's `core_pattern` replaced for `--core-dumps`,
+    /// if any; to be called when the run ends, however it ends.
+    pub fn restore_core_pattern(&self) {
+        put_back_core_pattern(
+            self.docker_config.docker_api.as_ref(),
+            &self.docker_config.server_docker_host,
+            &self.core_pattern,
+            &self.docker_config.logger,
+        );
+    }
 }
 
 //
@@ -351,17 +896,60 @@ impl<'a> Benchmarker<'a> {
 //
 impl<'a> Benchmarker<'a> {
     /// Runs the benchmarks for a given `DockerOrchestration` and `test_type`.
+    /// Returns the number of warmups run along with the results.
+    ///
+    /// The verifier checks each URL while retrieving its benchmark commands;
+    /// as some frameworks degrade under those checks, the application server
+    /// must be ready again (or, with `--restart-after-verification`, is
+    /// restarted into `orchestration`) before the benchmark starts.
     fn run_benchmarks(
         &mut self,
-        orchestration: &DockerOrchestration,
+        orchestration: &mut DockerOrchestration,
+        test: &Test,
         test_type: &(&String, &String),
         logger: &Logger,
-    ) -> ToolsetResult<Vec<BenchmarkResults>> {
+    ) -> ToolsetResult<(u32, Vec<BenchmarkResults>)> {
+        let protocol = test.get_protocol(test_type.0);
         let mut results = Vec::default();
         let mut logger = logger.clone();
-        logger.set_log_file(&format!("{}.txt", test_type.0));
+        logger.set_log_file(LogPath::Benchmark(test_type.0));
         logger.quiet = true;
-        let benchmark_commands = self.run_command_retrieval(&orchestration, &test_type, &logger)?;
+        let benchmark_commands =
+            self.get_benchmark_commands(orchestration, test, test_type, &logger)?;
+        if self.docker_config.restart_after_verification {
+            logger.log("Restarting application server after verification")?;
+            *orchestration = self.restart_application(orchestration, test, &logger)?;
+        } else {
+            self.check_for_container_deaths(orchestration)?;
+            self.wait_until_accepting_requests(
+                &(
+                    orchestration.host_container_id.clone(),
+                    orchestration.db_container_id.clone(),
+                ),
+                &orchestration.host_port,
+                test,
+            )?;
+        }
+        let orchestration = &*orchestration;
+        self.benchmarking_database =
+            test.database.is_some() && !DATABASE_FREE_TEST_TYPES.contains(&test_type.0.as_str());
+        self.sampled_database = match &test.database {
+            Some(database)
+                if self.docker_config.sample_connections
+                    && !DATABASE_FREE_TEST_TYPES.contains(&test_type.0.as_str()) =>
+            {
+                Some(database.to_lowercase())
+            }
+            _ => None,
+        };
+
+        if protocol == Protocol::Http {
+            check_server_is_reachable(
+                &self.docker_config,
+                &benchmark_commands.primer_command,
+                &logger,
+            )?;
+        }
 
         logger.log("---------------------------------------------------------")?;
         logger.log(" Running Primer")?;
@@ -370,7 +958,7 @@ impl<'a> Benchmarker<'a> {
             &benchmark_commands.primer_command.join(" ")
         ))?;
         logger.log("---------------------------------------------------------")?;
-        self.run_benchmark(&benchmark_commands.primer_command, &logger)?;
+        self.run_benchmark(&benchmark_commands.primer_command, protocol, &logger)?;
 
         logger.log("---------------------------------------------------------")?;
         logger.log(" Running Warmup")?;
@@ -379,84 +967,765 @@ impl<'a> Benchmarker<'a> {
             &benchmark_commands.warmup_command.join(" ")
         ))?;
         logger.log("---------------------------------------------------------")?;
-        self.run_benchmark(&benchmark_commands.warmup_command, &logger)?;
+        let warmups = self.run_warmups(&benchmark_commands.warmup_command, protocol, &logger)?;
 
-        for command in &benchmark_commands.benchmark_commands {
+        let highest_concurrency = benchmark_commands
+            .benchmark_commands
+            .len()
+            .saturating_sub(1);
+        for (index, command) in benchmark_commands.benchmark_commands.iter().enumerate() {
             logger.log("---------------------------------------------------------")?;
             logger.log(format!(" {}", command.join(" ")))?;
             logger.log("---------------------------------------------------------")?;
 
-            results.push(self.run_benchmark(command, &logger)?);
+            let counted_database = match (&test.database, &orchestration.db_container_id) {
+                (Some(database), Some(container_id)) if self.docker_config.count_queries => {
+                    get_expected_queries_per_request(
+                        test_type.0,
+                        command,
+                        self.docker_config.max_queries,
+                    )
+                    .map(|expected| (database.to_lowercase(), container_id.clone(), expected))
+                }
+                _ => None,
+            };
+            let statements_before = match &counted_database {
+                Some((database, container_id, _)) => {
+                    count_statements(&self.docker_config, database, container_id)?
+                }
+                None => None,
+            };
+
+            let profiler = if self.docker_config.profile && index == highest_concurrency {
+                logger.log("Profiling application server")?;
+                Some(Profiler::start(
+                    &self.docker_config,
+                    &orchestration.host_container_id,
+                    self.docker_config.get_duration(test_type.0),
+                )?)
+            } else {
+                None
+            };
+
+            let mut result = match self.docker_config.adaptive_cv {
+                Some(threshold) if protocol == Protocol::Http => {
+                    self.run_adaptive_benchmark(test_type.0, command, threshold, &logger)?
+                }
+                _ => self.run_benchmark(command, protocol, &logger)?,
+            };
+            if let Some(profiler) = profiler {
+                // Without a results dir (i.e. in CICD), use the working dir.
+                let destination = logger
+                    .get_log_path(LogPath::Profile(test_type.0))
+                    .unwrap_or_else(|| LogPath::Profile(test_type.0).get_path());
+                match profiler.finish(&self.docker_config, &destination) {
+                    Ok(()) => logger.log(format!(
+                        "Profile written to {}",
+                        destination.to_string_lossy()
+                    ))?,
+                    Err(e) => logger.error(format!("Failed to profile: {}", e))?,
+                }
+            }
+            self.metrics.gauge(
+                &format!(
+                    "rps.{}.{}",
+                    get_metric_segment(&test.get_name()),
+                    get_metric_segment(test_type.0)
+                ),
+                result.requests_per_second,
+            );
+            self.check_for_container_deaths(orchestration)?;
+            if let (Some((database, container_id, expected)), Some(before)) =
+                (&counted_database, statements_before)
+            {
+                if let Some(after) = count_statements(&self.docker_config, database, container_id)?
+                {
+                    let queries_per_request =
+                        after.saturating_sub(before) as f32 / result.total_requests.max(1) as f32;
+                    result.queries_per_request = Some(queries_per_request);
+                    result.expected_queries_per_request = Some(*expected);
+                    logger.log(format!(
+                        "Queries per request: {:.2} (expected at least {})",
+                        queries_per_request, expected
+                    ))?;
+                    if appears_cached(queries_per_request, *expected) {
+                        logger.error(format!(
+                            "{:.2} queries per request is fewer than {} requires; results may be cached",
+                            queries_per_request, test_type.0
+                        ))?;
+                    }
+                }
+            }
+            if let Some(connections) = &result.database_connections {
+                logger.log(format!(
+                    "Database connections: {:.1} on average, {} at most",
+                    connections.connections_avg, connections.connections_max
+                ))?;
+            }
+            if result.stalled {
+                logger.error(format!(
+                    "Benchmarker produced no output for {} seconds past the expected duration; killed",
+                    self.docker_config.stall_timeout
+                ))?;
+            }
+            results.push(result);
         }
 
-        Ok(results)
+        Ok((warmups, results))
     }
 
-    /// Runs the benchmarker container against the given `DockerOrchestration`.
-    fn run_benchmark(
+    /// Benchmarks the static index page of `CALIBRATION_IMAGE`, run in place
+    /// of an application server, at the highest concurrency level; a known
+    /// fast reference against which the client's own ceiling can be judged.
+    fn calibrate(&mut self, logger: &Logger) -> ToolsetResult<Calibration> {
+        logger.log(format!(
+            "Calibrating against {}; this may take some time.",
+            CALIBRATION_IMAGE
+        ))?;
+        pull_image(
+            &self.docker_config,
+            &self.docker_config.server_docker_host,
+            CALIBRATION_IMAGE,
+        )?;
+        let (_, (_, internal_port)) = self.start_application_container(
+            CALIBRATION_IMAGE,
+            None,
+            Some(CALIBRATION_PORT),
+            logger,
+        )?;
+        let results = self.run_calibration(&internal_port, logger);
+        self.release_container(&self.application_container_id, false);
+        let (concurrency, results) = results?;
+        logger.log(format!(
+            "Calibration: {:.2} requests/sec at {} connections",
+            results.requests_per_second, concurrency
+        ))?;
+
+        Ok(Calibration {
+            image: CALIBRATION_IMAGE.to_string(),
+            concurrency,
+            requests_per_second: results.requests_per_second,
+            latency_avg: results.thread_stats.latency.average,
+            latency_max: results.thread_stats.latency.max,
+            latency_stdev: results.thread_stats.latency.standard_deviation,
+            total_requests: results.total_requests,
+            start_time: results.start_time,
+            end_time: results.end_time,
+            container_stats: results.container_stats,
+        })
+    }
+
+    /// Calibrates (see `calibrate`) between tests with `--baseline-interval`,
+    /// once `tests_run` tests have been run, and records the result as a
+    /// `Baseline`. A failed baseline is logged rather than failing the run.
+    fn measure_baseline(
+        &mut self,
+        results: &mut Results,
+        tests_run: u32,
+        logger: &Logger,
+    ) -> ToolsetResult<()> {
+        let calibration = match self.calibrate(logger) {
+            Ok(calibration) => calibration,
+            Err(e) => {
+                logger.error(format!("Baseline after {} tests failed: {}", tests_run, e))?;
+                return Ok(());
+            }
+        };
+        let baseline = Baseline::new(tests_run, calibration, results.baselines.first());
+        let message = format!(
+            "Baseline after {} tests: {:+.1}% requests/sec since the first",
+            tests_run, baseline.drift_percent
+        );
+        if baseline.drift_percent.abs() > BASELINE_DRIFT_TOLERANCE {
+            logger.error(message)?;
+        } else {
+            logger.log(message)?;
+        }
+        results.baselines.push(baseline);
+
+        logger.write_results(results)
+    }
+
+    /// Does the work of `calibrate` once the reference server listening on
+    /// `internal_port` has started; returns the concurrency benchmarked along
+    /// with the results.
+    fn run_calibration(
+        &mut self,
+        internal_port: &str,
+        logger: &Logger,
+    ) -> ToolsetResult<(u32, BenchmarkResults)> {
+        // Give nginx a moment to start listening.
+        thread::sleep(Duration::from_secs(1));
+
+        let concurrency = self
+            .docker_config
+            .concurrency_levels
+            .split(',')
+            .filter_map(|level| str::parse::<u32>(level).ok())
+            .max()
+            .unwrap_or(CALIBRATION_THREADS);
+        let duration = self.docker_config.duration.to_string();
+        let connections = concurrency.to_string();
+        let threads = CALIBRATION_THREADS.min(concurrency).to_string();
+        let url = format!("http://tfb-server:{}/", internal_port);
+        let command: Vec<String> = [
+            "wrk",
+            "-H",
+            "Host: tfb-server",
+            "-H",
+            "Connection: keep-alive",
+            "--latency",
+            "-d",
+            &duration,
+            "-c",
+            &connections,
+            "--timeout",
+            "8",
+            "-t",
+            &threads,
+            &url,
+        ]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+        let results = self.run_benchmark(&command, Protocol::Http, logger)?;
+
+        Ok((concurrency, results))
+    }
+
+    /// Runs the warmup `command` once or, given `--warmup-tolerance`, until
+    /// its requests/sec is within that tolerance of the previous run's (at
+    /// most `--max-warmups` times), so that frameworks which warm up slowly
+    /// (e.g. those with a JIT) are all measured warm. Returns the number of
+    /// warmups run.
+    fn run_warmups(
         &mut self,
         command: &[String],
+        protocol: Protocol,
         logger: &Logger,
-    ) -> ToolsetResult<BenchmarkResults> {
-        let container_id = create_benchmarker_container(&self.docker_config, command)?;
+    ) -> ToolsetResult<u32> {
+        let mut previous = self.run_benchmark(command, protocol, logger)?;
+        let tolerance = match self.docker_config.warmup_tolerance {
+            Some(tolerance) => tolerance,
+            None => return Ok(1),
+        };
+        let mut warmups = 1;
+        while warmups < self.docker_config.max_warmups {
+            let current = self.run_benchmark(command, protocol, logger)?;
+            warmups += 1;
+            let converged = has_converged(
+                previous.requests_per_second,
+                current.requests_per_second,
+                tolerance,
+            );
+            logger.log(format!(
+                "Warmup {}: {:.2} requests/sec ({:.2} previously)",
+                warmups, current.requests_per_second, previous.requests_per_second
+            ))?;
+            if converged {
+                return Ok(warmups);
+            }
+            previous = current;
+        }
+        logger.log(format!(
+            "Requests/sec did not stabilize within {} warmups",
+            warmups
+        ))?;
 
-        connect_container_to_network(
+        Ok(warmups)
+    }
+
+    /// Pulls the verifier and records its digest in the run's manifest, so
+    /// that the run can be reproduced against the same verifier.
+    fn pull_verifier(&mut self, logger: &Logger) -> ToolsetResult<()> {
+        logger.log("Pulling verifier; this may take some time.")?;
+        // todo - how should we version this?
+        pull_image(
             &self.docker_config,
             &self.docker_config.client_docker_host,
-            &self.docker_config.client_network_id,
-            &container_id,
+            "techempower/tfb.verifier",
         )?;
+        self.docker_config.manifest.verifier_digest =
+            self.docker_config.docker_api.get_image_digest(
+                "techempower/tfb.verifier",
+                &self.docker_config.client_docker_host,
+            )?;
 
-        if let Ok(mut benchmarker) = self.benchmarker_container_id.lock() {
-            benchmarker.register(&container_id);
+        logger.write_manifest(&self.docker_config.manifest)
+    }
+
+    /// Points the server host's `core_pattern` at the directory mounted into
+    /// application containers when `--core-dumps` is given; the pattern it
+    /// replaced is put back by `restore_core_pattern`.
+    fn prepare_core_dumps(&self, logger: &Logger) -> ToolsetResult<()> {
+        if self.docker_config.core_dumps {
+            if self.docker_config.use_unix_socket {
+                let core_pattern = configure_core_pattern(
+                    &self.docker_config,
+                    &self.docker_config.server_docker_host,
+                )?;
+                logger.log(format!(
+                    "Replaced the server host's core_pattern (was: {})",
+                    core_pattern
+                ))?;
+                if let Ok(mut replaced) = self.core_pattern.lock() {
+                    *replaced = Some(core_pattern);
+                }
+            } else {
+                // The dump directory is bind-mounted from the results dir,
+                // which only exists on this machine.
+                logger.error("Core dumps can only be collected from a local server Docker host")?;
+            }
         }
 
-        self.trip();
-        let benchmark_results =
-            start_benchmarker_container(&self.docker_config, &container_id, logger)?;
+        Ok(())
+    }
 
-        // This signals that the benchmarker exited naturally on
-        // its own, so we don't need to stop its container.
-        if let Ok(mut benchmarker) = self.benchmarker_container_id.lock() {
-            benchmarker.unregister();
+    /// Gets (creating it, if necessary) the directory beneath the test's
+    /// results dir into which the application server's core dumps are written.
+    fn get_core_dump_dir(&self, logger: &Logger) -> ToolsetResult<Option<String>> {
+        if !self.docker_config.core_dumps || !self.docker_config.use_unix_socket {
+            return Ok(None);
+        }
+        if let Some(core_dump_dir) = logger.get_log_path(LogPath::CoreDumps) {
+            std::fs::create_dir_all(&core_dump_dir)?;
+            // The application server may not run as root.
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                std::fs::set_permissions(&core_dump_dir, std::fs::Permissions::from_mode(0o777))?;
+            }
+            let core_dump_dir = std::fs::canonicalize(core_dump_dir)?;
+
+            return Ok(Some(core_dump_dir.to_string_lossy().to_string()));
         }
 
-        Ok(benchmark_results)
+        Ok(None)
     }
 
-    /// Reports the successful benchmark of a given `framework` / `test_type`
-    /// via `results.json` output.
-    fn report_benchmark_success(
+    /// Gets the (absolute) directory of the given `project` to bind-mount into
+    /// the application container of `test` per `--mount-source`, if given.
+    /// Like `get_core_dump_dir`, this requires a local server Docker host.
+    fn get_source_dir(
         &self,
-        benchmark_results: &mut Results,
-        results: Vec<BenchmarkResults>,
-        framework: &Framework,
-        test_type: &str,
-        _logger: &Logger,
-    ) {
-        for result in results {
-            if benchmark_results.raw_data.get(test_type).is_none() {
-                benchmark_results
-                    .raw_data
-                    .insert(test_type.to_string(), HashMap::default());
-            }
+        project: &Project,
+        test: &Test,
+        logger: &Logger,
+    ) -> ToolsetResult<Option<String>> {
+        if !self.docker_config.mount_source {
+            return Ok(None);
+        }
+        if !self.docker_config.use_unix_socket {
+            logger.error("Cannot mount source; the server Docker host is not local")?;
+            return Ok(None);
+        }
+        if let Some(source_mount) = &test.source_mount {
+            let source_dir = std::fs::canonicalize(project.get_path()?)?;
+            let source_dir = source_dir.to_string_lossy().to_string();
+            logger.log(format!("Mounting {} at {}", source_dir, source_mount))?;
+
+            Ok(Some(source_dir))
+        } else {
+            logger.error(format!(
+                "Cannot mount source; `source_mount` is not set for {}",
+                test.get_name()
+            ))?;
+
+            Ok(None)
+        }
+    }
+
+    /// Writes the given `error`, and any core dumps collected since the test
+    /// started, to `failure.txt` in the test's results dir.
+    fn write_failure_summary(&self, error: &ToolsetError, logger: &Logger) {
+        let mut summary = logger.clone();
+        summary.quiet = true;
+        summary.set_log_file(LogPath::Failure);
+        let _ = summary.log(error);
+
+        if let Ok(Some(core_dump_dir)) = self.get_core_dump_dir(logger) {
+            for core_dump in get_core_dumps(Path::new(&core_dump_dir), self.test_started_at) {
+                let core_dump = core_dump.to_string_lossy().to_string();
+                let _ = summary.log(format!("Core dump: {}", core_dump));
+                let _ = logger.error(format!("Core dump collected: {}", core_dump));
+            }
+        }
+    }
+
+    /// Returns an error attributed to the application server or database
+    /// container of the given `orchestration` if either has died.
+    fn check_for_container_deaths(&self, orchestration: &DockerOrchestration) -> ToolsetResult<()> {
+        let mut containers = vec![("Application server", &orchestration.host_container_id)];
+        if let Some(db_container_id) = &orchestration.db_container_id {
+            containers.push(("Database", db_container_id));
+        }
+        for (name, container_id) in containers {
+            if let Some(death) = self.container_events.get_death(container_id) {
+                return Err(ContainerDiedError(
+                    name.to_string(),
+                    describe_container_death(&death),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Runs the given `wrk` command of `test_type` repeatedly for
+    /// `adaptive_interval` seconds at a time until its requests/sec stabilize;
+    /// see `run_until_stable`.
+    fn run_adaptive_benchmark(
+        &mut self,
+        test_type: &str,
+        command: &[String],
+        threshold: f32,
+        logger: &Logger,
+    ) -> ToolsetResult<BenchmarkResults> {
+        let interval = self.docker_config.adaptive_interval.max(1);
+        let max_duration = self.docker_config.get_adaptive_max_duration(test_type);
+        let command = BenchmarkCommands::with_duration(command, interval);
+
+        run_until_stable(
+            (interval, max_duration),
+            threshold,
+            || self.run_benchmark(&command, Protocol::Http, logger),
+            logger,
+        )
+    }
+
+    /// Searches for the concurrency at which the given `test_type` reaches its
+    /// peak throughput, starting from the lowest configured level; see
+    /// `search_concurrency`.
+    fn run_tuning(
+        &mut self,
+        orchestration: &DockerOrchestration,
+        test: &Test,
+        test_type: &(&String, &String),
+        logger: &Logger,
+    ) -> ToolsetResult<TunedData> {
+        let mut logger = logger.clone();
+        logger.set_log_file(LogPath::Benchmark(test_type.0));
+        logger.quiet = true;
+        let benchmark_commands =
+            self.get_benchmark_commands(orchestration, test, test_type, &logger)?;
+        let template = match benchmark_commands.benchmark_commands.last() {
+            Some(command) => command.clone(),
+            None => return Err(FailedBenchmarkCommandRetrievalError),
+        };
+
+        logger.log(" Running Primer")?;
+        self.run_benchmark(&benchmark_commands.primer_command, Protocol::Http, &logger)?;
+        logger.log(" Running Warmup")?;
+        self.run_benchmark(&benchmark_commands.warmup_command, Protocol::Http, &logger)?;
+
+        let lowest = self
+            .docker_config
+            .concurrency_levels
+            .split(',')
+            .filter_map(|level| str::parse::<u32>(level).ok())
+            .min()
+            .unwrap_or(16);
+        let probes = search_concurrency(
+            lowest,
+            self.docker_config.tune_max_concurrency,
+            |concurrency| self.run_tuning_probe(&template, concurrency, &logger),
+        )?;
+
+        let best = get_peak_concurrency(&probes);
+        let result = &probes[&best];
+        Ok(TunedData {
+            concurrency: best,
+            requests_per_second: result.requests_per_second,
+            latency_avg: result.thread_stats.latency.average.clone(),
+            latency_max: result.thread_stats.latency.max.clone(),
+            latency_stdev: result.thread_stats.latency.standard_deviation.clone(),
+            latency_50: result.latency_distribution.percentile_50.clone(),
+            latency_75: result.latency_distribution.percentile_75.clone(),
+            latency_90: result.latency_distribution.percentile_90.clone(),
+            latency_99: result.latency_distribution.percentile_99.clone(),
+            probes: probes
+                .iter()
+                .map(|(concurrency, result)| TuningProbe {
+                    concurrency: *concurrency,
+                    requests_per_second: result.requests_per_second,
+                })
+                .collect(),
+        })
+    }
+
+    /// Runs the given `wrk` command `template` at the given `concurrency`.
+    fn run_tuning_probe(
+        &mut self,
+        template: &[String],
+        concurrency: u32,
+        logger: &Logger,
+    ) -> ToolsetResult<BenchmarkResults> {
+        let command = BenchmarkCommands::with_concurrency(template, concurrency);
+        logger.log("---------------------------------------------------------")?;
+        logger.log(format!(" {}", command.join(" ")))?;
+        logger.log("---------------------------------------------------------")?;
+
+        self.run_benchmark(&command, Protocol::Http, logger)
+    }
+
+    /// Reports the successful tuning of a given `test` / `test_type` via
+    /// `results.json` output.
+    fn report_tuning_success(
+        &self,
+        benchmark_results: &mut Results,
+        tuned: TunedData,
+        project: &Project,
+        test: &Test,
+        test_type: &str,
+    ) {
+        let framework = &project.framework;
+        benchmark_results.record_status(project, test, test_type, STATUS_SUCCEEDED);
+        benchmark_results
+            .tuned
+            .entry(test_type.to_string())
+            .or_default()
+            .insert(framework.get_normalized_name(), tuned);
+        benchmark_results
+            .succeeded
+            .entry(test_type.to_string())
+            .or_default()
+            .push(framework.get_normalized_name());
+    }
+
+    /// Gets the `BenchmarkCommands` for the given `test_type`; for HTTP these
+    /// are retrieved from the verifier, otherwise they are built locally.
+    fn get_benchmark_commands(
+        &mut self,
+        orchestration: &DockerOrchestration,
+        test: &Test,
+        test_type: &(&String, &String),
+        logger: &Logger,
+    ) -> ToolsetResult<BenchmarkCommands> {
+        match test.get_protocol(test_type.0) {
+            Protocol::Http => {
+                let commands =
+                    self.run_command_retrieval(&orchestration, test, &test_type, logger)?;
+                if let Some(command_override) = test.get_command_override(test_type.0) {
+                    logger.log(format!(
+                        "Overriding benchmark commands for {}: {:?}",
+                        test_type.0, command_override
+                    ))?;
+                    Ok(commands.with_override(command_override))
+                } else {
+                    Ok(commands)
+                }
+            }
+            Protocol::Grpc => Ok(BenchmarkCommands::ghz(
+                &self.docker_config,
+                orchestration,
+                test_type.1,
+                self.docker_config.get_duration(test_type.0),
+            )),
+            Protocol::Websocket => Ok(BenchmarkCommands::tcpkali(
+                &self.docker_config,
+                orchestration,
+                test_type.1,
+                self.docker_config.get_duration(test_type.0),
+            )),
+        }
+    }
+
+    /// Runs the benchmarker container against the given `DockerOrchestration`.
+    fn run_benchmark(
+        &mut self,
+        command: &[String],
+        protocol: Protocol,
+        logger: &Logger,
+    ) -> ToolsetResult<BenchmarkResults> {
+        let container_id = create_benchmarker_container(&self.docker_config, protocol, command)?;
+
+        connect_container_to_network(
+            &self.docker_config,
+            &self.docker_config.client_docker_host,
+            &self.docker_config.client_network_mode,
+            &self.docker_config.client_network_id,
+            &container_id,
+        )?;
+
+        if let Ok(mut benchmarker) = self.benchmarker_container_id.lock() {
+            benchmarker.register(&container_id);
+        }
+
+        let application_container_id = match self.application_container_id.lock() {
+            Ok(application) => application.get_container_id(),
+            Err(_) => None,
+        };
+        let stats = application_container_id.map(|application_container_id| {
+            StatsCollector::start(
+                &self.docker_config.server_docker_host,
+                self.docker_config.use_unix_socket,
+                &application_container_id,
+            )
+        });
+        let database_container_id = match self.database_container_id.lock() {
+            Ok(database) => database.get_container_id(),
+            Err(_) => None,
+        };
+        let database_stats = match &database_container_id {
+            Some(database_container_id) if self.benchmarking_database => {
+                Some(StatsCollector::start(
+                    &self.docker_config.database_docker_host,
+                    self.docker_config.use_unix_socket,
+                    database_container_id,
+                ))
+            }
+            _ => None,
+        };
+        let connections = match (&self.sampled_database, database_container_id) {
+            (Some(database), Some(database_container_id)) => {
+                ConnectionSampler::start(&self.docker_config, database, &database_container_id)?
+            }
+            _ => None,
+        };
+
+        self.trip();
+        logger.record_event(
+            "benchmark.command.started",
+            &[
+                ("container_id", &container_id),
+                ("command", &command.join(" ")),
+            ],
+        )?;
+        let benchmark_results =
+            start_benchmarker_container(&self.docker_config, &container_id, protocol, logger);
+        logger.record_event(
+            "benchmark.command.finished",
+            &[
+                ("container_id", &container_id),
+                ("succeeded", &benchmark_results.is_ok().to_string()),
+            ],
+        )?;
+        let container_stats = stats.and_then(|stats| stats.stop());
+        let database_stats = database_stats.and_then(|stats| stats.stop());
+        let database_connections = match connections {
+            Some(connections) => match connections.stop(&self.docker_config) {
+                Ok(database_connections) => database_connections,
+                Err(e) => {
+                    logger.error(format!("Failed to sample database connections: {}", e))?;
+                    None
+                }
+            },
+            None => None,
+        };
+        self.release_container(&self.benchmarker_container_id, benchmark_results.is_ok());
+        let mut benchmark_results = benchmark_results?;
+        benchmark_results.container_stats = container_stats;
+        benchmark_results.database_stats = database_stats;
+        benchmark_results.database_connections = database_connections;
+
+        Ok(benchmark_results)
+    }
+
+    /// Records, in the results file, any restarts or health check failures of
+    /// the given containers of `test` observed while it was benchmarked.
+    fn report_container_incidents(
+        &self,
+        benchmark_results: &mut Results,
+        test: &Test,
+        container_ids: &[String],
+        logger: &Logger,
+    ) -> ToolsetResult<()> {
+        let container_ids: Vec<&str> = container_ids.iter().map(String::as_str).collect();
+        let incidents = self.container_events.get_incidents(&container_ids);
+        if incidents == ContainerIncidents::default() {
+            return Ok(());
+        }
+        logger.error(format!(
+            "Containers restarted {} time(s) and turned unhealthy {} time(s) during the test",
+            incidents.restarts, incidents.health_flaps
+        ))?;
+        benchmark_results
+            .container_incidents
+            .insert(test.get_normalized_name(), incidents);
+
+        logger.write_results(benchmark_results)
+    }
+
+    /// Renders the given `results` to the HTML file given via `--report`, if
+    /// any.
+    fn write_html_report(&self, results: &Results, logger: &Logger) -> ToolsetResult<()> {
+        if let Some(html_report) = &self.docker_config.html_report {
+            write_report(results, html_report)?;
+            logger.log(format!(
+                "Wrote the HTML report to {}",
+                html_report.to_string_lossy()
+            ))?;
+        }
+
+        Ok(())
+    }
+
+    /// Uploads the given `results` to the `--results-upload-uri`, if any. A
+    /// failed upload is logged rather than failing the run; `results.json`
+    /// still holds everything that would have been uploaded.
+    fn upload_results(&self, results: &Results, logger: &Logger) -> ToolsetResult<()> {
+        if let Some(upload_uri) = self.docker_config.results_upload_uri {
+            if let Err(e) = upload_results(results, upload_uri, logger) {
+                logger.error(&e)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reports the successful benchmark of a given `test` / `test_type` via
+    /// `results.json` output.
+    fn report_benchmark_success(
+        &self,
+        benchmark_results: &mut Results,
+        results: Vec<BenchmarkResults>,
+        project: &Project,
+        test: &Test,
+        test_type: &str,
+        _logger: &Logger,
+    ) {
+        let framework = &project.framework;
+        benchmark_results.record_status(project, test, test_type, STATUS_SUCCEEDED);
+        let duration = self.docker_config.get_duration(test_type);
+        let suspected_caching = results.iter().any(|result| {
+            match (
+                result.queries_per_request,
+                result.expected_queries_per_request,
+            ) {
+                (Some(queries_per_request), Some(expected)) => {
+                    appears_cached(queries_per_request, expected)
+                }
+                _ => false,
+            }
+        });
+        if suspected_caching {
+            benchmark_results
+                .suspected_caching
+                .entry(test_type.to_string())
+                .or_default()
+                .push(framework.get_normalized_name());
+        }
+        for result in results {
+            benchmark_results
+                .socket_errors
+                .get_or_insert_with(Default::default)
+                .record(
+                    &test.get_normalized_name(),
+                    test_type,
+                    result.socket_errors.as_ref(),
+                );
+            if benchmark_results.raw_data.get(test_type).is_none() {
+                benchmark_results
+                    .raw_data
+                    .insert(test_type.to_string(), HashMap::default());
+            }
             if let Some(test_type) = benchmark_results.raw_data.get_mut(test_type) {
-                if test_type
-                    .get(&framework.get_name().to_lowercase())
-                    .is_none()
-                {
-                    test_type.insert(framework.get_name().to_lowercase(), Vec::default());
+                if test_type.get(&framework.get_normalized_name()).is_none() {
+                    test_type.insert(framework.get_normalized_name(), Vec::default());
                 }
 
-                if let Some(results) = test_type.get_mut(&framework.get_name().to_lowercase()) {
-                    results.push(BenchmarkData {
-                        latency_avg: result.thread_stats.latency.average,
-                        latency_max: result.thread_stats.latency.max,
-                        latency_stdev: result.thread_stats.latency.standard_deviation,
-                        total_requests: result.total_requests,
-                        start_time: result.start_time,
-                        end_time: result.end_time,
-                    });
+                if let Some(results) = test_type.get_mut(&framework.get_normalized_name()) {
+                    results.push(BenchmarkData::new(result, duration));
                 }
             }
         }
@@ -466,15 +1735,22 @@ impl<'a> Benchmarker<'a> {
                 .insert(test_type.to_string(), Vec::default());
         }
         if let Some(test_type) = benchmark_results.succeeded.get_mut(test_type) {
-            test_type.push(framework.get_name().to_lowercase());
+            test_type.push(framework.get_normalized_name());
+        }
+        // Any failure of it in the run being resumed is superseded.
+        if let Some(failed) = benchmark_results.failed.get_mut(test_type) {
+            failed.retain(|failed| *failed != test.get_normalized_name());
         }
-        benchmark_results.completed.insert(
-            framework.get_name().to_lowercase(),
+        if let Some(failures) = benchmark_results.failures.get_mut(test_type) {
+            failures.remove(&test.get_normalized_name());
+        }
+        benchmark_results.record_completion(
+            &framework.get_normalized_name(),
+            test_type,
             SystemTime::now()
                 .duration_since(UNIX_EPOCH)
                 .unwrap()
-                .as_millis()
-                .to_string(),
+                .as_millis(),
         );
     }
 
@@ -483,23 +1759,39 @@ impl<'a> Benchmarker<'a> {
     fn report_benchmark_error(
         &self,
         benchmark_results: &mut Results,
+        project: &Project,
         test: &Test,
         test_type: &str,
-        _error: &ToolsetError,
-        _logger: &Logger,
+        error: &ToolsetError,
+        logger: &Logger,
     ) {
+        benchmark_results.record_status(project, test, test_type, STATUS_FAILED);
+        self.write_failure_summary(error, logger);
+        self.metrics.increment("benchmarks.failed");
         if benchmark_results.failed.get(test_type).is_none() {
             benchmark_results
                 .failed
                 .insert(test_type.to_string(), Vec::default());
         }
         if let Some(test_type) = benchmark_results.failed.get_mut(test_type) {
-            test_type.push(test.get_name());
+            // A resumed run may have failed it already.
+            if !test_type.contains(&test.get_normalized_name()) {
+                test_type.push(test.get_normalized_name());
+            }
         }
+        benchmark_results
+            .failures
+            .entry(test_type.to_string())
+            .or_default()
+            .insert(test.get_normalized_name(), Failure::new(error));
     }
 
     /// Runs the verifier against the given test orchestration and returns the
     /// `Verification` result.
+    ///
+    /// If verifying fails, the verifier is recreated and run again up to
+    /// `verify_retries` times, waiting a second longer before each retry, as
+    /// a test type may fail only because the application is slow to warm up.
     fn run_verification(
         &mut self,
         project: &Project,
@@ -507,14 +1799,66 @@ impl<'a> Benchmarker<'a> {
         orchestration: &DockerOrchestration,
         test_type: &(&String, &String),
         logger: &Logger,
+    ) -> ToolsetResult<Verification> {
+        let protocol = test.get_protocol(test_type.0);
+        if protocol != Protocol::Http {
+            // The verifier only speaks HTTP; other test types are reachable
+            // (see `wait_until_accepting_requests`) but otherwise unverified.
+            return Ok(Verification {
+                framework_name: project.framework.get_name(),
+                test_name: test.get_name(),
+                type_name: test_type.0.clone(),
+                warnings: vec![Warning {
+                    message: format!(
+                        "{:?} test types are not verified beyond accepting connections",
+                        protocol
+                    ),
+                    short_message: format!("Not verified ({:?})", protocol),
+                }],
+                errors: Vec::default(),
+            });
+        }
+
+        let mut retries = 0;
+        loop {
+            let verification = self.start_verifier(project, test, orchestration, test_type, logger);
+            let verified =
+                matches!(&verification, Ok(verification) if verification.errors.is_empty());
+            if verified || retries >= self.docker_config.verify_retries {
+                return verification;
+            }
+            retries += 1;
+            logger.error(format!(
+                "Verifying {} failed; retrying ({}/{})",
+                test_type.0, retries, self.docker_config.verify_retries
+            ))?;
+            thread::sleep(Duration::from_secs(u64::from(retries)));
+        }
+    }
+
+    /// Creates and starts a verifier container against the given test
+    /// orchestration and returns the `Verification` result.
+    fn start_verifier(
+        &mut self,
+        project: &Project,
+        test: &Test,
+        orchestration: &DockerOrchestration,
+        test_type: &(&String, &String),
+        logger: &Logger,
     ) -> ToolsetResult<Verification> {
         self.trip();
-        let container_id =
-            create_verifier_container(&self.docker_config, orchestration, Mode::Verify, test_type)?;
+        let container_id = create_verifier_container(
+            &self.docker_config,
+            orchestration,
+            Mode::Verify,
+            test,
+            test_type,
+        )?;
 
         connect_container_to_network(
             &self.docker_config,
             &self.docker_config.client_docker_host,
+            &self.docker_config.client_network_mode,
             &self.docker_config.client_network_id,
             &container_id,
         )?;
@@ -532,15 +1876,10 @@ impl<'a> Benchmarker<'a> {
             test_type,
             &container_id,
             logger,
-        )?;
-
-        // This signals that the verifier exited naturally on
-        // its own, so we don't need to stop its container.
-        if let Ok(mut verifier) = self.verifier_container_id.lock() {
-            verifier.unregister();
-        }
+        );
+        self.release_container(&self.verifier_container_id, verification.is_ok());
 
-        Ok(verification)
+        verification
     }
 
     /// Requests the verifier to start for the purposes of retrieving the run
@@ -550,6 +1889,7 @@ impl<'a> Benchmarker<'a> {
     fn run_command_retrieval(
         &mut self,
         orchestration: &DockerOrchestration,
+        test: &Test,
         test_type: &(&String, &String),
         logger: &Logger,
     ) -> ToolsetResult<BenchmarkCommands> {
@@ -558,12 +1898,14 @@ impl<'a> Benchmarker<'a> {
             &self.docker_config,
             orchestration,
             Mode::Benchmark,
+            test,
             test_type,
         )?;
 
         connect_container_to_network(
             &self.docker_config,
             &self.docker_config.client_docker_host,
+            &self.docker_config.client_network_mode,
             &self.docker_config.client_network_id,
             &container_id,
         )?;
@@ -579,14 +1921,10 @@ impl<'a> Benchmarker<'a> {
             &test_type,
             &container_id,
             logger,
-        )?;
-        // This signals that the verifier exited naturally on
-        // its own, so we don't need to stop its container.
-        if let Ok(mut verifier) = self.verifier_container_id.lock() {
-            verifier.unregister();
-        }
+        );
+        self.release_container(&self.verifier_container_id, commands.is_ok());
 
-        Ok(commands)
+        commands
     }
 
     /// Starts all the underlying docker orchestration required for the given
@@ -599,6 +1937,26 @@ impl<'a> Benchmarker<'a> {
         project: &Project,
         test: &Test,
         logger: &Logger,
+    ) -> ToolsetResult<DockerOrchestration> {
+        self.tracer.start_span("start");
+        self.test_started_at = SystemTime::now();
+        let started = Instant::now();
+        let orchestration = self.orchestrate_test(project, test, logger);
+        self.metrics.timing("phase.start", started.elapsed());
+        self.tracer.end_span_with(&orchestration);
+        if let Ok(orchestration) = &orchestration {
+            logger.write_topology(&Topology::new(&self.docker_config, orchestration))?;
+        }
+
+        orchestration
+    }
+
+    /// Does the work of `start_test_orchestration`.
+    fn orchestrate_test(
+        &mut self,
+        project: &Project,
+        test: &Test,
+        logger: &Logger,
     ) -> ToolsetResult<DockerOrchestration> {
         let database_container_id = self.start_database_if_necessary(test)?;
         let mut database_ports = (None, None);
@@ -606,63 +1964,234 @@ impl<'a> Benchmarker<'a> {
             let ports = get_port_bindings_for_container(
                 &self.docker_config,
                 &self.docker_config.database_docker_host,
+                &self.docker_config.database_network_mode,
                 container_id,
+                None,
             )?;
             database_ports = (Some(ports.0), Some(ports.1));
         }
 
-        let image_id = build_image(&self.docker_config, project, test, logger)?;
+        self.tracer.start_span("build");
+        let started = Instant::now();
+        let tag = test.get_tag(&self.docker_config.image_prefix);
+        let image_id = match self.build_pipeline.take(&tag) {
+            Some(image_id) => image_id,
+            None => build_image(&self.docker_config, project, test, logger),
+        };
+        self.metrics.timing("phase.build", started.elapsed());
+        self.tracer.end_span_with(&image_id);
+        let image_id = image_id?;
 
         if let Ok(mut application_container_id) = self.application_container_id.lock() {
             application_container_id.image_id(&image_id);
         }
 
-        let container_id = create_container(
-            &self.docker_config,
-            &image_id,
-            &self.docker_config.server_network_id,
-            &self.docker_config.server_host,
-            &self.docker_config.server_docker_host,
-        )?;
+        let source_dir = self.get_source_dir(project, test, logger)?;
+        let (container_id, host_ports) =
+            self.start_application(&image_id, test, &source_dir, &database_container_id, logger)?;
+
+        Ok(DockerOrchestration {
+            host_image_id: image_id,
+            host_source_dir: source_dir,
+            host_container_id: container_id,
+            host_port: host_ports.0,
+            host_internal_port: host_ports.1,
+            database_name: test.database.clone(),
+            database_host: test.database.as_ref().map(|_| {
+                self.docker_config
+                    .get_database_host(
+                        &self.docker_config.server_docker_host,
+                        &self.docker_config.server_network_mode,
+                    )
+                    .to_string()
+            }),
+            db_container_id: database_container_id,
+            db_host_port: database_ports.0,
+            db_internal_port: database_ports.1,
+        })
+    }
 
-        let container_ids = (container_id.clone(), database_container_id);
+    /// Starts the application container for the given `image_id` and blocks
+    /// until it accepts requests, returning its container id and its host and
+    /// internal ports; both being the port given via `--override-server-url`,
+    /// if any, at which the client reaches it.
+    fn start_application(
+        &mut self,
+        image_id: &str,
+        test: &Test,
+        source_dir: &Option<String>,
+        database_container_id: &Option<String>,
+        logger: &Logger,
+    ) -> ToolsetResult<(String, (String, String))> {
+        let source_mount = match (source_dir, &test.source_mount) {
+            (Some(source_dir), Some(target)) => Some((source_dir.as_str(), target.as_str())),
+            _ => None,
+        };
+        let (container_id, host_ports) =
+            self.start_application_container(image_id, source_mount, test.port, logger)?;
 
-        connect_container_to_network(
-            &self.docker_config,
-            &self.docker_config.server_docker_host,
-            &self.docker_config.server_network_id,
-            &container_id,
+        self.wait_until_accepting_requests(
+            &(container_id.clone(), database_container_id.clone()),
+            &host_ports.0,
+            test,
         )?;
 
-        if let Ok(mut application_container_id) = self.application_container_id.lock() {
-            application_container_id.register(&container_id);
+        if let Some(shaping) = &self.docker_config.network_shaping {
+            logger.log(format!(
+                "Shaping application server network: {}",
+                shaping.to_tc_command().join(" ")
+            ))?;
+            shape_container_network(
+                &self.docker_config,
+                &self.docker_config.server_docker_host,
+                &container_id,
+                shaping,
+            )?;
+        }
+        if let Some((host, port)) = &self.docker_config.server_url_override {
+            logger.log(format!(
+                "Client will reach the application server (host port {}) at {}:{}",
+                host_ports.0, host, port
+            ))?;
+            return Ok((container_id, (port.clone(), port.clone())));
         }
 
+        Ok((container_id, host_ports))
+    }
+
+    /// Replaces the application container of the given `orchestration` with a
+    /// fresh one from the same image, leaving the database running.
+    fn restart_application(
+        &mut self,
+        orchestration: &DockerOrchestration,
+        test: &Test,
+        logger: &Logger,
+    ) -> ToolsetResult<DockerOrchestration> {
         self.trip();
-        start_container(
+        remove_container(
             &self.docker_config,
-            &container_id,
             &self.docker_config.server_docker_host,
-            logger,
+            &orchestration.host_container_id,
         )?;
-
-        let host_ports = get_port_bindings_for_container(
-            &self.docker_config,
-            &self.docker_config.server_docker_host,
-            &container_id,
+        if let Ok(mut application_container_id) = self.application_container_id.lock() {
+            application_container_id.unregister();
+        }
+        let (container_id, host_ports) = self.start_application(
+            &orchestration.host_image_id,
+            test,
+            &orchestration.host_source_dir,
+            &orchestration.db_container_id,
+            logger,
         )?;
 
-        self.wait_until_accepting_requests(&container_ids, &host_ports.0, test)?;
-
-        Ok(DockerOrchestration {
-            host_container_id: container_ids.0,
+        let orchestration = DockerOrchestration {
+            host_image_id: orchestration.host_image_id.clone(),
+            host_source_dir: orchestration.host_source_dir.clone(),
+            host_container_id: container_id,
             host_port: host_ports.0,
             host_internal_port: host_ports.1,
-            database_name: test.database.clone(),
-            db_container_id: container_ids.1,
-            db_host_port: database_ports.0,
-            db_internal_port: database_ports.1,
-        })
+            database_name: orchestration.database_name.clone(),
+            database_host: orchestration.database_host.clone(),
+            db_container_id: orchestration.db_container_id.clone(),
+            db_host_port: orchestration.db_host_port.clone(),
+            db_internal_port: orchestration.db_internal_port.clone(),
+        };
+        logger.write_topology(&Topology::new(&self.docker_config, &orchestration))?;
+
+        Ok(orchestration)
+    }
+
+    /// Creates, networks, and starts the application container for the given
+    /// `image_id`, returning its container id and its host and internal ports,
+    /// preferring the given `port` if it exposes several.
+    ///
+    /// If the container cannot be started because of a host port conflict, it
+    /// is removed and recreated up to `port_retries` times, Docker publishing
+    /// it on a fresh ephemeral port. Only bridge network mode is retried, as
+    /// Docker binds no host ports for a container in host network mode; an
+    /// application which fails to bind its own port there simply exits. The
+    /// ports are only read from the container after a successful start, so a
+    /// port assigned on retry flows through to the verifier.
+    fn start_application_container(
+        &mut self,
+        image_id: &str,
+        source_mount: Option<(&str, &str)>,
+        port: Option<u16>,
+        logger: &Logger,
+    ) -> ToolsetResult<(String, (String, String))> {
+        let core_dump_dir = self.get_core_dump_dir(logger)?;
+        let mut retries = 0;
+        loop {
+            let mut mounts = core_dump_dir
+                .iter()
+                .map(|core_dump_dir| get_core_dump_mount(core_dump_dir))
+                .collect::<Vec<_>>();
+            if let Some((source_dir, target)) = source_mount {
+                mounts.push(get_source_mount(source_dir, target));
+            }
+            let container_id = create_container(
+                &self.docker_config,
+                image_id,
+                &self.docker_config.server_network_id,
+                &self.docker_config.server_host,
+                &self.docker_config.server_docker_host,
+                &self.docker_config.server_network_mode,
+                mounts,
+            )?;
+
+            connect_container_to_network(
+                &self.docker_config,
+                &self.docker_config.server_docker_host,
+                &self.docker_config.server_network_mode,
+                &self.docker_config.server_network_id,
+                &container_id,
+            )?;
+
+            if let Ok(mut application_container_id) = self.application_container_id.lock() {
+                application_container_id.register(&container_id);
+            }
+
+            self.trip();
+            match start_container(
+                &self.docker_config,
+                &container_id,
+                &self.docker_config.server_docker_host,
+                LogPath::Server,
+                logger,
+            ) {
+                Ok(()) => {
+                    let host_ports = get_port_bindings_for_container(
+                        &self.docker_config,
+                        &self.docker_config.server_docker_host,
+                        &self.docker_config.server_network_mode,
+                        &container_id,
+                        port,
+                    )?;
+
+                    return Ok((container_id, host_ports));
+                }
+                Err(PortConflictError(message))
+                    if matches!(self.docker_config.server_network_mode, Bridge)
+                        && retries < self.docker_config.port_retries =>
+                {
+                    retries += 1;
+                    logger.error(format!(
+                        "{}; retrying ({}/{})",
+                        message, retries, self.docker_config.port_retries
+                    ))?;
+                    remove_container(
+                        &self.docker_config,
+                        &self.docker_config.server_docker_host,
+                        &container_id,
+                    )?;
+                    if let Ok(mut application_container_id) = self.application_container_id.lock() {
+                        application_container_id.unregister();
+                    }
+                    thread::sleep(Duration::from_secs(u64::from(retries)));
+                }
+                Err(e) => return Err(e),
+            }
+        }
     }
 
     /// Sentinel helper for tripping when ctrlc has been pressed. Because the
@@ -685,37 +2214,235 @@ impl<'a> Benchmarker<'a> {
         }
     }
 
+    /// Blocks for as long as the pause file (see `--pause-file`) exists;
+    /// this is only called between tests, so an operator may pause a run (e.g.
+    /// for host maintenance) without killing a test part way through.
+    fn wait_while_paused(&mut self, logger: &Logger) -> ToolsetResult<()> {
+        let pause_file = match &self.docker_config.pause_file {
+            Some(pause_file) if pause_file.exists() => pause_file.clone(),
+            _ => return Ok(()),
+        };
+        logger.log(format!(
+            "Paused; remove {} to resume",
+            pause_file.to_string_lossy()
+        ))?;
+        logger.record_event(
+            "run.paused",
+            &[("pause_file", &pause_file.to_string_lossy())],
+        )?;
+        while pause_file.exists() {
+            self.trip();
+            thread::sleep(Duration::from_secs(1));
+        }
+        logger.log("Resumed")?;
+        logger.record_event("run.resumed", &[])?;
+
+        Ok(())
+    }
+
+    /// Blocks until benchmarks may run per `--start-at` and
+    /// `--schedule-window`; this is only called between tests, so a test is
+    /// never paused part way through.
+    fn wait_for_schedule(&mut self, logger: &Logger) -> ToolsetResult<()> {
+        let wait = self
+            .docker_config
+            .schedule
+            .get_wait(Local::now().naive_local());
+        if wait <= chrono::Duration::zero() {
+            return Ok(());
+        }
+        let resume_at = Local::now() + wait;
+        logger.log(format!(
+            "Outside of the schedule; pausing until {}",
+            resume_at.format("%Y-%m-%d %H:%M")
+        ))?;
+        logger.record_event("run.paused", &[("until", &resume_at.to_rfc3339())])?;
+        while Local::now() < resume_at {
+            self.trip();
+            thread::sleep(Duration::from_secs(1));
+        }
+        logger.record_event("run.resumed", &[])?;
+
+        Ok(())
+    }
+
+    /// Releases the container registered with the given `container_id` future
+    /// once it is no longer needed.
+    ///
+    /// If it `exited` naturally on its own, it has already been removed (per
+    /// `--rm`) and is merely unregistered. Otherwise, it may still be running
+    /// and is stopped, so that it is not orphaned by the next container
+    /// registered in its place.
+    fn release_container(&self, container_id: &Arc<Mutex<DockerContainerIdFuture>>, exited: bool) {
+        if exited {
+            if let Ok(mut container_id) = container_id.lock() {
+                container_id.unregister();
+            }
+        } else {
+            stop_docker_container_future(
+                self.docker_config.docker_api.as_ref(),
+                self.docker_config.clean_up,
+                container_id,
+                &self.docker_config.logger,
+            );
+        }
+    }
+
     /// Convenience method for stopping all running containers and popping them
     /// off the running containers vec.
     fn stop_containers(&mut self) {
         stop_docker_container_future(
-            self.docker_config.use_unix_socket,
+            self.docker_config.docker_api.as_ref(),
             self.docker_config.clean_up,
             &self.verifier_container_id,
+            &self.docker_config.logger,
         );
         stop_docker_container_future(
-            self.docker_config.use_unix_socket,
+            self.docker_config.docker_api.as_ref(),
             self.docker_config.clean_up,
             &self.benchmarker_container_id,
+            &self.docker_config.logger,
         );
         stop_docker_container_future(
-            self.docker_config.use_unix_socket,
+            self.docker_config.docker_api.as_ref(),
             self.docker_config.clean_up,
             &self.application_container_id,
+            &self.docker_config.logger,
         );
         stop_docker_container_future(
-            self.docker_config.use_unix_socket,
+            self.docker_config.docker_api.as_ref(),
             self.docker_config.clean_up,
             &self.database_container_id,
+            &self.docker_config.logger,
         );
     }
 
+    /// Creates a fresh network for the given `Test`, named for this run and
+    /// the test, and points the containers of every bridged role at it until
+    /// `remove_isolated_network` is called. Does nothing unless
+    /// `--isolate-networks` was given and some role is in bridge network mode.
+    fn isolate_network(
+        &mut self,
+        test: &Test,
+        logger: &Logger,
+    ) -> ToolsetResult<Option<IsolatedNetwork>> {
+        let bridged = (
+            matches!(self.docker_config.server_network_mode, Bridge),
+            matches!(self.docker_config.database_network_mode, Bridge),
+            matches!(self.docker_config.client_network_mode, Bridge),
+        );
+        if !self.docker_config.isolate_networks || bridged == (false, false, false) {
+            return Ok(None);
+        }
+
+        let network_name = get_isolated_network_name(&self.run_uuid, test);
+        logger.log(format!("Creating network {}", network_name))?;
+        let id = create_isolated_network(&self.docker_config, &network_name)?;
+
+        let isolated_network = IsolatedNetwork {
+            id: id.clone(),
+            server_network_id: self.docker_config.server_network_id.clone(),
+            database_network_id: self.docker_config.database_network_id.clone(),
+            client_network_id: self.docker_config.client_network_id.clone(),
+        };
+        if bridged.0 {
+            self.docker_config.server_network_id = id.clone();
+        }
+        if bridged.1 {
+            self.docker_config.database_network_id = id.clone();
+        }
+        if bridged.2 {
+            self.docker_config.client_network_id = id;
+        }
+
+        Ok(Some(isolated_network))
+    }
+
+    /// Removes the given network created by `isolate_network` and restores
+    /// the network ids it replaced. Must be called after the containers of
+    /// the test have been stopped.
+    fn remove_isolated_network(
+        &mut self,
+        isolated_network: Option<IsolatedNetwork>,
+        logger: &Logger,
+    ) -> ToolsetResult<()> {
+        if let Some(isolated_network) = isolated_network {
+            self.docker_config.server_network_id = isolated_network.server_network_id;
+            self.docker_config.database_network_id = isolated_network.database_network_id;
+            self.docker_config.client_network_id = isolated_network.client_network_id;
+            if let Err(e) = remove_network(&self.docker_config, &isolated_network.id) {
+                // A leftover network should not fail the remaining tests.
+                logger.error(format!(
+                    "Failed to remove network {}: {:?}",
+                    isolated_network.id, e
+                ))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Points the database of the given `Test` at the host given for it via
+    /// `--database-hosts` until `restore_database` is called; it is started
+    /// on that host's daemon, on that daemon's network, and reached at that
+    /// host. Does nothing if no host was given for it.
+    fn relocate_database(
+        &mut self,
+        test: &Test,
+        logger: &Logger,
+    ) -> ToolsetResult<Option<RelocatedDatabase<'a>>> {
+        let (database_host, database_docker_host) = match test
+            .database
+            .as_ref()
+            .and_then(|database| self.docker_config.get_database_host_override(database))
+        {
+            Some(hosts) => hosts,
+            None => return Ok(None),
+        };
+        logger.log(format!("Using the database on {}", database_host))?;
+        let docker_api = self.docker_config.docker_api.as_ref();
+        let database_network_id = match &self.docker_config.database_network_mode {
+            Bridge => get_tfb_network_id(docker_api, &database_docker_host)?,
+            Host => get_network_id(docker_api, &database_docker_host, "host")?,
+        };
+        self.container_events
+            .subscribe(&database_docker_host, self.docker_config.use_unix_socket);
+        if let Ok(mut database_container_id) = self.database_container_id.lock() {
+            database_container_id.docker_host(&database_docker_host);
+        }
+
+        let relocated_database = RelocatedDatabase {
+            database_host: self.docker_config.database_host,
+            database_docker_host: self.docker_config.database_docker_host.clone(),
+            database_network_id: self.docker_config.database_network_id.clone(),
+        };
+        self.docker_config.database_host = database_host;
+        self.docker_config.database_docker_host = database_docker_host;
+        self.docker_config.database_network_id = database_network_id;
+
+        Ok(Some(relocated_database))
+    }
+
+    /// Restores the database host, Docker host, and network id replaced by
+    /// `relocate_database`. Must be called after the containers of the test
+    /// have been stopped.
+    fn restore_database(&mut self, relocated_database: Option<RelocatedDatabase<'a>>) {
+        if let Some(relocated_database) = relocated_database {
+            if let Ok(mut database_container_id) = self.database_container_id.lock() {
+                database_container_id.docker_host(&relocated_database.database_docker_host);
+            }
+            self.docker_config.database_host = relocated_database.database_host;
+            self.docker_config.database_docker_host = relocated_database.database_docker_host;
+            self.docker_config.database_network_id = relocated_database.database_network_id;
+        }
+    }
+
     /// Starts the database for the given `Test` if one is specified as being
     /// required by the underlying configuration file.
     fn start_database_if_necessary(&mut self, test: &Test) -> ToolsetResult<Option<String>> {
         if let Some(database) = &test.database {
             let mut logger = Logger::with_prefix(&database);
-            let image_name = format!("techempower/tfb.database.{}", database.to_lowercase());
+            let image_name = self.docker_config.get_database_image(database);
             logger.log(format!("Pulling {}; this may take some time.", &image_name))?;
             pull_image(
                 &self.docker_config,
@@ -723,17 +2450,30 @@ impl<'a> Benchmarker<'a> {
                 &image_name,
             )?;
 
+            let mut mounts = Vec::new();
+            if let Some(size) = self.docker_config.database_tmpfs_size {
+                match get_database_data_dir(&database.to_lowercase()) {
+                    Some(data_dir) => mounts.push(get_tmpfs_mount(data_dir, Some(size))),
+                    None => logger.error(format!(
+                        "Cannot mount tmpfs; the data directory of {} is unknown",
+                        database
+                    ))?,
+                }
+            }
             let container_id = create_container(
                 &self.docker_config,
                 &image_name,
                 &self.docker_config.database_network_id,
                 &self.docker_config.database_host,
                 &self.docker_config.database_docker_host,
+                &self.docker_config.database_network_mode,
+                mounts,
             )?;
 
             connect_container_to_network(
                 &self.docker_config,
                 &self.docker_config.database_docker_host,
+                &self.docker_config.database_network_mode,
                 &self.docker_config.database_network_id,
                 &container_id,
             )?;
@@ -749,6 +2489,7 @@ impl<'a> Benchmarker<'a> {
                 &self.docker_config,
                 &container_id,
                 &self.docker_config.database_docker_host,
+                LogPath::Database,
                 &logger,
             )?;
 
@@ -760,6 +2501,7 @@ impl<'a> Benchmarker<'a> {
             connect_container_to_network(
                 &self.docker_config,
                 &self.docker_config.client_docker_host,
+                &self.docker_config.client_network_mode,
                 &self.docker_config.client_network_id,
                 &verifier_container_id,
             )?;
@@ -785,28 +2527,33 @@ impl<'a> Benchmarker<'a> {
         Ok(None)
     }
 
-    /// Blocks the current thread until either the operation times out or `Test`
-    /// responds successfully (200).
+    /// Blocks the current thread until either the operation times out (see
+    /// `--startup-timeout`) or `Test` responds successfully (200).
     fn wait_until_accepting_requests(
         &mut self,
         container_ids: &(String, Option<String>),
         host_port: &str,
         test: &Test,
     ) -> ToolsetResult<()> {
+        let timeout = self.docker_config.get_startup_timeout(test);
         let mut slept_for = 0;
         loop {
             self.trip();
-            let inspect = inspect_container(
-                &container_ids.0,
-                &self.docker_config.server_docker_host,
-                self.docker_config.use_unix_socket,
-                Simple::new(),
-            )?;
-            if !inspect.state.running {
-                return Err(AppServerContainerShutDownError);
+            if self.container_events.is_subscribed() {
+                if self.container_events.get_death(&container_ids.0).is_some() {
+                    return Err(AppServerContainerShutDownError);
+                }
+            } else {
+                let inspection = self
+                    .docker_config
+                    .docker_api
+                    .inspect_container(&container_ids.0, &self.docker_config.server_docker_host)?;
+                if !inspection.running {
+                    return Err(AppServerContainerShutDownError);
+                }
             }
             self.trip();
-            if slept_for > 60 {
+            if slept_for > timeout {
                 self.trip();
                 self.stop_containers();
 
@@ -814,10 +2561,19 @@ impl<'a> Benchmarker<'a> {
             }
             let mut easy = Easy2::new(Simple::new());
 
+            // gRPC servers cannot be expected to answer an HTTP/1.1 request,
+            // so we only check that they accept connections. WebSocket
+            // endpoints will at least respond to the (non-upgrade) request.
+            let mut connect_only = true;
             let mut endpoint = String::new();
-            if let Some(key) = test.urls.keys().next() {
+            if let Some(key) = test
+                .urls
+                .keys()
+                .find(|test_type| test.get_protocol(test_type) != Protocol::Grpc)
+            {
                 if let Some(_endpoint) = test.urls.get(key) {
                     endpoint = _endpoint.clone();
+                    connect_only = false;
                 }
             }
 
@@ -830,15 +2586,645 @@ impl<'a> Benchmarker<'a> {
             };
             easy.url(&url)?;
             easy.timeout(time::Duration::from_secs(1))?;
-            let _ = easy.perform();
-
-            if let Ok(code) = easy.response_code() {
-                if code > 0 {
+            if connect_only {
+                easy.connect_only(true)?;
+                if easy.perform().is_ok() {
                     return Ok(());
                 }
+            } else {
+                let _ = easy.perform();
+
+                if let Ok(code) = easy.response_code() {
+                    if code > 0 {
+                        return Ok(());
+                    }
+                }
             }
             slept_for += 1;
             thread::sleep(Duration::from_secs(1));
         }
     }
 }
+
+/// Logs the socket errors of the run, warning when they are widespread.
+fn report_socket_errors(socket_errors: &SocketErrorSummary, logger: &Logger) -> ToolsetResult<()> {
+    let summary = format!(
+        "Socket errors in {} of {} benchmark commands: connect {}, read {}, write {}, timeout {}",
+        socket_errors.commands_with_errors,
+        socket_errors.commands,
+        socket_errors.connect,
+        socket_errors.read,
+        socket_errors.write,
+        socket_errors.timeout
+    );
+    if socket_errors.is_widespread() {
+        logger.error(format!(
+            "{}; errors this widespread usually indicate a problem with the environment (e.g. the network or file descriptor limits) rather than with the frameworks",
+            summary
+        ))
+    } else {
+        logger.log(summary)
+    }
+}
+
+/// Gets the `projects` with only the test types which have not succeeded in
+/// `results`, dropping any tests and projects left without any; only those
+/// of a resumed run (see `--resume`) have. Test types which failed are run
+/// again.
+fn skip_completed_projects(
+    projects: &[Project],
+    results: &Results,
+    logger: &Logger,
+) -> ToolsetResult<Vec<Project>> {
+    let mut remaining = Vec::new();
+    for project in projects {
+        let mut tests = Vec::new();
+        for test in &project.tests {
+            let mut test = test.clone();
+            let mut completed: Vec<String> = test
+                .urls
+                .keys()
+                .filter(|test_type| results.has_succeeded(project, &test, test_type))
+                .cloned()
+                .collect();
+            if completed.is_empty() {
+                tests.push(test);
+                continue;
+            }
+            completed.sort();
+            logger.log(format!(
+                "Skipping {} ({}); completed by the run being resumed",
+                test.get_name(),
+                completed.join(", ")
+            ))?;
+            test.urls
+                .retain(|test_type, _| !completed.contains(test_type));
+            if !test.urls.is_empty() {
+                tests.push(test);
+            }
+        }
+        if !tests.is_empty() {
+            remaining.push(Project {
+                tests,
+                ..project.clone()
+            });
+        }
+    }
+
+    Ok(remaining)
+}
+
+/// Describes the given `death`; e.g. "exit code 137 (OOM killed)".
+fn describe_container_death(death: &ContainerDeath) -> String {
+    let exit_code = match death.exit_code {
+        Some(exit_code) => format!("exit code {}", exit_code),
+        None => "unknown exit code".to_string(),
+    };
+    if death.oom_killed {
+        format!("{} (OOM killed)", exit_code)
+    } else {
+        exit_code
+    }
+}
+
+/// Gets the concurrency of the probe with the highest throughput.
+fn get_peak_concurrency(probes: &BTreeMap<u32, BenchmarkResults>) -> u32 {
+    probes
+        .iter()
+        .fold(
+            None,
+            |peak: Option<(u32, f32)>, (concurrency, result)| match peak {
+                Some((_, rps)) if rps >= result.requests_per_second => peak,
+                _ => Some((*concurrency, result.requests_per_second)),
+            },
+        )
+        .map(|(concurrency, _)| concurrency)
+        .unwrap_or_default()
+}
+
+/// Probes concurrencies via `probe` for the one at which throughput peaks,
+/// returning every probe. Concurrency is doubled, starting from `lowest`,
+/// until throughput stops improving or `max_concurrency` is reached, after
+/// which the midpoints either side of the best measured concurrency are
+/// probed until they converge or `MAX_TUNING_PROBES` is reached.
+fn search_concurrency(
+    lowest: u32,
+    max_concurrency: u32,
+    mut probe: impl FnMut(u32) -> ToolsetResult<BenchmarkResults>,
+) -> ToolsetResult<BTreeMap<u32, BenchmarkResults>> {
+    let mut probes: BTreeMap<u32, BenchmarkResults> = BTreeMap::new();
+    let mut concurrency = lowest.min(max_concurrency);
+    let mut best_rps = 0f32;
+    loop {
+        let result = probe(concurrency)?;
+        let rps = result.requests_per_second;
+        probes.insert(concurrency, result);
+        if rps <= best_rps * (1f32 + TUNING_TOLERANCE) || concurrency >= max_concurrency {
+            break;
+        }
+        best_rps = rps;
+        concurrency = (concurrency * 2).min(max_concurrency);
+    }
+
+    while probes.len() < MAX_TUNING_PROBES {
+        let best = get_peak_concurrency(&probes);
+        let lower = probes.range(..best).next_back().map(|(c, _)| *c);
+        let upper = probes.range(best + 1..).next().map(|(c, _)| *c);
+        let candidates: Vec<u32> = vec![
+            lower.map(|lower| (lower + best) / 2),
+            upper.map(|upper| (best + upper) / 2),
+        ]
+        .into_iter()
+        .flatten()
+        .filter(|candidate| !probes.contains_key(candidate))
+        .collect();
+        if candidates.is_empty() {
+            break;
+        }
+        for candidate in candidates {
+            probes.insert(candidate, probe(candidate)?);
+        }
+    }
+
+    Ok(probes)
+}
+
+/// Runs a benchmark of `interval` seconds via `run` repeatedly until the
+/// coefficient of variation of the requests/sec across intervals falls below
+/// `threshold`, a benchmark stalls, or another interval would run past
+/// `max_duration` seconds.
+///
+/// The results are those of the final interval, with the request totals,
+/// duration, and requests/sec combined across every interval.
+fn run_until_stable(
+    (interval, max_duration): (u32, u32),
+    threshold: f32,
+    mut run: impl FnMut() -> ToolsetResult<BenchmarkResults>,
+    logger: &Logger,
+) -> ToolsetResult<BenchmarkResults> {
+    let mut intervals: Vec<BenchmarkResults> = Vec::new();
+    let mut elapsed = 0;
+    loop {
+        let result = run()?;
+        elapsed += interval;
+        let stalled = result.stalled;
+        intervals.push(result);
+        if stalled {
+            break;
+        }
+        let rps: Vec<f32> = intervals
+            .iter()
+            .map(|interval| interval.requests_per_second)
+            .collect();
+        // Fewer than three intervals say little about the variance.
+        if rps.len() >= 3 && coefficient_of_variation(&rps) < threshold {
+            logger.log(format!(
+                "Requests/sec stabilized after {} intervals ({}s)",
+                rps.len(),
+                elapsed
+            ))?;
+            break;
+        }
+        if elapsed + interval > max_duration {
+            logger.log(format!(
+                "Requests/sec did not stabilize within {}s",
+                max_duration
+            ))?;
+            break;
+        }
+    }
+
+    let start_time = intervals.first().map(|first| first.start_time);
+    let total_requests = intervals.iter().map(|i| i.total_requests).sum::<u32>();
+    let duration = intervals.iter().map(|i| i.duration).sum::<f32>();
+    let interval_rps: Vec<f32> = intervals.iter().map(|i| i.requests_per_second).collect();
+    let mut result = intervals.pop().unwrap();
+    result.start_time = start_time.unwrap_or(result.start_time);
+    result.total_requests = total_requests;
+    result.duration = duration;
+    if duration > 0f32 {
+        result.requests_per_second = total_requests as f32 / duration;
+    }
+    result.interval_requests_per_second = Some(interval_rps);
+
+    Ok(result)
+}
+
+/// Returns whether `current` requests/sec is within `tolerance` (a fraction)
+/// of `previous`.
+fn has_converged(previous: f32, current: f32, tolerance: f32) -> bool {
+    if previous == 0f32 {
+        return current == 0f32;
+    }
+
+    ((current - previous) / previous).abs() <= tolerance
+}
+
+/// Gets the coefficient of variation (standard deviation over mean) of the
+/// given `values`; zero when there are none or their mean is zero.
+fn coefficient_of_variation(values: &[f32]) -> f32 {
+    if values.is_empty() {
+        return 0f32;
+    }
+    let mean = values.iter().sum::<f32>() / values.len() as f32;
+    if mean == 0f32 {
+        return 0f32;
+    }
+    let variance = values
+        .iter()
+        .map(|value| (value - mean).powi(2))
+        .sum::<f32>()
+        / values.len() as f32;
+
+    variance.sqrt() / mean
+}
+
+/// Puts back the `core_pattern` of `docker_host` replaced for `--core-dumps`,
+/// if any, logging it should that fail so that it can be put back by hand.
+fn put_back_core_pattern(
+    docker_api: &dyn DockerApi,
+    docker_host: &str,
+    core_pattern: &Arc<Mutex<Option<String>>>,
+    logger: &Logger,
+) {
+    let core_pattern = match core_pattern.lock() {
+        Ok(mut core_pattern) => core_pattern.take(),
+        Err(_) => None,
+    };
+    if let Some(core_pattern) = core_pattern {
+        if let Err(e) = restore_core_pattern(docker_api, docker_host, &core_pattern) {
+            let _ = logger.error(format!(
+                "Failed to restore the server host's core_pattern to {}: {:?}",
+                core_pattern, e
+            ));
+        }
+    }
+}
+
+/// Gets the core dumps in `core_dump_dir` written since the given time, in
+/// order of their names.
+fn get_core_dumps(core_dump_dir: &Path, since: SystemTime) -> Vec<PathBuf> {
+    let mut core_dumps: Vec<PathBuf> = match std::fs::read_dir(core_dump_dir) {
+        Ok(entries) => entries
+            .flatten()
+            .filter(|entry| {
+                entry
+                    .metadata()
+                    .and_then(|metadata| metadata.modified())
+                    .is_ok_and(|modified| modified >= since)
+            })
+            .map(|entry| entry.path())
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    core_dumps.sort();
+
+    core_dumps
+}
+
+//
+// TESTS
+//
+
+#[cfg(test)]
+mod tests {
+    use crate::benchmarker::modes::CICD;
+    use crate::benchmarker::{
+        coefficient_of_variation, get_core_dumps, get_peak_concurrency, run_until_stable,
+        search_concurrency, skip_completed_projects, Benchmarker, MAX_TUNING_PROBES,
+    };
+    use crate::config::{Named, Project, Test};
+    use crate::docker::image::{build_image, BuildPipeline};
+    use crate::docker::listener::benchmarker::BenchmarkResults;
+    use crate::docker::mock::mock_docker_config;
+    use crate::docker::BenchmarkCommands;
+    use crate::error::ToolsetError::VerificationFailedException;
+    use crate::io::{Logger, TempDir};
+    use crate::metadata::list_projects_by_test_name;
+    use crate::results::{Results, STATUS_FAILED, STATUS_SUCCEEDED};
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    /// Tunes against the given throughput `curve`, returning the concurrency
+    /// found to peak and every concurrency probed.
+    fn tune(curve: impl Fn(u32) -> f32, lowest: u32, max_concurrency: u32) -> (u32, Vec<u32>) {
+        let probes = search_concurrency(lowest, max_concurrency, |concurrency| {
+            Ok(BenchmarkResults {
+                requests_per_second: curve(concurrency),
+                ..BenchmarkResults::default()
+            })
+        })
+        .unwrap();
+
+        (get_peak_concurrency(&probes), probes.into_keys().collect())
+    }
+
+    #[test]
+    fn it_can_find_a_peak_in_the_middle_of_the_range() {
+        let (peak, probed) = tune(
+            |concurrency| 100_000f32 - (concurrency as f32 - 300f32).powi(2),
+            16,
+            4096,
+        );
+
+        assert!((280..=320).contains(&peak), "peaked at {}", peak);
+        assert!(probed.len() <= MAX_TUNING_PROBES);
+        // Doubling stops once throughput falls, at 512.
+        assert!(probed.contains(&512));
+        assert!(!probed.contains(&1024));
+    }
+
+    #[test]
+    fn it_can_cap_the_concurrency_of_a_rising_curve() {
+        let (peak, probed) = tune(|concurrency| concurrency as f32 * 100f32, 16, 1000);
+
+        assert_eq!(peak, 1000);
+        assert_eq!(
+            probed,
+            vec![16, 32, 64, 128, 256, 512, 756, 878, 939, 969, 984, 1000]
+        );
+        // The lowest level is capped too.
+        assert_eq!(tune(|_| 1f32, 64, 32), (32, vec![32]));
+    }
+
+    #[test]
+    fn it_can_rewrite_the_concurrency_of_wrk_commands() {
+        let command: Vec<String> = [
+            "wrk",
+            "-H",
+            "Host: tfb-server",
+            "-d",
+            "15",
+            "-c",
+            "512",
+            "-t",
+            "8",
+            "http://tfb-server:8080/json",
+        ]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+
+        assert_eq!(
+            BenchmarkCommands::with_concurrency(&command, 64).join(" "),
+            "wrk -H Host: tfb-server -d 15 -c 64 -t 8 http://tfb-server:8080/json"
+        );
+        // wrk refuses to run with fewer connections than threads.
+        assert_eq!(
+            BenchmarkCommands::with_concurrency(&command, 4).join(" "),
+            "wrk -H Host: tfb-server -d 15 -c 4 -t 4 http://tfb-server:8080/json"
+        );
+    }
+
+    #[test]
+    fn it_can_measure_the_variation_of_requests_per_second() {
+        assert_eq!(coefficient_of_variation(&[]), 0f32);
+        assert_eq!(coefficient_of_variation(&[120_000f32]), 0f32);
+        assert_eq!(coefficient_of_variation(&[0f32, 0f32, 0f32]), 0f32);
+        assert!((coefficient_of_variation(&[90f32, 110f32]) - 0.1).abs() < 0.0001);
+    }
+
+    /// Benchmarks each of the given requests/sec in turn for `interval` seconds
+    /// until stable, returning the requests/sec of each interval run.
+    fn run_intervals(rps: &[f32], interval: u32, max_duration: u32) -> Vec<f32> {
+        let mut rps = rps.iter();
+        let result = run_until_stable(
+            (interval, max_duration),
+            0.05,
+            || {
+                let requests_per_second = *rps.next().unwrap();
+                Ok(BenchmarkResults {
+                    requests_per_second,
+                    total_requests: (requests_per_second * interval as f32) as u32,
+                    duration: interval as f32,
+                    ..BenchmarkResults::default()
+                })
+            },
+            &Logger::default(),
+        )
+        .unwrap();
+
+        result.interval_requests_per_second.unwrap()
+    }
+
+    #[test]
+    fn it_can_run_intervals_until_requests_per_second_converge() {
+        let mut rps = vec![90f32, 110f32];
+        rps.extend([100f32; 10].iter());
+        // The deviation of the first two shrinks below 5% after nine.
+        assert_eq!(run_intervals(&rps, 5, 60), rps[..9].to_vec());
+        // Steady from the start, but three intervals are needed to say so.
+        assert_eq!(run_intervals(&[100f32; 5], 5, 60).len(), 3);
+    }
+
+    #[test]
+    fn it_can_stop_intervals_at_the_max_duration() {
+        let rps = [100f32, 50f32, 100f32, 50f32, 100f32, 50f32];
+
+        // A third interval would run past 14s.
+        assert_eq!(run_intervals(&rps, 5, 14), vec![100f32, 50f32]);
+        assert_eq!(run_intervals(&rps, 5, 15).len(), 3);
+    }
+
+    #[test]
+    fn it_can_resume_a_partially_completed_run() {
+        let mut projects = list_projects_by_test_name(Some("gemini".to_string()), &[]).unwrap();
+        projects.extend(list_projects_by_test_name(Some("gemini-mysql".to_string()), &[]).unwrap());
+        let (main, mysql) = (&projects[0], &projects[1]);
+        let mut results = Results::default();
+        for test_type in &["json", "plaintext"] {
+            results.record_status(main, &main.tests[0], test_type, STATUS_SUCCEEDED);
+        }
+        results.record_status(mysql, &mysql.tests[0], "db", STATUS_SUCCEEDED);
+        results.record_status(mysql, &mysql.tests[0], "query", STATUS_FAILED);
+
+        let remaining = skip_completed_projects(&projects, &results, &Logger::default()).unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].tests[0].get_name(), "gemini-mysql");
+        let mut test_types: Vec<&String> = remaining[0].tests[0].urls.keys().collect();
+        test_types.sort();
+        assert_eq!(
+            test_types,
+            vec!["cached_query", "fortune", "query", "update"]
+        );
+
+        let (_, _, docker_config) = mock_docker_config(&[]);
+        let benchmarker = Benchmarker::new(docker_config, vec![], CICD);
+        let error = VerificationFailedException;
+        for _ in 0..2 {
+            benchmarker.report_benchmark_error(
+                &mut results,
+                mysql,
+                &mysql.tests[0],
+                "query",
+                &error,
+                &Logger::default(),
+            );
+        }
+        assert_eq!(results.failed["query"], vec!["gemini-mysql".to_string()]);
+
+        benchmarker.report_benchmark_success(
+            &mut results,
+            vec![BenchmarkResults::default()],
+            mysql,
+            &mysql.tests[0],
+            "query",
+            &Logger::default(),
+        );
+        assert!(results.failed["query"].is_empty());
+        assert!(results.failures["query"].is_empty());
+    }
+
+    #[test]
+    fn it_can_stop_registered_containers() {
+        let (_, docker_api, docker_config) = mock_docker_config(&[
+            "--server-docker-host",
+            "tfb-server",
+            "--database-docker-host",
+            "tfb-database",
+        ]);
+        let mut benchmarker = Benchmarker::new(docker_config, vec![], CICD);
+        if let Ok(mut application_container_id) = benchmarker.application_container_id.lock() {
+            application_container_id.register("application");
+        }
+        if let Ok(mut database_container_id) = benchmarker.database_container_id.lock() {
+            database_container_id.register("database");
+        }
+
+        benchmarker.stop_containers();
+
+        let operations = docker_api.get_operations();
+        assert!(operations.contains(&"kill container application on tfb-server:2375".to_string()));
+        assert!(operations.contains(&"kill container database on tfb-database:2375".to_string()));
+        assert!(benchmarker
+            .application_container_id
+            .lock()
+            .unwrap()
+            .get_container_id()
+            .is_none());
+    }
+
+    #[test]
+    fn it_can_stop_a_relocated_database_on_its_own_host() {
+        let (_, docker_api, docker_config) = mock_docker_config(&[
+            "--network-mode",
+            "host",
+            "--database-docker-host",
+            "tcp://tfb-database",
+            "--database-hosts",
+            "postgres=10.0.0.5",
+        ]);
+        let projects =
+            list_projects_by_test_name(Some("gemini-postgres".to_string()), &[]).unwrap();
+        let mut benchmarker = Benchmarker::new(docker_config, projects.clone(), CICD);
+        let logger = Logger::default();
+
+        let relocated_database = benchmarker
+            .relocate_database(&projects[0].tests[0], &logger)
+            .unwrap();
+        if let Ok(mut database_container_id) = benchmarker.database_container_id.lock() {
+            database_container_id.register("database");
+        }
+        benchmarker.stop_containers();
+        benchmarker.restore_database(relocated_database);
+        if let Ok(mut database_container_id) = benchmarker.database_container_id.lock() {
+            database_container_id.register("database");
+        }
+        benchmarker.stop_containers();
+
+        let operations = docker_api.get_operations();
+        assert!(operations.contains(&"inspect network host on 10.0.0.5:2375".to_string()));
+        assert!(operations.contains(&"kill container database on 10.0.0.5:2375".to_string()));
+        assert!(operations.contains(&"kill container database on tfb-database:2375".to_string()));
+        assert_eq!(
+            benchmarker.docker_config.database_docker_host,
+            "tfb-database:2375"
+        );
+    }
+
+    #[test]
+    fn it_can_list_the_core_dumps_of_a_test() {
+        let core_dump_dir = TempDir::new("cores");
+        std::fs::write(core_dump_dir.join("core.gemini.42.1600000000"), "").unwrap();
+        let now = SystemTime::now();
+
+        let since_epoch = get_core_dumps(&core_dump_dir, UNIX_EPOCH);
+        let since_later = get_core_dumps(&core_dump_dir, now + Duration::from_secs(60));
+
+        assert_eq!(
+            since_epoch,
+            vec![core_dump_dir.join("core.gemini.42.1600000000")]
+        );
+        assert!(since_later.is_empty());
+    }
+
+    #[test]
+    fn it_can_move_the_application_to_another_port_on_conflict() {
+        let (_, docker_api, docker_config) =
+            mock_docker_config(&["--network-mode", "bridge", "--port-retries", "1"]);
+        docker_api.fail_once(
+            "start container",
+            "Bind for 0.0.0.0:32768 failed: port is already allocated",
+        );
+        let mut benchmarker = Benchmarker::new(docker_config, vec![], CICD);
+
+        let (container_id, host_ports) = benchmarker
+            .start_application_container("gemini", None, Some(8080), &Logger::default())
+            .unwrap();
+
+        let operations = docker_api.get_operations();
+        assert_eq!(container_id, "mock-2");
+        assert_eq!(host_ports, ("32769".to_string(), "8080".to_string()));
+        assert!(operations.contains(&"delete container mock-1 on localhost:2375".to_string()));
+    }
+
+    #[test]
+    fn it_can_tag_images_with_the_run_id() {
+        let (_, docker_api, docker_config) =
+            mock_docker_config(&["--image-prefix", "registry.example.com/tfb-{run}/"]);
+        let projects = list_projects_by_test_name(Some("gemini".to_string()), &[]).unwrap();
+        let benchmarker = Benchmarker::new(docker_config, projects.clone(), CICD);
+
+        build_image(
+            &benchmarker.docker_config,
+            &projects[0],
+            &projects[0].tests[0],
+            &Logger::default(),
+        )
+        .unwrap();
+
+        assert!(docker_api.get_operations().contains(&format!(
+            "build image registry.example.com/tfb-{}/gemini on localhost:2375",
+            benchmarker.run_uuid
+        )));
+    }
+
+    #[test]
+    fn it_can_build_upcoming_images_in_the_background() {
+        let (_, docker_api, docker_config) = mock_docker_config(&["--build-jobs", "1"]);
+        let projects = list_projects_by_test_name(Some("gemini*".to_string()), &[]).unwrap();
+        let upcoming: Vec<(&Project, &Test)> = projects[0]
+            .tests
+            .iter()
+            .map(|test| (&projects[0], test))
+            .collect();
+        let mut pipeline = BuildPipeline::new(docker_config.build_jobs);
+
+        pipeline.prebuild(&docker_config, &upcoming).unwrap();
+        pipeline.prebuild(&docker_config, &upcoming).unwrap();
+
+        let first = upcoming[0].1.get_tag(&docker_config.image_prefix);
+        let second = upcoming[1].1.get_tag(&docker_config.image_prefix);
+        assert_eq!(pipeline.take(&first).unwrap().unwrap(), "mock-1");
+        assert!(pipeline.take(&first).is_none());
+        assert!(pipeline.take(&second).is_none());
+        let builds: Vec<String> = docker_api
+            .get_operations()
+            .into_iter()
+            .filter(|operation| operation.starts_with("build image"))
+            .collect();
+        assert_eq!(
+            builds,
+            vec![format!("build image {} on localhost:2375", first)]
+        );
+    }
+}
@@ -1,18 +1,27 @@
 use crate::benchmarker::{modes, Benchmarker};
+use crate::config::Project;
 use crate::docker::docker_config::DockerConfig;
-use crate::error::ToolsetError::UnknownBenchmarkerModeError;
+use crate::docker::dry_run::DryRunDockerApi;
+use crate::error::ToolsetError::{NoProjectsSelectedError, UnknownBenchmarkerModeError};
 use crate::error::ToolsetResult;
-use crate::io::get_tfb_dir;
-use crate::{io, metadata, options};
+use crate::io::{get_tfb_dir, Logger};
+use crate::options::log_formats;
+use crate::{audit, compare, io, metadata, options, parse, plan, status};
+use clap::ArgMatches;
+use std::path::Path;
+use std::sync::Arc;
 
 /// Runs the CLI matching the arguments/options passed and handling each.
 pub fn run() -> ToolsetResult<()> {
     let mut app = options::parse();
     let matches = app.clone().get_matches();
+    metadata::set_strict_config(matches.is_present(options::args::STRICT_CONFIG));
+    io::set_json_logs(matches.value_of(options::args::LOG_FORMAT) == Some(log_formats::JSON));
 
     if matches.is_present(options::args::AUDIT) {
-        // todo
-        println!("AUDIT");
+        if audit::run()? > 0 {
+            std::process::exit(1);
+        }
         Ok(())
     } else if matches.is_present(options::args::CLEAN) {
         let mut tfb_dir = get_tfb_dir()?;
@@ -27,22 +36,70 @@ pub fn run() -> ToolsetResult<()> {
         io::print_all_tests_for_framework(framework)
     } else if let Some(tag) = matches.value_of(options::args::LIST_TESTS_WITH_TAG) {
         io::print_all_tests_with_tag(tag)
-    } else if matches.is_present(options::args::PARSE_RESULTS) {
-        // todo
-        println!("PARSE_RESULTS");
+    } else if let Some(timestamp) = matches.value_of(options::args::PARSE_RESULTS) {
+        parse::run(timestamp)
+    } else if let Some(mut results_dirs) = matches.values_of(options::args::COMPARE) {
+        let before = Path::new(results_dirs.next().unwrap());
+        let after = Path::new(results_dirs.next().unwrap());
+        let threshold = str::parse::<f64>(
+            matches
+                .value_of(options::args::REGRESSION_THRESHOLD)
+                .unwrap(),
+        )
+        .unwrap();
+        if compare::run(before, after, threshold)? > 0 {
+            std::process::exit(1);
+        }
         Ok(())
+    } else if matches.is_present(options::args::STATUS) {
+        status::run(matches.value_of(options::args::STATUS).map(Path::new))
+    } else if matches.is_present(options::args::DRY_RUN) {
+        let projects = list_projects_to_run(&matches)?;
+        let docker_config = DockerConfig::with_docker_api(&matches, |_| Arc::new(DryRunDockerApi))?;
+        let mode = matches
+            .value_of(options::args::MODE)
+            .unwrap_or(modes::BENCHMARK);
+        plan::run(&docker_config, &projects, mode)
+    } else if matches.is_present(options::args::PRINT_COMMANDS) {
+        let projects = list_projects_to_run(&matches)?;
+        let docker_config = DockerConfig::new(&matches)?;
+        let mode = matches
+            .value_of(options::args::MODE)
+            .unwrap_or(modes::BENCHMARK);
+        let mut benchmarker = Benchmarker::new(docker_config, projects, mode);
+        benchmarker.print_commands()
     } else if let Some(mode) = matches.value_of(options::args::MODE) {
-        let docker_config = DockerConfig::new(&matches);
-        let projects = metadata::list_projects_to_run(&matches);
+        let projects = list_projects_to_run(&matches)?;
+        let docker_config = DockerConfig::new(&matches)?;
         let mut benchmarker = Benchmarker::new(docker_config, projects, mode);
-        match mode {
+        let result = match mode {
             modes::BENCHMARK => benchmarker.benchmark(),
             modes::VERIFY | modes::CICD => benchmarker.verify(),
             modes::DEBUG => benchmarker.debug(),
+            modes::TUNE => benchmarker.tune(),
             _ => Err(UnknownBenchmarkerModeError(mode.to_string())),
-        }
+        };
+        benchmarker.restore_core_pattern();
+
+        result
     } else {
         app.print_help().unwrap();
         Ok(())
     }
 }
+
+//
+// PRIVATES
+//
+
+/// Lists the projects selected by `matches`. If none are, this explains why
+/// and exits rather than starting a run with nothing to do.
+fn list_projects_to_run(matches: &ArgMatches) -> ToolsetResult<Vec<Project>> {
+    match metadata::list_projects_to_run(matches) {
+        Err(error @ NoProjectsSelectedError(..)) => {
+            Logger::default().error(error)?;
+            std::process::exit(1);
+        }
+        result => result,
+    }
+}
@@ -0,0 +1,246 @@
+//! The compare module compares the results of two runs (see `--compare`),
+//! printing the change in the peak throughput and latency of each test type
+//! of each framework benchmarked by both, and flagging regressions beyond a
+//! threshold (see `--regression-threshold`).
+
+use crate::error::ToolsetResult;
+use crate::io::Logger;
+use crate::results::{BenchmarkData, Results};
+use colored::Colorize;
+use std::path::Path;
+
+/// Compares the `results.json` of the results directory `after` to that of
+/// `before`, logging the change of each test type of each framework; returns
+/// the number of regressions, those whose requests/sec dropped or whose
+/// latency rose by more than `threshold` percent.
+pub fn run(before: &Path, after: &Path, threshold: f64) -> ToolsetResult<usize> {
+    let logger = Logger::default();
+    let (before, after) = (Results::load(before)?, Results::load(after)?);
+
+    let deltas = get_deltas(&before, &after);
+    logger.log(format!(
+        "{:14} {:24} {:>12} {:>12} {:>8} {:>10} {:>10} {:>8}",
+        "Test type", "Framework", "Req/sec", "(before)", "Change", "Latency", "(before)", "Change"
+    ))?;
+    let mut regressions = 0;
+    for delta in &deltas {
+        let line = format!(
+            "{:14} {:24} {:>12.0} {:>12.0} {:>7.1}% {:>10} {:>10} {:>8}",
+            delta.test_type,
+            delta.framework,
+            delta.after.0,
+            delta.before.0,
+            get_change(delta.before.0, delta.after.0),
+            format_latency(delta.after.1),
+            format_latency(delta.before.1),
+            match (delta.before.1, delta.after.1) {
+                (Some(before), Some(after)) => format!("{:.1}%", get_change(before, after)),
+                _ => String::default(),
+            }
+        );
+        if delta.regressed(threshold) {
+            regressions += 1;
+            logger.log(format!("{} {}", line, "REGRESSION".red()))?;
+        } else {
+            logger.log(line)?;
+        }
+    }
+
+    logger.log(format!(
+        "{} of {} test type(s) regressed by more than {}%",
+        regressions,
+        deltas.len(),
+        threshold
+    ))?;
+
+    Ok(regressions)
+}
+
+//
+// PRIVATES
+//
+
+/// The peak requests/sec, and average latency in milliseconds at that peak,
+/// of a test type of a framework in two runs.
+struct Delta<'a> {
+    test_type: &'a str,
+    framework: &'a str,
+    before: (f64, Option<f64>),
+    after: (f64, Option<f64>),
+}
+impl<'a> Delta<'a> {
+    /// Returns whether the requests/sec dropped, or the latency rose, by more
+    /// than `threshold` percent.
+    fn regressed(&self, threshold: f64) -> bool {
+        let latency_rose = match (self.before.1, self.after.1) {
+            (Some(before), Some(after)) => get_change(before, after) > threshold,
+            _ => false,
+        };
+
+        get_change(self.before.0, self.after.0) < -threshold || latency_rose
+    }
+}
+
+/// Gets the `Delta` of each test type of each framework benchmarked in both
+/// `before` and `after`, ordered by test type and framework.
+fn get_deltas<'a>(before: &'a Results, after: &'a Results) -> Vec<Delta<'a>> {
+    let mut deltas = Vec::new();
+    for (test_type, frameworks) in &after.raw_data {
+        for (framework, data) in frameworks {
+            let before = before
+                .raw_data
+                .get(test_type)
+                .and_then(|frameworks| frameworks.get(framework))
+                .and_then(|data| get_peak(data));
+            if let (Some(before), Some(after)) = (before, get_peak(data)) {
+                deltas.push(Delta {
+                    test_type,
+                    framework,
+                    before,
+                    after,
+                });
+            }
+        }
+    }
+    deltas.sort_by(|a, b| (a.test_type, a.framework).cmp(&(b.test_type, b.framework)));
+
+    deltas
+}
+
+/// Gets the peak requests/sec of the given benchmark commands, and the
+/// average latency in milliseconds of the command which reached it.
+fn get_peak(data: &[BenchmarkData]) -> Option<(f64, Option<f64>)> {
+    data.iter()
+        .filter(|data| data.total_requests > 0)
+        .max_by(|a, b| {
+            a.get_requests_per_second()
+                .total_cmp(&b.get_requests_per_second())
+        })
+        .map(|data| {
+            (
+                data.get_requests_per_second(),
+                parse_latency(&data.latency_avg),
+            )
+        })
+}
+
+/// Parses the given latency, as reported by `wrk` (e.g. `210.00us`), into
+/// milliseconds.
+fn parse_latency(latency: &str) -> Option<f64> {
+    let unit_index = latency.find(|c: char| c.is_ascii_alphabetic())?;
+    let (value, unit) = latency.split_at(unit_index);
+    let value = value.trim().parse::<f64>().ok()?;
+    let millis = match unit {
+        "us" => value / 1000f64,
+        "ms" => value,
+        "s" => value * 1000f64,
+        "m" => value * 60_000f64,
+        _ => return None,
+    };
+
+    Some(millis)
+}
+
+/// Formats the given latency in milliseconds, if known.
+fn format_latency(latency: Option<f64>) -> String {
+    match latency {
+        Some(latency) => format!("{:.2}ms", latency),
+        None => String::default(),
+    }
+}
+
+/// Gets the change from `before` to `after`, in percent.
+fn get_change(before: f64, after: f64) -> f64 {
+    if before > 0f64 {
+        (after - before) / before * 100f64
+    } else {
+        0f64
+    }
+}
+
+//
+// TESTS
+//
+
+#[cfg(test)]
+mod tests {
+    use crate::compare::{get_deltas, parse_latency};
+    use crate::results::{BenchmarkData, Results};
+    use std::collections::HashMap;
+
+    fn get_results(gemini: &[(u32, &str)], actix: &[(u32, &str)]) -> Results {
+        let get_data = |commands: &[(u32, &str)]| {
+            commands
+                .iter()
+                .map(|(total_requests, latency_avg)| BenchmarkData {
+                    total_requests: *total_requests,
+                    latency_avg: latency_avg.to_string(),
+                    duration: 10,
+                    ..BenchmarkData::default()
+                })
+                .collect()
+        };
+        let mut json = HashMap::new();
+        json.insert("gemini".to_string(), get_data(gemini));
+        json.insert("actix".to_string(), get_data(actix));
+        let mut raw_data = HashMap::new();
+        raw_data.insert("json".to_string(), json);
+
+        Results {
+            raw_data,
+            ..Results::default()
+        }
+    }
+
+    #[test]
+    fn it_can_parse_latencies() {
+        assert_eq!(parse_latency("210.00us"), Some(0.21));
+        assert_eq!(parse_latency("1.50ms"), Some(1.5));
+        assert_eq!(parse_latency("2.00s"), Some(2000f64));
+        assert_eq!(parse_latency(""), None);
+    }
+
+    #[test]
+    fn it_can_flag_regressions_between_runs() {
+        let before = get_results(
+            &[(100_000, "1.00ms"), (200_000, "2.00ms")],
+            &[(300_000, "1.00ms")],
+        );
+        let after = get_results(&[(190_000, "2.00ms")], &[(240_000, "1.00ms")]);
+
+        let deltas = get_deltas(&before, &after);
+
+        assert_eq!(deltas.len(), 2);
+        assert_eq!(deltas[0].framework, "actix");
+        assert_eq!(deltas[0].before, (30_000f64, Some(1f64)));
+        assert_eq!(deltas[0].after, (24_000f64, Some(1f64)));
+        assert!(deltas[0].regressed(10f64));
+        assert_eq!(deltas[1].framework, "gemini");
+        assert!(!deltas[1].regressed(10f64));
+        assert!(deltas[1].regressed(1f64));
+    }
+
+    #[test]
+    fn it_can_compare_against_results_recorded_without_a_duration() {
+        let mut before = get_results(&[(100_000, "1.00ms")], &[]);
+        for data in before
+            .raw_data
+            .get_mut("json")
+            .unwrap()
+            .get_mut("gemini")
+            .unwrap()
+        {
+            data.duration = 0;
+            data.start_time = 1_597_090_053_000;
+            data.end_time = 1_597_090_058_000;
+        }
+        let after = get_results(&[(150_000, "1.00ms")], &[]);
+
+        let deltas = get_deltas(&before, &after);
+
+        assert_eq!(deltas.len(), 1);
+        assert_eq!(deltas[0].before, (20_000f64, Some(1f64)));
+        assert_eq!(deltas[0].after, (15_000f64, Some(1f64)));
+        assert!(deltas[0].regressed(10f64));
+    }
+}
@@ -1,7 +1,7 @@
 //! The config module contains all the structs relating to test implementation
 //! configuration files.
 
-use crate::error::ToolsetError::{InvalidConfigError, LanguageNotFoundError};
+use crate::error::ToolsetError::{InvalidConfigError, LanguageNotFoundError, UnknownTestTypeError};
 use crate::error::ToolsetResult;
 use crate::io;
 use serde::Deserialize;
@@ -9,8 +9,36 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 use toml::Value;
 
+/// The standardized test types; any other key in `urls` must be declared as
+/// `custom` (or use a non-HTTP `Protocol`).
+pub const TEST_TYPES: [&str; 7] = [
+    "json",
+    "plaintext",
+    "db",
+    "query",
+    "cached_query",
+    "fortune",
+    "update",
+];
+
 pub trait Named {
     fn get_name(&self) -> String;
+
+    /// Gets the name as used in results keys, log directories, and image
+    /// tags; see `normalize_name`.
+    fn get_normalized_name(&self) -> String {
+        normalize_name(&self.get_name())
+    }
+}
+
+/// Normalizes the given `name` by trimming it, lowercasing it, and joining
+/// its words with `-`, such that `" Gemini  Servlet "` and `"gemini-servlet"`
+/// are the same name.
+pub fn normalize_name(name: &str) -> String {
+    name.split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<String>>()
+        .join("-")
 }
 
 #[derive(Deserialize, Clone, Debug)]
@@ -46,7 +74,52 @@ pub struct Test {
     pub database: Option<String>,
     pub versus: String,
     pub tags: Option<Vec<String>>,
+    /// Shown with the test's verification summary and recorded in the
+    /// results' metadata; e.g. why it departs from the requirements.
+    pub notes: Option<String>,
+    /// Issues known to affect the test, shown and recorded like `notes`.
+    pub known_issues: Option<Vec<String>>,
+    /// Relative to the test's directory, outside of which it may be shared
+    /// with other frameworks; e.g. `../common/base.dockerfile`.
     pub dockerfile: Option<String>,
+    /// The port on which the application accepts requests; only necessary
+    /// when the image exposes more than one port (metrics, admin, etc.).
+    pub port: Option<u16>,
+    /// The protocol spoken by each test type; test types not listed are
+    /// assumed to be `http`.
+    pub protocols: Option<HashMap<String, Protocol>>,
+    /// Keys of `urls` which are experimental, non-standard test types.
+    pub custom: Option<Vec<String>>,
+    /// Overrides applied to the `wrk` commands issued by the verifier for
+    /// each listed test type; intended for controlled experiments.
+    pub command_overrides: Option<HashMap<String, CommandOverride>>,
+    /// Where the image copies the framework's directory to, e.g. `/app`; the
+    /// directory is bind-mounted there in its place by `--mount-source`.
+    pub source_mount: Option<String>,
+    /// Overrides `--startup-timeout` for this test; in seconds.
+    pub startup_timeout: Option<u32>,
+    /// Overrides `--build-timeout` for this test; in seconds.
+    pub build_timeout: Option<u32>,
+}
+
+/// Replacements for parts of a verifier-issued `wrk` command. `duration` is
+/// not applied to the primer command, which is only a short sanity check.
+#[derive(Deserialize, Clone, Debug, Default)]
+pub struct CommandOverride {
+    pub duration: Option<u32>,
+    pub connections: Option<u32>,
+    pub threads: Option<u32>,
+    /// Replaces *all* of the headers the verifier would have sent.
+    pub headers: Option<Vec<String>>,
+}
+
+/// The protocol over which a test type is verified and benchmarked.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Protocol {
+    Http,
+    Grpc,
+    Websocket,
 }
 
 impl Named for Test {
@@ -56,13 +129,78 @@ impl Named for Test {
 }
 
 impl Test {
-    pub fn get_tag(&self) -> String {
-        format!("tfb.test.{}", self.get_name())
+    /// Gets the tag of the test's image, given the `image_prefix` of the run
+    /// (see `--image-prefix`).
+    pub fn get_tag(&self, image_prefix: &str) -> String {
+        format!("{}{}", image_prefix, self.get_normalized_name())
+    }
+    /// Gets the file name of the test's dockerfile, which defaults to
+    /// `<name>.dockerfile`.
+    pub fn get_dockerfile(&self) -> String {
+        match &self.dockerfile {
+            Some(dockerfile) => dockerfile.clone(),
+            None => format!("{}.dockerfile", self.get_name()),
+        }
+    }
+    /// Gets the `Protocol` of the given `test_type`, defaulting to `Http`.
+    pub fn get_protocol(&self, test_type: &str) -> Protocol {
+        if let Some(protocols) = &self.protocols {
+            if let Some(protocol) = protocols.get(test_type) {
+                return *protocol;
+            }
+        }
+        Protocol::Http
+    }
+    /// Gets the `CommandOverride` configured for the given `test_type`, if any.
+    pub fn get_command_override(&self, test_type: &str) -> Option<&CommandOverride> {
+        if let Some(overrides) = &self.command_overrides {
+            overrides.get(test_type)
+        } else {
+            None
+        }
+    }
+    /// Returns whether the test is tagged with the given `tag`.
+    pub fn is_tagged(&self, tag: &str) -> bool {
+        if let Some(tags) = &self.tags {
+            tags.iter().any(|tagged| tagged == tag)
+        } else {
+            false
+        }
+    }
+    /// Returns whether the given `test_type` is declared as `custom`.
+    pub fn is_custom(&self, test_type: &str) -> bool {
+        if let Some(custom) = &self.custom {
+            custom.iter().any(|custom| custom == test_type)
+        } else {
+            false
+        }
+    }
+    /// Gets the test types in `urls` which are neither standard, declared as
+    /// `custom`, nor benchmarked over a non-HTTP `Protocol`.
+    pub fn get_unknown_test_types(&self) -> Vec<String> {
+        let mut unknown: Vec<String> = self
+            .urls
+            .keys()
+            .filter(|test_type| {
+                !TEST_TYPES.contains(&test_type.as_str())
+                    && !self.is_custom(test_type)
+                    && self.get_protocol(test_type) == Protocol::Http
+            })
+            .cloned()
+            .collect();
+        unknown.sort();
+        unknown
     }
-    pub fn specify_test_type(&mut self, test_type: Option<&str>) {
-        if let Some(test_type) = test_type {
-            self.urls.retain(|key, _| key == test_type);
+    /// Retains only the given `test_types` of this `Test`, if any are given.
+    /// Returns whether this `Test` has any of them.
+    pub fn specify_test_types(&mut self, test_types: &[&str]) -> bool {
+        if test_types.is_empty() {
+            return true;
         }
+        self.urls
+            .retain(|key, _| test_types.contains(&key.as_str()));
+
+        !self.urls.is_empty()
     }
 }
 
@@ -85,7 +223,7 @@ impl Project {
         tfb_path.push(format!(
             "frameworks/{}/{}",
             self.language,
-            self.framework.get_name().to_lowercase()
+            self.framework.get_normalized_name()
         ));
 
         Ok(tfb_path)
@@ -108,14 +246,14 @@ pub fn get_language_by_config_file(framework: &Framework, file: &PathBuf) -> Too
                 .to_str()
                 .unwrap()
                 .to_lowercase()
-                == framework.get_name().to_lowercase()
+                == framework.get_normalized_name()
         {
             next = true;
         }
     }
     if language.is_none() {
         return Err(LanguageNotFoundError(
-            framework.get_name().to_lowercase(),
+            framework.get_normalized_name(),
             file.to_str().unwrap().to_string(),
         ));
     }
@@ -159,12 +297,18 @@ pub fn get_test_implementations_by_config_file(file: &PathBuf) -> ToolsetResult<
                 Ok(test) => {
                     let mut test: Test = test;
                     let mut test_name = String::new();
-                    test_name.push_str(&config.framework.name.to_lowercase());
+                    test_name.push_str(&config.framework.get_normalized_name());
                     if key != "main" {
                         test_name.push('-');
                         test_name.push_str(key);
                     }
                     test.name = Some(test_name);
+                    if let Some(test_type) = test.get_unknown_test_types().first() {
+                        return Err(UnknownTestTypeError(
+                            file.to_str().unwrap().to_string(),
+                            test_type.clone(),
+                        ));
+                    }
                     tests.push(test);
                 }
                 Err(e) => {
@@ -197,7 +341,7 @@ fn parse_config(file: &PathBuf) -> ToolsetResult<Config> {
 mod tests {
     use glob::glob;
 
-    use crate::config::Named;
+    use crate::config::{normalize_name, Framework, Named, Test};
     use crate::{config, io};
 
     #[test]
@@ -247,4 +391,38 @@ mod tests {
             Err(e) => panic!("io::get_tfb_dir() failed with error: {:?}", e),
         }
     }
+
+    #[test]
+    fn it_rejects_undeclared_custom_test_types() {
+        let mut test: Test = toml::from_str(
+            r#"
+            urls.json = "/json"
+            urls.json_compressed = "/json?gzip"
+            approach = "Realistic"
+            classification = "Micro"
+            platform = "None"
+            webserver = "None"
+            os = "Linux"
+            versus = "None"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(test.get_unknown_test_types(), vec!["json_compressed"]);
+
+        test.custom = Some(vec!["json_compressed".to_string()]);
+        assert!(test.get_unknown_test_types().is_empty());
+        assert!(test.is_custom("json_compressed"));
+    }
+
+    #[test]
+    fn it_can_normalize_names() {
+        let framework = Framework {
+            name: " Gemini\tServlet ".to_string(),
+            authors: None,
+            github: None,
+        };
+        assert_eq!(framework.get_normalized_name(), "gemini-servlet");
+        assert_eq!(normalize_name("ÄCTIX  Web"), "äctix-web");
+        assert_eq!(normalize_name("gemini"), "gemini");
+    }
 }
@@ -0,0 +1,433 @@
+//! The `DockerApi` trait abstracts the Docker daemon requests made by the
+//! `container`, `image`, and `network` modules so that orchestration can be
+//! exercised without a live daemon (see `docker::mock`).
+
+use crate::docker::check_daemon_result;
+use crate::docker::docker_config::DOCKER_SOCKET;
+use crate::docker::listener::build_container::BuildContainer;
+use crate::docker::listener::build_network::BuildNetwork;
+use crate::docker::listener::image_info::ImageInfo;
+use crate::docker::listener::simple::Simple;
+use crate::error::ToolsetResult;
+use curl::easy::{Easy2, Handler, WriteError};
+use dockurl::container::create::options::Options;
+use dockurl::error::DockerError;
+use dockurl::network::NetworkMode;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::path::Path;
+
+/// The requests made of the Docker daemons given by `docker_host`.
+///
+/// Requests which stream output (building images, attaching to containers,
+/// and reading their logs) write it to the given `handler` as it arrives.
+pub trait DockerApi: Debug + Send + Sync {
+    fn build_image(
+        &self,
+        tag: &str,
+        dockerfile: &Path,
+        context: &Path,
+        docker_host: &str,
+        handler: &mut dyn Handler,
+    ) -> ToolsetResult<String>;
+
+    fn pull_image(&self, image_name: &str, tag: &str, docker_host: &str) -> ToolsetResult<()>;
+
+    fn delete_image(&self, image_id: &str, docker_host: &str) -> ToolsetResult<()>;
+
+    /// Gets the digest of the image given by `image_name`, as pulled from its
+    /// registry, or else its id; `None` if there is no such image.
+    fn get_image_digest(
+        &self,
+        image_name: &str,
+        docker_host: &str,
+    ) -> ToolsetResult<Option<String>>;
+
+    fn delete_dangling_images(&self, docker_host: &str) -> ToolsetResult<()>;
+
+    fn create_container(&self, options: Options, docker_host: &str) -> ToolsetResult<String>;
+
+    fn start_container(&self, container_id: &str, docker_host: &str) -> ToolsetResult<()>;
+
+    fn attach_to_container(
+        &self,
+        container_id: &str,
+        docker_host: &str,
+        handler: &mut dyn Handler,
+    ) -> ToolsetResult<()>;
+
+    fn get_container_logs(
+        &self,
+        container_id: &str,
+        docker_host: &str,
+        handler: &mut dyn Handler,
+    ) -> ToolsetResult<()>;
+
+    fn inspect_container(
+        &self,
+        container_id: &str,
+        docker_host: &str,
+    ) -> ToolsetResult<ContainerInspection>;
+
+    fn wait_for_container_to_exit(
+        &self,
+        container_id: &str,
+        docker_host: &str,
+    ) -> ToolsetResult<()>;
+
+    fn kill_container(&self, container_id: &str, docker_host: &str) -> ToolsetResult<()>;
+
+    /// Forcibly removes the container and its volumes.
+    fn delete_container(&self, container_id: &str, docker_host: &str) -> ToolsetResult<()>;
+
+    /// Gets the id of the network given by `network_name`.
+    fn inspect_network(&self, network_name: &str, docker_host: &str) -> ToolsetResult<String>;
+
+    /// Creates a bridge network named `network_name` and returns its id.
+    fn create_network(&self, network_name: &str, docker_host: &str) -> ToolsetResult<String>;
+
+    fn connect_container_to_network(
+        &self,
+        container_id: &str,
+        network_id: &str,
+        docker_host: &str,
+    ) -> ToolsetResult<()>;
+
+    fn delete_network(&self, network_id: &str, docker_host: &str) -> ToolsetResult<()>;
+}
+
+/// The parts of a container's inspection used by the toolset.
+#[derive(Clone, Debug, Default)]
+pub struct ContainerInspection {
+    pub running: bool,
+    pub exit_code: i32,
+    /// The ports exposed by the image, e.g. `8080/tcp`, if any.
+    pub exposed_ports: Option<Vec<String>>,
+    /// The host ports bound to each exposed port.
+    pub host_ports: HashMap<String, Vec<String>>,
+}
+
+/// The `DockerApi` backed by `dockurl`, which talks to the daemons over TCP
+/// or, if `use_unix_socket`, the local unix socket.
+#[derive(Debug)]
+pub struct Dockurl {
+    use_unix_socket: bool,
+}
+impl Dockurl {
+    pub fn new(use_unix_socket: bool) -> Self {
+        Self { use_unix_socket }
+    }
+}
+impl DockerApi for Dockurl {
+    fn build_image(
+        &self,
+        tag: &str,
+        dockerfile: &Path,
+        context: &Path,
+        docker_host: &str,
+        handler: &mut dyn Handler,
+    ) -> ToolsetResult<String> {
+        let result = dockurl::image::build_image(
+            tag,
+            &dockerfile.to_path_buf(),
+            &context.to_path_buf(),
+            docker_host,
+            self.use_unix_socket,
+            HandlerRef(handler),
+        );
+
+        check_daemon_result("build image", docker_host, result, None)
+    }
+
+    fn pull_image(&self, image_name: &str, tag: &str, docker_host: &str) -> ToolsetResult<()> {
+        with_simple("pull image", docker_host, |listener| {
+            dockurl::image::create_image(
+                image_name,
+                tag,
+                docker_host,
+                self.use_unix_socket,
+                listener,
+            )
+        })
+    }
+
+    fn delete_image(&self, image_id: &str, docker_host: &str) -> ToolsetResult<()> {
+        with_simple("delete image", docker_host, |listener| {
+            dockurl::image::delete_image(
+                image_id,
+                true,
+                false,
+                docker_host,
+                self.use_unix_socket,
+                listener,
+            )
+        })?;
+
+        Ok(())
+    }
+
+    fn get_image_digest(
+        &self,
+        image_name: &str,
+        docker_host: &str,
+    ) -> ToolsetResult<Option<String>> {
+        // `dockurl` has no image inspection, so ask the daemon directly.
+        let mut easy = Easy2::new(ImageInfo::default());
+        if self.use_unix_socket {
+            easy.unix_socket(DOCKER_SOCKET)?;
+            easy.url(&format!("http://localhost/images/{}/json", image_name))?;
+        } else {
+            easy.url(&format!(
+                "http://{}/images/{}/json",
+                docker_host, image_name
+            ))?;
+        }
+        easy.perform()?;
+
+        Ok(easy.get_ref().parse_report().map(|report| {
+            report
+                .repo_digests
+                .and_then(|repo_digests| repo_digests.into_iter().next())
+                .unwrap_or(report.id)
+        }))
+    }
+
+    fn delete_dangling_images(&self, docker_host: &str) -> ToolsetResult<()> {
+        with_simple("delete dangling images", docker_host, |listener| {
+            dockurl::image::delete_unused_images(
+                "{\"dangling\":[\"true\"]}",
+                docker_host,
+                self.use_unix_socket,
+                listener,
+            )
+        })
+    }
+
+    fn create_container(&self, options: Options, docker_host: &str) -> ToolsetResult<String> {
+        // Creation is answered with the container's id, not a bare `Simple`
+        // message, so `BuildContainer` captures the error message instead.
+        let listener = BuildContainer::new();
+        let result = dockurl::container::create_container(
+            options,
+            self.use_unix_socket,
+            docker_host,
+            listener.clone(),
+        );
+
+        check_daemon_result(
+            "create container",
+            docker_host,
+            result,
+            listener.error_message.get(),
+        )
+    }
+
+    fn start_container(&self, container_id: &str, docker_host: &str) -> ToolsetResult<()> {
+        with_simple("start container", docker_host, |listener| {
+            dockurl::container::start_container(
+                container_id,
+                docker_host,
+                self.use_unix_socket,
+                listener,
+            )
+        })
+    }
+
+    fn attach_to_container(
+        &self,
+        container_id: &str,
+        docker_host: &str,
+        handler: &mut dyn Handler,
+    ) -> ToolsetResult<()> {
+        let result = dockurl::container::attach_to_container(
+            container_id,
+            docker_host,
+            self.use_unix_socket,
+            HandlerRef(handler),
+        );
+
+        check_daemon_result("attach to container", docker_host, result, None)?;
+
+        Ok(())
+    }
+
+    fn get_container_logs(
+        &self,
+        container_id: &str,
+        docker_host: &str,
+        handler: &mut dyn Handler,
+    ) -> ToolsetResult<()> {
+        let result = dockurl::container::get_container_logs(
+            container_id,
+            docker_host,
+            self.use_unix_socket,
+            HandlerRef(handler),
+        );
+
+        check_daemon_result("get container logs", docker_host, result, None)?;
+
+        Ok(())
+    }
+
+    fn inspect_container(
+        &self,
+        container_id: &str,
+        docker_host: &str,
+    ) -> ToolsetResult<ContainerInspection> {
+        let inspection = with_simple("inspect container", docker_host, |listener| {
+            dockurl::container::inspect_container(
+                container_id,
+                docker_host,
+                self.use_unix_socket,
+                listener,
+            )
+        })?;
+
+        Ok(ContainerInspection {
+            running: inspection.state.running,
+            exit_code: inspection.state.exit_code,
+            exposed_ports: inspection
+                .config
+                .exposed_ports
+                .map(|exposed_ports| exposed_ports.keys().cloned().collect()),
+            host_ports: inspection
+                .network_settings
+                .ports
+                .iter()
+                .map(|(port, port_mappings)| {
+                    let host_ports = port_mappings
+                        .iter()
+                        .map(|port_mapping| port_mapping.host_port.clone())
+                        .collect();
+                    (port.clone(), host_ports)
+                })
+                .collect(),
+        })
+    }
+
+    fn wait_for_container_to_exit(
+        &self,
+        container_id: &str,
+        docker_host: &str,
+    ) -> ToolsetResult<()> {
+        with_simple("wait for container", docker_host, |listener| {
+            dockurl::container::wait_for_container_to_exit(
+                container_id,
+                docker_host,
+                self.use_unix_socket,
+                listener,
+            )
+        })
+    }
+
+    fn kill_container(&self, container_id: &str, docker_host: &str) -> ToolsetResult<()> {
+        with_simple("kill container", docker_host, |listener| {
+            dockurl::container::kill_container(
+                container_id,
+                docker_host,
+                self.use_unix_socket,
+                listener,
+            )
+        })
+    }
+
+    fn delete_container(&self, container_id: &str, docker_host: &str) -> ToolsetResult<()> {
+        with_simple("delete container", docker_host, |listener| {
+            dockurl::container::delete_container(
+                container_id,
+                docker_host,
+                self.use_unix_socket,
+                listener,
+                true,
+                true,
+                false,
+            )
+        })
+    }
+
+    fn inspect_network(&self, network_name: &str, docker_host: &str) -> ToolsetResult<String> {
+        let network = with_simple("inspect network", docker_host, |listener| {
+            dockurl::network::inspect_network(
+                network_name,
+                docker_host,
+                self.use_unix_socket,
+                listener,
+            )
+        })?;
+
+        Ok(network.id)
+    }
+
+    fn create_network(&self, network_name: &str, docker_host: &str) -> ToolsetResult<String> {
+        // Like container creation, this is answered with the network's id.
+        let listener = BuildNetwork::new();
+        let result = dockurl::network::create_network(
+            network_name,
+            NetworkMode::Bridge,
+            docker_host,
+            self.use_unix_socket,
+            listener.clone(),
+        );
+
+        check_daemon_result(
+            "create network",
+            docker_host,
+            result,
+            listener.error_message.get(),
+        )
+    }
+
+    fn connect_container_to_network(
+        &self,
+        container_id: &str,
+        network_id: &str,
+        docker_host: &str,
+    ) -> ToolsetResult<()> {
+        with_simple("connect container to network", docker_host, |listener| {
+            dockurl::network::connect_container_to_network(
+                container_id,
+                network_id,
+                vec![],
+                docker_host,
+                self.use_unix_socket,
+                listener,
+            )
+        })
+    }
+
+    fn delete_network(&self, network_id: &str, docker_host: &str) -> ToolsetResult<()> {
+        with_simple("remove network", docker_host, |listener| {
+            dockurl::network::delete_network(
+                network_id,
+                docker_host,
+                self.use_unix_socket,
+                listener,
+            )
+        })
+    }
+}
+
+//
+// PRIVATES
+//
+
+/// Lends a `Handler` to `dockurl`, which takes its handlers by value.
+struct HandlerRef<'h>(&'h mut dyn Handler);
+impl Handler for HandlerRef<'_> {
+    fn write(&mut self, data: &[u8]) -> Result<usize, WriteError> {
+        self.0.write(data)
+    }
+}
+
+/// Makes the given `request` of the daemon on `docker_host` with a `Simple`
+/// listener, surfacing the daemon's explanation should it fail.
+fn with_simple<T>(
+    operation: &str,
+    docker_host: &str,
+    request: impl FnOnce(Simple) -> Result<T, DockerError>,
+) -> ToolsetResult<T> {
+    let listener = Simple::new();
+    let result = request(listener.clone());
+
+    check_daemon_result(operation, docker_host, result, listener.error_message.get())
+}
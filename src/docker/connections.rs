@@ -0,0 +1,128 @@
+//! Samples the number of connections open to the database while a benchmark
+//! command runs; a misconfigured connection pool (e.g. one connection per
+//! request, or far fewer connections than the concurrency) is a common source
+//! of disputed results.
+
+use crate::docker::container::create_database_sidecar_container;
+use crate::docker::docker_config::DockerConfig;
+use crate::docker::listener::counts::Counts;
+use crate::docker::listener::simple::Simple;
+use crate::error::ToolsetResult;
+use crate::results::ConnectionStats;
+use dockurl::container::{delete_container, get_container_logs, kill_container};
+
+pub struct ConnectionSampler {
+    container_id: String,
+}
+impl ConnectionSampler {
+    /// Starts sampling, about once per second, the connections open to the
+    /// `database` in the container given by `container_id`. Returns `None`
+    /// for databases which cannot be sampled.
+    pub fn start(
+        docker_config: &DockerConfig,
+        database: &str,
+        container_id: &str,
+    ) -> ToolsetResult<Option<Self>> {
+        let query = match get_connection_count_command(database) {
+            Some(query) => query,
+            None => return Ok(None),
+        };
+        let sampler_id = create_database_sidecar_container(
+            docker_config,
+            database,
+            container_id,
+            &format!("while true; do {}; sleep 1; done", query),
+        )?;
+        dockurl::container::start_container(
+            &sampler_id,
+            &docker_config.database_docker_host,
+            docker_config.use_unix_socket,
+            Simple::new(),
+        )?;
+
+        Ok(Some(Self {
+            container_id: sampler_id,
+        }))
+    }
+
+    /// Stops sampling and summarizes the samples collected, if any.
+    pub fn stop(self, docker_config: &DockerConfig) -> ToolsetResult<Option<ConnectionStats>> {
+        let docker_host = &docker_config.database_docker_host;
+        kill_container(
+            &self.container_id,
+            docker_host,
+            docker_config.use_unix_socket,
+            Simple::new(),
+        )?;
+        let counts = get_container_logs(
+            &self.container_id,
+            docker_host,
+            docker_config.use_unix_socket,
+            Counts::new(),
+        );
+        delete_container(
+            &self.container_id,
+            docker_host,
+            docker_config.use_unix_socket,
+            Simple::new(),
+            true,
+            true,
+            false,
+        )?;
+
+        Ok(get_connection_stats(&counts?.parse_counts()))
+    }
+}
+
+//
+// PRIVATES
+//
+
+/// Gets the shell command which prints the number of connections open to the
+/// benchmark database, excluding its own.
+fn get_connection_count_command(database: &str) -> Option<&'static str> {
+    match database {
+        "postgres" => Some(
+            "PGPASSWORD=benchmarkdbpass psql -h 127.0.0.1 -U benchmarkdbuser -d hello_world -tAc \
+             \"SELECT count(*) FROM pg_stat_activity WHERE datname = 'hello_world' AND pid <> pg_backend_pid()\"",
+        ),
+        "mysql" => Some(
+            "mysql -h 127.0.0.1 -u benchmarkdbuser -pbenchmarkdbpass -NBe \
+             \"SELECT COUNT(*) FROM information_schema.PROCESSLIST WHERE DB = 'hello_world' AND ID <> CONNECTION_ID()\"",
+        ),
+        "mongodb" => Some("mongo --quiet --eval 'db.serverStatus().connections.current - 1'"),
+        _ => None,
+    }
+}
+
+fn get_connection_stats(samples: &[u64]) -> Option<ConnectionStats> {
+    if samples.is_empty() {
+        return None;
+    }
+
+    Some(ConnectionStats {
+        connections_avg: samples.iter().sum::<u64>() as f32 / samples.len() as f32,
+        connections_max: samples.iter().copied().max().unwrap_or_default() as u32,
+        samples: samples.len() as u32,
+    })
+}
+
+//
+// TESTS
+//
+
+#[cfg(test)]
+mod tests {
+    use crate::docker::connections::{get_connection_count_command, get_connection_stats};
+
+    #[test]
+    fn it_can_summarize_connection_samples() {
+        assert!(get_connection_stats(&[]).is_none());
+        let stats = get_connection_stats(&[4, 16, 16, 12]).unwrap();
+        assert_eq!(stats.connections_avg, 12f32);
+        assert_eq!(stats.connections_max, 16);
+        assert_eq!(stats.samples, 4);
+        assert!(get_connection_count_command("postgres").is_some());
+        assert!(get_connection_count_command("cassandra").is_none());
+    }
+}
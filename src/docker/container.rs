@@ -1,30 +1,30 @@
 use crate::benchmarker::Mode;
-use crate::config::{Named, Project, Test};
+use crate::config::{Named, Project, Protocol, Test};
+use crate::docker::api::DockerApi;
 use crate::docker::docker_config::DockerConfig;
+use crate::docker::image::pull_image;
 use crate::docker::listener::application::Application;
 use crate::docker::listener::benchmark_command_listener::BenchmarkCommandListener;
 use crate::docker::listener::benchmarker::{BenchmarkResults, Benchmarker};
-use crate::docker::listener::build_container::BuildContainer;
-use crate::docker::listener::simple::Simple;
+use crate::docker::listener::output::Output;
 use crate::docker::listener::verifier::Verifier;
+use crate::docker::resources::Role;
 use crate::docker::{
     BenchmarkCommands, DockerContainerIdFuture, DockerOrchestration, Verification,
 };
 use crate::error::ToolsetError::{
-    ContainerPortMappingInspectionError, ExposePortError, FailedBenchmarkCommandRetrievalError,
+    BenchmarkCommandFailedError, ContainerPortMappingInspectionError, DockerDaemonError,
+    ExposePortError, FailedBenchmarkCommandRetrievalError, NoRequestsCompletedError,
+    PortConflictError,
 };
 use crate::error::ToolsetResult;
-use crate::io::Logger;
-use dockurl::container::create::host_config::{HostConfig, Ulimit};
+use crate::io::{LogPath, Logger};
+use crate::options::load_generators;
+use dockurl::container::create::host_config::{HostConfig, Mount, MountType, TmpfsOptions, Ulimit};
 use dockurl::container::create::networking_config::{
     EndpointSettings, EndpointsConfig, NetworkingConfig,
 };
 use dockurl::container::create::options::Options;
-use dockurl::container::{
-    attach_to_container, delete_container, get_container_logs, inspect_container, kill_container,
-    wait_for_container_to_exit,
-};
-use dockurl::image::{delete_image, delete_unused_images};
 use dockurl::network::NetworkMode;
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
@@ -32,14 +32,30 @@ use std::task::Poll;
 use std::thread;
 use std::time::Duration;
 
+/// Where core dumps are written within application containers.
+pub const CORE_DUMP_MOUNT: &str = "/tmp/tfb-cores";
+/// The kernel setting naming the file each core dump is written to.
+const CORE_PATTERN_FILE: &str = "/proc/sys/kernel/core_pattern";
+/// Seconds after which the preflight request is abandoned.
+const PREFLIGHT_TIMEOUT: &str = "10";
+
 /// Note: this function makes the assumption that the image is already
 /// built and that the Docker daemon is aware of it.
+///
+/// The given `mounts` are added to the container along with the tmpfs mounts
+/// and `/dev/shm` size configured via `--tmpfs` and `--shm-size`. If one of
+/// them is at `CORE_DUMP_MOUNT` (see `get_core_dump_mount`), the core file
+/// size limit is lifted. The container is constrained as a database if it is
+/// named for the database host, and as an application server otherwise (see
+/// `--resource-profile`).
 pub fn create_container(
     config: &DockerConfig,
     image_id: &str,
     network_id: &str,
     host_name: &str,
     docker_host: &str,
+    network_mode: &NetworkMode,
+    mut mounts: Vec<Mount>,
 ) -> ToolsetResult<String> {
     let mut options = Options::new();
     options.image(image_id);
@@ -47,33 +63,61 @@ pub fn create_container(
     options.domain_name(host_name);
 
     let mut host_config = HostConfig::new();
+    let mut ulimits = vec![
+        Ulimit {
+            name: "nofile",
+            soft: 200000,
+            hard: 200000,
+        },
+        Ulimit {
+            name: "rtprio",
+            soft: 99,
+            hard: 99,
+        },
+    ];
+    if mounts.iter().any(|mount| mount.target == CORE_DUMP_MOUNT) {
+        ulimits.push(Ulimit {
+            name: "core",
+            // Docker's ulimits are signed; this is effectively unlimited.
+            soft: i64::MAX as u64,
+            hard: i64::MAX as u64,
+        });
+    }
+    for (target, size_megabytes) in &config.tmpfs_mounts {
+        mounts.push(get_tmpfs_mount(target, *size_megabytes));
+    }
+    if !mounts.is_empty() {
+        host_config.mounts(mounts);
+    }
+    if let Some(shm_size) = config.shm_size {
+        host_config.shm_size(shm_size * 1024 * 1024);
+    }
+    if host_name == config.database_host {
+        config.apply_resource_limits(Role::Database, &mut host_config);
+    } else {
+        config.apply_resource_limits(Role::Server, &mut host_config);
+    }
     let mut endpoint_settings = EndpointSettings::new();
     endpoint_settings.network_id(network_id);
-    match &config.network_mode {
+    match network_mode {
         dockurl::network::NetworkMode::Bridge => {
             host_config.network_mode(dockurl::network::NetworkMode::Bridge);
             endpoint_settings.alias(host_name);
         }
         dockurl::network::NetworkMode::Host => {
-            host_config.extra_host("tfb-database", &config.database_host);
             host_config.network_mode(dockurl::network::NetworkMode::Host);
         }
     }
+    if let NetworkMode::Host = config.database_network_mode {
+        host_config.extra_host(
+            "tfb-database",
+            config.get_database_host(docker_host, network_mode),
+        );
+    }
     let mut sysctls = HashMap::new();
     sysctls.insert("net.core.somaxconn", "65535");
     host_config.sysctls(sysctls);
-    host_config.ulimits(vec![
-        Ulimit {
-            name: "nofile",
-            soft: 200000,
-            hard: 200000,
-        },
-        Ulimit {
-            name: "rtprio",
-            soft: 99,
-            hard: 99,
-        },
-    ]);
+    host_config.ulimits(ulimits);
     host_config.publish_all_ports(true);
     host_config.privileged(true);
 
@@ -84,53 +128,157 @@ pub fn create_container(
     options.host_config(host_config);
     options.tty(true);
 
-    let container_id = dockurl::container::create_container(
-        options,
-        config.use_unix_socket,
+    let container_id = config.docker_api.create_container(options, docker_host)?;
+    record_container_created(config, image_id, docker_host, &container_id)?;
+
+    Ok(container_id)
+}
+
+/// Sets the kernel's `core_pattern` on the given `docker_host` such that core
+/// dumps are written beneath `CORE_DUMP_MOUNT` of the crashing container, and
+/// returns the pattern it replaced, to be put back via `restore_core_pattern`.
+///
+/// Note: `core_pattern` is not namespaced, so this affects the whole host.
+pub fn configure_core_pattern(
+    docker_config: &DockerConfig,
+    docker_host: &str,
+) -> ToolsetResult<String> {
+    pull_image(docker_config, docker_host, "busybox")?;
+
+    run_privileged_command(
+        docker_config.docker_api.as_ref(),
+        docker_host,
+        &format!(
+            "cat {} && {}",
+            CORE_PATTERN_FILE,
+            get_write_core_pattern_command(&get_core_pattern())
+        ),
+    )
+}
+
+/// Puts back the given `core_pattern` on the given `docker_host`, as replaced
+/// by `configure_core_pattern`.
+pub fn restore_core_pattern(
+    docker_api: &dyn DockerApi,
+    docker_host: &str,
+    core_pattern: &str,
+) -> ToolsetResult<()> {
+    run_privileged_command(
+        docker_api,
         docker_host,
-        BuildContainer::new(),
+        &get_write_core_pattern_command(core_pattern),
     )?;
 
-    Ok(container_id)
+    Ok(())
+}
+
+/// Gets the image containing the load generator for the given `protocol`;
+/// HTTP is generated by wrk, in the verifier's image, unless `wrk2` is given
+/// as the `--load-generator`.
+pub fn get_load_generator_image(config: &DockerConfig, protocol: Protocol) -> &'static str {
+    match protocol {
+        Protocol::Http if config.load_generator == load_generators::WRK2 => "techempower/tfb.wrk2",
+        Protocol::Http => "techempower/tfb.verifier",
+        Protocol::Grpc => "techempower/tfb.ghz",
+        Protocol::Websocket => "techempower/tfb.tcpkali",
+    }
+}
+
+/// Gets the directory in which the given `database` image stores its data,
+/// if known.
+pub fn get_database_data_dir(database: &str) -> Option<&'static str> {
+    match database {
+        "postgres" => Some("/var/lib/postgresql/data"),
+        "mysql" => Some("/var/lib/mysql"),
+        "mongodb" => Some("/data/db"),
+        _ => None,
+    }
+}
+
+/// Gets a `Mount` of the given `core_dump_dir` at `CORE_DUMP_MOUNT`, into
+/// which the container's core dumps are written; see `configure_core_pattern`.
+pub fn get_core_dump_mount(core_dump_dir: &str) -> Mount {
+    Mount {
+        target: CORE_DUMP_MOUNT.to_string(),
+        source: Some(core_dump_dir.to_string()),
+        mount_type: MountType::Bind,
+        read_only: false,
+        tmpfs_options: None,
+    }
+}
+
+/// Gets a `Mount` of the framework's `source_dir` at the given `target`,
+/// over the copy of it made when the image was built.
+pub fn get_source_mount(source_dir: &str, target: &str) -> Mount {
+    Mount {
+        target: target.to_string(),
+        source: Some(source_dir.to_string()),
+        mount_type: MountType::Bind,
+        read_only: false,
+        tmpfs_options: None,
+    }
+}
+
+/// Gets a tmpfs `Mount` at the given `target`, limited to `size_megabytes` if
+/// given.
+pub fn get_tmpfs_mount(target: &str, size_megabytes: Option<u64>) -> Mount {
+    Mount {
+        target: target.to_string(),
+        source: None,
+        mount_type: MountType::Tmpfs,
+        read_only: false,
+        tmpfs_options: Some(TmpfsOptions {
+            size_bytes: size_megabytes.map(|size| size * 1024 * 1024),
+            mode: None,
+        }),
+    }
 }
 
 /// Creates the benchmarker container and returns the Docker ID
 pub fn create_benchmarker_container(
     config: &DockerConfig,
+    protocol: Protocol,
     command_strs: &[String],
 ) -> ToolsetResult<String> {
+    let options = get_benchmarker_options(config, protocol, command_strs);
+    let container_id = config
+        .docker_api
+        .create_container(options, &config.client_docker_host)?;
+    record_container_created(
+        config,
+        get_load_generator_image(config, protocol),
+        &config.client_docker_host,
+        &container_id,
+    )?;
+
+    Ok(container_id)
+}
+
+/// Creates a container which requests the given `url` once with `curl`, from
+/// the client's network; see `docker::preflight`.
+pub fn create_preflight_container(config: &DockerConfig, url: &str) -> ToolsetResult<String> {
+    let image = "techempower/tfb.verifier";
     let mut options = Options::new();
-    options.image("techempower/tfb.verifier");
+    options.image(image);
+    // Logs are only retrieved raw (without stream headers) from TTYs.
     options.tty(true);
-    options.attach_stderr(true);
-    // The command_str we get back is an array of strings that make up the wrk
-    // command; we want to replace `tfb-server` with the IP address
-    let mut command = vec![];
-    for command_str in command_strs {
-        command.push(command_str.replace("tfb-server", &config.server_host));
-    }
+    let command: Vec<String> = [
+        "curl",
+        "--silent",
+        "--show-error",
+        "--output",
+        "/dev/null",
+        "--max-time",
+        PREFLIGHT_TIMEOUT,
+        url,
+    ]
+    .iter()
+    .map(|arg| arg.to_string())
+    .collect();
     options.cmds(command.as_slice());
 
     let mut host_config = HostConfig::new();
-    match &config.network_mode {
-        dockurl::network::NetworkMode::Bridge => {
-            host_config.network_mode(dockurl::network::NetworkMode::Bridge);
-        }
-        dockurl::network::NetworkMode::Host => {
-            host_config.extra_host("tfb-server", &config.server_host);
-            host_config.network_mode(dockurl::network::NetworkMode::Host);
-        }
-    }
-    let mut sysctls = HashMap::new();
-    sysctls.insert("net.core.somaxconn", "65535");
-    host_config.sysctls(sysctls);
-    let ulimit = Ulimit {
-        name: "nofile",
-        soft: 65535,
-        hard: 65535,
-    };
-    host_config.ulimits(vec![ulimit]);
-
+    configure_client_network(config, &mut host_config);
     options.host_config(host_config);
 
     let mut endpoint_settings = EndpointSettings::new();
@@ -140,12 +288,10 @@ pub fn create_benchmarker_container(
         endpoints_config: EndpointsConfig { endpoint_settings },
     });
 
-    let container_id = dockurl::container::create_container(
-        options,
-        config.use_unix_socket,
-        &config.client_docker_host,
-        BuildContainer::new(),
-    )?;
+    let container_id = config
+        .docker_api
+        .create_container(options, &config.client_docker_host)?;
+    record_container_created(config, image, &config.client_docker_host, &container_id)?;
 
     Ok(container_id)
 }
@@ -157,6 +303,7 @@ pub fn create_verifier_container(
     config: &DockerConfig,
     orchestration: &DockerOrchestration,
     mode: Mode,
+    test: &Test,
     test_type: &(&String, &String),
 ) -> ToolsetResult<String> {
     let mut options = Options::new();
@@ -169,29 +316,43 @@ pub fn create_verifier_container(
             Mode::Benchmark => "benchmark",
         },
     );
-    options.add_env("PORT", &orchestration.host_internal_port);
+    // Only over a shared bridge network can the server be reached by its
+    // alias, on the port it exposes; otherwise, its host port is used.
+    match (&config.server_network_mode, &config.client_network_mode) {
+        (NetworkMode::Bridge, NetworkMode::Bridge) => {
+            options.add_env("PORT", &orchestration.host_internal_port)
+        }
+        _ => options.add_env("PORT", &orchestration.host_port),
+    }
     options.add_env("ENDPOINT", test_type.1);
-    options.add_env("TEST_TYPE", test_type.0);
+    if test.is_custom(test_type.0) {
+        // The verifier cannot know the semantics of a custom test type, so it
+        // is told only that it is custom (and its name, for reporting).
+        options.add_env("TEST_TYPE", "custom");
+        options.add_env("CUSTOM_TEST_TYPE", test_type.0);
+    } else {
+        options.add_env("TEST_TYPE", test_type.0);
+    }
     options.add_env("CONCURRENCY_LEVELS", &config.concurrency_levels);
     options.add_env(
         "PIPELINE_CONCURRENCY_LEVELS",
         &config.pipeline_concurrency_levels,
     );
+    // The verifier builds the benchmark commands, so must know for which
+    // load generator (wrk2 also requires a target rate).
+    options.add_env("LOAD_GENERATOR", config.load_generator);
+    if let Mode::Benchmark = mode {
+        // The verifier builds the benchmark commands, so it must be told how
+        // long each is to be measured.
+        options.add_env("DURATION", &config.get_duration(test_type.0).to_string());
+    }
     if let Some(database_name) = &orchestration.database_name {
         options.add_env("DATABASE", database_name);
     }
+    add_verifier_env(config, &mut options);
 
     let mut host_config = HostConfig::new();
-    match &config.network_mode {
-        dockurl::network::NetworkMode::Bridge => {
-            host_config.network_mode(dockurl::network::NetworkMode::Bridge);
-        }
-        dockurl::network::NetworkMode::Host => {
-            host_config.extra_host("tfb-server", &config.server_host);
-            host_config.extra_host("tfb-database", &config.database_host);
-            host_config.network_mode(dockurl::network::NetworkMode::Host);
-        }
-    }
+    configure_client_network(config, &mut host_config);
     host_config.publish_all_ports(true);
 
     options.host_config(host_config);
@@ -203,11 +364,14 @@ pub fn create_verifier_container(
         endpoints_config: EndpointsConfig { endpoint_settings },
     });
 
-    let container_id = dockurl::container::create_container(
-        options,
-        config.use_unix_socket,
+    let container_id = config
+        .docker_api
+        .create_container(options, &config.client_docker_host)?;
+    record_container_created(
+        config,
+        "techempower/tfb.verifier",
         &config.client_docker_host,
-        BuildContainer::new(),
+        &container_id,
     )?;
 
     Ok(container_id)
@@ -235,18 +399,10 @@ pub fn create_database_verifier_container(
         &config.pipeline_concurrency_levels,
     );
     options.add_env("DATABASE", database_name);
+    add_verifier_env(config, &mut options);
 
     let mut host_config = HostConfig::new();
-    match &config.network_mode {
-        dockurl::network::NetworkMode::Bridge => {
-            host_config.network_mode(dockurl::network::NetworkMode::Bridge);
-        }
-        dockurl::network::NetworkMode::Host => {
-            host_config.extra_host("tfb-server", &config.server_host);
-            host_config.extra_host("tfb-database", &config.database_host);
-            host_config.network_mode(dockurl::network::NetworkMode::Host);
-        }
-    }
+    configure_client_network(config, &mut host_config);
     host_config.publish_all_ports(true);
 
     options.host_config(host_config);
@@ -258,43 +414,91 @@ pub fn create_database_verifier_container(
         endpoints_config: EndpointsConfig { endpoint_settings },
     });
 
-    let container_id = dockurl::container::create_container(
-        options,
-        config.use_unix_socket,
+    let container_id = config
+        .docker_api
+        .create_container(options, &config.client_docker_host)?;
+    record_container_created(
+        config,
+        "techempower/tfb.verifier",
         &config.client_docker_host,
-        BuildContainer::new(),
+        &container_id,
     )?;
 
     Ok(container_id)
 }
 
+/// Creates a sidecar of the image of the given `database`, sharing the network
+/// namespace of the database container given by `database_container_id` so
+/// that `script` can query the database over `127.0.0.1`.
+/// Note: this function makes the assumption that the database image has
+/// already been pulled.
+pub fn create_database_sidecar_container(
+    config: &DockerConfig,
+    database: &str,
+    database_container_id: &str,
+    script: &str,
+) -> ToolsetResult<String> {
+    let image = config.get_database_image(database);
+    let mut options = Options::new();
+    options.image(&image);
+    // Logs are only retrieved raw (without stream headers) from TTYs.
+    options.tty(true);
+    let command = vec!["sh".to_string(), "-c".to_string(), script.to_string()];
+    options.cmds(command.as_slice());
+
+    let mut host_config = HostConfig::new();
+    host_config.container_network_mode(database_container_id);
+    options.host_config(host_config);
+
+    let container_id = config
+        .docker_api
+        .create_container(options, &config.database_docker_host)?;
+    record_container_created(config, &image, &config.database_docker_host, &container_id)?;
+
+    Ok(container_id)
+}
+
 /// Gets both the internal and host port binding for the container given by
 /// `container_id`.
+///
+/// When the image exposes more than one port, the `preferred_port` (if any)
+/// is used; otherwise, the lowest exposed port is chosen.
 pub fn get_port_bindings_for_container(
     docker_config: &DockerConfig,
     docker_host: &str,
+    network_mode: &NetworkMode,
     container_id: &str,
+    preferred_port: Option<u16>,
 ) -> ToolsetResult<(String, String)> {
-    let inspection = inspect_container(
-        container_id,
-        docker_host,
-        docker_config.use_unix_socket,
-        Simple::new(),
-    )?;
+    let inspection = docker_config
+        .docker_api
+        .inspect_container(container_id, docker_host)?;
 
-    if let Some(exposed_ports) = inspection.config.exposed_ports {
-        for key in exposed_ports.keys() {
+    if let Some(exposed_ports) = &inspection.exposed_ports {
+        // By port number, rather than as `port/protocol` text, which would put
+        // e.g. `10000/tcp` before `8080/tcp`.
+        let mut keys: Vec<&String> = exposed_ports.iter().collect();
+        keys.sort_by_key(|key| {
+            let port = key
+                .split('/')
+                .next()
+                .and_then(|port| port.parse::<u16>().ok());
+            (port, key.to_string())
+        });
+        if let Some(preferred_port) = preferred_port {
+            let preferred_port = preferred_port.to_string();
+            // Stable sort; moves the preferred port to the front.
+            keys.sort_by_key(|key| key.split('/').next() != Some(preferred_port.as_str()));
+        }
+        for key in keys {
             let inner_port: Vec<&str> = key.split('/').collect();
 
-            match docker_config.network_mode {
+            match network_mode {
                 NetworkMode::Bridge => {
-                    if let Some(key) = inspection.network_settings.ports.get(key) {
-                        if let Some(port_mapping) = key.get(0) {
+                    if let Some(host_ports) = inspection.host_ports.get(key) {
+                        if let Some(host_port) = host_ports.first() {
                             if let Some(inner_port) = inner_port.get(0) {
-                                return Ok((
-                                    port_mapping.host_port.clone(),
-                                    inner_port.to_string(),
-                                ));
+                                return Ok((host_port.clone(), inner_port.to_string()));
                             }
                         }
                     }
@@ -318,28 +522,70 @@ pub fn get_port_bindings_for_container(
 /// Note: this function makes the assumption that the container is already
 /// built and that the docker daemon is aware of it.
 /// Call `create_container()` before running.
+///
+/// Returns `PortConflictError` if the daemon could not bind a host port for
+/// the container so callers may recreate it and try again.
 pub fn start_container(
     docker_config: &DockerConfig,
     container_id: &str,
     docker_host: &str,
+    log_path: LogPath,
     logger: &Logger,
 ) -> ToolsetResult<()> {
     let cid = container_id.to_string();
     let host = docker_host.to_string();
-    let use_unix_socket = docker_config.use_unix_socket;
-    let logger = logger.clone();
+    let docker_api = Arc::clone(&docker_config.docker_api);
+    let mut application = Application::new(logger, log_path);
     thread::spawn(move || {
-        attach_to_container(&cid, &host, use_unix_socket, Application::new(&logger)).unwrap();
+        docker_api
+            .attach_to_container(&cid, &host, &mut application)
+            .unwrap();
     });
-    dockurl::container::start_container(
-        container_id,
-        docker_host,
-        docker_config.use_unix_socket,
-        Simple::new(),
-    )?;
+    match docker_config
+        .docker_api
+        .start_container(container_id, docker_host)
+    {
+        Err(DockerDaemonError(_, _, message))
+            if message.contains("port is already allocated")
+                || message.contains("address already in use") =>
+        {
+            Err(PortConflictError(message))
+        }
+        result => result,
+    }
+}
+
+/// Forcibly removes the container given by `container_id` on `docker_host`,
+/// killing it first if it is still running.
+pub fn remove_container(
+    docker_config: &DockerConfig,
+    docker_host: &str,
+    container_id: &str,
+) -> ToolsetResult<()> {
+    docker_config
+        .docker_api
+        .delete_container(container_id, docker_host)?;
+    record_container_killed(&docker_config.logger, docker_host, container_id, true);
+
     Ok(())
 }
 
+/// Removes the exited container given by `container_id` on `docker_host` if
+/// `--rm` was given. Failing to remove it does not fail the caller; it is
+/// merely left behind.
+pub fn remove_exited_container(
+    docker_config: &DockerConfig,
+    docker_host: &str,
+    container_id: &str,
+) {
+    if docker_config.clean_up {
+        docker_config
+            .docker_api
+            .delete_container(container_id, docker_host)
+            .unwrap_or(());
+    }
+}
+
 /// Retrieves the benchmark commands for the
 pub fn start_benchmark_command_retrieval_container(
     docker_config: &DockerConfig,
@@ -347,36 +593,18 @@ pub fn start_benchmark_command_retrieval_container(
     container_id: &str,
     logger: &Logger,
 ) -> ToolsetResult<BenchmarkCommands> {
-    dockurl::container::start_container(
-        container_id,
-        &docker_config.client_docker_host,
-        docker_config.use_unix_socket,
-        Simple::new(),
-    )?;
-    wait_for_container_to_exit(
-        container_id,
+    let docker_api = &docker_config.docker_api;
+    let client_docker_host = &docker_config.client_docker_host;
+    docker_api.start_container(container_id, client_docker_host)?;
+    docker_api.wait_for_container_to_exit(container_id, client_docker_host)?;
+    let mut listener = BenchmarkCommandListener::new(test_type, logger);
+    docker_api.get_container_logs(container_id, client_docker_host, &mut listener)?;
+
+    remove_exited_container(
+        docker_config,
         &docker_config.client_docker_host,
-        docker_config.use_unix_socket,
-        Simple::new(),
-    )?;
-    let listener = get_container_logs(
         container_id,
-        &docker_config.client_docker_host,
-        docker_config.use_unix_socket,
-        BenchmarkCommandListener::new(test_type, logger),
-    )?;
-
-    if docker_config.clean_up {
-        delete_container(
-            &container_id,
-            &docker_config.client_docker_host,
-            docker_config.use_unix_socket,
-            Simple::new(),
-            true,
-            true,
-            false,
-        )?;
-    }
+    );
     if let Some(commands) = listener.benchmark_commands {
         Ok(commands)
     } else {
@@ -385,43 +613,84 @@ pub fn start_benchmark_command_retrieval_container(
 }
 
 /// Starts the benchmarker container and logs its stdout/stderr.
+///
+/// If the benchmarker exits with a non-zero exit code, the benchmark is
+/// considered failed and its stderr is returned in the error.
+///
+/// If the benchmarker produces no output for `stall_timeout` seconds past the
+/// expected `duration`, the container is killed and the parsed results are
+/// marked as `stalled` so the run can continue with the next command.
 pub fn start_benchmarker_container(
     docker_config: &DockerConfig,
     container_id: &str,
+    protocol: Protocol,
     logger: &Logger,
 ) -> ToolsetResult<BenchmarkResults> {
-    dockurl::container::start_container(
-        container_id,
-        &docker_config.client_docker_host,
-        docker_config.use_unix_socket,
-        Simple::new(),
-    )?;
-    wait_for_container_to_exit(
-        container_id,
+    let benchmarker = Benchmarker::new(logger);
+
+    let benchmarker_container_id = container_id.to_string();
+    let client_docker_host = docker_config.client_docker_host.clone();
+    let docker_api = Arc::clone(&docker_config.docker_api);
+    let mut listener = benchmarker.clone();
+    // See `start_verification_container` for why we attach *before* starting.
+    let attached = thread::spawn(move || {
+        docker_api
+            .attach_to_container(
+                &benchmarker_container_id,
+                &client_docker_host,
+                &mut listener,
+            )
+            .unwrap();
+    });
+
+    docker_config
+        .docker_api
+        .start_container(container_id, &docker_config.client_docker_host)?;
+
+    let exit_code = wait_for_benchmarker_to_exit(docker_config, container_id, &benchmarker)?;
+    attached.join().unwrap_or(());
+    benchmarker.log_truncated_output();
+
+    remove_exited_container(
+        docker_config,
         &docker_config.client_docker_host,
-        docker_config.use_unix_socket,
-        Simple::new(),
-    )?;
-    let benchmarker = get_container_logs(
         container_id,
-        &docker_config.client_docker_host,
-        docker_config.use_unix_socket,
-        Benchmarker::new(logger),
-    )?;
+    );
 
-    if docker_config.clean_up {
-        delete_container(
-            &container_id,
-            &docker_config.client_docker_host,
-            docker_config.use_unix_socket,
-            Simple::new(),
-            true,
-            true,
-            false,
-        )?;
+    // A failed command (e.g. wrk given an unresolvable host) would otherwise
+    // parse as a benchmark of zero requests.
+    if let Some(exit_code) = exit_code {
+        if exit_code != 0 {
+            return Err(BenchmarkCommandFailedError(
+                exit_code,
+                benchmarker.get_stderr(),
+            ));
+        }
+    }
+
+    let mut results = match protocol {
+        Protocol::Http => benchmarker.parse_wrk_output()?,
+        Protocol::Grpc => benchmarker.parse_ghz_output()?,
+        Protocol::Websocket => benchmarker.parse_tcpkali_output()?,
+    };
+    // Nor is one which exited cleanly having completed no requests (e.g. wrk
+    // could not reach the server) written to the results as though it were a
+    // benchmark. A stalled command is killed before wrk summarizes, so it is
+    // reported as stalled instead.
+    if protocol == Protocol::Http && exit_code.is_some() && results.total_requests == 0 {
+        let mut output = benchmarker.get_output();
+        let stderr = benchmarker.get_stderr();
+        if !stderr.is_empty() {
+            output.push('\n');
+            output.push_str(&stderr);
+        }
+        return Err(NoRequestsCompletedError(output));
     }
+    results.stalled = exit_code.is_none();
+    results.exit_code = exit_code;
+    results.stderr = benchmarker.get_stderr();
 
-    benchmarker.parse_wrk_output()
+    Ok(results)
 }
 
 /// Starts the verification container, captures its stdout/stderr, parses any
@@ -444,11 +713,9 @@ pub fn start_verification_container(
     let verification = Arc::new(Mutex::new(to_ret.clone()));
 
     let verifier_container_id = container_id.to_string();
-    let config = docker_config.clone();
-    let client_docker_host = config.client_docker_host;
-    let use_unix_socket = docker_config.use_unix_socket;
-    let verifier_logger = logger.clone();
-    let inner_verification = Arc::clone(&verification);
+    let client_docker_host = docker_config.client_docker_host.clone();
+    let docker_api = Arc::clone(&docker_config.docker_api);
+    let mut verifier = Verifier::new(Arc::clone(&verification), test_type.0, logger);
     // This function is extremely complicated and seemingly in the wrong order, but it is very
     // convoluted and intended. We attach to the container *before* it is started in a new thread,
     // and, using an Arc, communicate stderr/stdout and messages from the container (when it runs)
@@ -460,40 +727,20 @@ pub fn start_verification_container(
     // container is started, and therefore it *will* exit after we are `attached` which will close
     // the connection.
     thread::spawn(move || {
-        dockurl::container::attach_to_container(
-            &verifier_container_id,
-            &client_docker_host,
-            use_unix_socket,
-            Verifier::new(Arc::clone(&inner_verification), &verifier_logger),
-        )
-        .unwrap();
+        docker_api
+            .attach_to_container(&verifier_container_id, &client_docker_host, &mut verifier)
+            .unwrap();
     });
 
-    dockurl::container::start_container(
-        &container_id,
-        &docker_config.client_docker_host,
-        docker_config.use_unix_socket,
-        Simple::new(),
-    )?;
+    let docker_api = &docker_config.docker_api;
+    docker_api.start_container(container_id, &docker_config.client_docker_host)?;
+    docker_api.wait_for_container_to_exit(container_id, &docker_config.client_docker_host)?;
 
-    wait_for_container_to_exit(
-        &container_id,
+    remove_exited_container(
+        docker_config,
         &docker_config.client_docker_host,
-        docker_config.use_unix_socket,
-        Simple::new(),
-    )?;
-
-    if docker_config.clean_up {
-        delete_container(
-            &container_id,
-            &docker_config.client_docker_host,
-            docker_config.use_unix_socket,
-            Simple::new(),
-            true,
-            true,
-            false,
-        )?;
-    }
+        container_id,
+    );
 
     if let Ok(verification) = verification.lock() {
         to_ret = verification.clone();
@@ -507,31 +754,15 @@ pub fn block_until_database_is_ready(
     docker_config: &DockerConfig,
     container_id: &str,
 ) -> ToolsetResult<()> {
-    dockurl::container::start_container(
-        container_id,
-        &docker_config.client_docker_host,
-        docker_config.use_unix_socket,
-        Simple::new(),
-    )?;
+    let docker_api = &docker_config.docker_api;
+    docker_api.start_container(container_id, &docker_config.client_docker_host)?;
+    docker_api.wait_for_container_to_exit(container_id, &docker_config.client_docker_host)?;
 
-    wait_for_container_to_exit(
-        container_id,
+    remove_exited_container(
+        docker_config,
         &docker_config.client_docker_host,
-        docker_config.use_unix_socket,
-        Simple::new(),
-    )?;
-
-    if docker_config.clean_up {
-        delete_container(
-            container_id,
-            &docker_config.client_docker_host,
-            docker_config.use_unix_socket,
-            Simple::new(),
-            true,
-            true,
-            false,
-        )?;
-    }
+        container_id,
+    );
 
     Ok(())
 }
@@ -542,9 +773,10 @@ pub fn block_until_database_is_ready(
 ///
 /// Note: this function blocks until the given `container` is in a ready state.
 pub fn stop_docker_container_future(
-    use_unix_socket: bool,
+    docker_api: &dyn DockerApi,
     docker_clean_up: bool,
     container_id: &Arc<Mutex<DockerContainerIdFuture>>,
+    logger: &Logger,
 ) {
     let mut requires_wait_to_stop = false;
     if let Ok(container) = container_id.lock() {
@@ -562,55 +794,463 @@ pub fn stop_docker_container_future(
         }
         if let Ok(mut container) = container_id.lock() {
             if let Some(container_id) = &container.container_id {
-                kill_container(
-                    container_id,
-                    &container.docker_host,
-                    use_unix_socket,
-                    Simple::new(),
-                )
-                .unwrap_or(());
+                docker_api
+                    .kill_container(container_id, &container.docker_host)
+                    .unwrap_or(());
                 // ↑ specifically succeeds even if there is an error
                 // For instance, if an application container stops running because the application
                 // crashed, we want to call this and continue.
 
                 if docker_clean_up {
-                    delete_container(
-                        container_id,
-                        &container.docker_host,
-                        use_unix_socket,
-                        Simple::new(),
-                        true,
-                        true,
-                        false,
-                    )
-                    .unwrap_or(());
+                    docker_api
+                        .delete_container(container_id, &container.docker_host)
+                        .unwrap_or(());
                 }
+                record_container_killed(
+                    logger,
+                    &container.docker_host,
+                    container_id,
+                    docker_clean_up,
+                );
 
                 container.unregister();
             }
             if let Some(image_id) = &container.image_id {
                 if docker_clean_up {
-                    delete_image(
-                        image_id,
-                        true,
-                        false,
-                        &container.docker_host,
-                        use_unix_socket,
-                        Simple::new(),
-                    )
-                    .unwrap_or(None);
+                    docker_api
+                        .delete_image(image_id, &container.docker_host)
+                        .unwrap_or(());
 
                     // Todo - this is jank... do this better.
-                    delete_unused_images(
-                        "{\"dangling\":[\"true\"]}",
-                        &container.docker_host,
-                        use_unix_socket,
-                        Simple::new(),
-                    )
-                    .unwrap_or(());
+                    docker_api
+                        .delete_dangling_images(&container.docker_host)
+                        .unwrap_or(());
                 }
             }
             container.image_id = None;
         }
     }
 }
+
+/// Kills and removes the container registered with the given `container_id`
+/// future, if any, without waiting for one to be registered.
+///
+/// Unlike `stop_docker_container_future`, the container is removed regardless
+/// of `--rm`; this is used to leave nothing behind when shutdown is forced.
+pub fn force_remove_docker_container_future(
+    docker_api: &dyn DockerApi,
+    container_id: &Arc<Mutex<DockerContainerIdFuture>>,
+    logger: &Logger,
+) {
+    if let Ok(mut container) = container_id.lock() {
+        if let Some(container_id) = &container.container_id {
+            docker_api
+                .kill_container(container_id, &container.docker_host)
+                .unwrap_or(());
+            docker_api
+                .delete_container(container_id, &container.docker_host)
+                .unwrap_or(());
+            record_container_killed(logger, &container.docker_host, container_id, true);
+
+            container.unregister();
+        }
+    }
+}
+
+//
+// PRIVATES
+//
+
+fn record_container_created(
+    config: &DockerConfig,
+    image: &str,
+    docker_host: &str,
+    container_id: &str,
+) -> ToolsetResult<()> {
+    config.logger.record_event(
+        "container.created",
+        &[
+            ("image", image),
+            ("docker_host", docker_host),
+            ("container_id", container_id),
+        ],
+    )
+}
+
+/// Records that the container given by `container_id` was killed (and
+/// `removed`, if so), failing silently; this is used when tearing down, where
+/// nothing may fail.
+fn record_container_killed(logger: &Logger, docker_host: &str, container_id: &str, removed: bool) {
+    let removed = removed.to_string();
+    logger
+        .record_event(
+            "container.killed",
+            &[
+                ("docker_host", docker_host),
+                ("container_id", container_id),
+                ("removed", &removed),
+            ],
+        )
+        .unwrap_or(());
+}
+
+/// Sets the network mode of a client (verifier or benchmarker) container,
+/// adding hosts for the roles it cannot reach by alias over a shared bridge
+/// network.
+fn configure_client_network(config: &DockerConfig, host_config: &mut HostConfig) {
+    match &config.client_network_mode {
+        NetworkMode::Bridge => host_config.network_mode(NetworkMode::Bridge),
+        NetworkMode::Host => host_config.network_mode(NetworkMode::Host),
+    }
+    match (&config.server_network_mode, &config.client_network_mode) {
+        (NetworkMode::Bridge, NetworkMode::Bridge) if config.server_url_override.is_none() => {}
+        _ => host_config.extra_host("tfb-server", config.get_client_server_host()),
+    }
+    if let NetworkMode::Host = config.database_network_mode {
+        host_config.extra_host(
+            "tfb-database",
+            config.get_database_host(&config.client_docker_host, &config.client_network_mode),
+        );
+    }
+}
+
+/// Adds the dataset size assumptions and the environment variables given via
+/// `--verifier-env` to the given verifier container `options`.
+fn add_verifier_env(config: &DockerConfig, options: &mut Options) {
+    options.add_env("MAX_QUERIES", &config.max_queries.to_string());
+    options.add_env("WORLD_ROWS", &config.world_rows.to_string());
+    for (key, value) in &config.verifier_env {
+        options.add_env(key, value);
+    }
+}
+
+/// The path, inside the benchmarker container, of the combined wrk script.
+const WRK_SCRIPT_PATH: &str = "/tmp/tfb.lua";
+
+/// Rewrites the given wrk `command` such that `wrk_summary.lua` is appended to
+/// whichever script (if any) the verifier asked wrk to run, and that combined
+/// script is run instead. This is done by a small shell wrapper which receives
+/// the scripts via env, which avoids needing to escape the wrk arguments.
+fn with_wrk_summary_script(options: &mut Options, command: Vec<String>) -> Vec<String> {
+    let mut wrk_args = vec![];
+    let mut script = None;
+    let mut args = command.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            // Everything after `--` is passed to the script itself.
+            wrk_args.push(arg);
+            wrk_args.extend(args.by_ref());
+        } else if arg == "-s" || arg == "--script" {
+            script = args.next();
+        } else if let Some(path) = arg.strip_prefix("--script=") {
+            script = Some(path.to_string());
+        } else {
+            wrk_args.push(arg);
+        }
+    }
+    if wrk_args.len() > 1 {
+        wrk_args.insert(1, "-s".to_string());
+        wrk_args.insert(2, WRK_SCRIPT_PATH.to_string());
+    }
+
+    options.add_env("TFB_WRK_SUMMARY", include_str!("wrk_summary.lua"));
+    options.add_env("TFB_WRK_SCRIPT", &script.unwrap_or_default());
+
+    let mut command = vec![
+        "sh".to_string(),
+        "-c".to_string(),
+        format!(
+            "{{ [ -n \"$TFB_WRK_SCRIPT\" ] && cat \"$TFB_WRK_SCRIPT\"; printf '%s\\n' \"$TFB_WRK_SUMMARY\"; }} > {} && exec \"$@\"",
+            WRK_SCRIPT_PATH
+        ),
+        "sh".to_string(),
+    ];
+    command.append(&mut wrk_args);
+
+    command
+}
+
+/// Blocks until the benchmarker container given by `container_id` exits or is
+/// considered stalled. Returns the exit code of the benchmarker, or `None` if
+/// it stalled, in which case its container has been killed.
+fn wait_for_benchmarker_to_exit(
+    docker_config: &DockerConfig,
+    container_id: &str,
+    benchmarker: &Benchmarker,
+) -> ToolsetResult<Option<i32>> {
+    // The benchmarker is not told the test type, so allow for the longest.
+    let stalled_after =
+        u64::from(docker_config.get_longest_duration() + docker_config.stall_timeout);
+    let mut slept_for = 0;
+    loop {
+        let inspection = docker_config
+            .docker_api
+            .inspect_container(container_id, &docker_config.client_docker_host)?;
+        if !inspection.running {
+            return Ok(Some(inspection.exit_code));
+        }
+        if slept_for > stalled_after
+            && benchmarker.seconds_since_last_write() > u64::from(docker_config.stall_timeout)
+        {
+            docker_config
+                .docker_api
+                .kill_container(container_id, &docker_config.client_docker_host)?;
+            record_container_killed(
+                &docker_config.logger,
+                &docker_config.client_docker_host,
+                container_id,
+                false,
+            );
+            return Ok(None);
+        }
+        slept_for += 1;
+        thread::sleep(Duration::from_secs(1));
+    }
+}
+
+/// Gets the `core_pattern` under which core dumps are written beneath
+/// `CORE_DUMP_MOUNT`, named for the crashing executable, its PID, and the
+/// time.
+fn get_core_pattern() -> String {
+    format!("{}/core.%e.%p.%t", CORE_DUMP_MOUNT)
+}
+
+/// Gets the shell command which writes the given `core_pattern` to the
+/// kernel, quoted such that it is written verbatim.
+fn get_write_core_pattern_command(core_pattern: &str) -> String {
+    format!(
+        "echo '{}' > {}",
+        core_pattern.replace('\'', "'\\''"),
+        CORE_PATTERN_FILE
+    )
+}
+
+/// Runs the given shell `command` in a privileged busybox container on the
+/// given `docker_host` and returns its output, trimmed; busybox must have
+/// been pulled.
+fn run_privileged_command(
+    docker_api: &dyn DockerApi,
+    docker_host: &str,
+    command: &str,
+) -> ToolsetResult<String> {
+    let mut options = Options::new();
+    options.image("busybox");
+    options.tty(true);
+    options.cmds(&["sh".to_string(), "-c".to_string(), command.to_string()]);
+    let mut host_config = HostConfig::new();
+    host_config.privileged(true);
+    options.host_config(host_config);
+
+    let container_id = docker_api.create_container(options, docker_host)?;
+    docker_api.start_container(&container_id, docker_host)?;
+    docker_api.wait_for_container_to_exit(&container_id, docker_host)?;
+    let mut output = Output::default();
+    docker_api.get_container_logs(&container_id, docker_host, &mut output)?;
+    docker_api.delete_container(&container_id, docker_host)?;
+
+    Ok(output.get_output())
+}
+
+/// Gets the options of the benchmarker container running `command_strs`.
+///
+/// The container has no TTY, so that Docker multiplexes its stdout and stderr
+/// onto the attached stream and the two can be told apart (see
+/// `listener::benchmarker::Benchmarker`).
+fn get_benchmarker_options(
+    config: &DockerConfig,
+    protocol: Protocol,
+    command_strs: &[String],
+) -> Options {
+    let mut options = Options::new();
+    options.image(get_load_generator_image(config, protocol));
+    options.attach_stderr(true);
+    // The command_str we get back is an array of strings that make up the wrk
+    // command; we want to replace `tfb-server` with the IP address
+    let mut command = vec![];
+    for command_str in command_strs {
+        command.push(command_str.replace("tfb-server", config.get_client_server_host()));
+    }
+    if protocol == Protocol::Http {
+        command = with_wrk_summary_script(&mut options, command);
+    }
+    options.cmds(command.as_slice());
+
+    let mut host_config = HostConfig::new();
+    configure_client_network(config, &mut host_config);
+    let mut sysctls = HashMap::new();
+    sysctls.insert("net.core.somaxconn", "65535");
+    host_config.sysctls(sysctls);
+    let ulimit = Ulimit {
+        name: "nofile",
+        soft: 65535,
+        hard: 65535,
+    };
+    host_config.ulimits(vec![ulimit]);
+    config.apply_resource_limits(Role::Client, &mut host_config);
+
+    options.host_config(host_config);
+
+    let mut endpoint_settings = EndpointSettings::new();
+    endpoint_settings.network_id(config.client_network_id.as_str());
+
+    options.networking_config(NetworkingConfig {
+        endpoints_config: EndpointsConfig { endpoint_settings },
+    });
+
+    options
+}
+
+//
+// TESTS
+//
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Protocol;
+    use crate::docker::container::{
+        add_verifier_env, configure_core_pattern, get_benchmarker_options, get_core_dump_mount,
+        get_core_pattern, get_port_bindings_for_container, get_write_core_pattern_command,
+        restore_core_pattern, start_benchmarker_container, start_container, CORE_DUMP_MOUNT,
+    };
+    use crate::docker::mock::mock_docker_config;
+    use crate::error::ToolsetError::{NoRequestsCompletedError, PortConflictError};
+    use crate::io::{LogPath, Logger};
+    use dockurl::container::create::options::Options;
+    use dockurl::network::NetworkMode;
+
+    #[test]
+    fn it_can_pass_env_to_the_verifier() {
+        let (_, _, docker_config) = mock_docker_config(&[
+            "--verifier-env",
+            "ACCEPT=text/html,application/json",
+            "--verifier-env",
+            "MAX_QUERIES=50",
+        ]);
+        assert_eq!(
+            docker_config.verifier_env,
+            vec![
+                (
+                    "ACCEPT".to_string(),
+                    "text/html,application/json".to_string()
+                ),
+                ("MAX_QUERIES".to_string(), "50".to_string()),
+            ]
+        );
+
+        let mut options = Options::new();
+        add_verifier_env(&docker_config, &mut options);
+        let options = options.to_json();
+        // Docker takes the last of any variables given twice.
+        let default = options.find(&format!("\"MAX_QUERIES={}\"", docker_config.max_queries));
+        let overridden = options.find("\"MAX_QUERIES=50\"");
+        assert!(default.unwrap() < overridden.unwrap());
+    }
+
+    #[test]
+    fn it_can_write_core_dumps_beneath_the_mount() {
+        let mount = get_core_dump_mount("/results/gemini/cores");
+        assert_eq!(mount.target, CORE_DUMP_MOUNT);
+        assert_eq!(mount.source.as_deref(), Some("/results/gemini/cores"));
+        assert!(get_core_pattern().starts_with(&format!("{}/core.", CORE_DUMP_MOUNT)));
+
+        assert_eq!(
+            get_write_core_pattern_command("|/usr/share/apport/apport %p %s"),
+            "echo '|/usr/share/apport/apport %p %s' > /proc/sys/kernel/core_pattern"
+        );
+        assert_eq!(
+            get_write_core_pattern_command("it's"),
+            "echo 'it'\\''s' > /proc/sys/kernel/core_pattern"
+        );
+    }
+
+    #[test]
+    fn it_can_configure_and_restore_the_core_pattern() {
+        let (_, docker_api, docker_config) = mock_docker_config(&[]);
+
+        configure_core_pattern(&docker_config, "tfb-server:2375").unwrap();
+        restore_core_pattern(docker_api.as_ref(), "tfb-server:2375", "core").unwrap();
+
+        let operations = docker_api.get_operations();
+        assert!(operations.contains(&"get container logs mock-1 on tfb-server:2375".to_string()));
+        assert!(operations.contains(&"delete container mock-1 on tfb-server:2375".to_string()));
+        assert!(operations.contains(&"delete container mock-2 on tfb-server:2375".to_string()));
+    }
+
+    #[test]
+    fn it_can_separate_the_benchmarker_output_streams() {
+        let (_, _, docker_config) = mock_docker_config(&[]);
+
+        let options = get_benchmarker_options(
+            &docker_config,
+            Protocol::Http,
+            &["wrk".to_string(), "http://tfb-server:8080/json".to_string()],
+        )
+        .to_json();
+
+        // With a TTY, Docker would not multiplex stderr onto the stream.
+        assert!(!options.contains("\"Tty\":true"));
+        assert!(options.contains("\"AttachStderr\":true"));
+    }
+
+    #[test]
+    fn it_can_pick_the_lowest_exposed_port() {
+        let (_, docker_api, docker_config) = mock_docker_config(&[]);
+        docker_api.expose_ports(&[10000, 8080]);
+
+        let get_port_bindings = |preferred_port| {
+            get_port_bindings_for_container(
+                &docker_config,
+                "localhost:2375",
+                &NetworkMode::Bridge,
+                "mock-1",
+                preferred_port,
+            )
+            .unwrap()
+        };
+
+        assert_eq!(
+            get_port_bindings(None),
+            ("33768".to_string(), "8080".to_string())
+        );
+        assert_eq!(
+            get_port_bindings(Some(10000)),
+            ("32768".to_string(), "10000".to_string())
+        );
+    }
+
+    #[test]
+    fn it_can_report_port_conflicts() {
+        let (_, docker_api, docker_config) = mock_docker_config(&[]);
+        docker_api.fail(
+            "start container",
+            "driver failed programming external connectivity: Bind for 0.0.0.0:8080 failed: port is already allocated",
+        );
+
+        match start_container(
+            &docker_config,
+            "application",
+            &docker_config.server_docker_host,
+            LogPath::Server,
+            &Logger::default(),
+        ) {
+            Err(PortConflictError(message)) => assert!(message.contains("8080")),
+            _ => panic!("start_container did not report the port conflict."),
+        }
+    }
+
+    #[test]
+    fn it_can_fail_benchmarks_of_zero_requests() {
+        let (_, _, docker_config) = mock_docker_config(&[]);
+
+        // The mock benchmarker exits cleanly without any output.
+        match start_benchmarker_container(
+            &docker_config,
+            "benchmarker",
+            Protocol::Http,
+            &Logger::default(),
+        ) {
+            Err(NoRequestsCompletedError(_)) => {}
+            _ => panic!("start_benchmarker_container did not fail the benchmark."),
+        }
+    }
+}
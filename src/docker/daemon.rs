@@ -0,0 +1,289 @@
+//! Records the configuration of the Docker daemon on each host before a run
+//! and, given an expected profile, refuses to run on hosts which deviate from
+//! it; a different storage or cgroup driver can skew results as much as
+//! different hardware.
+//!
+//! The skew of each daemon's clock from the toolset's is recorded as well;
+//! benchmark start and end times come from the toolset host, while container
+//! timestamps come from the daemons.
+
+use crate::docker::docker_config::{DockerConfig, DOCKER_SOCKET};
+use crate::docker::listener::daemon_info::DaemonInfo;
+use crate::error::ToolsetError::{ClockSkewError, DaemonInfoError, DaemonProfileMismatchError};
+use crate::error::ToolsetResult;
+use crate::io::Logger;
+use crate::results::DaemonConfiguration;
+use chrono::{DateTime, Utc};
+use curl::easy::Easy2;
+use serde::Deserialize;
+
+/// The daemon configuration expected of every host, as read from the TOML
+/// file given via `--expected-daemon-profile`; e.g.
+///
+/// ```toml
+/// storage_driver = "overlay2"
+/// cgroup_driver = "systemd"
+/// live_restore = false
+/// ```
+///
+/// Settings which are omitted are not checked.
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct DaemonProfile {
+    pub storage_driver: Option<String>,
+    pub cgroup_driver: Option<String>,
+    pub live_restore: Option<bool>,
+}
+impl DaemonProfile {
+    /// Gets a description of each way in which `daemon` deviates from this
+    /// profile.
+    pub fn get_mismatches(&self, daemon: &DaemonConfiguration) -> Vec<String> {
+        let mut mismatches = Vec::new();
+        if let Some(storage_driver) = &self.storage_driver {
+            if *storage_driver != daemon.storage_driver {
+                mismatches.push(format!(
+                    "{} uses the {} storage driver; expected {}",
+                    daemon.roles.join("/"),
+                    daemon.storage_driver,
+                    storage_driver
+                ));
+            }
+        }
+        if let Some(cgroup_driver) = &self.cgroup_driver {
+            if *cgroup_driver != daemon.cgroup_driver {
+                mismatches.push(format!(
+                    "{} uses the {} cgroup driver; expected {}",
+                    daemon.roles.join("/"),
+                    daemon.cgroup_driver,
+                    cgroup_driver
+                ));
+            }
+        }
+        if let Some(live_restore) = self.live_restore {
+            if live_restore != daemon.live_restore {
+                mismatches.push(format!(
+                    "{} has live-restore {}; expected it {}",
+                    daemon.roles.join("/"),
+                    get_enabled(daemon.live_restore),
+                    get_enabled(live_restore)
+                ));
+            }
+        }
+
+        mismatches
+    }
+}
+
+/// Gets the configuration of the Docker daemon of the server, database, and
+/// client hosts (once per distinct daemon) and logs it. If an expected profile
+/// was given, any host deviating from it fails the check, as does any host
+/// whose clock is skewed beyond `--max-clock-skew`.
+pub fn check_daemons(
+    docker_config: &DockerConfig,
+    logger: &Logger,
+) -> ToolsetResult<Vec<DaemonConfiguration>> {
+    let profile = match docker_config.expected_daemon_profile {
+        Some(path) => Some(toml::from_str::<DaemonProfile>(&std::fs::read_to_string(
+            path,
+        )?)?),
+        None => None,
+    };
+
+    let hosts = [
+        ("server", &docker_config.server_docker_host),
+        ("database", &docker_config.database_docker_host),
+        ("client", &docker_config.client_docker_host),
+    ];
+    let mut daemons: Vec<DaemonConfiguration> = Vec::new();
+    for (role, docker_host) in hosts.iter() {
+        match daemons
+            .iter_mut()
+            .find(|daemon| docker_config.is_same_daemon(&daemon.docker_host, docker_host))
+        {
+            Some(daemon) => daemon.roles.push(role.to_string()),
+            None => daemons.push(get_daemon_configuration(docker_config, role, docker_host)?),
+        }
+    }
+
+    let mut mismatches = Vec::new();
+    let mut skews = Vec::new();
+    for daemon in &daemons {
+        logger.log(format!(
+            "Docker {} on {} ({}): {} storage driver, {} cgroup driver (v{}), live-restore {}, clock skew {}",
+            daemon.server_version,
+            daemon.roles.join("/"),
+            daemon.docker_host,
+            daemon.storage_driver,
+            daemon.cgroup_driver,
+            daemon.cgroup_version,
+            get_enabled(daemon.live_restore),
+            match daemon.clock_skew_ms {
+                Some(skew) => format!("{}ms", skew),
+                None => "unknown".to_string(),
+            }
+        ))?;
+        if let Some(profile) = &profile {
+            mismatches.extend(profile.get_mismatches(daemon));
+        }
+        if let (Some(max), Some(skew)) = (docker_config.max_clock_skew, daemon.clock_skew_ms) {
+            if skew.abs() > max {
+                skews.push(format!(
+                    "the clock of {} is {}ms {} the toolset's",
+                    daemon.roles.join("/"),
+                    skew.abs(),
+                    if skew > 0 { "ahead of" } else { "behind" }
+                ));
+            }
+        }
+    }
+    if !mismatches.is_empty() {
+        for mismatch in &mismatches {
+            logger.error(mismatch)?;
+        }
+        return Err(DaemonProfileMismatchError(
+            docker_config.expected_daemon_profile.unwrap().to_string(),
+            mismatches.join("; "),
+        ));
+    }
+    if !skews.is_empty() {
+        for skew in &skews {
+            logger.error(skew)?;
+        }
+        return Err(ClockSkewError(
+            docker_config.max_clock_skew.unwrap(),
+            skews.join("; "),
+        ));
+    }
+
+    Ok(daemons)
+}
+
+//
+// PRIVATES
+//
+
+fn get_daemon_configuration(
+    docker_config: &DockerConfig,
+    role: &str,
+    docker_host: &str,
+) -> ToolsetResult<DaemonConfiguration> {
+    let mut easy = Easy2::new(DaemonInfo::new());
+    if docker_config.use_unix_socket {
+        easy.unix_socket(DOCKER_SOCKET)?;
+        easy.url("http://localhost/info")?;
+    } else {
+        easy.url(&format!("http://{}/info", docker_host))?;
+    }
+    let requested = Utc::now();
+    easy.perform()?;
+    let answered = Utc::now();
+
+    match easy.get_ref().parse_report() {
+        Some(report) => Ok(DaemonConfiguration {
+            roles: vec![role.to_string()],
+            docker_host: docker_host.to_string(),
+            server_version: report.server_version.unwrap_or_default(),
+            storage_driver: report.driver.unwrap_or_default(),
+            cgroup_driver: report.cgroup_driver.unwrap_or_default(),
+            // Daemons older than 20.10 only support cgroup v1.
+            cgroup_version: report.cgroup_version.unwrap_or_else(|| "1".to_string()),
+            live_restore: report.live_restore_enabled.unwrap_or_default(),
+            clock_skew_ms: report
+                .system_time
+                .and_then(|system_time| get_clock_skew(&system_time, requested, answered)),
+        }),
+        None => Err(DaemonInfoError(docker_host.to_string())),
+    }
+}
+
+/// Gets how many milliseconds the daemon's `system_time` is ahead of the
+/// midpoint of the request for it, made at `requested` and answered at
+/// `answered` per the toolset's clock.
+fn get_clock_skew(
+    system_time: &str,
+    requested: DateTime<Utc>,
+    answered: DateTime<Utc>,
+) -> Option<i64> {
+    let system_time = DateTime::parse_from_rfc3339(system_time).ok()?;
+    let midpoint = requested + answered.signed_duration_since(requested) / 2;
+
+    Some(
+        system_time
+            .with_timezone(&Utc)
+            .signed_duration_since(midpoint)
+            .num_milliseconds(),
+    )
+}
+
+fn get_enabled(enabled: bool) -> &'static str {
+    if enabled {
+        "enabled"
+    } else {
+        "disabled"
+    }
+}
+
+//
+// TESTS
+//
+
+#[cfg(test)]
+mod tests {
+    use crate::docker::daemon::{get_clock_skew, DaemonProfile};
+    use crate::results::DaemonConfiguration;
+    use chrono::{DateTime, Utc};
+
+    #[test]
+    fn it_can_compare_daemons_to_a_profile() {
+        let profile: DaemonProfile = toml::from_str(
+            r#"
+            storage_driver = "overlay2"
+            live_restore = false
+            "#,
+        )
+        .unwrap();
+        let mut daemon = DaemonConfiguration {
+            roles: vec!["server".to_string(), "database".to_string()],
+            docker_host: "localhost".to_string(),
+            server_version: "20.10.2".to_string(),
+            storage_driver: "overlay2".to_string(),
+            cgroup_driver: "cgroupfs".to_string(),
+            cgroup_version: "1".to_string(),
+            live_restore: false,
+            clock_skew_ms: None,
+        };
+        assert!(profile.get_mismatches(&daemon).is_empty());
+
+        daemon.storage_driver = "devicemapper".to_string();
+        daemon.live_restore = true;
+        assert_eq!(
+            profile.get_mismatches(&daemon),
+            vec![
+                "server/database uses the devicemapper storage driver; expected overlay2",
+                "server/database has live-restore enabled; expected it disabled",
+            ]
+        );
+        assert!(toml::from_str::<DaemonProfile>("storage = \"overlay2\"").is_err());
+    }
+
+    #[test]
+    fn it_can_measure_clock_skew() {
+        let at = |time: &str| {
+            DateTime::parse_from_rfc3339(time)
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        let requested = at("2021-01-05T12:00:00.000Z");
+        let answered = at("2021-01-05T12:00:00.200Z");
+
+        assert_eq!(
+            get_clock_skew("2021-01-05T12:00:01.600123456Z", requested, answered),
+            Some(1500)
+        );
+        assert_eq!(
+            get_clock_skew("2021-01-05T13:59:59.850+02:00", requested, answered),
+            Some(-250)
+        );
+        assert_eq!(get_clock_skew("yesterday", requested, answered), None);
+    }
+}
@@ -1,85 +1,330 @@
 use crate::benchmarker::modes;
+use crate::config::Test;
+use crate::docker::api::{DockerApi, Dockurl};
 use crate::docker::network::{get_network_id, get_tfb_network_id};
+use crate::docker::resources::{ResourceProfile, Role};
+use crate::error::ToolsetError::{
+    BridgedDatabaseFromHostNetworkError, DatabaseHostsInBridgeNetworkError,
+    DatabaseHostsOverUnixSocketError, HostNetworkShapingError, InvalidDurationError,
+    InvalidServerUrlError,
+};
+use crate::error::ToolsetResult;
 use crate::io::{create_results_dir, Logger};
 use crate::options;
+use crate::results::{Manifest, NetworkShaping};
+use crate::schedule::{parse_start_at, parse_window, Schedule};
+use chrono::Local;
+use dockurl::container::create::host_config::HostConfig;
+use dockurl::network::NetworkMode;
 use dockurl::network::NetworkMode::{Bridge, Host};
+use rand::Rng;
+use std::path::PathBuf;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// The port on which Docker daemons are reached when none is given.
+const DOCKER_PORT: u16 = 2375;
+/// The unix socket through which local Docker daemons are reached.
+pub const DOCKER_SOCKET: &str = "/var/run/docker.sock";
+/// The address at which a container in host network mode reaches another in
+/// host network mode on the same Docker daemon.
+const LOOPBACK_ADDRESS: &str = "127.0.0.1";
+/// The hosts by which a Docker daemon on the toolset's own host is given.
+const LOOPBACK_HOSTS: [&str; 3] = ["localhost", "127.0.0.1", "[::1]"];
 
 #[derive(Debug, Clone)]
 pub struct DockerConfig<'a> {
     pub use_unix_socket: bool,
+    pub docker_api: Arc<dyn DockerApi>,
     pub server_docker_host: String,
     pub server_host: &'a str,
+    pub server_url_override: Option<(String, String)>,
     pub server_network_id: String,
     pub database_docker_host: String,
     pub database_host: &'a str,
+    /// The host of each database given via `--database-hosts`, on whose
+    /// daemon it is started and at which it is reached in place of the
+    /// database host and Docker host.
+    pub database_hosts: Vec<(String, &'a str)>,
     pub database_network_id: String,
+    pub database_tmpfs_size: Option<u64>,
+    pub database_versions: Vec<(String, String)>,
+    pub shm_size: Option<u64>,
+    pub tmpfs_mounts: Vec<(String, Option<u64>)>,
     pub client_docker_host: String,
     pub client_host: &'a str,
     pub client_network_id: String,
-    pub network_mode: dockurl::network::NetworkMode,
+    pub server_network_mode: dockurl::network::NetworkMode,
+    pub database_network_mode: dockurl::network::NetworkMode,
+    pub client_network_mode: dockurl::network::NetworkMode,
+    pub isolate_networks: bool,
+    pub port_retries: u32,
+    pub verify_retries: u32,
+    pub expected_latency: Option<f32>,
+    pub expected_bandwidth: Option<f32>,
+    pub expected_daemon_profile: Option<&'a str>,
+    pub max_clock_skew: Option<i64>,
+    pub network_shaping: Option<NetworkShaping>,
+    pub resource_profile: Option<ResourceProfile>,
     pub concurrency_levels: String,
     pub pipeline_concurrency_levels: String,
     pub query_levels: String,
     pub cached_query_levels: String,
+    pub max_queries: u32,
+    pub world_rows: u32,
+    pub websocket_connections: String,
+    pub websocket_message_rate: u32,
+    pub verifier_env: Vec<(String, String)>,
     pub duration: u32,
+    pub test_type_durations: Vec<(String, u32)>,
+    pub stall_timeout: u32,
+    pub startup_timeout: u32,
+    pub build_timeout: Option<u32>,
+    pub profile: bool,
+    pub sample_connections: bool,
+    pub count_queries: bool,
+    pub core_dumps: bool,
+    pub mount_source: bool,
+    pub tune_max_concurrency: u32,
+    pub interleave: bool,
+    pub restart_after_verification: bool,
+    pub restart_between_types: bool,
+    pub calibrate: bool,
+    pub baseline_interval: Option<u32>,
+    pub build_jobs: usize,
+    pub pause_file: Option<PathBuf>,
+    pub schedule: Schedule,
+    pub adaptive_cv: Option<f32>,
+    pub adaptive_interval: u32,
+    pub adaptive_max_duration: Option<u32>,
+    pub warmup_tolerance: Option<f32>,
+    pub max_warmups: u32,
+    pub test_types: Vec<&'a str>,
     pub results_name: &'a str,
     pub results_environment: &'a str,
     pub results_upload_uri: Option<&'a str>,
+    pub run_uuid: String,
+    /// The results directory of the interrupted run to resume, if any.
+    pub resume: Option<PathBuf>,
+    /// The file to which the verification summary is written as JUnit XML.
+    pub junit_report: Option<PathBuf>,
+    /// The file to which the results of a benchmark are rendered as HTML.
+    pub html_report: Option<PathBuf>,
+    pub load_generator: &'a str,
+    pub otlp_endpoint: Option<&'a str>,
+    pub statsd_address: Option<&'a str>,
+    pub statsd_prefix: &'a str,
     pub logger: Logger,
+    pub manifest: Manifest,
     pub clean_up: bool,
+    /// The prefix of the tag of each test's image; any `{run}` is replaced
+    /// with the run's id by the `Benchmarker`.
+    pub image_prefix: String,
 }
 impl<'a> DockerConfig<'a> {
-    pub fn new(matches: &'a clap::ArgMatches) -> Self {
-        let server_docker_host = format!(
-            "{}:2375",
-            matches.value_of(options::args::SERVER_DOCKER_HOST).unwrap()
-        );
-        let database_docker_host = format!(
-            "{}:2375",
-            matches
-                .value_of(options::args::DATABASE_DOCKER_HOST)
-                .unwrap()
-        );
-        let client_docker_host = format!(
-            "{}:2375",
-            matches.value_of(options::args::CLIENT_DOCKER_HOST).unwrap()
-        );
+    pub fn new(matches: &'a clap::ArgMatches) -> ToolsetResult<Self> {
+        Self::with_docker_api(matches, |use_unix_socket| {
+            Arc::new(Dockurl::new(use_unix_socket))
+        })
+    }
+
+    /// Like `new`, but makes every request of the Docker daemons through the
+    /// `DockerApi` built by `docker_api`, given whether to use the unix socket.
+    pub fn with_docker_api(
+        matches: &'a clap::ArgMatches,
+        docker_api: impl FnOnce(bool) -> Arc<dyn DockerApi>,
+    ) -> ToolsetResult<Self> {
+        let docker_hosts = [
+            options::args::SERVER_DOCKER_HOST,
+            options::args::DATABASE_DOCKER_HOST,
+            options::args::CLIENT_DOCKER_HOST,
+        ]
+        .iter()
+        .map(|arg| parse_docker_host(matches.value_of(arg).unwrap()))
+        .collect::<Vec<DockerHost>>();
+        let server_docker_host = docker_hosts[0].address.clone();
+        let database_docker_host = docker_hosts[1].address.clone();
+        let client_docker_host = docker_hosts[2].address.clone();
         let server_host = matches.value_of(options::args::SERVER_HOST).unwrap();
+        let server_url_override = matches
+            .value_of(options::args::OVERRIDE_SERVER_URL)
+            .map(parse_server_url)
+            .transpose()?;
         let database_host = matches.value_of(options::args::DATABASE_HOST).unwrap();
         let client_host = matches.value_of(options::args::CLIENT_HOST).unwrap();
-        let network_mode = match matches.value_of(options::args::NETWORK_MODE).unwrap() {
-            options::network_modes::HOST => Host,
-            _ => Bridge,
-        };
-        let duration =
-            str::parse::<u32>(matches.value_of(options::args::DURATION).unwrap()).unwrap();
-        let concurrency_levels = matches
-            .values_of(options::args::CONCURRENCY_LEVELS)
-            .unwrap()
-            .collect::<Vec<&str>>()
-            .join(",");
-        let pipeline_concurrency_levels = matches
-            .values_of(options::args::PIPELINE_CONCURRENCY_LEVELS)
-            .unwrap()
-            .collect::<Vec<&str>>()
-            .join(",");
+        let server_network_mode = get_network_mode(matches, options::args::SERVER_NETWORK_MODE);
+        let database_network_mode = get_network_mode(matches, options::args::DATABASE_NETWORK_MODE);
+        let client_network_mode = get_network_mode(matches, options::args::CLIENT_NETWORK_MODE);
+        // The database listens on ports its clients cannot be told, so a
+        // bridged database is only reachable by its alias.
+        if matches!(database_network_mode, Bridge)
+            && (matches!(server_network_mode, Host) || matches!(client_network_mode, Host))
+        {
+            return Err(BridgedDatabaseFromHostNetworkError);
+        }
+        let isolate_networks = matches.is_present(options::args::ISOLATE_NETWORKS);
+        let port_retries =
+            str::parse::<u32>(matches.value_of(options::args::PORT_RETRIES).unwrap()).unwrap();
+        let verify_retries =
+            str::parse::<u32>(matches.value_of(options::args::VERIFY_RETRIES).unwrap()).unwrap();
+        let expected_latency = matches
+            .value_of(options::args::EXPECTED_LATENCY)
+            .map(|latency| str::parse::<f32>(latency).unwrap());
+        let expected_bandwidth = matches
+            .value_of(options::args::EXPECTED_BANDWIDTH)
+            .map(|bandwidth| str::parse::<f32>(bandwidth).unwrap());
+        let expected_daemon_profile = matches.value_of(options::args::EXPECTED_DAEMON_PROFILE);
+        let max_clock_skew = matches
+            .value_of(options::args::MAX_CLOCK_SKEW)
+            .map(|skew| str::parse::<i64>(skew).unwrap());
+        let netem_delay = matches.value_of(options::args::NETEM_DELAY);
+        let netem_jitter = matches.value_of(options::args::NETEM_JITTER);
+        let netem_loss = matches.value_of(options::args::NETEM_LOSS);
+        let network_shaping = if netem_delay.is_some() || netem_loss.is_some() {
+            Some(NetworkShaping {
+                delay_ms: netem_delay.map_or(0, |delay| str::parse::<u32>(delay).unwrap()),
+                jitter_ms: netem_jitter.map_or(0, |jitter| str::parse::<u32>(jitter).unwrap()),
+                loss_percent: netem_loss.map_or(0f32, |loss| str::parse::<f32>(loss).unwrap()),
+            })
+        } else {
+            None
+        };
+        let resource_profile = matches
+            .value_of(options::args::RESOURCE_PROFILE)
+            .map(|profile| ResourceProfile::load(profile).unwrap());
+        // Shaping given via `--netem-*` overrides that of the resource profile.
+        let network_shaping = network_shaping.or_else(|| {
+            resource_profile
+                .as_ref()
+                .and_then(|profile| profile.network.clone())
+        });
+        // An application server in host network mode shares the host's
+        // `eth0`, which would be left shaped after the run.
+        if network_shaping.is_some() && matches!(server_network_mode, Host) {
+            return Err(HostNetworkShapingError);
+        }
+        let (duration, test_type_durations) =
+            parse_durations(matches.value_of(options::args::DURATION).unwrap())?;
+        let startup_timeout =
+            str::parse::<u32>(matches.value_of(options::args::STARTUP_TIMEOUT).unwrap()).unwrap();
+        let build_timeout = matches
+            .value_of(options::args::BUILD_TIMEOUT)
+            .map(|timeout| str::parse::<u32>(timeout).unwrap());
+        let stall_timeout =
+            str::parse::<u32>(matches.value_of(options::args::STALL_TIMEOUT).unwrap()).unwrap();
+        let tune_max_concurrency = str::parse::<u32>(
+            matches
+                .value_of(options::args::TUNE_MAX_CONCURRENCY)
+                .unwrap(),
+        )
+        .unwrap();
+        let warmup_tolerance = matches
+            .value_of(options::args::WARMUP_TOLERANCE)
+            .map(|tolerance| str::parse::<f32>(tolerance).unwrap());
+        let max_warmups =
+            str::parse::<u32>(matches.value_of(options::args::MAX_WARMUPS).unwrap()).unwrap();
+        let interleave = matches.is_present(options::args::INTERLEAVE);
+        let restart_after_verification =
+            matches.is_present(options::args::RESTART_AFTER_VERIFICATION);
+        let restart_between_types = matches.is_present(options::args::RESTART_BETWEEN_TYPES);
+        let calibrate = matches.is_present(options::args::CALIBRATE);
+        let baseline_interval = matches
+            .value_of(options::args::BASELINE_INTERVAL)
+            .map(|interval| str::parse::<u32>(interval).unwrap())
+            .filter(|interval| *interval > 0);
+        let build_jobs =
+            str::parse::<usize>(matches.value_of(options::args::BUILD_JOBS).unwrap()).unwrap();
+        let now = Local::now().naive_local();
+        let schedule = Schedule {
+            start_at: match matches.value_of(options::args::START_AT) {
+                Some(start_at) => Some(parse_start_at(start_at, now)?),
+                None => None,
+            },
+            windows: match matches.values_of(options::args::SCHEDULE_WINDOWS) {
+                Some(windows) => windows.map(parse_window).collect::<ToolsetResult<_>>()?,
+                None => Vec::new(),
+            },
+        };
+        let adaptive_cv = matches
+            .value_of(options::args::ADAPTIVE_CV)
+            .map(|cv| str::parse::<f32>(cv).unwrap());
+        let adaptive_interval =
+            str::parse::<u32>(matches.value_of(options::args::ADAPTIVE_INTERVAL).unwrap()).unwrap();
+        let adaptive_max_duration = matches
+            .value_of(options::args::ADAPTIVE_MAX_DURATION)
+            .map(|max_duration| str::parse::<u32>(max_duration).unwrap());
+        let max_concurrency = matches
+            .value_of(options::args::MAX_CONCURRENCY)
+            .map(|max_concurrency| str::parse::<u32>(max_concurrency).unwrap());
+        let levels_subset: Vec<u32> = match matches.values_of(options::args::LEVELS_SUBSET) {
+            Some(levels) => levels
+                .map(|level| str::parse::<u32>(level).unwrap())
+                .collect(),
+            None => Vec::new(),
+        };
+        let concurrency_levels = filter_levels(
+            "concurrency",
+            matches
+                .values_of(options::args::CONCURRENCY_LEVELS)
+                .unwrap(),
+            max_concurrency,
+            &levels_subset,
+        );
+        let pipeline_concurrency_levels = filter_levels(
+            "pipeline concurrency",
+            matches
+                .values_of(options::args::PIPELINE_CONCURRENCY_LEVELS)
+                .unwrap(),
+            max_concurrency,
+            &levels_subset,
+        );
 
-        let query_levels = matches
-            .values_of(options::args::QUERY_LEVELS)
-            .unwrap()
-            .collect::<Vec<&str>>()
-            .join(",");
-        let cached_query_levels = matches
-            .values_of(options::args::CACHED_QUERY_LEVELS)
+        let query_levels_subset: Vec<u32> =
+            match matches.values_of(options::args::QUERY_LEVELS_SUBSET) {
+                Some(levels) => levels
+                    .map(|level| str::parse::<u32>(level).unwrap())
+                    .collect(),
+                None => Vec::new(),
+            };
+        let query_levels = filter_levels(
+            "query",
+            matches.values_of(options::args::QUERY_LEVELS).unwrap(),
+            None,
+            &query_levels_subset,
+        );
+        let cached_query_levels = filter_levels(
+            "cached query",
+            matches
+                .values_of(options::args::CACHED_QUERY_LEVELS)
+                .unwrap(),
+            None,
+            &query_levels_subset,
+        );
+        let max_queries =
+            str::parse::<u32>(matches.value_of(options::args::MAX_QUERIES).unwrap()).unwrap();
+        let world_rows =
+            str::parse::<u32>(matches.value_of(options::args::WORLD_ROWS).unwrap()).unwrap();
+        let websocket_connections = matches
+            .values_of(options::args::WEBSOCKET_CONNECTIONS)
             .unwrap()
             .collect::<Vec<&str>>()
             .join(",");
+        let websocket_message_rate = str::parse::<u32>(
+            matches
+                .value_of(options::args::WEBSOCKET_MESSAGE_RATE)
+                .unwrap(),
+        )
+        .unwrap();
 
         // By default, we communicate with docker over a unix socket.
         let use_unix_socket = if cfg!(windows) {
             // Even if we want to run locally, Windows cannot communicate over a
             // Unix socket, so don't bother or cURL will panic.
             false
+        } else if let Some(unix_socket_given) = get_explicit_transport(&docker_hosts) {
+            // An explicit socket, port, or scheme says how to reach the
+            // daemons, regardless of the hosts involved.
+            unix_socket_given
         } else {
             // However, in benchmarking with a multi-machine setup, we want to
             // communicate over TCP (also, Windows can only communicate over
@@ -87,30 +332,53 @@ impl<'a> DockerConfig<'a> {
             server_host == options::args::SERVER_HOST_DEFAULT
         };
 
-        let logger = match matches.value_of(options::args::MODE).unwrap() {
+        let docker_api = docker_api(use_unix_socket);
+
+        let resume = matches.value_of(options::args::RESUME).map(PathBuf::from);
+        let results_dir = match matches.value_of(options::args::MODE) {
             // We don't want to log to disk in CICD.
-            modes::CICD => Logger::default(),
-            &_ => Logger::in_dir(&create_results_dir().unwrap()),
+            Some(modes::CICD) => None,
+            // Nor in a dry run, which does nothing worth recording.
+            _ if matches.is_present(options::args::DRY_RUN) => None,
+            // A resumed run appends to the results directory it began in.
+            _ => match matches.value_of(options::args::RESUME) {
+                Some(results_dir) => Some(results_dir.to_string()),
+                None => Some(create_results_dir()?),
+            },
+        };
+        let logger = match &results_dir {
+            Some(results_dir) => Logger::in_dir(results_dir),
+            None => Logger::default(),
+        };
+        let manifest = Manifest::new(matches);
+        // A resumed run keeps the manifest of the run it resumes.
+        if resume.is_none() {
+            logger.write_manifest(&manifest)?;
+        }
+        let pause_file = match matches.value_of(options::args::PAUSE_FILE) {
+            Some(pause_file) => Some(PathBuf::from(pause_file)),
+            None => results_dir.map(|results_dir| PathBuf::from(results_dir).join("pause")),
         };
 
         // There is a chance this is a hack, but it seems that these two
         // networks are always available out of the box for Docker.
-        let server_network_id = match &network_mode {
-            Bridge => get_tfb_network_id(use_unix_socket, &database_docker_host),
-            Host => get_network_id(use_unix_socket, &server_docker_host, "host"),
-        }
-        .unwrap();
-        let database_network_id = match &network_mode {
-            Bridge => get_tfb_network_id(use_unix_socket, &database_docker_host),
-            Host => get_network_id(use_unix_socket, &database_docker_host, "host"),
-        }
-        .unwrap();
-        let client_network_id = match &network_mode {
-            Bridge => get_tfb_network_id(use_unix_socket, &database_docker_host),
-            Host => get_network_id(use_unix_socket, &client_docker_host, "host"),
-        }
-        .unwrap();
+        let server_network_id = match &server_network_mode {
+            Bridge => get_tfb_network_id(docker_api.as_ref(), &database_docker_host),
+            Host => get_network_id(docker_api.as_ref(), &server_docker_host, "host"),
+        }?;
+        let database_network_id = match &database_network_mode {
+            Bridge => get_tfb_network_id(docker_api.as_ref(), &database_docker_host),
+            Host => get_network_id(docker_api.as_ref(), &database_docker_host, "host"),
+        }?;
+        let client_network_id = match &client_network_mode {
+            Bridge => get_tfb_network_id(docker_api.as_ref(), &database_docker_host),
+            Host => get_network_id(docker_api.as_ref(), &client_docker_host, "host"),
+        }?;
 
+        let test_types = match matches.values_of(options::args::TYPES) {
+            Some(test_types) => test_types.collect(),
+            None => Vec::new(),
+        };
         let results_name = matches.value_of(options::args::RESULTS_NAME).unwrap();
         let results_environment = matches
             .value_of(options::args::RESULTS_ENVIRONMENT)
@@ -119,30 +387,789 @@ impl<'a> DockerConfig<'a> {
             None => None,
             Some(str) => Some(str),
         };
+        let run_uuid = match matches.value_of(options::args::RUN_UUID) {
+            Some(run_uuid) => Uuid::parse_str(run_uuid).unwrap(),
+            None => Uuid::from_u128(rand::thread_rng().gen::<u128>()),
+        }
+        .to_hyphenated()
+        .to_string();
+        let get_report = |format: &str| {
+            matches
+                .values_of(options::args::REPORT)
+                .into_iter()
+                .flatten()
+                .find_map(|report| report.strip_prefix(format))
+                .map(PathBuf::from)
+        };
+        let junit_report = get_report("junit=");
+        let html_report = get_report("html=");
+        let load_generator = matches.value_of(options::args::LOAD_GENERATOR).unwrap();
+        let database_tmpfs_size = if matches.is_present(options::args::DATABASE_TMPFS) {
+            Some(
+                str::parse::<u64>(
+                    matches
+                        .value_of(options::args::DATABASE_TMPFS_SIZE)
+                        .unwrap(),
+                )
+                .unwrap(),
+            )
+        } else {
+            None
+        };
+        let shm_size = matches
+            .value_of(options::args::SHM_SIZE)
+            .map(|size| str::parse::<u64>(size).unwrap());
+        let tmpfs_mounts = match matches.values_of(options::args::TMPFS) {
+            Some(mounts) => mounts
+                .map(|mount| match mount.rsplit_once(':') {
+                    Some((path, size)) => {
+                        (path.to_string(), Some(str::parse::<u64>(size).unwrap()))
+                    }
+                    None => (mount.to_string(), None),
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        let otlp_endpoint = matches.value_of(options::args::OTLP_ENDPOINT);
+        let statsd_address = matches.value_of(options::args::STATSD_ADDRESS);
+        let statsd_prefix = matches.value_of(options::args::STATSD_PREFIX).unwrap();
+        let database_versions = match matches.values_of(options::args::DATABASE_VERSIONS) {
+            Some(versions) => versions
+                .map(|version| match version.split_once('=') {
+                    Some((database, version)) => (database.to_lowercase(), version.to_string()),
+                    None => panic!(
+                        "Database versions must be given as DATABASE=VERSION: {}",
+                        version
+                    ),
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        let database_hosts: Vec<(String, &str)> =
+            match matches.values_of(options::args::DATABASE_HOSTS) {
+                Some(hosts) => hosts
+                    .map(|host| match host.split_once('=') {
+                        Some((database, host)) => (database.to_lowercase(), host),
+                        None => panic!("Database hosts must be given as DATABASE=HOST: {}", host),
+                    })
+                    .collect(),
+                None => Vec::new(),
+            };
+        // A bridged database is reached by its alias on the networks of the
+        // database daemon, so it cannot be moved to another.
+        if !database_hosts.is_empty() && matches!(database_network_mode, Bridge) {
+            return Err(DatabaseHostsInBridgeNetworkError);
+        }
+        // Requests over the unix socket all reach the local daemon, which
+        // would start a relocated database on this host rather than its own.
+        if !database_hosts.is_empty() && use_unix_socket {
+            return Err(DatabaseHostsOverUnixSocketError);
+        }
+        let verifier_env = match matches.values_of(options::args::VERIFIER_ENV) {
+            Some(variables) => variables
+                .map(|variable| match variable.split_once('=') {
+                    Some((key, value)) => (key.to_string(), value.to_string()),
+                    None => panic!("Verifier env must be given as KEY=VALUE: {}", variable),
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        let profile = matches.is_present(options::args::PROFILE);
+        let sample_connections = matches.is_present(options::args::SAMPLE_CONNECTIONS);
+        let count_queries = matches.is_present(options::args::COUNT_QUERIES);
+        let core_dumps = matches.is_present(options::args::CORE_DUMPS);
+        let mount_source = matches.is_present(options::args::MOUNT_SOURCE)
+            && matches.value_of(options::args::MODE) == Some(modes::DEBUG);
         let clean_up = matches.is_present(options::args::DOCKER_CLEANUP);
+        let image_prefix = matches
+            .value_of(options::args::IMAGE_PREFIX)
+            .unwrap()
+            .to_string();
 
-        Self {
+        Ok(Self {
             use_unix_socket,
+            docker_api,
             server_docker_host,
             server_host,
+            server_url_override,
             server_network_id,
             database_docker_host,
             database_host,
+            database_hosts,
             database_network_id,
+            database_tmpfs_size,
+            database_versions,
+            shm_size,
+            tmpfs_mounts,
             client_docker_host,
             client_host,
             client_network_id,
-            network_mode,
+            server_network_mode,
+            database_network_mode,
+            client_network_mode,
+            isolate_networks,
+            port_retries,
+            verify_retries,
+            expected_latency,
+            expected_bandwidth,
+            expected_daemon_profile,
+            max_clock_skew,
+            network_shaping,
+            resource_profile,
             concurrency_levels,
             pipeline_concurrency_levels,
             logger,
             query_levels,
             cached_query_levels,
+            max_queries,
+            world_rows,
+            websocket_connections,
+            websocket_message_rate,
+            verifier_env,
             duration,
+            test_type_durations,
+            stall_timeout,
+            startup_timeout,
+            build_timeout,
+            profile,
+            sample_connections,
+            count_queries,
+            core_dumps,
+            mount_source,
+            tune_max_concurrency,
+            interleave,
+            restart_after_verification,
+            restart_between_types,
+            calibrate,
+            baseline_interval,
+            build_jobs,
+            pause_file,
+            schedule,
+            adaptive_cv,
+            adaptive_interval,
+            adaptive_max_duration,
+            warmup_tolerance,
+            max_warmups,
+            test_types,
             results_name,
             results_environment,
             results_upload_uri,
+            run_uuid,
+            resume,
+            junit_report,
+            html_report,
+            load_generator,
+            otlp_endpoint,
+            statsd_address,
+            statsd_prefix,
+            manifest,
             clean_up,
+            image_prefix,
+        })
+    }
+
+    /// Gets the host at which the client reaches the application server;
+    /// the one given via `--override-server-url`, if any, or the loopback
+    /// address if both share the network of the same host.
+    pub fn get_client_server_host(&self) -> &str {
+        match &self.server_url_override {
+            Some((host, _)) => host,
+            None if self.shares_host_network(
+                (&self.client_docker_host, &self.client_network_mode),
+                (&self.server_docker_host, &self.server_network_mode),
+            ) =>
+            {
+                LOOPBACK_ADDRESS
+            }
+            None => self.server_host,
+        }
+    }
+
+    /// Gets the host at which a container on `docker_host` in `network_mode`
+    /// reaches the database; the loopback address if both share the network
+    /// of the same host.
+    pub fn get_database_host(&self, docker_host: &str, network_mode: &NetworkMode) -> &str {
+        if self.shares_host_network(
+            (docker_host, network_mode),
+            (&self.database_docker_host, &self.database_network_mode),
+        ) {
+            LOOPBACK_ADDRESS
+        } else {
+            self.database_host
+        }
+    }
+
+    /// Gets the host given via `--database-hosts` for the given `database`,
+    /// if any, along with the address of the Docker daemon on it.
+    pub fn get_database_host_override(&self, database: &str) -> Option<(&'a str, String)> {
+        let database = database.to_lowercase();
+        self.database_hosts
+            .iter()
+            .find(|(overridden, _)| *overridden == database)
+            .map(|(_, host)| (*host, parse_docker_host(host).address))
+    }
+
+    /// Returns whether `docker_host` and `other_docker_host` are the same
+    /// Docker daemon; always so over the unix socket, and otherwise if their
+    /// addresses are the same, any of the `LOOPBACK_HOSTS` being alike.
+    pub fn is_same_daemon(&self, docker_host: &str, other_docker_host: &str) -> bool {
+        self.use_unix_socket
+            || get_daemon_address(docker_host) == get_daemon_address(other_docker_host)
+    }
+
+    /// Returns whether the containers of two roles, each given by its Docker
+    /// host and network mode, are both in host network mode on the same
+    /// daemon, and so reach one another via the loopback address.
+    fn shares_host_network(
+        &self,
+        (docker_host, network_mode): (&str, &NetworkMode),
+        (other_docker_host, other_network_mode): (&str, &NetworkMode),
+    ) -> bool {
+        matches!((network_mode, other_network_mode), (Host, Host))
+            && self.is_same_daemon(docker_host, other_docker_host)
+    }
+
+    /// Gets the duration in seconds for which each benchmark of the given
+    /// `test_type` should be measured; the one given for it via `--duration`,
+    /// if any.
+    pub fn get_duration(&self, test_type: &str) -> u32 {
+        match self
+            .test_type_durations
+            .iter()
+            .find(|(duration_type, _)| duration_type == test_type)
+        {
+            Some((_, duration)) => *duration,
+            None => self.duration,
         }
     }
+
+    /// Gets the longest duration in seconds for which each benchmark of the
+    /// given `test_type` is run with `--adaptive-cv`; the one given via
+    /// `--adaptive-max-duration`, if any, else three times its own duration.
+    pub fn get_adaptive_max_duration(&self, test_type: &str) -> u32 {
+        self.adaptive_max_duration
+            .unwrap_or_else(|| self.get_duration(test_type) * 3)
+    }
+
+    /// Gets the seconds to wait for the application server of the given `test`
+    /// to accept requests; its `startup_timeout`, if any.
+    pub fn get_startup_timeout(&self, test: &Test) -> u32 {
+        test.startup_timeout.unwrap_or(self.startup_timeout)
+    }
+
+    /// Gets the seconds after which building the image of the given `test` is
+    /// abandoned; its `build_timeout`, if any.
+    pub fn get_build_timeout(&self, test: &Test) -> Option<u32> {
+        test.build_timeout.or(self.build_timeout)
+    }
+
+    /// Gets the longest duration for which any benchmark is measured.
+    pub fn get_longest_duration(&self) -> u32 {
+        self.test_type_durations
+            .iter()
+            .map(|(_, duration)| *duration)
+            .fold(self.duration, u32::max)
+    }
+
+    /// Gets the image of the given `database`, tagged with the version given
+    /// via `--database-version`, if any.
+    pub fn get_database_image(&self, database: &str) -> String {
+        let database = database.to_lowercase();
+        match self
+            .database_versions
+            .iter()
+            .find(|(versioned, _)| *versioned == database)
+        {
+            Some((_, version)) => format!("techempower/tfb.database.{}:{}", database, version),
+            None => format!("techempower/tfb.database.{}", database),
+        }
+    }
+
+    /// Applies the constraints of the `--resource-profile`, if any, on the
+    /// containers of the given `role` to `host_config`.
+    pub fn apply_resource_limits(&self, role: Role, host_config: &mut HostConfig) {
+        if let Some(resource_profile) = &self.resource_profile {
+            resource_profile.get_limits(role).apply(host_config);
+        }
+    }
+}
+
+/// Parses an `--override-server-url` given as `[http://]HOST:PORT` into its
+/// host and port; the benchmarker and verifier only speak plain HTTP, so any
+/// other scheme is rejected.
+pub fn parse_server_url(url: &str) -> ToolsetResult<(String, String)> {
+    let address = url.trim_start_matches("http://").trim_end_matches('/');
+    match address.rsplit_once(':') {
+        Some((host, port))
+            if !host.is_empty() && !host.contains("://") && str::parse::<u16>(port).is_ok() =>
+        {
+            Ok((host.to_string(), port.to_string()))
+        }
+        _ => Err(InvalidServerUrlError(url.to_string())),
+    }
+}
+
+/// Parses a `--duration` given as a comma-separated list of durations, each
+/// either bare or for a test type (e.g. `20,json=15,plaintext=30`), into the
+/// duration of test types not given one and those given one.
+pub fn parse_durations(durations: &str) -> ToolsetResult<(u32, Vec<(String, u32)>)> {
+    let parse = |duration: &str| {
+        str::parse::<u32>(duration.trim()).map_err(|_| InvalidDurationError(durations.to_string()))
+    };
+    let mut duration = parse(options::args::DURATION_DEFAULT)?;
+    let mut test_type_durations = Vec::new();
+    for entry in durations.split(',').map(str::trim) {
+        match entry.split_once('=') {
+            Some((test_type, test_type_duration)) if !test_type.trim().is_empty() => {
+                test_type_durations.push((test_type.trim().to_string(), parse(test_type_duration)?))
+            }
+            Some(_) => return Err(InvalidDurationError(durations.to_string())),
+            None => duration = parse(entry)?,
+        }
+    }
+
+    Ok((duration, test_type_durations))
+}
+
+//
+// PRIVATES
+//
+
+/// Joins the given `levels`, less those above `max` and, if a `subset` is
+/// given, those not in it. Panics if the levels are not in ascending order
+/// (the last is taken to be the highest) or if none remain, as nothing of the
+/// kind would be benchmarked.
+fn filter_levels<'a>(
+    kind: &str,
+    levels: impl Iterator<Item = &'a str>,
+    max: Option<u32>,
+    subset: &[u32],
+) -> String {
+    let levels: Vec<&str> = levels.collect();
+    let parsed: Vec<u32> = levels
+        .iter()
+        .map(|level| str::parse::<u32>(level).unwrap())
+        .collect();
+    if parsed.windows(2).any(|pair| pair[0] >= pair[1]) {
+        panic!(
+            "The {} levels must be in ascending order: {}",
+            kind,
+            levels.join(",")
+        );
+    }
+    let levels: Vec<&str> = levels
+        .into_iter()
+        .filter(|level| {
+            let level = str::parse::<u32>(level).unwrap();
+            let within_max = match max {
+                Some(max) => level <= max,
+                None => true,
+            };
+            within_max && (subset.is_empty() || subset.contains(&level))
+        })
+        .collect();
+    if levels.is_empty() {
+        panic!(
+            "No {} levels remain after filtering by --max-concurrency or the levels subset",
+            kind
+        );
+    }
+
+    levels.join(",")
+}
+
+/// Gets the network mode given via the per-role option `arg`, defaulting to
+/// the one given via `--network-mode`.
+fn get_network_mode(matches: &clap::ArgMatches, arg: &str) -> dockurl::network::NetworkMode {
+    let network_mode = matches
+        .value_of(arg)
+        .or_else(|| matches.value_of(options::args::NETWORK_MODE))
+        .unwrap();
+    match network_mode {
+        options::network_modes::HOST => Host,
+        _ => Bridge,
+    }
+}
+
+/// How a Docker daemon given via one of the `--*-docker-host` options is
+/// reached.
+#[derive(Debug, PartialEq)]
+struct DockerHost {
+    address: String,
+    unix_socket: bool,
+    explicit_tcp: bool,
+}
+
+/// Gets whether the given `docker_hosts` are explicitly reached over the unix
+/// socket (`true`) or over TCP (`false`), if any says how; a single
+/// transport is used for every daemon.
+///
+/// Panics if they mix the two, including a unix socket alongside a bare
+/// hostname which is not local, and so could not be reached over it.
+fn get_explicit_transport(docker_hosts: &[DockerHost]) -> Option<bool> {
+    let unix_socket_given = docker_hosts.iter().any(|host| host.unix_socket);
+    let tcp_given = docker_hosts.iter().any(|host| host.explicit_tcp);
+    if unix_socket_given && tcp_given {
+        panic!("Docker hosts cannot mix unix sockets with TCP addresses");
+    }
+    if unix_socket_given {
+        if let Some(remote) = docker_hosts.iter().find(|host| {
+            !host.unix_socket && !get_daemon_address(&host.address).starts_with("localhost:")
+        }) {
+            panic!(
+                "Docker hosts cannot mix unix sockets with the remote {}",
+                remote.address
+            );
+        }
+    }
+
+    match (unix_socket_given, tcp_given) {
+        (false, false) => None,
+        (unix_socket_given, _) => Some(unix_socket_given),
+    }
+}
+
+/// Gets the given `docker_host` address (`host:port`) with any of the
+/// `LOOPBACK_HOSTS` made `localhost`, such that the addresses of the same
+/// daemon are equal.
+fn get_daemon_address(docker_host: &str) -> String {
+    match docker_host.rsplit_once(':') {
+        Some((host, port)) if LOOPBACK_HOSTS.contains(&host) => format!("localhost:{}", port),
+        _ => docker_host.to_string(),
+    }
+}
+
+/// Parses the given `docker_host`, which is one of:
+///
+/// * a hostname or IP, reached over TCP on `DOCKER_PORT`, or over the unix
+///   socket if local; see `DockerConfig::new`. IPv6 addresses must be
+///   bracketed, e.g. `[::1]`.
+/// * a `host:port` or `tcp://host[:port]`, always reached over TCP.
+/// * `unix://` followed by `DOCKER_SOCKET` (the only socket supported).
+fn parse_docker_host(docker_host: &str) -> DockerHost {
+    if let Some(socket) = docker_host.strip_prefix("unix://") {
+        if socket != DOCKER_SOCKET {
+            panic!("Only the {} unix socket is supported", DOCKER_SOCKET);
+        }
+        return DockerHost {
+            address: format!("{}:{}", options::args::DOCKER_HOST_DEFAULT, DOCKER_PORT),
+            unix_socket: true,
+            explicit_tcp: false,
+        };
+    }
+
+    let (host, scheme_given) = match docker_host.strip_prefix("tcp://") {
+        Some(host) => (host.trim_end_matches('/'), true),
+        None => (docker_host, false),
+    };
+    let port_given = if host.starts_with('[') {
+        // An IPv6 address, e.g. `[::1]:2376`.
+        host.contains("]:")
+    } else if host.matches(':').count() > 1 {
+        panic!(
+            "The IPv6 Docker host {} must be bracketed, e.g. [::1]",
+            host
+        );
+    } else {
+        host.contains(':')
+    };
+    let address = if port_given {
+        host.to_string()
+    } else {
+        format!("{}:{}", host, DOCKER_PORT)
+    };
+
+    DockerHost {
+        address,
+        unix_socket: false,
+        explicit_tcp: scheme_given || port_given,
+    }
+}
+
+//
+// TESTS
+//
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Named;
+    use crate::docker::docker_config::{
+        filter_levels, get_explicit_transport, parse_docker_host, parse_durations,
+        parse_server_url, DockerHost,
+    };
+    use crate::docker::mock::{mock_docker_config, try_mock_docker_config};
+    use crate::error::ToolsetError::{
+        BridgedDatabaseFromHostNetworkError, DatabaseHostsInBridgeNetworkError,
+        DatabaseHostsOverUnixSocketError, HostNetworkShapingError, InvalidDurationError,
+        InvalidServerUrlError,
+    };
+    use crate::metadata::list_projects_by_test_name;
+    use dockurl::network::NetworkMode::Host;
+
+    #[test]
+    fn it_can_parse_docker_hosts() {
+        let tcp = |address: &str, explicit_tcp: bool| DockerHost {
+            address: address.to_string(),
+            unix_socket: false,
+            explicit_tcp,
+        };
+        assert_eq!(parse_docker_host("localhost"), tcp("localhost:2375", false));
+        assert_eq!(
+            parse_docker_host("10.0.0.2:2376"),
+            tcp("10.0.0.2:2376", true)
+        );
+        assert_eq!(
+            parse_docker_host("tcp://tfb-server/"),
+            tcp("tfb-server:2375", true)
+        );
+        assert_eq!(parse_docker_host("[::1]"), tcp("[::1]:2375", false));
+        assert_eq!(parse_docker_host("[::1]:2376"), tcp("[::1]:2376", true));
+        assert_eq!(
+            parse_docker_host("unix:///var/run/docker.sock"),
+            DockerHost {
+                address: "localhost:2375".to_string(),
+                unix_socket: true,
+                explicit_tcp: false,
+            }
+        );
+        assert!(std::panic::catch_unwind(|| parse_docker_host("::1")).is_err());
+
+        let transport = |docker_hosts: [&str; 3]| {
+            std::panic::catch_unwind(|| {
+                get_explicit_transport(&docker_hosts.map(parse_docker_host))
+            })
+            .ok()
+        };
+        assert_eq!(
+            transport(["localhost", "localhost", "10.0.0.3"]),
+            Some(None)
+        );
+        assert_eq!(
+            transport(["unix:///var/run/docker.sock", "127.0.0.1", "[::1]"]),
+            Some(Some(true))
+        );
+        assert_eq!(
+            transport(["10.0.0.1:2375", "localhost", "localhost"]),
+            Some(Some(false))
+        );
+        assert_eq!(
+            transport(["unix:///var/run/docker.sock", "localhost", "10.0.0.3"]),
+            None
+        );
+        assert_eq!(
+            transport([
+                "unix:///var/run/docker.sock",
+                "tcp://localhost",
+                "localhost"
+            ]),
+            None
+        );
+    }
+
+    #[test]
+    fn it_can_filter_levels() {
+        let levels = || vec!["16", "32", "64", "128", "256", "512"].into_iter();
+        assert_eq!(
+            filter_levels("concurrency", levels(), None, &[]),
+            "16,32,64,128,256,512"
+        );
+        assert_eq!(
+            filter_levels("concurrency", levels(), Some(64), &[]),
+            "16,32,64"
+        );
+        assert_eq!(
+            filter_levels("concurrency", levels(), Some(256), &[16, 256, 512]),
+            "16,256"
+        );
+    }
+
+    #[test]
+    fn it_can_parse_server_urls() {
+        let url = |host: &str, port: &str| (host.to_string(), port.to_string());
+        assert_eq!(
+            parse_server_url("http://203.0.113.7:18080/").unwrap(),
+            url("203.0.113.7", "18080")
+        );
+        assert_eq!(
+            parse_server_url("10.0.0.2:8080").unwrap(),
+            url("10.0.0.2", "8080")
+        );
+        for url in &[
+            "https://203.0.113.7:18443",
+            "10.0.0.2",
+            "10.0.0.2:http",
+            ":8080",
+        ] {
+            assert!(matches!(
+                parse_server_url(url),
+                Err(InvalidServerUrlError(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn it_can_parse_durations_per_test_type() {
+        assert_eq!(parse_durations("30").unwrap(), (30, vec![]));
+        assert_eq!(
+            parse_durations("json=10, plaintext=30").unwrap(),
+            (
+                15,
+                vec![("json".to_string(), 10), ("plaintext".to_string(), 30)]
+            )
+        );
+        assert_eq!(
+            parse_durations("20,fortune=60").unwrap(),
+            (20, vec![("fortune".to_string(), 60)])
+        );
+        for durations in &["thirty", "json=", "=15", "20,json=-5"] {
+            assert!(matches!(
+                parse_durations(durations),
+                Err(InvalidDurationError(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn it_can_bound_adaptive_runs_by_the_duration_of_each_test_type() {
+        let (_, _, docker_config) =
+            mock_docker_config(&["--duration", "10,plaintext=30", "--adaptive-cv", "0.05"]);
+        assert_eq!(docker_config.get_adaptive_max_duration("json"), 30);
+        assert_eq!(docker_config.get_adaptive_max_duration("plaintext"), 90);
+
+        let (_, _, docker_config) = mock_docker_config(&[
+            "--duration",
+            "10,plaintext=30",
+            "--adaptive-max-duration",
+            "45",
+        ]);
+        assert_eq!(docker_config.get_adaptive_max_duration("json"), 45);
+        assert_eq!(docker_config.get_adaptive_max_duration("plaintext"), 45);
+    }
+
+    #[test]
+    #[should_panic]
+    fn it_cannot_filter_unordered_levels() {
+        filter_levels("query", vec!["1", "10", "5"].into_iter(), None, &[]);
+    }
+
+    #[test]
+    fn it_cannot_reach_a_bridged_database_from_host_mode_roles() {
+        let config = |args: &[&str]| try_mock_docker_config(args).2;
+        assert!(matches!(
+            config(&[
+                "--network-mode",
+                "host",
+                "--database-network-mode",
+                "bridge"
+            ]),
+            Err(BridgedDatabaseFromHostNetworkError)
+        ));
+        assert!(matches!(
+            config(&["--network-mode", "bridge", "--client-network-mode", "host"]),
+            Err(BridgedDatabaseFromHostNetworkError)
+        ));
+        assert!(config(&["--network-mode", "host"]).is_ok());
+    }
+
+    #[test]
+    fn it_cannot_shape_the_network_of_a_host_mode_server() {
+        assert!(matches!(
+            try_mock_docker_config(&["--network-mode", "host", "--netem-delay", "5"]).2,
+            Err(HostNetworkShapingError)
+        ));
+    }
+
+    #[test]
+    fn it_can_detect_roles_sharing_a_daemon() {
+        let (_, _, docker_config) = mock_docker_config(&[
+            "--network-mode",
+            "host",
+            "--server-docker-host",
+            "tcp://127.0.0.1",
+            "--client-docker-host",
+            "tcp://localhost",
+            "--database-docker-host",
+            "tcp://10.0.0.3",
+        ]);
+
+        let server = &docker_config.server_docker_host;
+        let client = &docker_config.client_docker_host;
+        let database = &docker_config.database_docker_host;
+        assert!(docker_config.is_same_daemon(server, client));
+        assert!(!docker_config.is_same_daemon(server, database));
+        assert_eq!(docker_config.get_client_server_host(), "127.0.0.1");
+        assert_eq!(
+            docker_config.get_database_host(client, &Host),
+            "tfb-database"
+        );
+    }
+
+    #[test]
+    fn it_can_override_the_database_host_per_database() {
+        let database_hosts = ["--database-hosts", "Postgres=10.0.0.5,mongodb=10.0.0.6"];
+        // A bridged database is reached by its alias on the database daemon.
+        assert!(matches!(
+            try_mock_docker_config(&database_hosts).2,
+            Err(DatabaseHostsInBridgeNetworkError)
+        ));
+        // The local unix socket would reach only this host's daemon.
+        assert!(matches!(
+            try_mock_docker_config(&[&["--network-mode", "host"][..], &database_hosts].concat()).2,
+            Err(DatabaseHostsOverUnixSocketError)
+        ));
+        let (_, _, docker_config) = mock_docker_config(
+            &[
+                &[
+                    "--network-mode",
+                    "host",
+                    "--server-docker-host",
+                    "tcp://localhost",
+                ][..],
+                &database_hosts,
+            ]
+            .concat(),
+        );
+
+        assert_eq!(
+            docker_config.get_database_host_override("postgres"),
+            Some(("10.0.0.5", "10.0.0.5:2375".to_string()))
+        );
+        assert_eq!(
+            docker_config.get_database_host_override("MongoDB"),
+            Some(("10.0.0.6", "10.0.0.6:2375".to_string()))
+        );
+        assert_eq!(docker_config.get_database_host_override("mysql"), None);
+    }
+
+    #[test]
+    fn it_can_override_timeouts_per_test() {
+        let (_, _, docker_config) = mock_docker_config(&["--build-timeout", "600"]);
+        let projects = list_projects_by_test_name(Some("gemini*".to_string()), &[]).unwrap();
+        let test = |name: &str| {
+            projects[0]
+                .tests
+                .iter()
+                .find(|test| test.get_name() == name)
+                .unwrap()
+        };
+
+        assert_eq!(docker_config.get_startup_timeout(test("gemini")), 60);
+        assert_eq!(
+            docker_config.get_startup_timeout(test("gemini-postgres")),
+            180
+        );
+        assert_eq!(docker_config.get_build_timeout(test("gemini")), Some(600));
+    }
+
+    #[test]
+    fn it_can_use_a_given_run_uuid() {
+        let run_uuid = |args: &[&str]| mock_docker_config(args).2.run_uuid;
+
+        let given = run_uuid(&["--run-uuid", "6BA7B810-9DAD-11D1-80B4-00C04FD430C8"]);
+        let random = run_uuid(&[]);
+
+        assert_eq!(given, "6ba7b810-9dad-11d1-80b4-00c04fd430c8");
+        assert_eq!(random.len(), given.len());
+        assert_ne!(random, run_uuid(&[]));
+    }
 }
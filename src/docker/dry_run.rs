@@ -0,0 +1,126 @@
+//! A `DockerApi` for `--dry-run`, which makes no requests of any daemon.
+
+use crate::docker::api::{ContainerInspection, DockerApi};
+use crate::error::ToolsetError::DockerDaemonError;
+use crate::error::ToolsetResult;
+use curl::easy::Handler;
+use dockurl::container::create::options::Options;
+use std::path::Path;
+
+/// Refuses every request but to inspect a network, which is answered with the
+/// network's name in place of its id, as the `DockerConfig` of a dry run
+/// still names the networks its containers would join.
+#[derive(Debug)]
+pub struct DryRunDockerApi;
+impl DryRunDockerApi {
+    fn refuse<T>(&self, operation: &str, docker_host: &str) -> ToolsetResult<T> {
+        Err(DockerDaemonError(
+            operation.to_string(),
+            docker_host.to_string(),
+            "no requests are made in a dry run".to_string(),
+        ))
+    }
+}
+impl DockerApi for DryRunDockerApi {
+    fn build_image(
+        &self,
+        _tag: &str,
+        _dockerfile: &Path,
+        _context: &Path,
+        docker_host: &str,
+        _handler: &mut dyn Handler,
+    ) -> ToolsetResult<String> {
+        self.refuse("build image", docker_host)
+    }
+
+    fn pull_image(&self, _image_name: &str, _tag: &str, docker_host: &str) -> ToolsetResult<()> {
+        self.refuse("pull image", docker_host)
+    }
+
+    fn delete_image(&self, _image_id: &str, docker_host: &str) -> ToolsetResult<()> {
+        self.refuse("delete image", docker_host)
+    }
+
+    fn get_image_digest(
+        &self,
+        _image_name: &str,
+        docker_host: &str,
+    ) -> ToolsetResult<Option<String>> {
+        self.refuse("inspect image", docker_host)
+    }
+
+    fn delete_dangling_images(&self, docker_host: &str) -> ToolsetResult<()> {
+        self.refuse("delete dangling images", docker_host)
+    }
+
+    fn create_container(&self, _options: Options, docker_host: &str) -> ToolsetResult<String> {
+        self.refuse("create container", docker_host)
+    }
+
+    fn start_container(&self, _container_id: &str, docker_host: &str) -> ToolsetResult<()> {
+        self.refuse("start container", docker_host)
+    }
+
+    fn attach_to_container(
+        &self,
+        _container_id: &str,
+        docker_host: &str,
+        _handler: &mut dyn Handler,
+    ) -> ToolsetResult<()> {
+        self.refuse("attach to container", docker_host)
+    }
+
+    fn get_container_logs(
+        &self,
+        _container_id: &str,
+        docker_host: &str,
+        _handler: &mut dyn Handler,
+    ) -> ToolsetResult<()> {
+        self.refuse("get container logs", docker_host)
+    }
+
+    fn inspect_container(
+        &self,
+        _container_id: &str,
+        docker_host: &str,
+    ) -> ToolsetResult<ContainerInspection> {
+        self.refuse("inspect container", docker_host)
+    }
+
+    fn wait_for_container_to_exit(
+        &self,
+        _container_id: &str,
+        docker_host: &str,
+    ) -> ToolsetResult<()> {
+        self.refuse("wait for container", docker_host)
+    }
+
+    fn kill_container(&self, _container_id: &str, docker_host: &str) -> ToolsetResult<()> {
+        self.refuse("kill container", docker_host)
+    }
+
+    fn delete_container(&self, _container_id: &str, docker_host: &str) -> ToolsetResult<()> {
+        self.refuse("delete container", docker_host)
+    }
+
+    fn inspect_network(&self, network_name: &str, _docker_host: &str) -> ToolsetResult<String> {
+        Ok(network_name.to_string())
+    }
+
+    fn create_network(&self, _network_name: &str, docker_host: &str) -> ToolsetResult<String> {
+        self.refuse("create network", docker_host)
+    }
+
+    fn connect_container_to_network(
+        &self,
+        _container_id: &str,
+        _network_id: &str,
+        docker_host: &str,
+    ) -> ToolsetResult<()> {
+        self.refuse("connect container to network", docker_host)
+    }
+
+    fn delete_network(&self, _network_id: &str, docker_host: &str) -> ToolsetResult<()> {
+        self.refuse("remove network", docker_host)
+    }
+}
@@ -0,0 +1,162 @@
+//! Subscribes to the Docker daemon's events stream so that containers dying
+//! (crashing, being OOM killed, etc.) are noticed as soon as they happen
+//! rather than the next time they are inspected, and so that restarts and
+//! health check failures, which may not fail a test, can still be counted.
+
+use crate::docker::docker_config::DOCKER_SOCKET;
+use crate::docker::listener::events::Events;
+use crate::error::ToolsetResult;
+use crate::results::ContainerIncidents;
+use curl::easy::Easy2;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Only container `die`, `oom`, `start`, and `health_status` events; i.e.
+/// `{"type":["container"],"event":["die","oom","start","health_status"]}`
+const EVENT_FILTERS: &str = "%7B%22type%22%3A%5B%22container%22%5D%2C%22event%22%3A%5B%22die%22%2C%22oom%22%2C%22start%22%2C%22health_status%22%5D%7D";
+
+/// The death of a container as reported by the Docker daemon.
+#[derive(Clone, Debug, Default)]
+pub struct ContainerDeath {
+    pub exit_code: Option<i32>,
+    pub oom_killed: bool,
+}
+
+/// The container deaths seen across all subscribed Docker daemons. Clones
+/// share the same underlying state.
+#[derive(Clone, Debug, Default)]
+pub struct ContainerEvents {
+    deaths: Arc<Mutex<HashMap<String, ContainerDeath>>>,
+    /// OOM events precede the `die` event for the same container.
+    oom_killed: Arc<Mutex<Vec<String>>>,
+    /// The number of times each container was started.
+    starts: Arc<Mutex<HashMap<String, u32>>>,
+    /// The number of times each container's health check turned unhealthy.
+    unhealthy: Arc<Mutex<HashMap<String, u32>>>,
+    subscribed: Arc<AtomicBool>,
+    /// The Docker daemons subscribed to, so that none is subscribed twice.
+    docker_hosts: Arc<Mutex<HashSet<String>>>,
+}
+impl ContainerEvents {
+    /// Subscribes, on a background thread, to the events stream of the Docker
+    /// daemon given by `docker_host`. Should the subscription fail or end,
+    /// `is_subscribed` will return `false`. Does nothing if already subscribed
+    /// to that daemon.
+    pub fn subscribe(&self, docker_host: &str, use_unix_socket: bool) {
+        if let Ok(mut docker_hosts) = self.docker_hosts.lock() {
+            if !docker_hosts.insert(docker_host.to_string()) {
+                return;
+            }
+        }
+        let events = self.clone();
+        let docker_host = docker_host.to_string();
+        self.subscribed.store(true, Ordering::Release);
+        thread::spawn(move || {
+            let _ = stream_events(&docker_host, use_unix_socket, &events);
+            events.subscribed.store(false, Ordering::Release);
+        });
+    }
+
+    /// Returns whether the events stream is currently being received; callers
+    /// should fall back to inspecting containers when it is not.
+    pub fn is_subscribed(&self) -> bool {
+        self.subscribed.load(Ordering::Acquire)
+    }
+
+    /// Gets the death of the container given by `container_id`, if it died.
+    pub fn get_death(&self, container_id: &str) -> Option<ContainerDeath> {
+        if let Ok(deaths) = self.deaths.lock() {
+            deaths.get(container_id).cloned()
+        } else {
+            None
+        }
+    }
+
+    /// Gets the restarts (starts beyond the first) and health check failures
+    /// of the containers given by `container_ids`, combined.
+    pub fn get_incidents(&self, container_ids: &[&str]) -> ContainerIncidents {
+        let mut incidents = ContainerIncidents::default();
+        if let Ok(starts) = self.starts.lock() {
+            incidents.restarts = container_ids
+                .iter()
+                .filter_map(|container_id| starts.get(*container_id))
+                .map(|starts| starts.saturating_sub(1))
+                .sum();
+        }
+        if let Ok(unhealthy) = self.unhealthy.lock() {
+            incidents.health_flaps = container_ids
+                .iter()
+                .filter_map(|container_id| unhealthy.get(*container_id))
+                .sum();
+        }
+
+        incidents
+    }
+
+    pub fn record_start(&self, container_id: &str) {
+        if let Ok(mut starts) = self.starts.lock() {
+            *starts.entry(container_id.to_string()).or_default() += 1;
+        }
+    }
+
+    pub fn record_unhealthy(&self, container_id: &str) {
+        if let Ok(mut unhealthy) = self.unhealthy.lock() {
+            *unhealthy.entry(container_id.to_string()).or_default() += 1;
+        }
+    }
+
+    pub fn record_oom(&self, container_id: &str) {
+        if let Ok(mut oom_killed) = self.oom_killed.lock() {
+            oom_killed.push(container_id.to_string());
+        }
+    }
+
+    pub fn record_die(&self, container_id: &str, exit_code: Option<i32>) {
+        let mut oom_killed = false;
+        if let Ok(mut oom) = self.oom_killed.lock() {
+            if let Some(index) = oom.iter().position(|id| id == container_id) {
+                oom.remove(index);
+                oom_killed = true;
+            }
+        }
+        if let Ok(mut deaths) = self.deaths.lock() {
+            deaths.insert(
+                container_id.to_string(),
+                ContainerDeath {
+                    exit_code,
+                    oom_killed,
+                },
+            );
+        }
+    }
+}
+
+//
+// PRIVATES
+//
+
+/// Blocks for as long as the daemon streams events.
+fn stream_events(
+    docker_host: &str,
+    use_unix_socket: bool,
+    events: &ContainerEvents,
+) -> ToolsetResult<()> {
+    let mut easy = Easy2::new(Events::new(events));
+    if use_unix_socket {
+        easy.unix_socket(DOCKER_SOCKET)?;
+        easy.url(&format!(
+            "http://localhost/events?filters={}",
+            EVENT_FILTERS
+        ))?;
+    } else {
+        easy.url(&format!(
+            "http://{}/events?filters={}",
+            docker_host, EVENT_FILTERS
+        ))?;
+    }
+    easy.perform()?;
+
+    Ok(())
+}
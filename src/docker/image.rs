@@ -1,11 +1,13 @@
-use crate::config::{Named, Project, Test};
+use crate::config::{Project, Test};
+use crate::docker::api::DockerApi;
 use crate::docker::docker_config::DockerConfig;
 use crate::docker::listener::build_image::BuildImage;
-use crate::docker::listener::simple::Simple;
-use crate::error::ToolsetError::DockerError;
+use crate::error::ToolsetError::BuildTimeoutError;
 use crate::error::ToolsetResult;
 use crate::io::Logger;
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::thread::{self, JoinHandle};
 
 /// Takes a `framework_dir` and the `Test` to run and instructs docker to
 /// build the image.
@@ -15,36 +17,222 @@ pub fn build_image(
     test: &Test,
     logger: &Logger,
 ) -> ToolsetResult<String> {
-    let mut dockerfile;
-    if test.dockerfile.is_some() {
-        dockerfile = test.dockerfile.clone().unwrap();
-    } else {
-        dockerfile = test.get_name();
-        dockerfile.push_str(".dockerfile");
+    build(
+        config.docker_api.as_ref(),
+        &config.server_docker_host,
+        &test.get_tag(&config.image_prefix),
+        &PathBuf::from(test.get_dockerfile()),
+        &project.get_path()?,
+        logger,
+        config.get_build_timeout(test),
+    )
+}
+
+/// Builds the images of upcoming tests in the background, up to `jobs` at a
+/// time, while the current test is benchmarked (see `--build-jobs`).
+#[derive(Debug, Default)]
+pub struct BuildPipeline {
+    jobs: usize,
+    builds: HashMap<String, JoinHandle<ToolsetResult<String>>>,
+}
+impl BuildPipeline {
+    pub fn new(jobs: usize) -> Self {
+        Self {
+            jobs,
+            builds: HashMap::default(),
+        }
     }
 
-    let image_id = dockurl::image::build_image(
-        &test.get_tag(),
-        &PathBuf::from(dockerfile),
-        &project.get_path()?,
-        &config.server_docker_host,
-        config.use_unix_socket,
-        BuildImage::new(logger),
-    )?;
+    /// Starts building the images of the first `jobs` of the `upcoming`
+    /// tests, in order, which are not already being built.
+    ///
+    /// Each build is logged quietly to the `build/log.txt` of its test.
+    pub fn prebuild(
+        &mut self,
+        config: &DockerConfig,
+        upcoming: &[(&Project, &Test)],
+    ) -> ToolsetResult<()> {
+        for (project, test) in upcoming.iter().take(self.jobs) {
+            let tag = test.get_tag(&config.image_prefix);
+            if self.builds.contains_key(&tag) {
+                continue;
+            }
+            let docker_api = config.docker_api.clone();
+            let docker_host = config.server_docker_host.clone();
+            let dockerfile = PathBuf::from(test.get_dockerfile());
+            let context = project.get_path()?;
+            let timeout = config.get_build_timeout(test);
+            let mut logger = config.logger.clone();
+            logger.set_test(test);
+            logger.quiet = true;
+            config
+                .logger
+                .log(format!("Building {} in the background", tag))?;
 
-    Ok(image_id)
+            let build_tag = tag.clone();
+            let build = thread::spawn(move || {
+                build(
+                    docker_api.as_ref(),
+                    &docker_host,
+                    &build_tag,
+                    &dockerfile,
+                    &context,
+                    &logger,
+                    timeout,
+                )
+            });
+            self.builds.insert(tag, build);
+        }
+
+        Ok(())
+    }
+
+    /// Waits for the background build of the image given by `tag` and gets
+    /// its id; `None` if it was not built in the background.
+    pub fn take(&mut self, tag: &str) -> Option<ToolsetResult<String>> {
+        let build = self.builds.remove(tag)?;
+
+        Some(
+            build
+                .join()
+                .unwrap_or_else(|panic| std::panic::resume_unwind(panic)),
+        )
+    }
 }
 
 /// Pulls the given `image_name`.
 pub fn pull_image(config: &DockerConfig, docker_host: &str, image_name: &str) -> ToolsetResult<()> {
-    match dockurl::image::create_image(
-        image_name,
-        "latest",
+    config
+        .docker_api
+        .pull_image(image_name, "latest", docker_host)
+}
+
+//
+// PRIVATES
+//
+
+/// Builds the image given by `tag` from `dockerfile` in `context` on the
+/// daemon on `docker_host`, logging its output to the `build/log.txt` of the
+/// test of `logger`; abandoned after `timeout` seconds, if given.
+fn build(
+    docker_api: &dyn DockerApi,
+    docker_host: &str,
+    tag: &str,
+    dockerfile: &Path,
+    context: &Path,
+    logger: &Logger,
+    timeout: Option<u32>,
+) -> ToolsetResult<String> {
+    logger.record_event("image.build.started", &[("image", tag)])?;
+    let staged_dockerfile = StagedDockerfile::new(tag, dockerfile, context)?;
+    let mut handler = BuildImage::new(logger, timeout);
+    let mut image_id = docker_api.build_image(
+        tag,
+        staged_dockerfile
+            .as_ref()
+            .map_or(dockerfile, |staged| &staged.dockerfile),
+        context,
         docker_host,
-        config.use_unix_socket,
-        Simple::new(),
-    ) {
-        Ok(()) => Ok(()),
-        Err(e) => Err(DockerError(e)),
+        &mut handler,
+    );
+    drop(staged_dockerfile);
+    if let (true, Some(timeout)) = (handler.timed_out, timeout) {
+        image_id = Err(BuildTimeoutError(tag.to_string(), timeout));
+    }
+    match &image_id {
+        Ok(image_id) => logger.record_event(
+            "image.build.finished",
+            &[("image", tag), ("image_id", image_id)],
+        )?,
+        Err(error) => logger.record_event(
+            "image.build.failed",
+            &[("image", tag), ("error", &error.to_string())],
+        )?,
+    }
+
+    image_id
+}
+
+/// A dockerfile shared between frameworks, e.g. `../common/base.dockerfile`,
+/// copied into the `context` of a build for its duration; only the context is
+/// sent to the daemon, and, as with `docker build -f`, the paths the
+/// dockerfile copies from remain relative to the test's own directory.
+struct StagedDockerfile {
+    /// The copy's path, relative to the context.
+    dockerfile: PathBuf,
+    context: PathBuf,
+}
+impl StagedDockerfile {
+    /// Stages the given `dockerfile` of the image given by `tag` in `context`
+    /// if it is outside of it; `None` if it is not (or does not exist, which
+    /// the daemon reports).
+    fn new(tag: &str, dockerfile: &Path, context: &Path) -> ToolsetResult<Option<Self>> {
+        let (shared, context) = match (
+            context.join(dockerfile).canonicalize(),
+            context.canonicalize(),
+        ) {
+            (Ok(shared), Ok(context)) if !shared.starts_with(&context) => (shared, context),
+            _ => return Ok(None),
+        };
+        let dockerfile = PathBuf::from(format!(
+            ".tfb-{}.dockerfile",
+            tag.chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+                .collect::<String>()
+        ));
+        std::fs::copy(shared, context.join(&dockerfile))?;
+
+        Ok(Some(Self {
+            dockerfile,
+            context,
+        }))
+    }
+}
+impl Drop for StagedDockerfile {
+    fn drop(&mut self) {
+        std::fs::remove_file(self.context.join(&self.dockerfile)).ok();
+    }
+}
+
+//
+// TESTS
+//
+
+#[cfg(test)]
+mod tests {
+    use crate::docker::image::StagedDockerfile;
+    use crate::io::TempDir;
+    use std::path::Path;
+
+    #[test]
+    fn it_can_stage_shared_dockerfiles() {
+        let language_dir = TempDir::new("shared-dockerfile");
+        let context = language_dir.join("gemini");
+        std::fs::create_dir_all(&context).unwrap();
+        std::fs::create_dir_all(language_dir.join("common")).unwrap();
+        std::fs::write(context.join("gemini.dockerfile"), "FROM scratch\n").unwrap();
+        std::fs::write(language_dir.join("common/base.dockerfile"), "FROM base\n").unwrap();
+
+        let own =
+            StagedDockerfile::new("tfb.test.gemini", Path::new("gemini.dockerfile"), &context);
+        let shared = StagedDockerfile::new(
+            "tfb.test.gemini",
+            Path::new("../common/base.dockerfile"),
+            &context,
+        )
+        .unwrap()
+        .unwrap();
+        let staged = context.join(&shared.dockerfile);
+        let contents = std::fs::read_to_string(&staged);
+        drop(shared);
+        let removed = !staged.exists();
+
+        assert!(own.unwrap().is_none());
+        assert_eq!(
+            staged.file_name().unwrap(),
+            ".tfb-tfb-test-gemini.dockerfile"
+        );
+        assert_eq!(contents.unwrap(), "FROM base\n");
+        assert!(removed);
     }
 }
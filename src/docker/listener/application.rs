@@ -2,7 +2,7 @@
 //! containers running in Docker. The module should not be called except by the
 //! `docker` module in practice.
 
-use crate::io::Logger;
+use crate::io::{LogPath, Logger};
 use curl::easy::{Handler, WriteError};
 
 #[derive(Clone)]
@@ -11,9 +11,9 @@ pub struct Application {
     pub logger: Logger,
 }
 impl Application {
-    pub fn new(logger: &Logger) -> Self {
+    pub fn new(logger: &Logger, log_path: LogPath) -> Self {
         let mut logger = logger.clone();
-        logger.set_log_file("log.txt");
+        logger.set_log_file(log_path);
 
         Self {
             error_message: None,
@@ -0,0 +1,18 @@
+use curl::easy::{Handler, WriteError};
+
+/// Collects the tar archive of a path within a container.
+pub struct Archive {
+    pub data: Vec<u8>,
+}
+impl Archive {
+    pub fn new() -> Self {
+        Self { data: vec![] }
+    }
+}
+impl Handler for Archive {
+    fn write(&mut self, data: &[u8]) -> Result<usize, WriteError> {
+        self.data.extend_from_slice(data);
+
+        Ok(data.len())
+    }
+}
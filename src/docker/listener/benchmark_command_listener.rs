@@ -1,5 +1,5 @@
 use crate::docker::BenchmarkCommands;
-use crate::io::Logger;
+use crate::io::{LogPath, Logger};
 use curl::easy::{Handler, WriteError};
 
 #[derive(Clone)]
@@ -11,7 +11,7 @@ pub struct BenchmarkCommandListener {
 impl BenchmarkCommandListener {
     pub fn new(test_type: &(&String, &String), logger: &Logger) -> Self {
         let mut logger = logger.clone();
-        logger.set_log_file(&format!("{}.txt", test_type.0));
+        logger.set_log_file(LogPath::CommandRetrieval(test_type.0));
         logger.quiet = true;
 
         Self {
@@ -1,14 +1,33 @@
+use crate::docker::WEBSOCKET_MESSAGE;
 use crate::error::ToolsetError::BenchmarkDataParseError;
 use crate::error::ToolsetResult;
 use crate::io::Logger;
+use crate::results::{ConnectionStats, ContainerStats, LatencyHistogram};
 use curl::easy::{Handler, WriteError};
 use regex::Regex;
-use std::time::{SystemTime, UNIX_EPOCH};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
+/// Note: `data` and `last_write` are shared between clones so that one clone
+/// can be handed to the thread attached to the container while another is
+/// used to monitor (and later parse) the output from the main thread.
+///
+/// When the container is not attached to a TTY, Docker multiplexes its stdout
+/// and stderr onto the attached stream; stdout is collected into `data` for
+/// parsing and stderr into `stderr`.
+///
+/// wrk reports every failed connection, so either stream can be enormous;
+/// each keeps (and logs) only its first and last `MAX_OUTPUT_BYTES`, dropping
+/// the middle. The summaries which are parsed are printed last, so survive.
 #[derive(Clone)]
 pub struct Benchmarker {
     logger: Logger,
-    data: Vec<u8>,
+    data: Arc<Mutex<Output>>,
+    stderr: Arc<Mutex<Output>>,
+    demultiplexer: Arc<Mutex<Demultiplexer>>,
+    last_write: Arc<Mutex<Instant>>,
     start_time: u128,
     pub error_message: Option<String>,
 }
@@ -21,10 +40,87 @@ impl Benchmarker {
                 .as_millis(),
             logger: logger.clone(),
             error_message: None,
-            data: vec![],
+            data: Arc::new(Mutex::new(Output::default())),
+            stderr: Arc::new(Mutex::new(Output::default())),
+            demultiplexer: Arc::new(Mutex::new(Demultiplexer::default())),
+            last_write: Arc::new(Mutex::new(Instant::now())),
         }
     }
+
+    /// Returns the number of seconds since any bytes were last received from
+    /// the benchmarker container.
+    pub fn seconds_since_last_write(&self) -> u64 {
+        if let Ok(last_write) = self.last_write.lock() {
+            last_write.elapsed().as_secs()
+        } else {
+            0
+        }
+    }
+
+    /// Returns everything the benchmarker container wrote to stderr, less any
+    /// output dropped from its middle.
+    pub fn get_stderr(&self) -> String {
+        if let Ok(stderr) = self.stderr.lock() {
+            String::from_utf8_lossy(&stderr.get())
+                .trim_end()
+                .to_string()
+        } else {
+            String::default()
+        }
+    }
+
+    /// Returns everything the benchmarker container wrote to stdout, less any
+    /// output dropped from its middle.
+    pub fn get_output(&self) -> String {
+        if let Ok(data) = self.data.lock() {
+            String::from_utf8_lossy(&data.get()).trim_end().to_string()
+        } else {
+            String::default()
+        }
+    }
+
+    /// Logs the output held back once either stream outgrew its head: how
+    /// much was dropped, followed by the tail which was kept. Should be called
+    /// once the container has exited.
+    pub fn log_truncated_output(&self) {
+        for output in &[&self.data, &self.stderr] {
+            if let Ok(output) = output.lock() {
+                if output.dropped > 0 {
+                    self.logger
+                        .log(format!("[{} bytes of output dropped]", output.dropped))
+                        .unwrap();
+                }
+                log_lines(&self.logger, &output.tail);
+            }
+        }
+    }
+
+    /// Parses the output of wrk (or wrk2) into `BenchmarkResults`.
+    ///
+    /// The JSON summary printed by `wrk_summary.lua` is preferred, but wrk's
+    /// human-readable output is scraped first as a fallback, and for the few
+    /// values (threads, connections, etc.) which the summary lacks.
     pub fn parse_wrk_output(&self) -> ToolsetResult<BenchmarkResults> {
+        let mut results = self.scrape_wrk_output()?;
+        let data = match self.data.lock() {
+            Ok(data) => data.get(),
+            Err(_) => return Err(BenchmarkDataParseError),
+        };
+        if let Ok(data) = std::str::from_utf8(&data) {
+            if let Some(message) = data
+                .lines()
+                .filter(|line| line.trim_start().starts_with('{'))
+                .find_map(|line| serde_json::from_str::<WrkSummaryMessage>(line.trim()).ok())
+            {
+                message.wrk_summary.apply_to(&mut results);
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Scrapes wrk's human-readable output via regular expressions.
+    fn scrape_wrk_output(&self) -> ToolsetResult<BenchmarkResults> {
         lazy_static! {
             static ref THREADS_CONNECTIONS: Regex = Regex::new(r"([0-9]+) threads and ([0-9]+) connections").unwrap();
             static ref LATENCY: Regex = Regex::new(r"Latency(\s)*([0-9]+\.*[0-9]*[us|ms|s|m|%]+)(\s)*([0-9]+\.*[0-9]*[us|ms|s|m|%]+)(\s)*([0-9]+\.*[0-9]*[us|ms|s|m|%]+)(\s)*([0-9]+\.*[0-9]*[us|ms|s|m|%]+)").unwrap();
@@ -33,10 +129,10 @@ impl Benchmarker {
             static ref NON_2XX_3XX: Regex = Regex::new(r"Non-2xx or 3xx responses: ([0-9]+)").unwrap();
             static ref REQUESTS_PER_SECOND: Regex = Regex::new(r"Requests/sec:(\s)*([0-9]+\.*[0-9]*)").unwrap();
             static ref TRANSFER_PER_SECOND: Regex = Regex::new(r"Transfer/sec:(\s)*([0-9]+\.*[0-9]*[B|KB|MB]+)").unwrap();
-            static ref LATENCY_DIST_50: Regex = Regex::new(r"50%(\s)*([0-9]+\.*[0-9]*[us|ms|s|m|%]+)").unwrap();
-            static ref LATENCY_DIST_75: Regex = Regex::new(r"75%(\s)*([0-9]+\.*[0-9]*[us|ms|s|m|%]+)").unwrap();
-            static ref LATENCY_DIST_90: Regex = Regex::new(r"90%(\s)*([0-9]+\.*[0-9]*[us|ms|s|m|%]+)").unwrap();
-            static ref LATENCY_DIST_99: Regex = Regex::new(r"99%(\s)*([0-9]+\.*[0-9]*[us|ms|s|m|%]+)").unwrap();
+            static ref LATENCY_DIST_50: Regex = Regex::new(r"\b50(?:\.0+)?%(\s)*([0-9]+\.*[0-9]*[us|ms|s|m|%]+)").unwrap();
+            static ref LATENCY_DIST_75: Regex = Regex::new(r"\b75(?:\.0+)?%(\s)*([0-9]+\.*[0-9]*[us|ms|s|m|%]+)").unwrap();
+            static ref LATENCY_DIST_90: Regex = Regex::new(r"\b90(?:\.0+)?%(\s)*([0-9]+\.*[0-9]*[us|ms|s|m|%]+)").unwrap();
+            static ref LATENCY_DIST_99: Regex = Regex::new(r"\b99(?:\.0+)?%(\s)*([0-9]+\.*[0-9]*[us|ms|s|m|%]+)").unwrap();
             static ref SOCKET_ERRORS: Regex = Regex::new(r"Socket errors: connect ([0-9]+), read ([0-9]+), write ([0-9]+), timeout ([0-9]+)").unwrap();
             // Socket Errors
             static ref CONNECT: Regex = Regex::new(r"connect ([0-9]+)").unwrap();
@@ -44,7 +140,11 @@ impl Benchmarker {
             static ref WRITE: Regex = Regex::new(r"write ([0-9]+)").unwrap();
             static ref TIMEOUT: Regex = Regex::new(r"timeout ([0-9]+)").unwrap();
         }
-        if let Ok(data) = std::str::from_utf8(&self.data) {
+        let data = match self.data.lock() {
+            Ok(data) => data.get(),
+            Err(_) => return Err(BenchmarkDataParseError),
+        };
+        if let Ok(data) = std::str::from_utf8(&data) {
             let mut threads = 0;
             let mut connections = 0;
             let mut latency_average = String::default();
@@ -66,6 +166,7 @@ impl Benchmarker {
             let mut percentile_75 = String::default();
             let mut percentile_90 = String::default();
             let mut percentile_99 = String::default();
+            let latency_percentiles = parse_latency_spectrum(data);
             for line in data.lines() {
                 if let Some(captures) = THREADS_CONNECTIONS.captures(line) {
                     threads = str::parse::<u32>(captures.get(1).unwrap().as_str()).unwrap();
@@ -153,29 +254,253 @@ impl Benchmarker {
                 requests_per_second,
                 transfer_per_second,
                 non_2xx_3xx,
+                messages_per_second: None,
+                interval_requests_per_second: None,
+                latency_histogram: latency_percentiles
+                    .as_deref()
+                    .and_then(LatencyHistogram::from_spectrum),
+                latency_percentiles,
+                container_stats: None,
+                database_stats: None,
+                database_connections: None,
+                queries_per_request: None,
+                expected_queries_per_request: None,
+                stalled: false,
+                exit_code: None,
+                stderr: String::default(),
             })
         } else {
             Err(BenchmarkDataParseError)
         }
     }
+
+    /// Parses the JSON report printed by `ghz --format json` into
+    /// `BenchmarkResults`. Latencies, which ghz reports in nanoseconds, are
+    /// converted to milliseconds to match the units typically seen from wrk.
+    pub fn parse_ghz_output(&self) -> ToolsetResult<BenchmarkResults> {
+        let data = match self.data.lock() {
+            Ok(data) => data.get(),
+            Err(_) => return Err(BenchmarkDataParseError),
+        };
+        let data = match std::str::from_utf8(&data) {
+            Ok(data) => data,
+            Err(_) => return Err(BenchmarkDataParseError),
+        };
+        let report = match data
+            .lines()
+            .filter(|line| line.trim_start().starts_with('{'))
+            .find_map(|line| serde_json::from_str::<GhzReport>(line).ok())
+        {
+            Some(report) => report,
+            None => return Err(BenchmarkDataParseError),
+        };
+
+        let as_millis = |nanos: f64| format!("{:.2}ms", nanos / 1_000_000f64);
+        let percentile = |percentage: u32| {
+            report
+                .latency_distribution
+                .iter()
+                .find(|latency| latency.percentage == percentage)
+                .map(|latency| as_millis(latency.latency))
+                .unwrap_or_default()
+        };
+        let non_ok = report
+            .status_code_distribution
+            .iter()
+            .filter(|(status, _)| status.as_str() != "OK")
+            .map(|(_, count)| count)
+            .sum::<u32>();
+        let duration = (report.total / 1_000_000_000f64) as f32;
+
+        Ok(BenchmarkResults {
+            start_time: self.start_time,
+            end_time: self.start_time + (duration * 1_000f32) as u128,
+            threads: 0,
+            connections: report.options.connections.max(report.options.concurrency),
+            thread_stats: ThreadStats {
+                latency: Latency {
+                    average: as_millis(report.average),
+                    standard_deviation: String::default(),
+                    max: as_millis(report.slowest),
+                    plus_minus_std_dev: String::default(),
+                },
+                requests_per_second: RequestsPerSecond {
+                    average: String::default(),
+                    standard_deviation: String::default(),
+                    max: String::default(),
+                    plus_minus_std_dev: String::default(),
+                },
+            },
+            latency_distribution: LatencyDistribution {
+                percentile_50: percentile(50),
+                percentile_75: percentile(75),
+                percentile_90: percentile(90),
+                percentile_99: percentile(99),
+            },
+            total_requests: report.count,
+            duration,
+            data_read: String::default(),
+            socket_errors: None,
+            requests_per_second: report.rps,
+            transfer_per_second: String::default(),
+            non_2xx_3xx: if non_ok > 0 { Some(non_ok) } else { None },
+            messages_per_second: None,
+            interval_requests_per_second: None,
+            latency_percentiles: None,
+            latency_histogram: None,
+            container_stats: None,
+            database_stats: None,
+            database_connections: None,
+            queries_per_request: None,
+            expected_queries_per_request: None,
+            stalled: false,
+            exit_code: None,
+            stderr: String::default(),
+        })
+    }
+
+    /// Parses the summary printed by `tcpkali --websocket` into
+    /// `BenchmarkResults`. tcpkali does not count messages itself, so the
+    /// number of messages received is derived from the bytes received and the
+    /// size of each echoed `WEBSOCKET_MESSAGE` frame.
+    pub fn parse_tcpkali_output(&self) -> ToolsetResult<BenchmarkResults> {
+        lazy_static! {
+            static ref TOTAL_RECEIVED: Regex =
+                Regex::new(r"Total data received:.*\(([0-9]+) bytes\)").unwrap();
+            static ref TEST_DURATION: Regex =
+                Regex::new(r"Test duration:(\s)*([0-9]+\.*[0-9]*) s").unwrap();
+            static ref LATENCY_PERCENTILES: Regex = Regex::new(
+                r"Message latency at percentiles:(\s)*([0-9./]+)%(\s)*=(\s)*([0-9./]+) ms"
+            )
+            .unwrap();
+            static ref CONNECTIONS: Regex =
+                Regex::new(r"Ramped up to ([0-9]+) connections").unwrap();
+        }
+        let data = match self.data.lock() {
+            Ok(data) => data.get(),
+            Err(_) => return Err(BenchmarkDataParseError),
+        };
+        let data = match std::str::from_utf8(&data) {
+            Ok(data) => data,
+            Err(_) => return Err(BenchmarkDataParseError),
+        };
+        let mut bytes_received = None;
+        let mut duration = 0f32;
+        let mut connections = 0;
+        let mut latency_distribution = LatencyDistribution {
+            percentile_50: String::default(),
+            percentile_75: String::default(),
+            percentile_90: String::default(),
+            percentile_99: String::default(),
+        };
+        for line in data.lines() {
+            if let Some(captures) = TOTAL_RECEIVED.captures(line) {
+                bytes_received = str::parse::<u64>(captures.get(1).unwrap().as_str()).ok();
+            }
+            if let Some(captures) = TEST_DURATION.captures(line) {
+                duration = str::parse::<f32>(captures.get(2).unwrap().as_str()).unwrap_or(0f32);
+            }
+            if let Some(captures) = CONNECTIONS.captures(line) {
+                connections = str::parse::<u32>(captures.get(1).unwrap().as_str()).unwrap_or(0);
+            }
+            if let Some(captures) = LATENCY_PERCENTILES.captures(line) {
+                let percentiles = captures.get(2).unwrap().as_str().split('/');
+                let latencies = captures.get(5).unwrap().as_str().split('/');
+                for (percentile, latency) in percentiles.zip(latencies) {
+                    let latency = format!("{}ms", latency);
+                    match str::parse::<f32>(percentile).unwrap_or(0f32) as u32 {
+                        50 => latency_distribution.percentile_50 = latency,
+                        75 => latency_distribution.percentile_75 = latency,
+                        90 => latency_distribution.percentile_90 = latency,
+                        99 => latency_distribution.percentile_99 = latency,
+                        _ => {}
+                    }
+                }
+            }
+        }
+        let bytes_received = match bytes_received {
+            Some(bytes_received) => bytes_received,
+            None => return Err(BenchmarkDataParseError),
+        };
+
+        // Server frames are unmasked, so small messages carry a 2 byte header.
+        let total_messages = (bytes_received / (WEBSOCKET_MESSAGE.len() as u64 + 2)) as u32;
+        let messages_per_second = if duration > 0f32 {
+            total_messages as f32 / duration
+        } else {
+            0f32
+        };
+
+        Ok(BenchmarkResults {
+            start_time: self.start_time,
+            end_time: self.start_time + (duration * 1_000f32) as u128,
+            threads: 0,
+            connections,
+            thread_stats: ThreadStats {
+                latency: Latency {
+                    average: String::default(),
+                    standard_deviation: String::default(),
+                    max: String::default(),
+                    plus_minus_std_dev: String::default(),
+                },
+                requests_per_second: RequestsPerSecond {
+                    average: String::default(),
+                    standard_deviation: String::default(),
+                    max: String::default(),
+                    plus_minus_std_dev: String::default(),
+                },
+            },
+            latency_distribution,
+            total_requests: total_messages,
+            duration,
+            data_read: format!("{}B", bytes_received),
+            socket_errors: None,
+            requests_per_second: 0f32,
+            transfer_per_second: String::default(),
+            non_2xx_3xx: None,
+            messages_per_second: Some(messages_per_second),
+            interval_requests_per_second: None,
+            latency_percentiles: None,
+            latency_histogram: None,
+            container_stats: None,
+            database_stats: None,
+            database_connections: None,
+            queries_per_request: None,
+            expected_queries_per_request: None,
+            stalled: false,
+            exit_code: None,
+            stderr: String::default(),
+        })
+    }
 }
 impl Handler for Benchmarker {
     fn write(&mut self, data: &[u8]) -> Result<usize, WriteError> {
-        self.data.extend_from_slice(data);
-
-        if let Ok(logs) = std::str::from_utf8(&data) {
-            for line in logs.lines() {
-                if !line.trim().is_empty() {
-                    self.logger.log(line.trim_end()).unwrap();
-                }
+        let frames = match self.demultiplexer.lock() {
+            Ok(mut demultiplexer) => demultiplexer.push(data),
+            Err(_) => vec![(STDOUT, data.to_vec())],
+        };
+        for (stream, frame) in frames {
+            let buffer = if stream == STDERR {
+                &self.stderr
+            } else {
+                &self.data
+            };
+            // Output beyond the head is held back from the log until the
+            // container exits, as most of it may yet be dropped.
+            if let Ok(mut buffer) = buffer.lock() {
+                let head = buffer.push(&frame);
+                log_lines(&self.logger, head);
             }
         }
+        if let Ok(mut last_write) = self.last_write.lock() {
+            *last_write = Instant::now();
+        }
 
         Ok(data.len())
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct BenchmarkResults {
     pub start_time: u128,
     pub end_time: u128,
@@ -190,15 +515,27 @@ pub struct BenchmarkResults {
     pub requests_per_second: f32,
     pub transfer_per_second: String,
     pub non_2xx_3xx: Option<u32>,
+    pub messages_per_second: Option<f32>,
+    pub interval_requests_per_second: Option<Vec<f32>>,
+    pub latency_percentiles: Option<Vec<(f64, f64)>>,
+    pub latency_histogram: Option<LatencyHistogram>,
+    pub container_stats: Option<ContainerStats>,
+    pub database_stats: Option<ContainerStats>,
+    pub database_connections: Option<ConnectionStats>,
+    pub queries_per_request: Option<f32>,
+    pub expected_queries_per_request: Option<u32>,
+    pub stalled: bool,
+    pub exit_code: Option<i32>,
+    pub stderr: String,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct ThreadStats {
     pub latency: Latency,
     pub requests_per_second: RequestsPerSecond,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct Latency {
     pub average: String,
     pub standard_deviation: String,
@@ -206,7 +543,7 @@ pub struct Latency {
     pub plus_minus_std_dev: String,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct RequestsPerSecond {
     pub average: String,
     pub standard_deviation: String,
@@ -214,7 +551,7 @@ pub struct RequestsPerSecond {
     pub plus_minus_std_dev: String,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct LatencyDistribution {
     pub percentile_50: String,
     pub percentile_75: String,
@@ -229,3 +566,530 @@ pub struct SocketErrors {
     pub write: u32,
     pub timeout: u32,
 }
+
+const STDOUT: u8 = 1;
+const STDERR: u8 = 2;
+
+/// The most bytes kept from each of the start and the end of a stream.
+const MAX_OUTPUT_BYTES: usize = 64 * 1024;
+
+/// A stream's output, bounded to its first and last `MAX_OUTPUT_BYTES`; the
+/// number of bytes dropped from between the two is counted in `dropped`.
+#[derive(Default)]
+struct Output {
+    head: Vec<u8>,
+    tail: Vec<u8>,
+    dropped: usize,
+}
+impl Output {
+    /// Appends `data`, returning the part of it which fit in the head.
+    fn push<'d>(&mut self, data: &'d [u8]) -> &'d [u8] {
+        let fits = (MAX_OUTPUT_BYTES - self.head.len()).min(data.len());
+        self.head.extend_from_slice(&data[..fits]);
+        self.tail.extend_from_slice(&data[fits..]);
+        if self.tail.len() > MAX_OUTPUT_BYTES {
+            let excess = self.tail.len() - MAX_OUTPUT_BYTES;
+            self.tail.drain(..excess);
+            self.dropped += excess;
+        }
+
+        &data[..fits]
+    }
+
+    /// Gets the output kept, with a line marking where any was dropped.
+    fn get(&self) -> Vec<u8> {
+        let mut output = self.head.clone();
+        if self.dropped > 0 {
+            output.extend_from_slice(
+                format!("\n[{} bytes of output dropped]\n", self.dropped).as_bytes(),
+            );
+        }
+        output.extend_from_slice(&self.tail);
+
+        output
+    }
+}
+
+/// Logs each non-blank line of `output`.
+fn log_lines(logger: &Logger, output: &[u8]) {
+    for line in String::from_utf8_lossy(output).lines() {
+        if !line.trim().is_empty() {
+            logger.log(line.trim_end()).unwrap();
+        }
+    }
+}
+
+/// Splits the stream from an attached, non-TTY container into its stdout and
+/// stderr frames. Each frame is preceded by an 8 byte header: the stream type,
+/// three zero bytes, and the big-endian length of the frame. Frames may span
+/// (or share) writes, so incomplete frames are held in `pending`.
+///
+/// Streams which do not begin with a valid header are treated as raw stdout.
+#[derive(Default)]
+struct Demultiplexer {
+    multiplexed: Option<bool>,
+    pending: Vec<u8>,
+}
+impl Demultiplexer {
+    fn push(&mut self, data: &[u8]) -> Vec<(u8, Vec<u8>)> {
+        self.pending.extend_from_slice(data);
+        if self.multiplexed.is_none() {
+            if self.pending.len() < 8 {
+                return vec![];
+            }
+            self.multiplexed = Some(self.pending[0] <= STDERR && self.pending[1..4] == [0, 0, 0]);
+        }
+        if self.multiplexed == Some(false) {
+            return vec![(STDOUT, self.pending.drain(..).collect())];
+        }
+
+        let mut frames = vec![];
+        while self.pending.len() >= 8 {
+            let mut size = [0u8; 4];
+            size.copy_from_slice(&self.pending[4..8]);
+            let size = u32::from_be_bytes(size) as usize;
+            if self.pending.len() < 8 + size {
+                break;
+            }
+            let stream = self.pending[0];
+            let frame = self.pending[8..8 + size].to_vec();
+            self.pending.drain(..8 + size);
+            frames.push((stream, frame));
+        }
+
+        frames
+    }
+}
+
+#[derive(Deserialize)]
+struct WrkSummaryMessage {
+    wrk_summary: WrkSummary,
+}
+
+/// The summary printed by `wrk_summary.lua`; all durations are microseconds.
+#[derive(Deserialize, Debug)]
+struct WrkSummary {
+    duration: u64,
+    requests: u64,
+    bytes: u64,
+    errors: WrkErrors,
+    latency: WrkLatency,
+    requests_per_thread: WrkRequestsPerThread,
+}
+impl WrkSummary {
+    fn apply_to(&self, results: &mut BenchmarkResults) {
+        let seconds = self.duration as f64 / 1_000_000f64;
+        results.duration = seconds as f32;
+        results.end_time = results.start_time + (self.duration / 1_000) as u128;
+        results.total_requests = self.requests as u32;
+        results.data_read = format_bytes(self.bytes as f64);
+        if seconds > 0f64 {
+            results.requests_per_second = (self.requests as f64 / seconds) as f32;
+            results.transfer_per_second = format_bytes(self.bytes as f64 / seconds);
+        }
+        results.thread_stats.latency.average = format_micros(self.latency.mean);
+        results.thread_stats.latency.standard_deviation = format_micros(self.latency.stdev);
+        results.thread_stats.latency.max = format_micros(self.latency.max as f64);
+        results.thread_stats.requests_per_second.average =
+            format_count(self.requests_per_thread.mean);
+        results.thread_stats.requests_per_second.standard_deviation =
+            format_count(self.requests_per_thread.stdev);
+        results.thread_stats.requests_per_second.max =
+            format_count(self.requests_per_thread.max as f64);
+        results.latency_distribution = LatencyDistribution {
+            percentile_50: format_micros(self.latency.p50 as f64),
+            percentile_75: format_micros(self.latency.p75 as f64),
+            percentile_90: format_micros(self.latency.p90 as f64),
+            percentile_99: format_micros(self.latency.p99 as f64),
+        };
+        // wrk2's spectrum, if printed, is the more precise.
+        if results.latency_histogram.is_none() {
+            results.latency_histogram = self.latency.get_histogram();
+        }
+        let errors = &self.errors;
+        results.socket_errors = if errors.connect + errors.read + errors.write + errors.timeout > 0
+        {
+            Some(SocketErrors {
+                connect: errors.connect,
+                read: errors.read,
+                write: errors.write,
+                timeout: errors.timeout,
+            })
+        } else {
+            None
+        };
+        results.non_2xx_3xx = if errors.status > 0 {
+            Some(errors.status)
+        } else {
+            None
+        };
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct WrkErrors {
+    connect: u32,
+    read: u32,
+    write: u32,
+    status: u32,
+    timeout: u32,
+}
+
+#[derive(Deserialize, Debug)]
+struct WrkLatency {
+    mean: f64,
+    stdev: f64,
+    max: u64,
+    p50: u64,
+    p75: u64,
+    p90: u64,
+    p99: u64,
+    p99_9: Option<u64>,
+    p99_99: Option<u64>,
+    p99_999: Option<u64>,
+}
+impl WrkLatency {
+    /// Gets the histogram of these latencies; `None` if the summary was
+    /// printed without the tail percentiles.
+    fn get_histogram(&self) -> Option<LatencyHistogram> {
+        let as_millis = |micros: u64| micros as f64 / 1_000f64;
+
+        Some(LatencyHistogram {
+            p50: as_millis(self.p50),
+            p75: as_millis(self.p75),
+            p90: as_millis(self.p90),
+            p99: as_millis(self.p99),
+            p99_9: as_millis(self.p99_9?),
+            p99_99: as_millis(self.p99_99?),
+            p99_999: as_millis(self.p99_999?),
+        })
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct WrkRequestsPerThread {
+    mean: f64,
+    stdev: f64,
+    max: u64,
+}
+
+/// Parses the detailed percentile spectrum printed by wrk2 with `--latency`
+/// into (percentile, latency) pairs, the percentile being a fraction (e.g.
+/// `0.999`) and the latency in milliseconds; `None` if there is none.
+fn parse_latency_spectrum(data: &str) -> Option<Vec<(f64, f64)>> {
+    let mut lines = data
+        .lines()
+        .skip_while(|line| !line.trim().starts_with("Detailed Percentile spectrum:"));
+    lines.next()?;
+    // The table's header row and blank lines do not parse, and it is followed
+    // by `#[Mean = ..., StdDeviation = ...]` and the like.
+    let spectrum = lines
+        .map(str::trim)
+        .take_while(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let mut columns = line.split_whitespace();
+            let latency = str::parse::<f64>(columns.next()?).ok()?;
+            let percentile = str::parse::<f64>(columns.next()?).ok()?;
+            Some((percentile, latency))
+        })
+        .collect::<Vec<_>>();
+
+    if spectrum.is_empty() {
+        None
+    } else {
+        Some(spectrum)
+    }
+}
+
+/// Formats the given `micros` the way wrk does; e.g. `1.25ms`.
+fn format_micros(micros: f64) -> String {
+    if micros < 1_000f64 {
+        format!("{:.2}us", micros)
+    } else if micros < 1_000_000f64 {
+        format!("{:.2}ms", micros / 1_000f64)
+    } else {
+        format!("{:.2}s", micros / 1_000_000f64)
+    }
+}
+
+/// Formats the given `bytes` the way wrk does; e.g. `12.40MB`.
+fn format_bytes(bytes: f64) -> String {
+    if bytes < 1024f64 {
+        format!("{:.2}B", bytes)
+    } else if bytes < 1024f64 * 1024f64 {
+        format!("{:.2}KB", bytes / 1024f64)
+    } else if bytes < 1024f64 * 1024f64 * 1024f64 {
+        format!("{:.2}MB", bytes / (1024f64 * 1024f64))
+    } else {
+        format!("{:.2}GB", bytes / (1024f64 * 1024f64 * 1024f64))
+    }
+}
+
+/// Formats the given `count` the way wrk does; e.g. `10.52k`.
+fn format_count(count: f64) -> String {
+    if count < 1_000f64 {
+        format!("{:.2}", count)
+    } else if count < 1_000_000f64 {
+        format!("{:.2}k", count / 1_000f64)
+    } else {
+        format!("{:.2}M", count / 1_000_000f64)
+    }
+}
+
+/// The subset of the report printed by `ghz --format json` which we consume.
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct GhzReport {
+    options: GhzOptions,
+    count: u32,
+    total: f64,
+    average: f64,
+    slowest: f64,
+    rps: f32,
+    latency_distribution: Vec<GhzLatency>,
+    status_code_distribution: HashMap<String, u32>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct GhzOptions {
+    concurrency: u32,
+    connections: u32,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct GhzLatency {
+    percentage: u32,
+    latency: f64,
+}
+
+//
+// TESTS
+//
+
+#[cfg(test)]
+mod tests {
+    use crate::docker::listener::benchmarker::{Benchmarker, MAX_OUTPUT_BYTES};
+    use crate::io::Logger;
+    use crate::results::LatencyHistogram;
+    use curl::easy::Handler;
+
+    const WRK_OUTPUT: &[u8] = b"Running 15s test @ http://tfb-server:8080/json\n\
+          4 threads and 16 connections\n\
+          Thread Stats   Avg      Stdev     Max   +/- Stdev\n\
+            Latency   210.00us  100.00us   5.00ms   90.00%\n\
+            Req/Sec    19.00k     1.00k   21.00k    70.00%\n\
+          1140000 requests in 15.00s, 160.00MB read\n\
+        Requests/sec:  76000.00\n\
+        Transfer/sec:     10.67MB\n";
+
+    #[test]
+    fn it_can_scrape_wrk_output_without_a_summary() {
+        let mut benchmarker = Benchmarker::new(&Logger::default());
+        benchmarker.write(WRK_OUTPUT).unwrap();
+
+        match benchmarker.parse_wrk_output() {
+            Ok(results) => {
+                assert_eq!(results.threads, 4);
+                assert_eq!(results.connections, 16);
+                assert_eq!(results.total_requests, 1_140_000);
+                assert_eq!(results.thread_stats.latency.average, "210.00us");
+            }
+            Err(e) => panic!("Benchmarker::parse_wrk_output failed. error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn it_prefers_the_wrk_summary() {
+        let mut benchmarker = Benchmarker::new(&Logger::default());
+        benchmarker.write(WRK_OUTPUT).unwrap();
+        benchmarker
+            .write(br#"{"wrk_summary":{"duration":15000000,"requests":1140005,"bytes":167772160,"errors":{"connect":0,"read":0,"write":0,"status":3,"timeout":2},"latency":{"mean":212.5,"stdev":99.0,"max":5000,"p50":180,"p75":240,"p90":330,"p99":1500},"requests_per_thread":{"mean":19000.0,"stdev":1000.0,"max":21000}}}"#)
+            .unwrap();
+
+        match benchmarker.parse_wrk_output() {
+            Ok(results) => {
+                assert_eq!(results.threads, 4);
+                assert_eq!(results.total_requests, 1_140_005);
+                assert_eq!(results.thread_stats.latency.average, "212.50us");
+                assert_eq!(results.latency_distribution.percentile_99, "1.50ms");
+                assert_eq!(results.data_read, "160.00MB");
+                assert_eq!(results.non_2xx_3xx, Some(3));
+                assert_eq!(results.socket_errors.unwrap().timeout, 2);
+            }
+            Err(e) => panic!("Benchmarker::parse_wrk_output failed. error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn it_can_parse_the_wrk2_latency_spectrum() {
+        let mut benchmarker = Benchmarker::new(&Logger::default());
+        benchmarker.write(WRK_OUTPUT).unwrap();
+        benchmarker
+            .write(
+                b"  Detailed Percentile spectrum:\n\
+                       Value   Percentile   TotalCount 1/(1-Percentile)\n\
+                \n\
+                       0.120     0.000000            1         1.00\n\
+                       0.180     0.500000       570000         2.00\n\
+                       1.500     0.990000      1128600       100.00\n\
+                       5.000     1.000000      1140000          inf\n\
+                #[Mean    =        0.212, StdDeviation   =        0.099]\n\
+                #[Max     =        5.000, Total count    =      1140000]\n",
+            )
+            .unwrap();
+
+        match benchmarker.parse_wrk_output() {
+            Ok(results) => {
+                assert_eq!(
+                    results.latency_percentiles,
+                    Some(vec![(0.0, 0.12), (0.5, 0.18), (0.99, 1.5), (1.0, 5.0)])
+                );
+                let histogram = results.latency_histogram.unwrap();
+                assert_eq!(histogram.p50, 0.18);
+                assert_eq!(histogram.p75, 1.5);
+                assert_eq!(histogram.p99, 1.5);
+                assert_eq!(histogram.p99_9, 5.0);
+                assert_eq!(histogram.p99_999, 5.0);
+            }
+            Err(e) => panic!("Benchmarker::parse_wrk_output failed. error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn it_can_get_the_latency_histogram_from_the_wrk_summary() {
+        let mut benchmarker = Benchmarker::new(&Logger::default());
+        benchmarker.write(WRK_OUTPUT).unwrap();
+        benchmarker
+            .write(br#"{"wrk_summary":{"duration":15000000,"requests":1140005,"bytes":167772160,"errors":{"connect":0,"read":0,"write":0,"status":0,"timeout":0},"latency":{"mean":212.5,"stdev":99.0,"max":5000,"p50":180,"p75":240,"p90":330,"p99":1500,"p99_9":2500,"p99_99":4000,"p99_999":4900},"requests_per_thread":{"mean":19000.0,"stdev":1000.0,"max":21000}}}"#)
+            .unwrap();
+
+        match benchmarker.parse_wrk_output() {
+            Ok(results) => assert_eq!(
+                results.latency_histogram,
+                Some(LatencyHistogram {
+                    p50: 0.18,
+                    p75: 0.24,
+                    p90: 0.33,
+                    p99: 1.5,
+                    p99_9: 2.5,
+                    p99_99: 4.0,
+                    p99_999: 4.9,
+                })
+            ),
+            Err(e) => panic!("Benchmarker::parse_wrk_output failed. error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn it_can_parse_wrk2_output() {
+        let mut benchmarker = Benchmarker::new(&Logger::default());
+        benchmarker
+            .write(
+                b"Running 15s test @ http://tfb-server:8080/json\n\
+                  4 threads and 16 connections\n\
+                  Thread Stats   Avg      Stdev     Max   +/- Stdev\n\
+                    Latency     1.05ms  500.00us   5.00ms   75.00%\n\
+                    Req/Sec     2.60k   100.00    3.00k    70.00%\n\
+                  Latency Distribution (HdrHistogram - Recorded Latency)\n\
+                 50.000%    1.02ms\n\
+                 75.000%    1.40ms\n\
+                 90.000%    1.80ms\n\
+                 99.000%    2.60ms\n\
+                 99.900%    4.00ms\n\
+                  150000 requests in 15.00s, 21.00MB read\n\
+                Requests/sec:  10000.00\n\
+                Transfer/sec:      1.40MB\n",
+            )
+            .unwrap();
+
+        match benchmarker.parse_wrk_output() {
+            Ok(results) => {
+                assert_eq!(results.total_requests, 150_000);
+                assert_eq!(results.latency_distribution.percentile_50, "1.02ms");
+                assert_eq!(results.latency_distribution.percentile_75, "1.40ms");
+                assert_eq!(results.latency_distribution.percentile_90, "1.80ms");
+                assert_eq!(results.latency_distribution.percentile_99, "2.60ms");
+            }
+            Err(e) => panic!("Benchmarker::parse_wrk_output failed. error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn it_can_drop_the_middle_of_enormous_output() {
+        let mut benchmarker = Benchmarker::new(&Logger::default());
+        let error = b"unable to connect to tfb-server:8080 Connection refused\n";
+        for _ in 0..10_000 {
+            benchmarker.write(error).unwrap();
+        }
+        benchmarker.write(WRK_OUTPUT).unwrap();
+
+        let output = benchmarker.get_output();
+        assert!(output.len() < 2 * MAX_OUTPUT_BYTES + 100);
+        assert!(output.starts_with("unable to connect"));
+        assert!(output.contains("bytes of output dropped]"));
+        match benchmarker.parse_wrk_output() {
+            Ok(results) => assert_eq!(results.total_requests, 1_140_000),
+            Err(e) => panic!("Benchmarker::parse_wrk_output failed. error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn it_can_separate_stderr_from_stdout() {
+        let mut benchmarker = Benchmarker::new(&Logger::default());
+        let mut stream = vec![1, 0, 0, 0, 0, 0, 0, 6];
+        stream.extend_from_slice(b"stdout");
+        stream.extend_from_slice(&[2, 0, 0, 0, 0, 0, 0, 6]);
+        stream.extend_from_slice(b"stderr");
+        // Frames may be split across writes.
+        benchmarker.write(&stream[..11]).unwrap();
+        benchmarker.write(&stream[11..]).unwrap();
+
+        assert_eq!(benchmarker.get_output(), "stdout");
+        assert_eq!(benchmarker.get_stderr(), "stderr");
+    }
+
+    #[test]
+    fn it_can_parse_ghz_output() {
+        let mut benchmarker = Benchmarker::new(&Logger::default());
+        benchmarker
+            .write(br#"{"options":{"concurrency":16,"connections":16},"count":1200,"total":15000000000,"average":2500000,"slowest":9000000,"rps":80.5,"latencyDistribution":[{"percentage":50,"latency":2000000},{"percentage":99,"latency":8000000}],"statusCodeDistribution":{"OK":1190,"Unavailable":10}}"#)
+            .unwrap();
+
+        match benchmarker.parse_ghz_output() {
+            Ok(results) => {
+                assert_eq!(results.total_requests, 1200);
+                assert_eq!(results.connections, 16);
+                assert_eq!(results.thread_stats.latency.average, "2.50ms");
+                assert_eq!(results.latency_distribution.percentile_99, "8.00ms");
+                assert_eq!(results.non_2xx_3xx, Some(10));
+            }
+            Err(e) => panic!("Benchmarker::parse_ghz_output failed. error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn it_can_parse_tcpkali_output() {
+        let mut benchmarker = Benchmarker::new(&Logger::default());
+        benchmarker
+            .write(
+                b"Ramped up to 16 connections.\n\
+                Total data sent:     1.5 MiB (1600000 bytes)\n\
+                Total data received: 1.5 MiB (1600000 bytes)\n\
+                Message latency at percentiles: 50.0/90.0/99.0% = 0.4/0.9/2.1 ms\n\
+                Test duration: 10.0 s.\n",
+            )
+            .unwrap();
+
+        match benchmarker.parse_tcpkali_output() {
+            Ok(results) => {
+                assert_eq!(results.connections, 16);
+                assert_eq!(results.total_requests, 160_000);
+                assert_eq!(results.messages_per_second, Some(16_000f32));
+                assert_eq!(results.latency_distribution.percentile_90, "0.9ms");
+            }
+            Err(e) => panic!("Benchmarker::parse_tcpkali_output failed. error: {:?}", e),
+        }
+    }
+}
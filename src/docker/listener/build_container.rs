@@ -1,15 +1,17 @@
+use crate::docker::listener::ErrorMessage;
 use curl::easy::{Handler, WriteError};
 use serde_json::Value;
 
+#[derive(Clone)]
 pub struct BuildContainer {
     pub container_id: Option<String>,
-    pub error_message: Option<String>,
+    pub error_message: ErrorMessage,
 }
 impl BuildContainer {
     pub fn new() -> Self {
         Self {
             container_id: None,
-            error_message: None,
+            error_message: ErrorMessage::default(),
         }
     }
 }
@@ -25,8 +27,7 @@ impl Handler for BuildContainer {
                             self.container_id = Some(container_id.to_string());
                         } else if !json["message"].is_null() {
                             // fixme - this APPEARS to be how docker communicates error messages.
-                            let error = json["message"].as_str().unwrap().to_string();
-                            self.error_message = Some(error);
+                            self.error_message.set(json["message"].as_str().unwrap());
                         }
                     }
                 }
@@ -1,27 +1,43 @@
-use crate::io::Logger;
+use crate::io::{LogPath, Logger};
 use colored::Colorize;
 use curl::easy::{Handler, WriteError};
 use serde_json::Value;
+use std::time::{Duration, Instant};
 
 pub struct BuildImage {
     pub image_id: Option<String>,
     pub error_message: Option<String>,
+    /// Whether the build was abandoned for running past its deadline.
+    pub timed_out: bool,
+    deadline: Option<Instant>,
     logger: Logger,
 }
 impl BuildImage {
-    pub fn new(logger: &Logger) -> Self {
+    /// The build is abandoned (the transfer aborted) once it has run for
+    /// `timeout` seconds, if given; this is only noticed as the daemon reports
+    /// its progress, i.e. at the latest once the current step finishes.
+    pub fn new(logger: &Logger, timeout: Option<u32>) -> Self {
         let mut logger = logger.clone();
-        logger.set_log_file("log.txt");
+        logger.set_log_file(LogPath::Build);
 
         Self {
             image_id: None,
             error_message: None,
+            timed_out: false,
+            deadline: timeout.map(|timeout| Instant::now() + Duration::from_secs(timeout.into())),
             logger,
         }
     }
 }
 impl Handler for BuildImage {
     fn write(&mut self, data: &[u8]) -> Result<usize, WriteError> {
+        if let Some(deadline) = self.deadline {
+            if Instant::now() > deadline {
+                self.timed_out = true;
+                // Handling less than was given aborts the transfer.
+                return Ok(0);
+            }
+        }
         if let Ok(logs) = std::str::from_utf8(&data) {
             for line in logs.lines() {
                 if !line.trim().is_empty() {
@@ -1,15 +1,17 @@
+use crate::docker::listener::ErrorMessage;
 use curl::easy::{Handler, WriteError};
 use serde_json::Value;
 
+#[derive(Clone)]
 pub struct BuildNetwork {
     pub network_id: Option<String>,
-    pub error_message: Option<String>,
+    pub error_message: ErrorMessage,
 }
 impl BuildNetwork {
     pub fn new() -> Self {
         Self {
             network_id: None,
-            error_message: None,
+            error_message: ErrorMessage::default(),
         }
     }
 }
@@ -25,8 +27,7 @@ impl Handler for BuildNetwork {
                             self.network_id = Some(network_id.to_string());
                         } else if !json["message"].is_null() {
                             // fixme - this APPEARS to be how docker communicates error messages.
-                            let error = json["message"].as_str().unwrap().to_string();
-                            self.error_message = Some(error);
+                            self.error_message.set(json["message"].as_str().unwrap());
                         }
                     }
                 }
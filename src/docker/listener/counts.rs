@@ -0,0 +1,28 @@
+use curl::easy::{Handler, WriteError};
+
+/// Collects the output of a database sidecar which prints one count (e.g. of
+/// connections or statements) per line, interspersed with whatever else the
+/// database client printed.
+pub struct Counts {
+    data: Vec<u8>,
+}
+impl Counts {
+    pub fn new() -> Self {
+        Self { data: vec![] }
+    }
+
+    /// Parses each count printed.
+    pub fn parse_counts(&self) -> Vec<u64> {
+        String::from_utf8_lossy(&self.data)
+            .lines()
+            .filter_map(|line| str::parse::<u64>(line.trim()).ok())
+            .collect()
+    }
+}
+impl Handler for Counts {
+    fn write(&mut self, data: &[u8]) -> Result<usize, WriteError> {
+        self.data.extend_from_slice(data);
+
+        Ok(data.len())
+    }
+}
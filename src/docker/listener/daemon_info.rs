@@ -0,0 +1,36 @@
+use curl::easy::{Handler, WriteError};
+use serde::Deserialize;
+
+/// Collects the response of the Docker daemon's `/info` end-point.
+pub struct DaemonInfo {
+    data: Vec<u8>,
+}
+impl DaemonInfo {
+    pub fn new() -> Self {
+        Self { data: vec![] }
+    }
+
+    /// Parses the collected response into a `DaemonInfoReport`, if it is one.
+    pub fn parse_report(&self) -> Option<DaemonInfoReport> {
+        serde_json::from_slice::<DaemonInfoReport>(&self.data).ok()
+    }
+}
+impl Handler for DaemonInfo {
+    fn write(&mut self, data: &[u8]) -> Result<usize, WriteError> {
+        self.data.extend_from_slice(data);
+
+        Ok(data.len())
+    }
+}
+
+/// The subset of the daemon's `/info` response which we consume.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct DaemonInfoReport {
+    pub server_version: Option<String>,
+    pub driver: Option<String>,
+    pub cgroup_driver: Option<String>,
+    pub cgroup_version: Option<String>,
+    pub live_restore_enabled: Option<bool>,
+    pub system_time: Option<String>,
+}
@@ -0,0 +1,137 @@
+use crate::docker::events::ContainerEvents;
+use curl::easy::{Handler, WriteError};
+use serde::Deserialize;
+
+/// Listens to the Docker daemon's events stream and records container `die`,
+/// `oom`, `start`, and unhealthy `health_status` events in the shared
+/// `ContainerEvents`.
+pub struct Events {
+    events: ContainerEvents,
+    buffer: Vec<u8>,
+}
+impl Events {
+    pub fn new(events: &ContainerEvents) -> Self {
+        Self {
+            events: events.clone(),
+            buffer: vec![],
+        }
+    }
+}
+impl Handler for Events {
+    fn write(&mut self, data: &[u8]) -> Result<usize, WriteError> {
+        // Events are newline-delimited JSON, but a single event may span
+        // writes, so only complete lines are parsed.
+        self.buffer.extend_from_slice(data);
+        while let Some(index) = self.buffer.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=index).collect();
+            if let Ok(event) = serde_json::from_slice::<Event>(&line) {
+                match event.action.as_str() {
+                    "oom" => self.events.record_oom(&event.actor.id),
+                    "start" => self.events.record_start(&event.actor.id),
+                    "health_status: unhealthy" => self.events.record_unhealthy(&event.actor.id),
+                    "die" => {
+                        let exit_code = event
+                            .actor
+                            .attributes
+                            .exit_code
+                            .and_then(|code| str::parse::<i32>(&code).ok());
+                        self.events.record_die(&event.actor.id, exit_code);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(data.len())
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct Event {
+    #[serde(rename = "Action")]
+    action: String,
+    #[serde(rename = "Actor")]
+    actor: Actor,
+}
+
+#[derive(Deserialize, Debug)]
+struct Actor {
+    #[serde(rename = "ID")]
+    id: String,
+    #[serde(rename = "Attributes", default)]
+    attributes: Attributes,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct Attributes {
+    #[serde(rename = "exitCode")]
+    exit_code: Option<String>,
+}
+
+//
+// TESTS
+//
+
+#[cfg(test)]
+mod tests {
+    use crate::docker::events::ContainerEvents;
+    use crate::docker::listener::events::Events;
+    use curl::easy::Handler;
+
+    #[test]
+    fn it_can_record_container_deaths() {
+        let events = ContainerEvents::default();
+        let mut listener = Events::new(&events);
+        let stream = concat!(
+            r#"{"Type":"container","Action":"oom","Actor":{"ID":"abc","Attributes":{}}}"#,
+            "\n",
+            r#"{"Type":"container","Action":"die","Actor":{"ID":"abc","Attributes":{"exitCode":"137"}}}"#,
+            "\n"
+        )
+        .as_bytes();
+        // Events may be split across writes.
+        listener.write(&stream[..40]).unwrap();
+        listener.write(&stream[40..]).unwrap();
+
+        match events.get_death("abc") {
+            Some(death) => {
+                assert!(death.oom_killed);
+                assert_eq!(death.exit_code, Some(137));
+            }
+            None => panic!("ContainerEvents::get_death failed. events: {:?}", events),
+        }
+        assert!(events.get_death("def").is_none());
+    }
+
+    #[test]
+    fn it_can_count_container_incidents() {
+        let events = ContainerEvents::default();
+        let mut listener = Events::new(&events);
+        let event = |action: &str, id: &str| {
+            format!(
+                concat!(
+                    r#"{{"Type":"container","Action":"{}","Actor":{{"ID":"{}"}}}}"#,
+                    "\n"
+                ),
+                action, id
+            )
+        };
+        for (action, id) in &[
+            ("start", "abc"),
+            ("health_status: healthy", "abc"),
+            ("health_status: unhealthy", "abc"),
+            ("start", "abc"),
+            ("start", "def"),
+            ("health_status: unhealthy", "def"),
+        ] {
+            listener.write(event(action, id).as_bytes()).unwrap();
+        }
+
+        let incidents = events.get_incidents(&["abc"]);
+        assert_eq!(incidents.restarts, 1);
+        assert_eq!(incidents.health_flaps, 1);
+        let incidents = events.get_incidents(&["abc", "def"]);
+        assert_eq!(incidents.restarts, 1);
+        assert_eq!(incidents.health_flaps, 2);
+    }
+}
@@ -0,0 +1,31 @@
+use curl::easy::{Handler, WriteError};
+use serde::Deserialize;
+
+/// Collects the response of the Docker daemon's `/images/{name}/json`
+/// end-point.
+#[derive(Default)]
+pub struct ImageInfo {
+    data: Vec<u8>,
+}
+impl ImageInfo {
+    /// Parses the collected response into an `ImageInfoReport`, if it is one.
+    pub fn parse_report(&self) -> Option<ImageInfoReport> {
+        serde_json::from_slice::<ImageInfoReport>(&self.data).ok()
+    }
+}
+impl Handler for ImageInfo {
+    fn write(&mut self, data: &[u8]) -> Result<usize, WriteError> {
+        self.data.extend_from_slice(data);
+
+        Ok(data.len())
+    }
+}
+
+/// The subset of the daemon's image inspection which we consume.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct ImageInfoReport {
+    pub id: String,
+    /// The `<repository>@<digest>` of each registry the image was pulled from.
+    pub repo_digests: Option<Vec<String>>,
+}
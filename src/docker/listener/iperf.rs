@@ -0,0 +1,116 @@
+use curl::easy::{Handler, WriteError};
+use serde::Deserialize;
+use std::sync::{Arc, Mutex};
+
+/// Collects the JSON report printed by `iperf3 --json`.
+#[derive(Clone)]
+pub struct Iperf {
+    data: Arc<Mutex<Vec<u8>>>,
+}
+impl Iperf {
+    pub fn new() -> Self {
+        Self {
+            data: Arc::new(Mutex::new(vec![])),
+        }
+    }
+
+    /// Parses the collected output into an `IperfReport`, if it is one.
+    pub fn parse_report(&self) -> Option<IperfReport> {
+        if let Ok(data) = self.data.lock() {
+            serde_json::from_slice::<IperfReport>(&data).ok()
+        } else {
+            None
+        }
+    }
+}
+impl Handler for Iperf {
+    fn write(&mut self, data: &[u8]) -> Result<usize, WriteError> {
+        if let Ok(mut buffer) = self.data.lock() {
+            buffer.extend_from_slice(data);
+        }
+
+        Ok(data.len())
+    }
+}
+
+/// The subset of the report printed by `iperf3 --json` which we consume.
+#[derive(Deserialize, Debug)]
+pub struct IperfReport {
+    pub error: Option<String>,
+    pub end: Option<IperfEnd>,
+}
+impl IperfReport {
+    /// Gets the mean round-trip time in milliseconds; only reported for TCP
+    /// tests run on Linux.
+    pub fn get_latency_ms(&self) -> Option<f32> {
+        let end = self.end.as_ref()?;
+        let streams = end.streams.as_ref()?;
+        let rtts: Vec<f32> = streams
+            .iter()
+            .filter_map(|stream| stream.sender.mean_rtt)
+            .map(|micros| micros as f32 / 1_000f32)
+            .collect();
+        if rtts.is_empty() {
+            None
+        } else {
+            Some(rtts.iter().sum::<f32>() / rtts.len() as f32)
+        }
+    }
+
+    /// Gets the bandwidth, as received, in gigabits per second.
+    pub fn get_bandwidth_gbps(&self) -> Option<f32> {
+        let end = self.end.as_ref()?;
+        let received = end.sum_received.as_ref()?;
+
+        Some((received.bits_per_second / 1_000_000_000f64) as f32)
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct IperfEnd {
+    pub streams: Option<Vec<IperfStream>>,
+    pub sum_received: Option<IperfSum>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct IperfStream {
+    pub sender: IperfSender,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct IperfSender {
+    pub mean_rtt: Option<u64>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct IperfSum {
+    pub bits_per_second: f64,
+}
+
+//
+// TESTS
+//
+
+#[cfg(test)]
+mod tests {
+    use crate::docker::listener::iperf::Iperf;
+    use curl::easy::Handler;
+
+    #[test]
+    fn it_can_parse_iperf_report() {
+        let mut iperf = Iperf::new();
+        iperf
+            .write(
+                br#"{"start":{},"intervals":[],"end":{"streams":[{"sender":{"bytes":1,"mean_rtt":250}}],"sum_received":{"bits_per_second":9400000000.0}}}"#,
+            )
+            .unwrap();
+
+        match iperf.parse_report() {
+            Some(report) => {
+                assert_eq!(report.get_latency_ms(), Some(0.25));
+                assert_eq!(report.get_bandwidth_gbps(), Some(9.4));
+            }
+            None => panic!("Iperf::parse_report failed."),
+        }
+    }
+}
@@ -1,8 +1,38 @@
 pub mod application;
+pub mod archive;
 pub mod benchmark_command_listener;
 pub mod benchmarker;
 pub mod build_container;
 pub mod build_image;
 pub mod build_network;
+pub mod counts;
+pub mod daemon_info;
+pub mod events;
+pub mod image_info;
+pub mod iperf;
+pub mod output;
 pub mod simple;
+pub mod stats;
 pub mod verifier;
+
+use std::sync::{Arc, Mutex};
+
+/// The message with which the Docker daemon explained a failed request, if
+/// any; shared by all clones of the listener holding it, as dockurl is given
+/// a clone while the caller checks the original.
+#[derive(Clone, Debug, Default)]
+pub struct ErrorMessage(Arc<Mutex<Option<String>>>);
+impl ErrorMessage {
+    pub fn get(&self) -> Option<String> {
+        match self.0.lock() {
+            Ok(error_message) => error_message.clone(),
+            Err(_) => None,
+        }
+    }
+
+    pub fn set(&self, message: &str) {
+        if let Ok(mut error_message) = self.0.lock() {
+            *error_message = Some(message.to_string());
+        }
+    }
+}
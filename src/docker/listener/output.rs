@@ -0,0 +1,21 @@
+use curl::easy::{Handler, WriteError};
+
+/// Collects the output of a short-lived container, e.g. of the preflight
+/// request (see `docker::preflight`).
+#[derive(Default)]
+pub struct Output {
+    data: Vec<u8>,
+}
+impl Output {
+    /// Gets the collected output, trimmed.
+    pub fn get_output(&self) -> String {
+        String::from_utf8_lossy(&self.data).trim().to_string()
+    }
+}
+impl Handler for Output {
+    fn write(&mut self, data: &[u8]) -> Result<usize, WriteError> {
+        self.data.extend_from_slice(data);
+
+        Ok(data.len())
+    }
+}
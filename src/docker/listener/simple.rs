@@ -1,13 +1,15 @@
+use crate::docker::listener::ErrorMessage;
 use curl::easy::{Handler, WriteError};
 use serde_json::Value;
 
+#[derive(Clone)]
 pub struct Simple {
-    pub error_message: Option<String>,
+    pub error_message: ErrorMessage,
 }
 impl Simple {
     pub fn new() -> Self {
         Self {
-            error_message: None,
+            error_message: ErrorMessage::default(),
         }
     }
 }
@@ -18,8 +20,7 @@ impl Handler for Simple {
                 if !line.trim().is_empty() {
                     if let Ok(json) = serde_json::from_str::<Value>(line) {
                         if !json["message"].is_null() {
-                            let error = json["message"].as_str().unwrap().to_string();
-                            self.error_message = Some(error);
+                            self.error_message.set(json["message"].as_str().unwrap());
                         }
                     }
                 }
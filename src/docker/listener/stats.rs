@@ -0,0 +1,214 @@
+use curl::easy::{Handler, WriteError};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Listens to the stream of stats for a container, reducing each to a
+/// `StatsSample`, until `stop` is set.
+#[derive(Clone)]
+pub struct Stats {
+    samples: Arc<Mutex<Vec<StatsSample>>>,
+    stop: Arc<AtomicBool>,
+    buffer: Vec<u8>,
+}
+impl Stats {
+    pub fn new(samples: &Arc<Mutex<Vec<StatsSample>>>, stop: &Arc<AtomicBool>) -> Self {
+        Self {
+            samples: Arc::clone(samples),
+            stop: Arc::clone(stop),
+            buffer: vec![],
+        }
+    }
+}
+impl Handler for Stats {
+    fn write(&mut self, data: &[u8]) -> Result<usize, WriteError> {
+        if self.stop.load(Ordering::Acquire) {
+            // Consuming fewer bytes than given aborts the transfer.
+            return Ok(0);
+        }
+        self.buffer.extend_from_slice(data);
+        while let Some(index) = self.buffer.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=index).collect();
+            if let Ok(stats) = serde_json::from_slice::<ContainerStatsResponse>(&line) {
+                if let Ok(mut samples) = self.samples.lock() {
+                    samples.push(stats.to_sample());
+                }
+            }
+        }
+
+        Ok(data.len())
+    }
+}
+
+/// The CPU, memory, and IO usage of a container at a point in time.
+#[derive(Clone, Debug)]
+pub struct StatsSample {
+    /// Percentage of a single CPU; i.e. 400.0 is four CPUs fully utilized.
+    pub cpu_percent: f64,
+    /// Memory usage less the inactive page cache, as `docker stats` reports.
+    pub memory_bytes: u64,
+    /// Only reported on cgroup v1 hosts.
+    pub max_memory_bytes: Option<u64>,
+    /// Bytes read from block devices since the container started.
+    pub io_read_bytes: u64,
+    /// Bytes written to block devices since the container started.
+    pub io_write_bytes: u64,
+    pub cgroup_v2: bool,
+}
+
+/// The subset of the response from the stats endpoint which we consume. The
+/// layout differs between cgroup v1 and v2 (unified hierarchy) hosts: v2
+/// reports neither `percpu_usage` nor `max_usage`, and its memory stats are
+/// keyed without the v1 `total_` prefixes; its IO operations are lowercase.
+#[derive(Deserialize, Debug)]
+struct ContainerStatsResponse {
+    cpu_stats: CpuStats,
+    precpu_stats: CpuStats,
+    memory_stats: MemoryStats,
+    blkio_stats: Option<BlkioStats>,
+}
+impl ContainerStatsResponse {
+    fn to_sample(&self) -> StatsSample {
+        let cpu_delta = self.cpu_stats.cpu_usage.total_usage as f64
+            - self.precpu_stats.cpu_usage.total_usage as f64;
+        let system_delta = self.cpu_stats.system_cpu_usage.unwrap_or(0) as f64
+            - self.precpu_stats.system_cpu_usage.unwrap_or(0) as f64;
+        let online_cpus = match self.cpu_stats.online_cpus {
+            Some(online_cpus) if online_cpus > 0 => online_cpus as f64,
+            _ => match &self.cpu_stats.cpu_usage.percpu_usage {
+                Some(percpu_usage) if !percpu_usage.is_empty() => percpu_usage.len() as f64,
+                _ => 1f64,
+            },
+        };
+        let cpu_percent = if cpu_delta > 0f64 && system_delta > 0f64 {
+            cpu_delta / system_delta * online_cpus * 100f64
+        } else {
+            0f64
+        };
+
+        let memory = &self.memory_stats;
+        let usage = memory.usage.unwrap_or(0);
+        let stats = memory.stats.as_ref();
+        let cgroup_v2 = match stats {
+            Some(stats) => !stats.contains_key("total_inactive_file"),
+            None => memory.max_usage.is_none(),
+        };
+        let inactive_file = stats.and_then(|stats| {
+            stats
+                .get("total_inactive_file")
+                .or_else(|| stats.get("inactive_file"))
+                .cloned()
+        });
+        let memory_bytes = match inactive_file {
+            Some(inactive_file) if inactive_file < usage => usage - inactive_file,
+            _ => usage,
+        };
+
+        StatsSample {
+            cpu_percent,
+            memory_bytes,
+            max_memory_bytes: memory.max_usage,
+            io_read_bytes: self.get_io_bytes("read"),
+            io_write_bytes: self.get_io_bytes("write"),
+            cgroup_v2,
+        }
+    }
+
+    /// Gets the bytes transferred by the given IO `operation` across every
+    /// block device.
+    fn get_io_bytes(&self, operation: &str) -> u64 {
+        self.blkio_stats
+            .as_ref()
+            .and_then(|blkio| blkio.io_service_bytes_recursive.as_ref())
+            .map(|entries| {
+                entries
+                    .iter()
+                    .filter(|entry| entry.op.eq_ignore_ascii_case(operation))
+                    .map(|entry| entry.value)
+                    .sum()
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct CpuStats {
+    cpu_usage: CpuUsage,
+    system_cpu_usage: Option<u64>,
+    online_cpus: Option<u32>,
+}
+
+#[derive(Deserialize, Debug)]
+struct CpuUsage {
+    total_usage: u64,
+    percpu_usage: Option<Vec<u64>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct MemoryStats {
+    usage: Option<u64>,
+    max_usage: Option<u64>,
+    stats: Option<HashMap<String, u64>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct BlkioStats {
+    io_service_bytes_recursive: Option<Vec<BlkioEntry>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct BlkioEntry {
+    op: String,
+    value: u64,
+}
+
+//
+// TESTS
+//
+
+#[cfg(test)]
+mod tests {
+    use crate::docker::listener::stats::Stats;
+    use curl::easy::Handler;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn it_can_sample_cgroup_v1_stats() {
+        let samples = Arc::new(Mutex::new(vec![]));
+        let mut stats = Stats::new(&samples, &Arc::new(AtomicBool::new(false)));
+        stats
+            .write(br#"{"cpu_stats":{"cpu_usage":{"total_usage":3000,"percpu_usage":[1,1,1,1]},"system_cpu_usage":10000},"precpu_stats":{"cpu_usage":{"total_usage":1000},"system_cpu_usage":6000},"memory_stats":{"usage":1000,"max_usage":1500,"stats":{"total_inactive_file":200,"inactive_file":100}},"blkio_stats":{"io_service_bytes_recursive":[{"major":8,"minor":0,"op":"Read","value":4096},{"major":8,"minor":0,"op":"Write","value":512},{"major":8,"minor":0,"op":"Total","value":4608}]}}
+"#)
+            .unwrap();
+
+        let samples = samples.lock().unwrap();
+        assert_eq!(samples.len(), 1);
+        assert_eq!(samples[0].cpu_percent, 200f64);
+        assert_eq!(samples[0].memory_bytes, 800);
+        assert_eq!(samples[0].max_memory_bytes, Some(1500));
+        assert_eq!(samples[0].io_read_bytes, 4096);
+        assert_eq!(samples[0].io_write_bytes, 512);
+        assert!(!samples[0].cgroup_v2);
+    }
+
+    #[test]
+    fn it_can_sample_cgroup_v2_stats() {
+        let samples = Arc::new(Mutex::new(vec![]));
+        let mut stats = Stats::new(&samples, &Arc::new(AtomicBool::new(false)));
+        stats
+            .write(br#"{"cpu_stats":{"cpu_usage":{"total_usage":3000},"system_cpu_usage":10000,"online_cpus":2},"precpu_stats":{"cpu_usage":{"total_usage":1000},"system_cpu_usage":6000},"memory_stats":{"usage":1000,"stats":{"anon":600,"inactive_file":300}},"blkio_stats":{"io_service_bytes_recursive":[{"major":8,"minor":0,"op":"read","value":1024},{"major":8,"minor":16,"op":"read","value":1024},{"major":8,"minor":0,"op":"write","value":2048}]}}
+"#)
+            .unwrap();
+
+        let samples = samples.lock().unwrap();
+        assert_eq!(samples.len(), 1);
+        assert_eq!(samples[0].cpu_percent, 100f64);
+        assert_eq!(samples[0].memory_bytes, 700);
+        assert_eq!(samples[0].max_memory_bytes, None);
+        assert_eq!(samples[0].io_read_bytes, 2048);
+        assert_eq!(samples[0].io_write_bytes, 2048);
+        assert!(samples[0].cgroup_v2);
+    }
+}
@@ -1,8 +1,8 @@
 // use crate::config::{Named, Project, Test};
 use crate::docker::Verification;
-use crate::io::Logger;
+use crate::io::{LogPath, Logger};
 use curl::easy::{Handler, WriteError};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::sync::{Arc, Mutex};
 
 #[derive(Clone, Debug)]
@@ -11,9 +11,9 @@ pub struct Verifier {
     logger: Logger,
 }
 impl Verifier {
-    pub fn new(verification: Arc<Mutex<Verification>>, logger: &Logger) -> Self {
+    pub fn new(verification: Arc<Mutex<Verification>>, test_type: &str, logger: &Logger) -> Self {
         let mut logger = logger.clone();
-        logger.set_log_file("verifications.txt");
+        logger.set_log_file(LogPath::Verification(test_type));
 
         Self {
             logger,
@@ -45,12 +45,14 @@ impl Handler for Verifier {
     }
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all(serialize = "camelCase"))]
 pub struct Warning {
     pub message: String,
     pub short_message: String,
 }
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all(serialize = "camelCase"))]
 pub struct Error {
     pub message: String,
     pub short_message: String,
@@ -0,0 +1,289 @@
+//! A `DockerApi` which talks to no daemon, for testing orchestration.
+
+use crate::benchmarker::modes::CICD;
+use crate::docker::api::{ContainerInspection, DockerApi};
+use crate::docker::docker_config::DockerConfig;
+use crate::error::ToolsetError::DockerDaemonError;
+use crate::error::ToolsetResult;
+use crate::options;
+use clap::ArgMatches;
+use curl::easy::Handler;
+use dockurl::container::create::options::Options;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Records the requests made of it, as `<operation> <subject> on <host>`,
+/// and answers them with sequential ids (`mock-1`, `mock-2`, ...) and an
+/// inspection of a container which exited cleanly and binds `8080/tcp` to
+/// host port `32768` (`32767 + n` for `mock-n`, as though each container were
+/// published on a fresh port), unless other ports are exposed via
+/// `expose_ports`. Every image has the digest `<name>@sha256:mock`.
+#[derive(Debug, Default)]
+pub struct MockDockerApi {
+    operations: Mutex<Vec<String>>,
+    failures: Mutex<HashMap<String, String>>,
+    failures_once: Mutex<HashMap<String, String>>,
+    missing_networks: Mutex<Vec<String>>,
+    exposed_ports: Mutex<Vec<u16>>,
+    ids: AtomicUsize,
+}
+impl MockDockerApi {
+    /// Fails every later request to perform `operation` (e.g. "start
+    /// container") as the daemon would, explaining it with `message`.
+    pub fn fail(&self, operation: &str, message: &str) {
+        if let Ok(mut failures) = self.failures.lock() {
+            failures.insert(operation.to_string(), message.to_string());
+        }
+    }
+
+    /// Fails only the next request to perform `operation`, as `fail` would.
+    pub fn fail_once(&self, operation: &str, message: &str) {
+        if let Ok(mut failures_once) = self.failures_once.lock() {
+            failures_once.insert(operation.to_string(), message.to_string());
+        }
+    }
+
+    /// Answers requests to inspect the network given by `network_name` as if
+    /// it did not exist, until it is created.
+    pub fn remove_network(&self, network_name: &str) {
+        if let Ok(mut missing_networks) = self.missing_networks.lock() {
+            missing_networks.push(network_name.to_string());
+        }
+    }
+
+    /// Answers requests to inspect a container as if it exposed the given
+    /// `ports` over TCP, in that order, each bound to its own host port.
+    pub fn expose_ports(&self, ports: &[u16]) {
+        if let Ok(mut exposed_ports) = self.exposed_ports.lock() {
+            *exposed_ports = ports.to_vec();
+        }
+    }
+
+    /// Gets the requests made so far, in order.
+    pub fn get_operations(&self) -> Vec<String> {
+        match self.operations.lock() {
+            Ok(operations) => operations.clone(),
+            Err(_) => vec![],
+        }
+    }
+
+    fn perform(&self, operation: &str, subject: &str, docker_host: &str) -> ToolsetResult<()> {
+        if let Ok(mut operations) = self.operations.lock() {
+            operations.push(format!("{} {} on {}", operation, subject, docker_host));
+        }
+        let mut failure = None;
+        if let Ok(mut failures_once) = self.failures_once.lock() {
+            failure = failures_once.remove(operation);
+        }
+        if let Ok(failures) = self.failures.lock() {
+            failure = failure.or_else(|| failures.get(operation).cloned());
+        }
+        match failure {
+            Some(message) => Err(DockerDaemonError(
+                operation.to_string(),
+                docker_host.to_string(),
+                message,
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn next_id(&self) -> String {
+        format!("mock-{}", self.ids.fetch_add(1, Ordering::SeqCst) + 1)
+    }
+}
+impl DockerApi for MockDockerApi {
+    fn build_image(
+        &self,
+        tag: &str,
+        _dockerfile: &Path,
+        _context: &Path,
+        docker_host: &str,
+        _handler: &mut dyn Handler,
+    ) -> ToolsetResult<String> {
+        self.perform("build image", tag, docker_host)?;
+
+        Ok(self.next_id())
+    }
+
+    fn pull_image(&self, image_name: &str, _tag: &str, docker_host: &str) -> ToolsetResult<()> {
+        self.perform("pull image", image_name, docker_host)
+    }
+
+    fn delete_image(&self, image_id: &str, docker_host: &str) -> ToolsetResult<()> {
+        self.perform("delete image", image_id, docker_host)
+    }
+
+    fn get_image_digest(
+        &self,
+        image_name: &str,
+        docker_host: &str,
+    ) -> ToolsetResult<Option<String>> {
+        self.perform("inspect image", image_name, docker_host)?;
+
+        Ok(Some(format!("{}@sha256:mock", image_name)))
+    }
+
+    fn delete_dangling_images(&self, docker_host: &str) -> ToolsetResult<()> {
+        self.perform("delete dangling images", "", docker_host)
+    }
+
+    fn create_container(&self, _options: Options, docker_host: &str) -> ToolsetResult<String> {
+        let container_id = self.next_id();
+        self.perform("create container", &container_id, docker_host)?;
+
+        Ok(container_id)
+    }
+
+    fn start_container(&self, container_id: &str, docker_host: &str) -> ToolsetResult<()> {
+        self.perform("start container", container_id, docker_host)
+    }
+
+    fn attach_to_container(
+        &self,
+        container_id: &str,
+        docker_host: &str,
+        _handler: &mut dyn Handler,
+    ) -> ToolsetResult<()> {
+        self.perform("attach to container", container_id, docker_host)
+    }
+
+    fn get_container_logs(
+        &self,
+        container_id: &str,
+        docker_host: &str,
+        _handler: &mut dyn Handler,
+    ) -> ToolsetResult<()> {
+        self.perform("get container logs", container_id, docker_host)
+    }
+
+    fn inspect_container(
+        &self,
+        container_id: &str,
+        docker_host: &str,
+    ) -> ToolsetResult<ContainerInspection> {
+        self.perform("inspect container", container_id, docker_host)?;
+
+        let host_port = container_id
+            .strip_prefix("mock-")
+            .and_then(|n| str::parse::<u32>(n).ok())
+            .map_or(32768, |n| 32767 + n);
+        let mut ports = match self.exposed_ports.lock() {
+            Ok(exposed_ports) => exposed_ports.clone(),
+            Err(_) => vec![],
+        };
+        if ports.is_empty() {
+            ports.push(8080);
+        }
+        let exposed_ports: Vec<String> = ports.iter().map(|port| format!("{}/tcp", port)).collect();
+        let mut host_ports = HashMap::new();
+        for (index, exposed_port) in exposed_ports.iter().enumerate() {
+            let host_port = host_port + 1000 * index as u32;
+            host_ports.insert(exposed_port.clone(), vec![host_port.to_string()]);
+        }
+        Ok(ContainerInspection {
+            running: false,
+            exit_code: 0,
+            exposed_ports: Some(exposed_ports),
+            host_ports,
+        })
+    }
+
+    fn wait_for_container_to_exit(
+        &self,
+        container_id: &str,
+        docker_host: &str,
+    ) -> ToolsetResult<()> {
+        self.perform("wait for container", container_id, docker_host)
+    }
+
+    fn kill_container(&self, container_id: &str, docker_host: &str) -> ToolsetResult<()> {
+        self.perform("kill container", container_id, docker_host)
+    }
+
+    fn delete_container(&self, container_id: &str, docker_host: &str) -> ToolsetResult<()> {
+        self.perform("delete container", container_id, docker_host)
+    }
+
+    fn inspect_network(&self, network_name: &str, docker_host: &str) -> ToolsetResult<String> {
+        self.perform("inspect network", network_name, docker_host)?;
+        if let Ok(missing_networks) = self.missing_networks.lock() {
+            if missing_networks
+                .iter()
+                .any(|missing| missing == network_name)
+            {
+                return Err(DockerDaemonError(
+                    "inspect network".to_string(),
+                    docker_host.to_string(),
+                    format!("network {} not found", network_name),
+                ));
+            }
+        }
+
+        Ok(format!("{}-id", network_name))
+    }
+
+    fn create_network(&self, network_name: &str, docker_host: &str) -> ToolsetResult<String> {
+        self.perform("create network", network_name, docker_host)?;
+        if let Ok(mut missing_networks) = self.missing_networks.lock() {
+            missing_networks.retain(|missing| missing != network_name);
+        }
+
+        Ok(format!("{}-id", network_name))
+    }
+
+    fn connect_container_to_network(
+        &self,
+        container_id: &str,
+        network_id: &str,
+        docker_host: &str,
+    ) -> ToolsetResult<()> {
+        self.perform(
+            "connect container to network",
+            &format!("{} to {}", container_id, network_id),
+            docker_host,
+        )
+    }
+
+    fn delete_network(&self, network_id: &str, docker_host: &str) -> ToolsetResult<()> {
+        self.perform("remove network", network_id, docker_host)
+    }
+}
+
+/// Parses `tfb --mode cicd` followed by the given `args`, and builds a
+/// `DockerConfig` of them which makes every request of the returned
+/// `MockDockerApi`. The matches are leaked, as the config borrows them for
+/// the rest of the test.
+pub fn mock_docker_config(
+    args: &[&str],
+) -> (
+    &'static ArgMatches,
+    Arc<MockDockerApi>,
+    DockerConfig<'static>,
+) {
+    let (matches, docker_api, docker_config) = try_mock_docker_config(args);
+
+    (matches, docker_api, docker_config.unwrap())
+}
+
+/// Like `mock_docker_config`, but gets the error building the `DockerConfig`
+/// of the given `args`, if any, rather than panicking.
+pub fn try_mock_docker_config(
+    args: &[&str],
+) -> (
+    &'static ArgMatches,
+    Arc<MockDockerApi>,
+    ToolsetResult<DockerConfig<'static>>,
+) {
+    let mut command_line = vec!["tfb", "--mode", CICD];
+    command_line.extend_from_slice(args);
+    let matches: &'static ArgMatches =
+        Box::leak(Box::new(options::parse().get_matches_from(command_line)));
+    let docker_api = Arc::new(MockDockerApi::default());
+    let docker_config =
+        DockerConfig::with_docker_api(matches, |_| Arc::clone(&docker_api) as Arc<dyn DockerApi>);
+
+    (matches, docker_api, docker_config)
+}
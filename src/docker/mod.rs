@@ -2,29 +2,84 @@
 //! This includes actions like building `Test` images, building containers for
 //! those images, and running containers in Docker.
 
+use crate::config::CommandOverride;
+use crate::docker::docker_config::DockerConfig;
 use crate::docker::listener::verifier::Error;
 use crate::docker::listener::verifier::Warning;
-use serde::Deserialize;
+use crate::error::ToolsetError::DockerDaemonError;
+use crate::error::ToolsetResult;
+use dockurl::error::DockerError;
+use serde::{Deserialize, Serialize};
 use std::task::Poll;
 
+pub mod api;
+pub mod connections;
 pub mod container;
+pub mod daemon;
 pub mod docker_config;
+pub mod dry_run;
+pub mod events;
 pub mod image;
 pub mod listener;
+#[cfg(test)]
+pub mod mock;
 pub mod network;
+pub mod network_check;
+pub mod preflight;
+pub mod profile;
+pub mod queries;
+pub mod resources;
+pub mod stats;
 
-#[derive(Debug)]
+#[derive(Serialize, Debug)]
 pub struct DockerOrchestration {
+    pub host_image_id: String,
+    /// The framework directory bind-mounted into the application container
+    /// by `--mount-source`, if any.
+    pub host_source_dir: Option<String>,
     pub host_container_id: String,
     pub host_port: String,
     pub host_internal_port: String,
     pub database_name: Option<String>,
+    /// The host at which the application reaches its database, if any; see
+    /// `--database-hosts`.
+    pub database_host: Option<String>,
     pub db_container_id: Option<String>,
     pub db_host_port: Option<String>,
     pub db_internal_port: Option<String>,
 }
 
-#[derive(Clone, Debug)]
+/// What was connected to what for a test; written to its `topology.json`
+/// so that post-run debugging and audits can reconstruct it.
+#[derive(Serialize, Debug)]
+pub struct Topology<'a> {
+    #[serde(flatten)]
+    pub orchestration: &'a DockerOrchestration,
+    pub server_docker_host: &'a str,
+    pub database_docker_host: &'a str,
+    pub client_docker_host: &'a str,
+    pub server_network_id: &'a str,
+    pub database_network_id: &'a str,
+    pub client_network_id: &'a str,
+}
+impl<'a> Topology<'a> {
+    pub fn new(config: &'a DockerConfig, orchestration: &'a DockerOrchestration) -> Self {
+        Self {
+            orchestration,
+            server_docker_host: &config.server_docker_host,
+            database_docker_host: &config.database_docker_host,
+            client_docker_host: &config.client_docker_host,
+            server_network_id: &config.server_network_id,
+            database_network_id: &config.database_network_id,
+            client_network_id: &config.client_network_id,
+        }
+    }
+}
+
+/// The outcome of verifying a test type; written, with the others of the
+/// run, to its `verifications.json`.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
 pub struct Verification {
     pub framework_name: String,
     pub test_name: String,
@@ -33,12 +88,149 @@ pub struct Verification {
     pub errors: Vec<Error>,
 }
 
+/// The message sent on (and expected to be echoed back over) every WebSocket
+/// connection; it doubles as tcpkali's latency marker.
+pub const WEBSOCKET_MESSAGE: &str = "tfb-ping";
+
 #[derive(Deserialize, Clone, Debug)]
 pub struct BenchmarkCommands {
     pub primer_command: Vec<String>,
     pub warmup_command: Vec<String>,
     pub benchmark_commands: Vec<Vec<String>>,
 }
+impl BenchmarkCommands {
+    /// Returns a copy of the given `wrk` command run at `concurrency`.
+    pub fn with_concurrency(command: &[String], concurrency: u32) -> Vec<String> {
+        override_wrk_command(
+            command,
+            &CommandOverride {
+                connections: Some(concurrency),
+                ..CommandOverride::default()
+            },
+        )
+    }
+
+    /// Returns a copy of the given `wrk` command run for `duration` seconds.
+    pub fn with_duration(command: &[String], duration: u32) -> Vec<String> {
+        override_wrk_command(
+            command,
+            &CommandOverride {
+                duration: Some(duration),
+                ..CommandOverride::default()
+            },
+        )
+    }
+
+    /// Rewrites the `wrk` commands with the values from `command_override`.
+    pub fn with_override(self, command_override: &CommandOverride) -> Self {
+        let primer = CommandOverride {
+            duration: None,
+            ..command_override.clone()
+        };
+
+        Self {
+            primer_command: override_wrk_command(&self.primer_command, &primer),
+            warmup_command: override_wrk_command(&self.warmup_command, command_override),
+            benchmark_commands: self
+                .benchmark_commands
+                .iter()
+                .map(|command| override_wrk_command(command, command_override))
+                .collect(),
+        }
+    }
+
+    /// Builds the `ghz` commands for benchmarking the given gRPC `call` (e.g.
+    /// `helloworld.Greeter.SayHello`) for `duration` seconds each; the
+    /// verifier only speaks HTTP, so these cannot be retrieved from it like
+    /// the `wrk` commands are.
+    pub fn ghz(
+        config: &DockerConfig,
+        orchestration: &DockerOrchestration,
+        call: &str,
+        duration: u32,
+    ) -> Self {
+        let target = format!("tfb-server:{}", orchestration.host_internal_port);
+        let command = |concurrency: &str, duration: u32| {
+            let duration = format!("{}s", duration);
+            [
+                "ghz",
+                "--insecure",
+                "--call",
+                call,
+                "--concurrency",
+                concurrency,
+                "--connections",
+                concurrency,
+                "--duration",
+                &duration,
+                "--format",
+                "json",
+                &target,
+            ]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect::<Vec<String>>()
+        };
+        let levels: Vec<&str> = config.concurrency_levels.split(',').collect();
+
+        Self {
+            primer_command: command("8", 5),
+            warmup_command: command(levels.last().unwrap_or(&"8"), duration),
+            benchmark_commands: levels
+                .iter()
+                .map(|level| command(level, duration))
+                .collect(),
+        }
+    }
+
+    /// Builds the `tcpkali` commands for benchmarking the given WebSocket
+    /// `endpoint` for `duration` seconds, one per configured connection count,
+    /// each sending `websocket_message_rate` messages per second per
+    /// connection.
+    pub fn tcpkali(
+        config: &DockerConfig,
+        orchestration: &DockerOrchestration,
+        endpoint: &str,
+        duration: u32,
+    ) -> Self {
+        let target = format!(
+            "tfb-server:{}{}",
+            orchestration.host_internal_port, endpoint
+        );
+        let message_rate = config.websocket_message_rate.to_string();
+        let command = |connections: &str, duration: u32| {
+            let duration = format!("{}s", duration);
+            [
+                "tcpkali",
+                "--websocket",
+                "--connections",
+                connections,
+                "--message-rate",
+                &message_rate,
+                "--message",
+                WEBSOCKET_MESSAGE,
+                "--latency-marker",
+                WEBSOCKET_MESSAGE,
+                "--duration",
+                &duration,
+                &target,
+            ]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect::<Vec<String>>()
+        };
+        let levels: Vec<&str> = config.websocket_connections.split(',').collect();
+
+        Self {
+            primer_command: command("8", 5),
+            warmup_command: command(levels.last().unwrap_or(&"8"), duration),
+            benchmark_commands: levels
+                .iter()
+                .map(|level| command(level, duration))
+                .collect(),
+        }
+    }
+}
 
 #[derive(Clone, Debug)]
 pub struct DockerContainerIdFuture {
@@ -61,11 +253,21 @@ impl DockerContainerIdFuture {
         self.image_id = Some(image_id.to_string());
     }
 
+    /// Points this future at the daemon on `docker_host`, on which the
+    /// containers registered from then on run.
+    pub fn docker_host(&mut self, docker_host: &str) {
+        self.docker_host = docker_host.to_string();
+    }
+
     pub fn register(&mut self, container_id: &str) {
         self.requires_wait_to_stop = true;
         self.container_id = Some(container_id.to_string());
     }
 
+    pub fn get_container_id(&self) -> Option<String> {
+        self.container_id.clone()
+    }
+
     pub fn unregister(&mut self) {
         self.requires_wait_to_stop = false;
         self.container_id = None;
@@ -83,3 +285,180 @@ impl DockerContainerIdFuture {
         }
     }
 }
+
+/// Checks the `result` of asking the Docker daemon on `docker_host` to
+/// perform `operation`, preferring the daemon's own explanation (as captured
+/// by the listener into `error_message`) over the generic dockurl error.
+///
+/// Note: the daemon may explain a failure while dockurl still reports
+/// success, so an `error_message` fails an otherwise `Ok` result.
+pub fn check_daemon_result<T>(
+    operation: &str,
+    docker_host: &str,
+    result: Result<T, DockerError>,
+    error_message: Option<String>,
+) -> ToolsetResult<T> {
+    match (result, error_message) {
+        (Ok(value), None) => Ok(value),
+        (Err(error), None) => Err(DockerDaemonError(
+            operation.to_string(),
+            docker_host.to_string(),
+            error.to_string(),
+        )),
+        (_, Some(message)) => Err(DockerDaemonError(
+            operation.to_string(),
+            docker_host.to_string(),
+            message,
+        )),
+    }
+}
+
+//
+// PRIVATES
+//
+
+/// Returns a copy of the given `wrk` command with its duration, connection
+/// and thread counts replaced, and its headers swapped out, per
+/// `command_override`.
+fn override_wrk_command(command: &[String], command_override: &CommandOverride) -> Vec<String> {
+    let mut overridden = Vec::with_capacity(command.len());
+    let mut args = command.iter();
+    if let Some(program) = args.next() {
+        overridden.push(program.clone());
+    }
+    if let Some(headers) = &command_override.headers {
+        for header in headers {
+            overridden.push("-H".to_string());
+            overridden.push(header.clone());
+        }
+    }
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-d" | "--duration" if command_override.duration.is_some() => {
+                args.next();
+                overridden.push(arg.clone());
+                overridden.push(command_override.duration.unwrap().to_string());
+            }
+            "-c" | "--connections" if command_override.connections.is_some() => {
+                args.next();
+                overridden.push(arg.clone());
+                overridden.push(command_override.connections.unwrap().to_string());
+            }
+            "-t" | "--threads"
+                if command_override.threads.is_some() || command_override.connections.is_some() =>
+            {
+                let original = args
+                    .next()
+                    .and_then(|threads| str::parse::<u32>(threads).ok());
+                let mut threads = command_override.threads.or(original).unwrap_or(1);
+                if let Some(connections) = command_override.connections {
+                    // wrk refuses to run with fewer connections than threads.
+                    threads = threads.min(connections);
+                }
+                overridden.push(arg.clone());
+                overridden.push(threads.to_string());
+            }
+            "-H" | "--header" if command_override.headers.is_some() => {
+                args.next();
+            }
+            _ => overridden.push(arg.clone()),
+        }
+    }
+
+    overridden
+}
+
+//
+// TESTS
+//
+
+#[cfg(test)]
+mod tests {
+    use crate::config::CommandOverride;
+    use crate::docker::listener::simple::Simple;
+    use crate::docker::{check_daemon_result, BenchmarkCommands};
+    use crate::error::ToolsetError::DockerDaemonError;
+    use curl::easy::Handler;
+
+    fn wrk(duration: &str) -> Vec<String> {
+        [
+            "wrk",
+            "-H",
+            "Host: tfb-server",
+            "--latency",
+            "-d",
+            duration,
+            "-c",
+            "16",
+            "-t",
+            "8",
+            "http://tfb-server:8080/json",
+        ]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect()
+    }
+
+    #[test]
+    fn it_can_override_wrk_commands() {
+        let commands = BenchmarkCommands {
+            primer_command: wrk("5"),
+            warmup_command: wrk("15"),
+            benchmark_commands: vec![wrk("15")],
+        }
+        .with_override(&CommandOverride {
+            duration: Some(60),
+            connections: None,
+            threads: Some(2),
+            headers: Some(vec!["Accept: */*".to_string()]),
+        });
+
+        let expected = |duration: &str| {
+            [
+                "wrk",
+                "-H",
+                "Accept: */*",
+                "--latency",
+                "-d",
+                duration,
+                "-c",
+                "16",
+                "-t",
+                "2",
+                "http://tfb-server:8080/json",
+            ]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect::<Vec<String>>()
+        };
+        assert_eq!(commands.primer_command, expected("5"));
+        assert_eq!(commands.warmup_command, expected("60"));
+        assert_eq!(commands.benchmark_commands, vec![expected("60")]);
+    }
+
+    #[test]
+    fn it_can_surface_daemon_error_messages() {
+        let listener = Simple::new();
+        listener
+            .clone()
+            .write(br#"{"message":"No such container: abc123"}"#)
+            .unwrap();
+
+        match check_daemon_result(
+            "start container",
+            "tcp://10.0.0.1:2375",
+            Ok(()),
+            listener.error_message.get(),
+        ) {
+            Err(DockerDaemonError(operation, host, message)) => {
+                assert_eq!(operation, "start container");
+                assert_eq!(host, "tcp://10.0.0.1:2375");
+                assert_eq!(message, "No such container: abc123");
+            }
+            _ => panic!("check_daemon_result did not surface the daemon's message."),
+        }
+        assert!(
+            check_daemon_result("start container", "tcp://10.0.0.1:2375", Ok(()), None).is_ok()
+        );
+    }
+}
@@ -1,65 +1,161 @@
+use crate::config::{Named, Test};
+use crate::docker::api::DockerApi;
 use crate::docker::docker_config::DockerConfig;
-use crate::docker::listener::build_network::BuildNetwork;
-use crate::docker::listener::simple::Simple;
-use crate::error::ToolsetError::DockerError;
+use crate::docker::image::pull_image;
+use crate::error::ToolsetError::NetworkShapingError;
 use crate::error::ToolsetResult;
+use crate::results::NetworkShaping;
+use dockurl::container::create::host_config::HostConfig;
+use dockurl::container::create::options::Options;
 use dockurl::network::NetworkMode;
 
+/// The image containing `tc`, run to shape the network of other containers.
+const NETEM_IMAGE: &str = "techempower/tfb.netem";
+
 /// Gets the network id for the given `docker_host` and `network_name`.
 pub fn get_network_id(
-    use_unix_socket: bool,
+    docker_api: &dyn DockerApi,
     docker_host: &str,
     network_name: &str,
 ) -> ToolsetResult<String> {
-    match dockurl::network::inspect_network(
-        network_name,
-        docker_host,
-        use_unix_socket,
-        Simple::new(),
-    ) {
-        Ok(network) => Ok(network.id),
-        Err(error) => Err(DockerError(error)),
-    }
+    docker_api.inspect_network(network_name, docker_host)
 }
 
 /// Gets the network id for the "TFBNetwork" on the given `docker_host`.
 /// Will create the network if it does not already exist.
-pub fn get_tfb_network_id(use_unix_socket: bool, docker_host: &str) -> ToolsetResult<String> {
-    if let Ok(network) =
-        dockurl::network::inspect_network("TFBNetwork", docker_host, use_unix_socket, Simple::new())
-    {
-        Ok(network.id)
+pub fn get_tfb_network_id(docker_api: &dyn DockerApi, docker_host: &str) -> ToolsetResult<String> {
+    if let Ok(network_id) = docker_api.inspect_network("TFBNetwork", docker_host) {
+        Ok(network_id)
     } else {
-        match dockurl::network::create_network(
-            "TFBNetwork",
-            NetworkMode::Bridge,
-            docker_host,
-            use_unix_socket,
-            BuildNetwork::new(),
-        ) {
-            Ok(network_id) => Ok(network_id),
-            Err(error) => Err(DockerError(error)),
-        }
+        docker_api.create_network("TFBNetwork", docker_host)
     }
 }
 
+/// Gets the name of the network to which the containers of `test` are
+/// isolated (see `--isolate-networks`) in the run given by `run_uuid`.
+pub fn get_isolated_network_name(run_uuid: &str, test: &Test) -> String {
+    format!(
+        "tfb-{}-{}",
+        run_uuid,
+        test.get_name()
+            .chars()
+            .map(|c| match c {
+                'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '.' | '-' => c,
+                _ => '-',
+            })
+            .collect::<String>()
+    )
+}
+
+/// Creates a bridge network named `network_name` on the database docker host
+/// (where the "TFBNetwork" lives) and returns its id.
+pub fn create_isolated_network(
+    docker_config: &DockerConfig,
+    network_name: &str,
+) -> ToolsetResult<String> {
+    docker_config
+        .docker_api
+        .create_network(network_name, &docker_config.database_docker_host)
+}
+
+/// Removes the network given by `network_id` from the database docker host.
+pub fn remove_network(docker_config: &DockerConfig, network_id: &str) -> ToolsetResult<()> {
+    docker_config
+        .docker_api
+        .delete_network(network_id, &docker_config.database_docker_host)
+}
+
 /// Attaches the container given by `container_id` to the network given by
-/// `network_id` on the given `docker_host`.
+/// `network_id` on the given `docker_host`, unless it is in host
+/// `network_mode`; such a container already shares the host's network, and
+/// the daemon refuses to connect it again.
 pub fn connect_container_to_network(
     docker_config: &DockerConfig,
     docker_host: &str,
+    network_mode: &NetworkMode,
     network_id: &str,
     container_id: &str,
 ) -> ToolsetResult<()> {
-    match dockurl::network::connect_container_to_network(
-        container_id,
-        network_id,
-        vec![],
-        docker_host,
-        docker_config.use_unix_socket,
-        Simple::new(),
-    ) {
-        Ok(()) => Ok(()),
-        Err(error) => Err(DockerError(error)),
+    if let NetworkMode::Host = network_mode {
+        return Ok(());
+    }
+
+    docker_config
+        .docker_api
+        .connect_container_to_network(container_id, network_id, docker_host)
+}
+
+/// Applies the given `shaping` via `tc`/`netem` to the network of the
+/// container given by `container_id` on `docker_host`, using a privileged
+/// helper container which shares its network namespace.
+///
+/// Note: this shapes *all* egress from the container; for the application
+/// server that includes its traffic to the database.
+pub fn shape_container_network(
+    docker_config: &DockerConfig,
+    docker_host: &str,
+    container_id: &str,
+    shaping: &NetworkShaping,
+) -> ToolsetResult<()> {
+    pull_image(docker_config, docker_host, NETEM_IMAGE)?;
+
+    let mut options = Options::new();
+    options.image(NETEM_IMAGE);
+    options.tty(true);
+    options.cmds(shaping.to_tc_command().as_slice());
+
+    let mut host_config = HostConfig::new();
+    host_config.container_network_mode(container_id);
+    host_config.privileged(true);
+    options.host_config(host_config);
+
+    let docker_api = &docker_config.docker_api;
+    let helper_id = docker_api.create_container(options, docker_host)?;
+    docker_api.start_container(&helper_id, docker_host)?;
+    docker_api.wait_for_container_to_exit(&helper_id, docker_host)?;
+    let inspection = docker_api.inspect_container(&helper_id, docker_host)?;
+    docker_api.delete_container(&helper_id, docker_host)?;
+
+    if inspection.exit_code != 0 {
+        return Err(NetworkShapingError(format!(
+            "`{}` exited with code {}",
+            shaping.to_tc_command().join(" "),
+            inspection.exit_code
+        )));
+    }
+
+    Ok(())
+}
+
+//
+// TESTS
+//
+
+#[cfg(test)]
+mod tests {
+    use crate::docker::mock::MockDockerApi;
+    use crate::docker::network::get_tfb_network_id;
+
+    #[test]
+    fn it_can_create_the_tfb_network_when_missing() {
+        let docker_api = MockDockerApi::default();
+        docker_api.remove_network("TFBNetwork");
+
+        assert_eq!(
+            get_tfb_network_id(&docker_api, "tfb-database:2375").unwrap(),
+            "TFBNetwork-id"
+        );
+        assert_eq!(
+            get_tfb_network_id(&docker_api, "tfb-database:2375").unwrap(),
+            "TFBNetwork-id"
+        );
+        assert_eq!(
+            docker_api.get_operations(),
+            vec![
+                "inspect network TFBNetwork on tfb-database:2375",
+                "create network TFBNetwork on tfb-database:2375",
+                "inspect network TFBNetwork on tfb-database:2375",
+            ]
+        );
     }
 }
@@ -0,0 +1,224 @@
+//! Checks the network between the hosts of a multi-machine setup before a
+//! run; a misconfigured link (e.g. negotiated at 1Gbps instead of 10Gbps)
+//! would otherwise silently skew every result.
+
+use crate::docker::docker_config::DockerConfig;
+use crate::docker::image::pull_image;
+use crate::docker::listener::build_container::BuildContainer;
+use crate::docker::listener::iperf::Iperf;
+use crate::docker::listener::simple::Simple;
+use crate::error::ToolsetResult;
+use crate::io::Logger;
+use crate::results::NetworkCheck;
+use dockurl::container::create::host_config::HostConfig;
+use dockurl::container::create::options::Options;
+use dockurl::container::{delete_container, get_container_logs, wait_for_container_to_exit};
+use dockurl::network::NetworkMode;
+use std::thread;
+use std::time::Duration;
+
+const IPERF_IMAGE: &str = "networkstatic/iperf3";
+/// Seconds for which each bandwidth measurement is run.
+const IPERF_DURATION: &str = "5";
+/// Measured bandwidth below this fraction of the expected value warns.
+const BANDWIDTH_TOLERANCE: f32 = 0.8;
+/// Measured latency above this multiple of the expected value warns.
+const LATENCY_TOLERANCE: f32 = 1.5;
+
+/// Measures the latency and bandwidth from the client to the server host and
+/// from the server to the database host, warning when either is unreachable
+/// or deviates from the expected values given in `docker_config`.
+pub fn check_network(
+    docker_config: &DockerConfig,
+    logger: &Logger,
+) -> ToolsetResult<Vec<NetworkCheck>> {
+    logger.log("Checking the network between hosts; this may take some time.")?;
+    let pairs = [
+        (
+            "client",
+            &docker_config.client_docker_host,
+            "server",
+            &docker_config.server_docker_host,
+            docker_config.server_host,
+        ),
+        (
+            "server",
+            &docker_config.server_docker_host,
+            "database",
+            &docker_config.database_docker_host,
+            docker_config.database_host,
+        ),
+    ];
+    let mut checks = Vec::new();
+    for (from, from_docker_host, to, to_docker_host, to_host) in pairs.iter() {
+        // Containers on the same daemon share its host; there is no network
+        // between them to measure.
+        if docker_config.is_same_daemon(from_docker_host, to_docker_host) {
+            logger.log(format!(
+                "Skipping the network check from {} to {}; they share a Docker daemon",
+                from, to
+            ))?;
+            continue;
+        }
+        pull_image(docker_config, from_docker_host, IPERF_IMAGE)?;
+        pull_image(docker_config, to_docker_host, IPERF_IMAGE)?;
+        let check = measure(
+            docker_config,
+            (from, from_docker_host),
+            (to, to_docker_host, to_host),
+        )?;
+        report(docker_config, &check, logger)?;
+        checks.push(check);
+    }
+
+    Ok(checks)
+}
+
+//
+// PRIVATES
+//
+
+/// Runs a one-off `iperf3` server on the `to` Docker host and an `iperf3`
+/// client against it from the `from` Docker host.
+fn measure(
+    docker_config: &DockerConfig,
+    from: (&str, &str),
+    to: (&str, &str, &str),
+) -> ToolsetResult<NetworkCheck> {
+    let server_id = create_iperf_container(docker_config, to.1, &["-s", "-1"])?;
+    dockurl::container::start_container(
+        &server_id,
+        to.1,
+        docker_config.use_unix_socket,
+        Simple::new(),
+    )?;
+    // Give the server a moment to start listening.
+    thread::sleep(Duration::from_secs(1));
+
+    let client_id = create_iperf_container(
+        docker_config,
+        from.1,
+        &["-c", to.2, "--json", "-t", IPERF_DURATION],
+    )?;
+    dockurl::container::start_container(
+        &client_id,
+        from.1,
+        docker_config.use_unix_socket,
+        Simple::new(),
+    )?;
+    wait_for_container_to_exit(
+        &client_id,
+        from.1,
+        docker_config.use_unix_socket,
+        Simple::new(),
+    )?;
+    let iperf = get_container_logs(
+        &client_id,
+        from.1,
+        docker_config.use_unix_socket,
+        Iperf::new(),
+    )?;
+
+    for (container_id, docker_host) in &[(&client_id, from.1), (&server_id, to.1)] {
+        delete_container(
+            container_id,
+            docker_host,
+            docker_config.use_unix_socket,
+            Simple::new(),
+            true,
+            true,
+            false,
+        )?;
+    }
+
+    let report = iperf.parse_report();
+    let reachable = match &report {
+        Some(report) => report.error.is_none(),
+        None => false,
+    };
+
+    Ok(NetworkCheck {
+        from: from.0.to_string(),
+        to: to.0.to_string(),
+        reachable,
+        latency_ms: report.as_ref().and_then(|report| report.get_latency_ms()),
+        bandwidth_gbps: report
+            .as_ref()
+            .and_then(|report| report.get_bandwidth_gbps()),
+    })
+}
+
+/// Logs the given `check`, warning when it deviates from what is expected.
+fn report(
+    docker_config: &DockerConfig,
+    check: &NetworkCheck,
+    logger: &Logger,
+) -> ToolsetResult<()> {
+    if !check.reachable {
+        return logger.error(format!(
+            "The {} host could not reach the {} host",
+            check.from, check.to
+        ));
+    }
+    logger.log(format!(
+        "{} -> {}: latency {}, bandwidth {}",
+        check.from,
+        check.to,
+        check
+            .latency_ms
+            .map_or("unknown".to_string(), |latency| format!("{:.3}ms", latency)),
+        check
+            .bandwidth_gbps
+            .map_or("unknown".to_string(), |bandwidth| format!(
+                "{:.2}Gbps",
+                bandwidth
+            )),
+    ))?;
+    if let (Some(expected), Some(latency)) = (docker_config.expected_latency, check.latency_ms) {
+        if latency > expected * LATENCY_TOLERANCE {
+            logger.error(format!(
+                "Latency from the {} host to the {} host ({:.3}ms) is well above the expected {}ms",
+                check.from, check.to, latency, expected
+            ))?;
+        }
+    }
+    if let (Some(expected), Some(bandwidth)) =
+        (docker_config.expected_bandwidth, check.bandwidth_gbps)
+    {
+        if bandwidth < expected * BANDWIDTH_TOLERANCE {
+            logger.error(format!(
+                "Bandwidth from the {} host to the {} host ({:.2}Gbps) is well below the expected {}Gbps",
+                check.from, check.to, bandwidth, expected
+            ))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Creates an `iperf3` container on the host network of `docker_host`.
+fn create_iperf_container(
+    docker_config: &DockerConfig,
+    docker_host: &str,
+    command: &[&str],
+) -> ToolsetResult<String> {
+    let mut options = Options::new();
+    options.image(IPERF_IMAGE);
+    // Logs are only retrieved raw (without stream headers) from TTYs.
+    options.tty(true);
+    let command: Vec<String> = command.iter().map(|arg| arg.to_string()).collect();
+    options.cmds(command.as_slice());
+
+    let mut host_config = HostConfig::new();
+    host_config.network_mode(NetworkMode::Host);
+    options.host_config(host_config);
+
+    let container_id = dockurl::container::create_container(
+        options,
+        docker_config.use_unix_socket,
+        docker_host,
+        BuildContainer::new(),
+    )?;
+
+    Ok(container_id)
+}
@@ -0,0 +1,105 @@
+//! Requests the application server once from the client's network before a
+//! test type's primer. Were the server unreachable, wrk would report zero
+//! requests, which parses "successfully"; instead, the test fails early with
+//! the reason (e.g. an unresolvable host or a refused connection).
+
+use crate::docker::container::{create_preflight_container, remove_exited_container};
+use crate::docker::docker_config::DockerConfig;
+use crate::docker::listener::output::Output;
+use crate::error::ToolsetError::ServerUnreachableError;
+use crate::error::ToolsetResult;
+use crate::io::Logger;
+
+/// Requests the URL targeted by the given benchmark `command`, if any, from
+/// the client. Fails if it is unreachable; any other failure of the request
+/// (e.g. `curl` missing from the image) is only logged, as the benchmark may
+/// yet succeed.
+pub fn check_server_is_reachable(
+    docker_config: &DockerConfig,
+    command: &[String],
+    logger: &Logger,
+) -> ToolsetResult<()> {
+    let url = match command
+        .iter()
+        .find(|arg| arg.starts_with("http://") || arg.starts_with("https://"))
+    {
+        Some(url) => url.replace("tfb-server", docker_config.get_client_server_host()),
+        None => return Ok(()),
+    };
+    let docker_api = &docker_config.docker_api;
+    let client_docker_host = &docker_config.client_docker_host;
+    let container_id = create_preflight_container(docker_config, &url)?;
+    docker_api.start_container(&container_id, client_docker_host)?;
+    docker_api.wait_for_container_to_exit(&container_id, client_docker_host)?;
+    let exit_code = docker_api
+        .inspect_container(&container_id, client_docker_host)?
+        .exit_code;
+    let mut output = Output::default();
+    docker_api.get_container_logs(&container_id, client_docker_host, &mut output)?;
+    remove_exited_container(docker_config, client_docker_host, &container_id);
+
+    if exit_code == 0 {
+        return Ok(());
+    }
+    match get_unreachable_reason(exit_code) {
+        Some(reason) => Err(ServerUnreachableError(
+            url,
+            format!("{} ({})", reason, output.get_output()),
+        )),
+        None => logger.log(format!(
+            "Preflight request to {} exited with code {}; continuing: {}",
+            url,
+            exit_code,
+            output.get_output()
+        )),
+    }
+}
+
+//
+// PRIVATES
+//
+
+/// Gets why the server was unreachable, given the `curl` `exit_code`, if it
+/// was.
+fn get_unreachable_reason(exit_code: i32) -> Option<&'static str> {
+    match exit_code {
+        6 => Some("could not resolve the host"),
+        7 => Some("the connection was refused"),
+        28 => Some("the request timed out"),
+        35 | 51 | 58 | 60 => Some("the TLS handshake failed"),
+        52 => Some("the server closed the connection without replying"),
+        55 | 56 => Some("the connection was reset"),
+        _ => None,
+    }
+}
+
+//
+// TESTS
+//
+
+#[cfg(test)]
+mod tests {
+    use crate::docker::mock::mock_docker_config;
+    use crate::docker::preflight::{check_server_is_reachable, get_unreachable_reason};
+    use crate::io::Logger;
+
+    #[test]
+    fn it_can_request_the_benchmarked_url() {
+        let (_, docker_api, docker_config) = mock_docker_config(&[]);
+        let command: Vec<String> = ["wrk", "-d", "5", "http://tfb-server:8080/json"]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+
+        check_server_is_reachable(&docker_config, &command, &Logger::default()).unwrap();
+
+        let operations = docker_api.get_operations();
+        assert!(operations.contains(&"start container mock-1 on localhost:2375".to_string()));
+        assert!(operations.contains(&"inspect container mock-1 on localhost:2375".to_string()));
+        assert_eq!(
+            get_unreachable_reason(6),
+            Some("could not resolve the host")
+        );
+        assert_eq!(get_unreachable_reason(127), None);
+    }
+}
@@ -0,0 +1,113 @@
+//! Profiles the application server with `perf` from a privileged sidecar
+//! which shares its PID namespace, producing the raw samples and a flamegraph
+//! to help framework authors diagnose hotspots.
+
+use crate::docker::docker_config::{DockerConfig, DOCKER_SOCKET};
+use crate::docker::image::pull_image;
+use crate::docker::listener::archive::Archive;
+use crate::docker::listener::build_container::BuildContainer;
+use crate::docker::listener::simple::Simple;
+use crate::error::ToolsetResult;
+use curl::easy::Easy2;
+use dockurl::container::create::host_config::HostConfig;
+use dockurl::container::create::options::Options;
+use dockurl::container::{delete_container, wait_for_container_to_exit};
+use std::path::Path;
+
+/// The image containing `perf` and the FlameGraph scripts.
+const PERF_IMAGE: &str = "techempower/tfb.perf";
+
+pub struct Profiler {
+    container_id: String,
+}
+impl Profiler {
+    /// Starts recording every process (other than the sidecar's own) in the
+    /// application container given by `container_id` for `duration` seconds.
+    pub fn start(
+        docker_config: &DockerConfig,
+        container_id: &str,
+        duration: u32,
+    ) -> ToolsetResult<Self> {
+        let docker_host = &docker_config.server_docker_host;
+        pull_image(docker_config, docker_host, PERF_IMAGE)?;
+
+        let mut options = Options::new();
+        options.image(PERF_IMAGE);
+        options.tty(true);
+        let script = format!(
+            "mkdir -p /profile && \
+             perf record -F 99 -g -o /profile/perf.data -p \"$(pgrep -d, -v -f 'perf record')\" -- sleep {} && \
+             perf script -i /profile/perf.data > /profile/perf.script && \
+             stackcollapse-perf.pl /profile/perf.script | flamegraph.pl > /profile/flamegraph.svg",
+            duration
+        );
+        let command = vec!["sh".to_string(), "-c".to_string(), script];
+        options.cmds(command.as_slice());
+
+        let mut host_config = HostConfig::new();
+        host_config.container_pid_mode(container_id);
+        host_config.privileged(true);
+        options.host_config(host_config);
+
+        let profiler_id = dockurl::container::create_container(
+            options,
+            docker_config.use_unix_socket,
+            docker_host,
+            BuildContainer::new(),
+        )?;
+        dockurl::container::start_container(
+            &profiler_id,
+            docker_host,
+            docker_config.use_unix_socket,
+            Simple::new(),
+        )?;
+
+        Ok(Self {
+            container_id: profiler_id,
+        })
+    }
+
+    /// Waits for the recording to finish and unpacks the resulting `profile`
+    /// directory (`perf.data`, `perf.script`, and `flamegraph.svg`) into
+    /// `destination`.
+    pub fn finish(self, docker_config: &DockerConfig, destination: &Path) -> ToolsetResult<()> {
+        let docker_host = &docker_config.server_docker_host;
+        wait_for_container_to_exit(
+            &self.container_id,
+            docker_host,
+            docker_config.use_unix_socket,
+            Simple::new(),
+        )?;
+
+        let mut easy = Easy2::new(Archive::new());
+        if docker_config.use_unix_socket {
+            easy.unix_socket(DOCKER_SOCKET)?;
+            easy.url(&format!(
+                "http://localhost/containers/{}/archive?path=/profile",
+                self.container_id
+            ))?;
+        } else {
+            easy.url(&format!(
+                "http://{}/containers/{}/archive?path=/profile",
+                docker_host, self.container_id
+            ))?;
+        }
+        let downloaded = easy.perform();
+
+        delete_container(
+            &self.container_id,
+            docker_host,
+            docker_config.use_unix_socket,
+            Simple::new(),
+            true,
+            true,
+            false,
+        )?;
+
+        downloaded?;
+        std::fs::create_dir_all(destination)?;
+        tar::Archive::new(easy.get_ref().data.as_slice()).unpack(destination)?;
+
+        Ok(())
+    }
+}
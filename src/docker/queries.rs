@@ -0,0 +1,159 @@
+//! Counts the statements the database executes over a benchmark command and
+//! compares them to the number each request of the test type must make; an
+//! implementation making fewer (e.g. a `db` test answering from memory)
+//! appears to cache where the test type forbids it.
+
+use crate::docker::container::create_database_sidecar_container;
+use crate::docker::docker_config::DockerConfig;
+use crate::docker::listener::counts::Counts;
+use crate::docker::listener::simple::Simple;
+use crate::error::ToolsetResult;
+use dockurl::container::{delete_container, get_container_logs, wait_for_container_to_exit};
+
+/// Queries per request below this fraction of the expected number are
+/// flagged.
+const QUERY_TOLERANCE: f32 = 0.9;
+
+/// Gets the number of statements the `database` in the container given by
+/// `container_id` has executed so far, by way of its own statement counters.
+/// Returns `None` for databases which cannot be counted.
+pub fn count_statements(
+    docker_config: &DockerConfig,
+    database: &str,
+    container_id: &str,
+) -> ToolsetResult<Option<u64>> {
+    let query = match get_statement_count_command(database) {
+        Some(query) => query,
+        None => return Ok(None),
+    };
+    let docker_host = &docker_config.database_docker_host;
+    let counter_id =
+        create_database_sidecar_container(docker_config, database, container_id, query)?;
+    dockurl::container::start_container(
+        &counter_id,
+        docker_host,
+        docker_config.use_unix_socket,
+        Simple::new(),
+    )?;
+    wait_for_container_to_exit(
+        &counter_id,
+        docker_host,
+        docker_config.use_unix_socket,
+        Simple::new(),
+    )?;
+    let counts = get_container_logs(
+        &counter_id,
+        docker_host,
+        docker_config.use_unix_socket,
+        Counts::new(),
+    );
+    delete_container(
+        &counter_id,
+        docker_host,
+        docker_config.use_unix_socket,
+        Simple::new(),
+        true,
+        true,
+        false,
+    )?;
+
+    Ok(counts?.parse_counts().last().copied())
+}
+
+/// Gets the number of queries each request of `test_type`, benchmarked by
+/// `command`, must make at least; `None` for test types which may cache
+/// (`cached_query`) or make no queries at all.
+///
+/// Updates may be batched into a single statement, so `update` requests are
+/// only expected to make a query for each row read.
+pub fn get_expected_queries_per_request(
+    test_type: &str,
+    command: &[String],
+    max_queries: u32,
+) -> Option<u32> {
+    match test_type {
+        "db" | "fortune" => Some(1),
+        "query" | "update" => {
+            // E.g. `http://tfb-server:8080/queries?queries=20`; as with the
+            // test implementations, invalid counts are treated as `1`.
+            let queries = command
+                .last()
+                .and_then(|url| url.rsplit('=').next())
+                .and_then(|queries| str::parse::<u32>(queries).ok())
+                .unwrap_or(1);
+            Some(queries.max(1).min(max_queries.max(1)))
+        }
+        _ => None,
+    }
+}
+
+/// Returns whether `queries_per_request` falls short of `expected` by more
+/// than the tolerance, suggesting results are cached.
+pub fn appears_cached(queries_per_request: f32, expected: u32) -> bool {
+    queries_per_request < expected as f32 * QUERY_TOLERANCE
+}
+
+//
+// PRIVATES
+//
+
+/// Gets the shell command which prints the number of statements executed by
+/// the benchmark database; on Postgres, whose statistics do not count
+/// statements without `pg_stat_statements`, the number of transactions.
+fn get_statement_count_command(database: &str) -> Option<&'static str> {
+    match database {
+        "postgres" => Some(
+            "PGPASSWORD=benchmarkdbpass psql -h 127.0.0.1 -U benchmarkdbuser -d hello_world -tAc \
+             \"SELECT xact_commit + xact_rollback FROM pg_stat_database WHERE datname = 'hello_world'\"",
+        ),
+        "mysql" => Some(
+            "mysql -h 127.0.0.1 -u benchmarkdbuser -pbenchmarkdbpass -NBe \
+             \"SELECT SUM(VARIABLE_VALUE) FROM performance_schema.global_status WHERE VARIABLE_NAME IN ('Com_select', 'Com_update')\"",
+        ),
+        "mongodb" => Some(
+            "mongo --quiet --eval 'var ops = db.serverStatus().opcounters; print(ops.query + ops.update)'",
+        ),
+        _ => None,
+    }
+}
+
+//
+// TESTS
+//
+
+#[cfg(test)]
+mod tests {
+    use crate::docker::queries::{appears_cached, get_expected_queries_per_request};
+
+    fn wrk(url: &str) -> Vec<String> {
+        vec!["wrk".to_string(), "--latency".to_string(), url.to_string()]
+    }
+
+    #[test]
+    fn it_can_get_expected_queries_per_request() {
+        let db = wrk("http://tfb-server:8080/db");
+        assert_eq!(get_expected_queries_per_request("db", &db, 500), Some(1));
+        assert_eq!(get_expected_queries_per_request("json", &db, 500), None);
+        let queries = wrk("http://tfb-server:8080/queries?queries=20");
+        assert_eq!(
+            get_expected_queries_per_request("query", &queries, 500),
+            Some(20)
+        );
+        assert_eq!(
+            get_expected_queries_per_request("update", &queries, 10),
+            Some(10)
+        );
+        assert_eq!(
+            get_expected_queries_per_request("cached_query", &queries, 500),
+            None
+        );
+        let invalid = wrk("http://tfb-server:8080/queries?queries=foo");
+        assert_eq!(
+            get_expected_queries_per_request("query", &invalid, 500),
+            Some(1)
+        );
+
+        assert!(!appears_cached(19.5, 20));
+        assert!(appears_cached(0.1, 1));
+    }
+}
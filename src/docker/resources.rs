@@ -0,0 +1,186 @@
+//! Resource profiles (see `--resource-profile`) constrain the CPU and memory
+//! of the containers of each role, and shape the network of the application
+//! server, to emulate one of the official hardware classes on an arbitrary
+//! machine.
+//!
+//! A profile is either one of the `BUILT_IN_PROFILES` or a TOML file of the
+//! same layout, e.g.:
+//!
+//! ```toml
+//! [server]
+//! cpus = 4.0
+//! memory_mb = 14336
+//!
+//! [database]
+//! cpuset = "4-7"
+//!
+//! [network]
+//! delay_ms = 1
+//! jitter_ms = 0
+//! loss_percent = 0.0
+//! ```
+
+use crate::error::ToolsetResult;
+use crate::results::NetworkShaping;
+use dockurl::container::create::host_config::HostConfig;
+use serde::{Deserialize, Serialize};
+
+/// Each of the official hardware classes: Citrine, three servers of 28
+/// threads (14 cores) and 32 GB on 10 GbE, and the cloud, virtual machines of
+/// 4 vCPUs and 14 GB within one region.
+pub const BUILT_IN_PROFILES: [(&str, &str); 2] = [
+    (
+        "citrine",
+        "[server]
+cpus = 28.0
+memory_mb = 32768
+
+[database]
+cpus = 28.0
+memory_mb = 32768
+
+[client]
+cpus = 28.0
+memory_mb = 32768
+",
+    ),
+    (
+        "cloud",
+        "[server]
+cpus = 4.0
+memory_mb = 14336
+
+[database]
+cpus = 4.0
+memory_mb = 14336
+
+[client]
+cpus = 4.0
+memory_mb = 14336
+
+[network]
+delay_ms = 1
+jitter_ms = 0
+loss_percent = 0.0
+",
+    ),
+];
+
+/// The roles whose containers a `ResourceProfile` constrains.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Role {
+    Server,
+    Database,
+    Client,
+}
+
+/// The constraints of the containers of each role, and the shaping of the
+/// application server's network, if any; recorded in the results.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceProfile {
+    /// The built-in profile or the file the profile was loaded from.
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub server: ResourceLimits,
+    #[serde(default)]
+    pub database: ResourceLimits,
+    #[serde(default)]
+    pub client: ResourceLimits,
+    /// Used unless shaping is given via `--netem-delay` or `--netem-loss`.
+    pub network: Option<NetworkShaping>,
+}
+impl ResourceProfile {
+    /// Loads the built-in profile of the given `name`, or else the profile in
+    /// the TOML file of that name.
+    pub fn load(name: &str) -> ToolsetResult<Self> {
+        let profile = match BUILT_IN_PROFILES
+            .iter()
+            .find(|(built_in, _)| *built_in == name)
+        {
+            Some((_, profile)) => profile.to_string(),
+            None => std::fs::read_to_string(name)?,
+        };
+        let mut profile: ResourceProfile = toml::from_str(&profile)?;
+        profile.name = name.to_string();
+
+        Ok(profile)
+    }
+
+    /// Gets the constraints of the containers of the given `role`.
+    pub fn get_limits(&self, role: Role) -> &ResourceLimits {
+        match role {
+            Role::Server => &self.server,
+            Role::Database => &self.database,
+            Role::Client => &self.client,
+        }
+    }
+}
+
+/// The CPU and memory constraints of a container; each is unconstrained
+/// unless given.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceLimits {
+    /// How many CPUs' worth of time the container may use, e.g. `1.5`.
+    pub cpus: Option<f64>,
+    /// The CPUs the container may run on, e.g. `0-3` or `0,2`.
+    pub cpuset: Option<String>,
+    #[serde(alias = "memory_mb")]
+    pub memory_mb: Option<u64>,
+}
+impl ResourceLimits {
+    /// Applies these constraints to the given `host_config`.
+    pub fn apply(&self, host_config: &mut HostConfig) {
+        if let Some(cpus) = self.cpus {
+            host_config.nano_cpus((cpus * 1_000_000_000f64) as i64);
+        }
+        if let Some(cpuset) = &self.cpuset {
+            host_config.cpuset_cpus(cpuset);
+        }
+        if let Some(memory_mb) = self.memory_mb {
+            host_config.memory(memory_mb * 1024 * 1024);
+        }
+    }
+}
+
+//
+// TESTS
+//
+
+#[cfg(test)]
+mod tests {
+    use crate::docker::resources::{ResourceLimits, ResourceProfile, Role};
+    use crate::io::TempDir;
+
+    #[test]
+    fn it_can_load_built_in_resource_profiles() {
+        let cloud = ResourceProfile::load("cloud").unwrap();
+
+        assert_eq!(cloud.name, "cloud");
+        assert_eq!(
+            cloud.get_limits(Role::Database),
+            &ResourceLimits {
+                cpus: Some(4f64),
+                cpuset: None,
+                memory_mb: Some(14336),
+            }
+        );
+        assert_eq!(cloud.network.unwrap().delay_ms, 1);
+        assert!(ResourceProfile::load("citrine").unwrap().network.is_none());
+    }
+
+    #[test]
+    fn it_can_load_a_resource_profile_from_a_file() {
+        let profile_dir = TempDir::new("resources");
+        let profile_file = profile_dir.join("profile.toml");
+        std::fs::write(&profile_file, "[server]\ncpuset = \"0-3\"\n").unwrap();
+
+        let profile = ResourceProfile::load(profile_file.to_str().unwrap()).unwrap();
+        assert_eq!(profile.server.cpuset.as_deref(), Some("0-3"));
+        assert_eq!(profile.server.cpus, None);
+        assert_eq!(profile.client, ResourceLimits::default());
+        assert!(profile.network.is_none());
+    }
+}
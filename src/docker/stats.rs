@@ -0,0 +1,116 @@
+//! Collects the CPU, memory, and IO usage of a container while a benchmark
+//! command runs against it.
+
+use crate::docker::docker_config::DOCKER_SOCKET;
+use crate::docker::listener::stats::{Stats, StatsSample};
+use crate::error::ToolsetResult;
+use crate::results::ContainerStats;
+use curl::easy::Easy2;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::thread::JoinHandle;
+
+pub struct StatsCollector {
+    samples: Arc<Mutex<Vec<StatsSample>>>,
+    stop: Arc<AtomicBool>,
+    streaming: JoinHandle<()>,
+}
+impl StatsCollector {
+    /// Starts streaming the stats of the container given by `container_id` on
+    /// `docker_host` on a background thread.
+    pub fn start(docker_host: &str, use_unix_socket: bool, container_id: &str) -> Self {
+        let samples = Arc::new(Mutex::new(Vec::new()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let listener = Stats::new(&samples, &stop);
+        let docker_host = docker_host.to_string();
+        let container_id = container_id.to_string();
+        let streaming = thread::spawn(move || {
+            let _ = stream_stats(&docker_host, use_unix_socket, &container_id, listener);
+        });
+
+        Self {
+            samples,
+            stop,
+            streaming,
+        }
+    }
+
+    /// Stops streaming and summarizes the samples collected, if any.
+    pub fn stop(self) -> Option<ContainerStats> {
+        self.stop.store(true, Ordering::Release);
+        // The stream is aborted upon the next sample (about once per second).
+        self.streaming.join().unwrap_or(());
+
+        let samples = match self.samples.lock() {
+            Ok(samples) => samples.clone(),
+            Err(_) => return None,
+        };
+        // The IO counters are cumulative, so the first sample is their start.
+        let (io_read_bytes, io_write_bytes) = match (samples.first(), samples.last()) {
+            (Some(first), Some(last)) => (
+                last.io_read_bytes.saturating_sub(first.io_read_bytes),
+                last.io_write_bytes.saturating_sub(first.io_write_bytes),
+            ),
+            _ => (0, 0),
+        };
+        // Docker's first sample has no previous CPU usage to compare against.
+        let samples = if samples.len() > 1 {
+            &samples[1..]
+        } else {
+            &samples[..]
+        };
+        if samples.is_empty() {
+            return None;
+        }
+        let count = samples.len() as f64;
+
+        Some(ContainerStats {
+            cpu_percent_avg: samples.iter().map(|s| s.cpu_percent).sum::<f64>() / count,
+            cpu_percent_max: samples.iter().map(|s| s.cpu_percent).fold(0f64, f64::max),
+            memory_bytes_avg: (samples.iter().map(|s| s.memory_bytes as f64).sum::<f64>() / count)
+                as u64,
+            memory_bytes_max: samples
+                .iter()
+                .map(|s| s.max_memory_bytes.unwrap_or(s.memory_bytes))
+                .max()
+                .unwrap_or_default(),
+            io_read_bytes,
+            io_write_bytes,
+            cgroup_version: if samples.iter().any(|s| s.cgroup_v2) {
+                2
+            } else {
+                1
+            },
+        })
+    }
+}
+
+//
+// PRIVATES
+//
+
+/// Blocks for as long as the daemon streams stats or until `listener` aborts.
+fn stream_stats(
+    docker_host: &str,
+    use_unix_socket: bool,
+    container_id: &str,
+    listener: Stats,
+) -> ToolsetResult<()> {
+    let mut easy = Easy2::new(listener);
+    if use_unix_socket {
+        easy.unix_socket(DOCKER_SOCKET)?;
+        easy.url(&format!(
+            "http://localhost/containers/{}/stats",
+            container_id
+        ))?;
+    } else {
+        easy.url(&format!(
+            "http://{}/containers/{}/stats",
+            docker_host, container_id
+        ))?;
+    }
+    easy.perform()?;
+
+    Ok(())
+}
@@ -0,0 +1,21 @@
+-- Appended to any script requested for a wrk command so that, in addition to
+-- wrk's human-readable output, a single line of JSON summarizing the run is
+-- printed when it completes. Durations and latencies are in microseconds.
+done = function(summary, latency, requests)
+  io.write(string.format(
+    '{"wrk_summary":{"duration":%d,"requests":%d,"bytes":%d,' ..
+    '"errors":{"connect":%d,"read":%d,"write":%d,"status":%d,"timeout":%d},' ..
+    '"latency":{"mean":%f,"stdev":%f,"max":%d,"p50":%d,"p75":%d,"p90":%d,"p99":%d,' ..
+    '"p99_9":%d,"p99_99":%d,"p99_999":%d},' ..
+    '"requests_per_thread":{"mean":%f,"stdev":%f,"max":%d}}}\n',
+    summary.duration, summary.requests, summary.bytes,
+    summary.errors.connect, summary.errors.read, summary.errors.write,
+    summary.errors.status, summary.errors.timeout,
+    latency.mean, latency.stdev, latency.max,
+    latency:percentile(50), latency:percentile(75),
+    latency:percentile(90), latency:percentile(99),
+    latency:percentile(99.9), latency:percentile(99.99),
+    latency:percentile(99.999),
+    requests.mean, requests.stdev, requests.max
+  ))
+end
@@ -24,6 +24,9 @@ pub enum ToolsetError {
     #[error("Invalid config.toml: {0}, {1}")]
     InvalidConfigError(String, toml::de::Error),
 
+    #[error("Unknown test type in {0}: {1}; non-standard test types must be declared as custom")]
+    UnknownTestTypeError(String, String),
+
     #[error("Serde json error")]
     SerdeJsonError(#[from] serde_json::error::Error),
 
@@ -42,15 +45,72 @@ pub enum ToolsetError {
     #[error("Unknown benchmarker mode: {0}")]
     UnknownBenchmarkerModeError(String),
 
+    #[error("Durations must be given as SECONDS or TEST_TYPE=SECONDS, comma-separated: {0}")]
+    InvalidDurationError(String),
+
+    #[error("The server URL override must be given as [http://]HOST:PORT, as only plain HTTP is benchmarked: {0}")]
+    InvalidServerUrlError(String),
+
+    #[error("Invalid time of day (expected HH:MM): {0}")]
+    InvalidScheduleTimeError(String),
+
+    #[error("Schedule windows must be given as HH:MM-HH:MM: {0}")]
+    InvalidScheduleWindowError(String),
+
     #[error("Debug failed")]
     DebugFailedException,
 
     #[error("Verification failed")]
     VerificationFailedException,
 
+    #[error("Docker daemon on {1} failed to {0}: {2}")]
+    DockerDaemonError(String, String, String),
+
+    #[error("Host port conflict starting container: {0}")]
+    PortConflictError(String),
+
+    #[error("Building {0} timed out after {1}s")]
+    BuildTimeoutError(String, u32),
+
+    #[error("No run with a status.json found in {0}")]
+    NoRunStatusError(String),
+
     #[error("Application server container shut down after start")]
     AppServerContainerShutDownError,
 
+    #[error("{0} container died during the run: {1}")]
+    ContainerDiedError(String, String),
+
+    #[error("A bridged database cannot be reached from roles in host network mode")]
+    BridgedDatabaseFromHostNetworkError,
+
+    #[error("Database hosts can only be given in host network mode")]
+    DatabaseHostsInBridgeNetworkError,
+
+    #[error("Database hosts cannot be reached over the local unix socket; give the Docker hosts as tcp:// URLs")]
+    DatabaseHostsOverUnixSocketError,
+
+    #[error("The network of an application server in host network mode cannot be shaped")]
+    HostNetworkShapingError,
+
+    #[error("Failed to shape the network: {0}")]
+    NetworkShapingError(String),
+
+    #[error("Failed to get the Docker daemon info of {0}")]
+    DaemonInfoError(String),
+
+    #[error("Docker daemon configuration does not match {0}: {1}")]
+    DaemonProfileMismatchError(String, String),
+
+    #[error("Docker daemon clocks are skewed beyond {0}ms: {1}")]
+    ClockSkewError(i64, String),
+
+    #[error("Failed to export trace to {0}: HTTP {1}")]
+    TraceExportError(String, u32),
+
+    #[error("Failed to upload results to {0}: HTTP {1}")]
+    ResultsUploadError(String, u32),
+
     #[error("Failed to inspect container for port mappings")]
     ContainerPortMappingInspectionError,
 
@@ -62,4 +122,31 @@ pub enum ToolsetError {
 
     #[error("Failed to parse benchmark results")]
     BenchmarkDataParseError,
+
+    #[error("Benchmark command exited with code {0}: {1}")]
+    BenchmarkCommandFailedError(i32, String),
+
+    #[error("Benchmark command completed no requests; its output was:\n{0}")]
+    NoRequestsCompletedError(String),
+
+    #[error("The benchmark client could not reach {0}: {1}")]
+    ServerUnreachableError(String, String),
+
+    #[error("No tests were selected by the filters: {0}{}", get_did_you_mean(.1))]
+    NoProjectsSelectedError(String, Vec<String>),
+
+    #[error("{} invalid config.toml file(s):\n{}", .0.len(), .0.join("\n"))]
+    InvalidConfigsError(Vec<String>),
+}
+
+//
+// PRIVATES
+//
+
+fn get_did_you_mean(suggestions: &[String]) -> String {
+    if suggestions.is_empty() {
+        String::default()
+    } else {
+        format!("; did you mean {}?", suggestions.join(", "))
+    }
 }
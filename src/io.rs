@@ -1,16 +1,112 @@
-use crate::config::{Named, Test};
-use crate::docker::Verification;
-use crate::error::ToolsetError::InvalidFrameworkBenchmarksDirError;
+use crate::config::{Named, Project, Test};
+use crate::docker::listener::simple::Simple;
+use crate::docker::{Topology, Verification};
+use crate::error::ToolsetError::{InvalidFrameworkBenchmarksDirError, ResultsUploadError};
 use crate::error::{ToolsetError, ToolsetResult};
 use crate::metadata;
-use crate::results::Results;
+use crate::results::{Manifest, Results};
+use crate::status::Status;
 use chrono::Utc;
 use colored::Colorize;
-use std::collections::HashMap;
+use curl::easy::{Easy2, List};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
 use std::env;
 use std::fs::{File, OpenOptions};
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// How many times `upload_results` attempts an upload before giving up.
+const UPLOAD_ATTEMPTS: u32 = 4;
+/// How long `upload_results` waits before its first retry; doubled for each.
+const UPLOAD_RETRY_DELAY: Duration = Duration::from_secs(5);
+/// How long a single upload may take.
+const UPLOAD_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Whether every `Logger` prints JSON records rather than text; see
+/// `set_json_logs`.
+static JSON_LOGS: AtomicBool = AtomicBool::new(false);
+
+/// The files and directories into which a run is logged, beneath the results
+/// dir of the run (e.g. `results/20200619191252`); see `Logger::set_test`.
+///
+/// ```text
+/// benchmark.txt                          the verification report
+/// <test>/build/log.txt                   output of building the test's image
+/// <test>/server/log.txt                  output of the application server
+/// <test>/database/log.txt                output of the database
+/// <test>/verify/<type>.txt               output of verifying each test type
+/// <test>/benchmark/<type>/commands.txt   output of retrieving the commands
+/// <test>/benchmark/<type>/log.txt        output of the benchmark commands
+/// <test>/benchmark/<type>/profile/       profile of the application server
+/// <test>/cores/                          core dumps of the application server
+/// <test>/failure.txt                     why the test failed, if it did
+/// <test>/topology.json                   the test's containers and networks
+/// ```
+///
+/// This layout is also described by the `manifest.json` of each run.
+#[derive(Debug, Clone, Copy)]
+pub enum LogPath<'a> {
+    Report,
+    Build,
+    Server,
+    Database,
+    Verification(&'a str),
+    CommandRetrieval(&'a str),
+    Benchmark(&'a str),
+    Profile(&'a str),
+    CoreDumps,
+    Failure,
+    Topology,
+}
+impl<'a> LogPath<'a> {
+    /// Gets this path, relative to the results dir of a test (or, for
+    /// `Report`, of the run).
+    pub fn get_path(&self) -> PathBuf {
+        match self {
+            LogPath::Report => PathBuf::from("benchmark.txt"),
+            LogPath::Build => PathBuf::from("build/log.txt"),
+            LogPath::Server => PathBuf::from("server/log.txt"),
+            LogPath::Database => PathBuf::from("database/log.txt"),
+            LogPath::Verification(test_type) => PathBuf::from(format!("verify/{}.txt", test_type)),
+            LogPath::CommandRetrieval(test_type) => {
+                PathBuf::from(format!("benchmark/{}/commands.txt", test_type))
+            }
+            LogPath::Benchmark(test_type) => {
+                PathBuf::from(format!("benchmark/{}/log.txt", test_type))
+            }
+            LogPath::Profile(test_type) => {
+                PathBuf::from(format!("benchmark/{}/profile", test_type))
+            }
+            LogPath::CoreDumps => PathBuf::from("cores"),
+            LogPath::Failure => PathBuf::from("failure.txt"),
+            LogPath::Topology => PathBuf::from("topology.json"),
+        }
+    }
+
+    /// Gets what is logged to this path.
+    pub fn get_description(&self) -> &'static str {
+        match self {
+            LogPath::Report => "The verification report of the run",
+            LogPath::Build => "Output of building the test's image",
+            LogPath::Server => "Output of the application server",
+            LogPath::Database => "Output of the database",
+            LogPath::Verification(_) => "Output of verifying the test type",
+            LogPath::CommandRetrieval(_) => {
+                "Output of retrieving the test type's benchmark commands"
+            }
+            LogPath::Benchmark(_) => "Output of the test type's benchmark commands",
+            LogPath::Profile(_) => {
+                "Profile of the application server under the highest concurrency"
+            }
+            LogPath::CoreDumps => "Core dumps of the application server",
+            LogPath::Failure => "Why the test failed, if it did",
+            LogPath::Topology => "The containers, ports, networks, and Docker hosts of the test",
+        }
+    }
+}
 
 /// `Logger` is used for logging to stdout and optionally to a file.
 ///
@@ -81,7 +177,7 @@ impl Logger {
     pub fn set_test(&mut self, test: &Test) {
         if let Some(log_dir) = &self.log_dir {
             let mut log_dir = log_dir.clone();
-            log_dir.push(test.get_name());
+            log_dir.push(test.get_normalized_name());
 
             if !log_dir.exists() && std::fs::create_dir_all(&log_dir).is_err() {
                 return;
@@ -93,15 +189,13 @@ impl Logger {
         self.prefix = Some(test.get_name());
     }
 
-    /// Sets the path to the file to which `log` calls will write.
+    /// Sets the file to which `log` calls will write.
     ///
     /// Note: This function relies upon `log_dir` being set prior to the call.
     ///       If this `Logger` does not have a `log_dir` set prior, it will
     ///       result in a no-op.
-    pub fn set_log_file(&mut self, file_name: &str) {
-        if let Some(mut log_file) = self.log_dir.clone() {
-            log_file.push(file_name);
-
+    pub fn set_log_file(&mut self, log_path: LogPath) {
+        if let Some(log_file) = self.get_log_path(log_path) {
             if !log_file.exists() && File::create(&log_file).is_err() {
                 return;
             }
@@ -109,12 +203,205 @@ impl Logger {
         }
     }
 
+    /// Gets the given `log_path` beneath the directory to which this `Logger`
+    /// writes, if any, creating its parent directory if necessary.
+    pub fn get_log_path(&self, log_path: LogPath) -> Option<PathBuf> {
+        let mut path = self.log_dir.clone()?;
+        path.push(log_path.get_path());
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).ok()?;
+        }
+
+        Some(path)
+    }
+
+    /// Writes the given `manifest` to `manifest.json` in the root of the
+    /// current `results` directory, along with the layout of the files logged
+    /// for each test.
+    pub fn write_manifest(&self, manifest: &Manifest) -> ToolsetResult<()> {
+        if let Some(results_dir) = &self.results_dir {
+            let mut manifest_file = results_dir.clone();
+            manifest_file.push("manifest.json");
+
+            let mut layout = Map::new();
+            layout.insert(
+                "results.json".to_string(),
+                Value::from("The results of the run"),
+            );
+            layout.insert(
+                "verifications.json".to_string(),
+                Value::from("The outcome of verifying each test type"),
+            );
+            layout.insert(
+                "status.json".to_string(),
+                Value::from("The progress of the run, rewritten as it goes"),
+            );
+            layout.insert(
+                "events.ndjson".to_string(),
+                Value::from("Every significant action taken during the run"),
+            );
+            layout.insert(
+                LogPath::Report.get_path().to_string_lossy().to_string(),
+                Value::from(LogPath::Report.get_description()),
+            );
+            for log_path in &[
+                LogPath::Build,
+                LogPath::Server,
+                LogPath::Database,
+                LogPath::Verification("<type>"),
+                LogPath::CommandRetrieval("<type>"),
+                LogPath::Benchmark("<type>"),
+                LogPath::Profile("<type>"),
+                LogPath::CoreDumps,
+                LogPath::Failure,
+                LogPath::Topology,
+            ] {
+                let mut path = PathBuf::from("<test>");
+                path.push(log_path.get_path());
+                layout.insert(
+                    path.to_string_lossy().to_string(),
+                    Value::from(log_path.get_description()),
+                );
+            }
+            let mut manifest = match serde_json::to_value(manifest)? {
+                Value::Object(manifest) => manifest,
+                _ => Map::new(),
+            };
+            manifest.insert("layout".to_string(), Value::Object(layout));
+
+            std::fs::write(manifest_file, serde_json::to_vec_pretty(&manifest)?)?;
+        }
+
+        Ok(())
+    }
+
     /// Logs output to standard out and optionally to the given file in the
     /// configured `log_dir`.
     pub fn log<T>(&self, text: T) -> ToolsetResult<()>
     where
         T: std::fmt::Display,
     {
+        self.log_at("info", text)
+    }
+
+    /// Serializes and writes the given `results` to `results.json` in the root
+    /// of the current `results` directory; replaced whole, as the results of a
+    /// resumed run may be shorter than those they replace.
+    pub fn write_results(&self, results: &Results) -> ToolsetResult<()> {
+        if let Some(results_dir) = &self.results_dir {
+            let mut results_file = results_dir.clone();
+            results_file.push("results.json");
+            let mut staged_file = results_dir.clone();
+            staged_file.push(".results.json");
+
+            let mut json = serde_json::to_string(results)?;
+            json.push('\n');
+            std::fs::write(&staged_file, json)?;
+            std::fs::rename(staged_file, results_file)?;
+        }
+
+        Ok(())
+    }
+
+    /// Serializes and writes the given `status` to `status.json` in the root of
+    /// the current `results` directory; replaced whole, so that it can be read
+    /// at any time.
+    pub fn write_status(&self, status: &Status) -> ToolsetResult<()> {
+        if let Some(results_dir) = &self.results_dir {
+            let mut status_file = results_dir.clone();
+            status_file.push("status.json");
+            let mut staged_file = results_dir.clone();
+            staged_file.push(".status.json");
+
+            std::fs::write(&staged_file, serde_json::to_vec_pretty(status)?)?;
+            std::fs::rename(staged_file, status_file)?;
+        }
+
+        Ok(())
+    }
+
+    /// Serializes and writes the given `verifications` to `verifications.json`
+    /// in the root of the current `results` directory.
+    pub fn write_verifications(&self, verifications: &[Verification]) -> ToolsetResult<()> {
+        if let Some(results_dir) = &self.results_dir {
+            let mut verifications_file = results_dir.clone();
+            verifications_file.push("verifications.json");
+
+            std::fs::write(
+                verifications_file,
+                serde_json::to_vec_pretty(verifications)?,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Serializes and writes the given `topology` to the `topology.json` of
+    /// the current test, replacing any written before (e.g. prior to the
+    /// application server being restarted).
+    pub fn write_topology(&self, topology: &Topology) -> ToolsetResult<()> {
+        if let Some(topology_file) = self.get_log_path(LogPath::Topology) {
+            std::fs::write(topology_file, serde_json::to_vec_pretty(topology)?)?;
+        }
+
+        Ok(())
+    }
+
+    /// Appends the given `event` and its `details` as a line of JSON to
+    /// `events.ndjson` in the root of the current `results` directory, so that
+    /// what the toolset did during a run can be reconstructed afterwards.
+    ///
+    /// Example: `{"timestamp":1597946993187,"event":"container.killed",
+    ///          "test":"gemini","container_id":"4f2a..."}`
+    pub fn record_event(&self, event: &str, details: &[(&str, &str)]) -> ToolsetResult<()> {
+        if let Some(results_dir) = &self.results_dir {
+            let mut events_file = results_dir.clone();
+            events_file.push("events.ndjson");
+
+            let mut line = Map::new();
+            line.insert(
+                "timestamp".to_string(),
+                Value::from(Utc::now().timestamp_millis()),
+            );
+            line.insert("event".to_string(), Value::from(event));
+            if let Some(prefix) = &self.prefix {
+                line.insert("test".to_string(), Value::from(prefix.as_str()));
+            }
+            for (key, value) in details {
+                line.insert(key.to_string(), Value::from(*value));
+            }
+            let mut line = serde_json::to_vec(&line)?;
+            line.push(b'\n');
+
+            // Each event is a single append, so events recorded concurrently
+            // from clones of this `Logger` are not interleaved.
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(events_file)?;
+            file.write_all(&line)?;
+        }
+
+        Ok(())
+    }
+
+    /// Logs output to standard out and optionally to the given file in the
+    /// configured `log_dir`.
+    pub fn error<T>(&self, text: T) -> ToolsetResult<()>
+    where
+        T: std::fmt::Display,
+    {
+        self.log_at("error", text.to_string().red())
+    }
+
+    /// Logs output at the given `level` to standard out, as text or, if
+    /// `set_json_logs` was given `true`, as JSON records, and optionally to
+    /// the given file in the configured `log_dir`.
+    fn log_at<T>(&self, level: &str, text: T) -> ToolsetResult<()>
+    where
+        T: std::fmt::Display,
+    {
+        let json_logs = JSON_LOGS.load(Ordering::SeqCst);
         for line in text.to_string().lines() {
             if !line.trim().is_empty() {
                 let bytes_with_colors = line.as_bytes();
@@ -127,7 +414,12 @@ impl Logger {
                     file.write_all(strip_ansi_escapes::strip(&bytes_with_colors)?.as_slice())?;
                     file.write_all(&[b'\n'])?;
                 }
-                if !self.quiet {
+                if self.quiet {
+                    continue;
+                }
+                if json_logs {
+                    println!("{}", self.get_log_record(level, line)?);
+                } else {
                     if let Some(prefix) = &self.prefix {
                         print!("{}: ", prefix.white().bold());
                     }
@@ -138,37 +430,35 @@ impl Logger {
         Ok(())
     }
 
-    /// Serializes and writes the given `results` to `results.json` in the root
-    /// of the current `results` directory.
-    pub fn write_results(&self, results: &Results) -> ToolsetResult<()> {
-        if let Some(results_dir) = &self.results_dir {
-            let mut results_file = results_dir.clone();
-            results_file.push("results.json");
-
-            if !results_file.exists() {
-                File::create(&results_file)?;
-            }
-
-            let mut file = OpenOptions::new()
-                .write(true)
-                .append(false)
-                .open(results_file)
-                .unwrap();
-            file.write_all(serde_json::to_string(results).unwrap().as_bytes())?;
-            file.write_all(&[b'\n'])?;
+    /// Gets the JSON record of the given `line` logged at `level`, stripped
+    /// of its colors.
+    ///
+    /// Example: `{"timestamp":1597946993187,"level":"info","test":"gemini",
+    ///          "message":"Building image"}`
+    fn get_log_record(&self, level: &str, line: &str) -> ToolsetResult<String> {
+        let message = strip_ansi_escapes::strip(line.trim_end().as_bytes())?;
+        let mut record = Map::new();
+        record.insert(
+            "timestamp".to_string(),
+            Value::from(Utc::now().timestamp_millis()),
+        );
+        record.insert("level".to_string(), Value::from(level));
+        if let Some(prefix) = &self.prefix {
+            record.insert("test".to_string(), Value::from(prefix.as_str()));
         }
+        record.insert(
+            "message".to_string(),
+            Value::from(String::from_utf8_lossy(&message).as_ref()),
+        );
 
-        Ok(())
+        Ok(serde_json::to_string(&record)?)
     }
+}
 
-    /// Logs output to standard out and optionally to the given file in the
-    /// configured `log_dir`.
-    pub fn error<T>(&self, text: T) -> ToolsetResult<()>
-    where
-        T: std::fmt::Display,
-    {
-        self.log(text.to_string().red())
-    }
+/// Sets whether every `Logger` prints a JSON record per line (see
+/// `--log-format`) rather than text.
+pub fn set_json_logs(json_logs: bool) {
+    JSON_LOGS.store(json_logs, Ordering::SeqCst);
 }
 
 /// Walks the FrameworkBenchmarks directory (and subs) searching for test
@@ -235,12 +525,21 @@ pub fn create_results_dir() -> ToolsetResult<String> {
     Ok(result_dir)
 }
 
-/// Produces user-consumable output for the given verifications.
+/// Produces user-consumable output for the given verifications, along with
+/// the notes and known issues of their tests among `projects`, and writes
+/// them to `verifications.json` for consumption by CI and the website and,
+/// if given, to `junit_report` as JUnit XML.
 pub fn report_verifications(
     verifications: Vec<Verification>,
+    projects: &[Project],
+    junit_report: Option<&Path>,
     mut logger: Logger,
 ) -> ToolsetResult<()> {
-    logger.set_log_file("benchmark.txt");
+    logger.write_verifications(&verifications)?;
+    if let Some(junit_report) = junit_report {
+        std::fs::write(junit_report, get_junit_report(&verifications))?;
+    }
+    logger.set_log_file(LogPath::Report);
     let mut test_results = HashMap::new();
     for verification in &verifications {
         if !test_results.contains_key(&verification.test_name) {
@@ -264,6 +563,23 @@ pub fn report_verifications(
 
     for test_result in test_results {
         logger.log(format!("{} {}", "|".cyan(), test_result.0.cyan()))?;
+        let test = projects
+            .iter()
+            .flat_map(|project| &project.tests)
+            .find(|test| test.get_name() == test_result.0);
+        if let Some(test) = test {
+            if let Some(notes) = &test.notes {
+                logger.log(format!("{:8}{:13}: {}", "|".cyan(), "notes".cyan(), notes))?;
+            }
+            for known_issue in test.known_issues.iter().flatten() {
+                logger.log(format!(
+                    "{:8}{:13}: {}",
+                    "|".cyan(),
+                    "known issue".cyan(),
+                    known_issue.yellow()
+                ))?;
+            }
+        }
         for verification in test_result.1 {
             if !verification.errors.is_empty() {
                 logger.log(format!(
@@ -296,10 +612,169 @@ pub fn report_verifications(
     Ok(())
 }
 
+/// POSTs the given `results`, as they would be written to `results.json`, to
+/// `upload_uri`, retrying up to `UPLOAD_ATTEMPTS` times with a doubling delay
+/// should the upload fail.
+pub fn upload_results(results: &Results, upload_uri: &str, logger: &Logger) -> ToolsetResult<()> {
+    let body = serde_json::to_vec(results)?;
+    let mut delay = UPLOAD_RETRY_DELAY;
+    let mut attempt = 1;
+    loop {
+        match post_json(&body, upload_uri) {
+            Ok(()) => {
+                return logger.log(format!("Uploaded results to {}", upload_uri));
+            }
+            Err(e) if attempt < UPLOAD_ATTEMPTS => {
+                logger.error(format!(
+                    "Failed to upload results (attempt {} of {}): {}; retrying in {}s",
+                    attempt,
+                    UPLOAD_ATTEMPTS,
+                    e,
+                    delay.as_secs()
+                ))?;
+                std::thread::sleep(delay);
+                delay *= 2;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// A directory beneath the system's temporary directory, named for the test
+/// using it (`tfb-<name>-<pid>`), which is removed when dropped, even if the
+/// test fails.
+#[cfg(test)]
+pub struct TempDir(PathBuf);
+#[cfg(test)]
+impl TempDir {
+    pub fn new(name: &str) -> Self {
+        let mut path = env::temp_dir();
+        path.push(format!("tfb-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&path).unwrap();
+
+        Self(path)
+    }
+}
+#[cfg(test)]
+impl std::ops::Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+#[cfg(test)]
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
 //
 // PRIVATES
 //
 
+/// POSTs the JSON `body` to `uri`, failing unless it is answered with 2xx.
+fn post_json(body: &[u8], uri: &str) -> ToolsetResult<()> {
+    let mut headers = List::new();
+    headers.append("Content-Type: application/json")?;
+    let mut easy = Easy2::new(Simple::new());
+    easy.url(uri)?;
+    easy.http_headers(headers)?;
+    easy.post(true)?;
+    easy.post_fields_copy(body)?;
+    easy.timeout(UPLOAD_TIMEOUT)?;
+    easy.perform()?;
+
+    let code = easy.response_code()?;
+    if !(200..300).contains(&code) {
+        return Err(ResultsUploadError(uri.to_string(), code));
+    }
+
+    Ok(())
+}
+
+/// Gets the given `verifications` as a JUnit XML report, with a test suite
+/// per test and a test case per test type; errors are failures, and warnings
+/// are written to the output of their test case.
+fn get_junit_report(verifications: &[Verification]) -> String {
+    let mut suites: BTreeMap<&str, Vec<&Verification>> = BTreeMap::new();
+    for verification in verifications {
+        suites
+            .entry(&verification.test_name)
+            .or_default()
+            .push(verification);
+    }
+    let count_failures = |verifications: &[&Verification]| {
+        verifications
+            .iter()
+            .filter(|verification| !verification.errors.is_empty())
+            .count()
+    };
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<testsuites name=\"tfb\" tests=\"{}\" failures=\"{}\">\n",
+        verifications.len(),
+        count_failures(&verifications.iter().collect::<Vec<_>>())
+    ));
+    for (test_name, verifications) in suites {
+        xml.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\">\n",
+            escape_xml(test_name),
+            verifications.len(),
+            count_failures(&verifications)
+        ));
+        for verification in verifications {
+            xml.push_str(&format!(
+                "    <testcase classname=\"{}.{}\" name=\"{}\">\n",
+                escape_xml(&verification.framework_name),
+                escape_xml(&verification.test_name),
+                escape_xml(&verification.type_name)
+            ));
+            if let Some(error) = verification.errors.first() {
+                let messages: Vec<&str> = verification
+                    .errors
+                    .iter()
+                    .map(|error| error.message.as_str())
+                    .collect();
+                xml.push_str(&format!(
+                    "      <failure message=\"{}\">{}</failure>\n",
+                    escape_xml(&error.short_message),
+                    escape_xml(&messages.join("\n"))
+                ));
+            }
+            if !verification.warnings.is_empty() {
+                let messages: Vec<String> = verification
+                    .warnings
+                    .iter()
+                    .map(|warning| format!("WARN: {}", warning.message))
+                    .collect();
+                xml.push_str(&format!(
+                    "      <system-out>{}</system-out>\n",
+                    escape_xml(&messages.join("\n"))
+                ));
+            }
+            xml.push_str("    </testcase>\n");
+        }
+        xml.push_str("  </testsuite>\n");
+    }
+    xml.push_str("</testsuites>\n");
+
+    xml
+}
+
+/// Escapes the given `text` for use in XML (or HTML) content or attribute
+/// values.
+pub fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
 /// Helper function to print a vector of `Named` entries to standard out.
 fn print_all<T: Named>(result: Result<Vec<T>, ToolsetError>) -> ToolsetResult<()> {
     match result {
@@ -319,11 +794,21 @@ fn print_all<T: Named>(result: Result<Vec<T>, ToolsetError>) -> ToolsetResult<()
 
 #[cfg(test)]
 mod tests {
+    use crate::docker::listener::verifier::Error;
+    use crate::docker::Verification;
     use crate::io::get_tfb_dir;
     use crate::io::print_all_frameworks;
     use crate::io::print_all_tests;
     use crate::io::print_all_tests_with_tag;
-    use crate::metadata::TAG_BROKEN;
+    use crate::io::{
+        get_junit_report, report_verifications, upload_results, LogPath, Logger, TempDir,
+    };
+    use crate::metadata::{list_projects_by_test_name, TAG_BROKEN};
+    use crate::options;
+    use crate::results::{Manifest, Results};
+    use colored::Colorize;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
 
     #[test]
     fn it_will_get_a_valid_tfb_dir() {
@@ -349,6 +834,244 @@ mod tests {
         };
     }
 
+    #[test]
+    fn it_can_record_events() {
+        let results_dir = TempDir::new("events");
+        let mut logger = Logger::in_dir(results_dir.to_str().unwrap());
+        logger.quiet = true;
+
+        logger
+            .record_event("image.build.started", &[("image", "tfb.test.gemini")])
+            .unwrap();
+        logger
+            .record_event("container.killed", &[("container_id", "4f2a")])
+            .unwrap();
+
+        let events = std::fs::read_to_string(results_dir.join("events.ndjson")).unwrap();
+        let events = events
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["event"], "image.build.started");
+        assert_eq!(events[0]["image"], "tfb.test.gemini");
+        assert!(events[0]["timestamp"].is_i64());
+        assert_eq!(events[1]["container_id"], "4f2a");
+    }
+
+    #[test]
+    fn it_can_format_log_records() {
+        let logger = Logger::with_prefix("gemini");
+
+        let record = logger
+            .get_log_record("error", &"Failed to start".red().to_string())
+            .unwrap();
+
+        let record = serde_json::from_str::<serde_json::Value>(&record).unwrap();
+        assert!(record["timestamp"].is_i64());
+        assert_eq!(record["level"], "error");
+        assert_eq!(record["test"], "gemini");
+        assert_eq!(record["message"], "Failed to start");
+    }
+
+    #[test]
+    fn it_can_write_a_manifest_of_the_log_layout() {
+        let results_dir = TempDir::new("manifest");
+        let logger = Logger::in_dir(results_dir.to_str().unwrap());
+
+        logger.write_manifest(&Manifest::default()).unwrap();
+        let log_path = logger.get_log_path(LogPath::Benchmark("json")).unwrap();
+
+        let manifest = std::fs::read_to_string(results_dir.join("manifest.json")).unwrap();
+        let manifest = serde_json::from_str::<serde_json::Value>(&manifest).unwrap();
+        assert!(log_path.ends_with("benchmark/json/log.txt"));
+        assert!(log_path.parent().unwrap().is_dir());
+        assert_eq!(
+            manifest["layout"]["<test>/verify/<type>.txt"],
+            "Output of verifying the test type"
+        );
+        assert!(manifest["layout"]["<test>/build/log.txt"].is_string());
+        assert!(manifest["layout"]["<test>/topology.json"].is_string());
+    }
+
+    #[test]
+    fn it_can_rewrite_shorter_results() {
+        let results_dir = TempDir::new("rewrite-results");
+        let logger = Logger::in_dir(results_dir.to_str().unwrap());
+        let mut results = Results::default();
+        results.failed.insert(
+            "db".to_string(),
+            vec!["gemini".to_string(), "gemini-mysql".to_string()],
+        );
+        logger.write_results(&results).unwrap();
+
+        // As a resumed run does when a failed test type then succeeds.
+        results.failed.clear();
+        logger.write_results(&results).unwrap();
+
+        let loaded = Results::load(&results_dir).unwrap();
+        assert!(loaded.failed.is_empty());
+    }
+
+    #[test]
+    fn it_can_write_a_manifest_of_the_run() {
+        let results_dir = TempDir::new("run-manifest");
+        let logger = Logger::in_dir(results_dir.to_str().unwrap());
+        let matches = options::parse().get_matches_from(vec![
+            "tfb",
+            "--mode",
+            "verify",
+            "--test",
+            "gemini",
+            "servlet",
+            "--profile",
+        ]);
+
+        let mut manifest = Manifest::new(&matches);
+        manifest.verifier_digest = Some("techempower/tfb.verifier@sha256:abc".to_string());
+        logger.write_manifest(&manifest).unwrap();
+
+        let manifest = std::fs::read_to_string(results_dir.join("manifest.json")).unwrap();
+        let manifest = serde_json::from_str::<serde_json::Value>(&manifest).unwrap();
+        assert_eq!(manifest["options"]["mode"], "verify");
+        assert_eq!(
+            manifest["options"]["test"],
+            serde_json::json!(["gemini", "servlet"])
+        );
+        assert_eq!(manifest["options"]["profile"], true);
+        assert_eq!(manifest["toolsetVersion"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(
+            manifest["verifierDigest"],
+            "techempower/tfb.verifier@sha256:abc"
+        );
+        assert!(manifest["invocation"].is_array());
+        assert!(manifest["layout"]["results.json"].is_string());
+    }
+
+    #[test]
+    fn it_can_write_verifications() {
+        let results_dir = TempDir::new("verifications");
+        let mut logger = Logger::in_dir(results_dir.to_str().unwrap());
+        logger.quiet = true;
+        let verification = Verification {
+            framework_name: "Gemini".to_string(),
+            test_name: "gemini".to_string(),
+            type_name: "json".to_string(),
+            warnings: vec![],
+            errors: vec![Error {
+                message: "Invalid Content-Type header".to_string(),
+                short_message: "Invalid Content-Type".to_string(),
+            }],
+        };
+
+        report_verifications(vec![verification], &[], None, logger).unwrap();
+
+        let verifications =
+            std::fs::read_to_string(results_dir.join("verifications.json")).unwrap();
+        let verifications = serde_json::from_str::<serde_json::Value>(&verifications).unwrap();
+        assert_eq!(
+            verifications,
+            serde_json::json!([{
+                "frameworkName": "Gemini",
+                "testName": "gemini",
+                "typeName": "json",
+                "warnings": [],
+                "errors": [{
+                    "message": "Invalid Content-Type header",
+                    "shortMessage": "Invalid Content-Type",
+                }],
+            }])
+        );
+    }
+
+    #[test]
+    fn it_can_summarize_the_notes_of_verified_tests() {
+        let results_dir = TempDir::new("notes");
+        let mut logger = Logger::in_dir(results_dir.to_str().unwrap());
+        logger.quiet = true;
+        let projects =
+            list_projects_by_test_name(Some("gemini-postgres".to_string()), &[]).unwrap();
+        let verification = Verification {
+            framework_name: "Gemini".to_string(),
+            test_name: "gemini-postgres".to_string(),
+            type_name: "db".to_string(),
+            warnings: vec![],
+            errors: vec![],
+        };
+
+        report_verifications(vec![verification], &projects, None, logger).unwrap();
+
+        let summary = std::fs::read_to_string(results_dir.join(LogPath::Report.get_path()));
+        let summary = summary.unwrap();
+        assert!(summary.contains("notes        : Uses the JDBC driver's prepared statement cache"));
+        assert!(
+            summary.contains("known issue  : Updates are slow to converge under high concurrency")
+        );
+    }
+
+    #[test]
+    fn it_can_report_verifications_as_junit() {
+        let verification = |type_name: &str, errors: Vec<Error>| Verification {
+            framework_name: "Gemini".to_string(),
+            test_name: "gemini".to_string(),
+            type_name: type_name.to_string(),
+            warnings: vec![],
+            errors,
+        };
+        let verifications = vec![
+            verification("json", vec![]),
+            verification(
+                "fortune",
+                vec![Error {
+                    message: "Expected <script> to be escaped".to_string(),
+                    short_message: "Unescaped \"<script>\"".to_string(),
+                }],
+            ),
+        ];
+
+        let report = get_junit_report(&verifications);
+
+        assert!(report.contains("<testsuites name=\"tfb\" tests=\"2\" failures=\"1\">"));
+        assert!(report.contains("<testsuite name=\"gemini\" tests=\"2\" failures=\"1\">"));
+        assert!(report
+            .contains("<testcase classname=\"Gemini.gemini\" name=\"json\">\n    </testcase>"));
+        assert!(report.contains(
+            "<failure message=\"Unescaped &quot;&lt;script&gt;&quot;\">Expected &lt;script&gt; to be escaped</failure>"
+        ));
+    }
+
+    #[test]
+    fn it_can_upload_results() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let upload_uri = format!("http://{}/results", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0; 4096];
+            while !String::from_utf8_lossy(&request).ends_with('}') {
+                let read = stream.read(&mut buffer).unwrap();
+                request.extend_from_slice(&buffer[..read]);
+            }
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+                .unwrap();
+            String::from_utf8(request).unwrap()
+        });
+        let mut logger = Logger::default();
+        logger.quiet = true;
+        let results = Results {
+            name: "upload".to_string(),
+            ..Default::default()
+        };
+
+        upload_results(&results, &upload_uri, &logger).unwrap();
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /results HTTP/1.1"));
+        assert!(request.contains("Content-Type: application/json"));
+        assert!(request.contains("\"name\":\"upload\""));
+    }
+
     #[test]
     fn it_can_print_all_tests_with_tag() {
         match print_all_tests_with_tag(TAG_BROKEN) {
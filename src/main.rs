@@ -1,12 +1,21 @@
+mod audit;
 mod benchmarker;
 mod cli;
+mod compare;
 mod config;
 mod docker;
 mod error;
 mod io;
 mod metadata;
+mod metrics;
 mod options;
+mod parse;
+mod plan;
+mod report;
 mod results;
+mod schedule;
+mod status;
+mod trace;
 
 #[macro_use]
 extern crate lazy_static;
@@ -2,28 +2,56 @@
 //! test implementation configuration files, as well as returning useful
 //! structs representing those configuration files.
 
-use crate::config::{Framework, Named, Project, Test};
-use crate::error::ToolsetResult;
+use crate::benchmarker::modes;
+use crate::config::{normalize_name, Framework, Named, Project, Test, TEST_TYPES};
+use crate::error::ToolsetError::{
+    InvalidConfigError, InvalidConfigsError, NoProjectsSelectedError,
+};
+use crate::error::{ToolsetError, ToolsetResult};
 use crate::io::Logger;
 use crate::{config, io, options};
 use clap::ArgMatches;
-use glob::glob;
-use std::path::PathBuf;
+use glob::{glob, MatchOptions, Pattern};
+use std::collections::HashSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
 
 pub const TAG_BROKEN: &str = "broken";
+const TEST_NAME_MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: false,
+    require_literal_separator: false,
+    require_literal_leading_dot: false,
+};
+/// The most test names suggested for one which matches nothing.
+const MAX_SUGGESTIONS: usize = 5;
 
 /// Walks the FrameworkBenchmarks directory's `framework` sub-dir to find all
 /// test implementations' `config.toml`, parse each file, and pushes the top-
 /// level `framework` to the return Vec.
 pub fn list_all_frameworks() -> ToolsetResult<Vec<Framework>> {
-    let mut frameworks: Vec<Framework> = Vec::new();
     let mut tfb_path = io::get_tfb_dir()?;
     tfb_path.push("frameworks/*/*/config.toml");
-    for path in glob(tfb_path.to_str().unwrap()).unwrap() {
-        frameworks.push(config::get_framework_by_config_file(&path.unwrap())?);
-    }
 
-    Ok(frameworks)
+    Ok(parse_config_files(&tfb_path)?
+        .into_iter()
+        .map(|config| config.framework)
+        .collect())
+}
+
+/// Walks the FrameworkBenchmarks directory's `framework` sub-dir to find all
+/// test implementations' `config.toml`, without parsing them.
+pub fn list_config_files() -> ToolsetResult<Vec<PathBuf>> {
+    let mut tfb_path = io::get_tfb_dir()?;
+    tfb_path.push("frameworks/*/*/config.toml");
+
+    Ok(glob(tfb_path.to_str().unwrap())
+        .unwrap()
+        .filter_map(Result::ok)
+        .collect())
 }
 
 /// Walks the FrameworkBenchmarks directory's `framework` sub-dir to find all
@@ -43,7 +71,7 @@ pub fn list_tests_for_framework(framework_name: &str) -> ToolsetResult<Vec<Test>
     let mut tfb_path = io::get_tfb_dir()?;
     tfb_path.push(format!(
         "frameworks/*/{}/config.toml",
-        framework_name.to_lowercase()
+        normalize_name(framework_name)
     ));
 
     get_test_implementations_by_path(&tfb_path)
@@ -53,16 +81,12 @@ pub fn list_tests_for_framework(framework_name: &str) -> ToolsetResult<Vec<Test>
 /// test implementations' `config.toml`, parse each file, and pushes the top-
 /// level `Test`s with the given `tag` to the return Vec.
 pub fn list_tests_by_tag(tag: &str) -> ToolsetResult<Vec<Test>> {
-    let mut test_implementations = Vec::new();
     let mut tfb_path = io::get_tfb_dir()?;
     tfb_path.push("frameworks/*/*/config.toml");
-    for path in glob(tfb_path.to_str().unwrap()).unwrap() {
-        for test in config::get_test_implementations_by_config_file(&path.unwrap())? {
-            if test.tags.is_some() && test.clone().tags.unwrap().contains(&tag.to_string()) {
-                test_implementations.push(test);
-            }
-        }
-    }
+    let mut test_implementations = get_test_implementations_by_path(&tfb_path)?;
+    test_implementations.retain(|test| {
+        test.tags.is_some() && test.clone().tags.unwrap().contains(&tag.to_string())
+    });
 
     Ok(test_implementations)
 }
@@ -79,23 +103,25 @@ pub fn list_tests_by_tag(tag: &str) -> ToolsetResult<Vec<Test>> {
 /// Say that (Java, FooFramework) and (C#, FooFramework)
 /// both have a `default` test implementation, then this would return the
 /// `Project`s for both when queried with "FooFramework".
+///
+/// Test names are matched ignoring case, and may be glob patterns; e.g.
+/// `actix*` selects every test whose name starts with `actix`.
 pub fn list_projects_by_test_name(
     test_name: Option<String>,
-    test_type: Option<&str>,
+    test_types: &[&str],
 ) -> ToolsetResult<Vec<Project>> {
+    let test_name_pattern = test_name.as_deref().map(get_test_name_pattern);
     let mut projects = Vec::new();
     let mut tfb_path = io::get_tfb_dir()?;
     tfb_path.push("frameworks/*/*/config.toml");
-    for path in glob(tfb_path.to_str().unwrap()).unwrap() {
-        let path_buf: &PathBuf = &path.unwrap();
-        let project_name = config::get_project_name_by_config_file(&path_buf)?;
-        let framework = config::get_framework_by_config_file(&path_buf)?;
+    for config in parse_config_files(&tfb_path)? {
         let mut tests = Vec::new();
-        let language = config::get_language_by_config_file(&framework, &path_buf)?;
-        for mut test in config::get_test_implementations_by_config_file(&path_buf)? {
-            test.specify_test_type(test_type);
-            if let Some(name) = &test_name {
-                if test.get_name() == *name {
+        for mut test in config.tests {
+            if !test.specify_test_types(test_types) {
+                continue;
+            }
+            if let Some(pattern) = &test_name_pattern {
+                if pattern.matches_with(&test.get_name(), TEST_NAME_MATCH_OPTIONS) {
                     tests.push(test);
                 }
             } else {
@@ -104,10 +130,10 @@ pub fn list_projects_by_test_name(
         }
         if !tests.is_empty() {
             projects.push(Project {
-                name: project_name,
-                framework,
+                name: config.project_name,
+                framework: config.framework,
                 tests,
-                language,
+                language: config.language,
             });
         }
     }
@@ -118,36 +144,25 @@ pub fn list_projects_by_test_name(
 /// Lists projects by language name
 pub fn list_projects_by_language_name(
     language_name: &str,
-    test_type: Option<&str>,
+    test_types: &[&str],
 ) -> ToolsetResult<Vec<Project>> {
     let mut projects = Vec::new();
     let mut tfb_path = io::get_tfb_dir()?;
     tfb_path.push("frameworks/*/*/config.toml");
-    for path in glob(tfb_path.to_str().unwrap()).unwrap() {
-        let path_buf: &PathBuf = &path.unwrap();
-        let project_name = config::get_project_name_by_config_file(&path_buf)?;
-        let framework = config::get_framework_by_config_file(&path_buf)?;
-        let mut tests = Vec::new();
-        let language = config::get_language_by_config_file(&framework, &path_buf)?;
-        if language_name.to_lowercase() == language.to_lowercase() {
-            for mut test in config::get_test_implementations_by_config_file(&path_buf)? {
-                if test.tags.is_none()
-                    || !test
-                        .tags
-                        .as_ref()
-                        .unwrap()
-                        .contains(&TAG_BROKEN.to_string())
-                {
-                    test.specify_test_type(test_type);
+    for config in parse_config_files(&tfb_path)? {
+        if language_name.to_lowercase() == config.language.to_lowercase() {
+            let mut tests = Vec::new();
+            for mut test in config.tests {
+                if test.specify_test_types(test_types) {
                     tests.push(test);
                 }
             }
             if !tests.is_empty() {
                 projects.push(Project {
-                    name: project_name,
-                    framework,
+                    name: config.project_name,
+                    framework: config.framework,
                     tests,
-                    language,
+                    language: config.language,
                 });
             }
         }
@@ -162,35 +177,24 @@ pub fn list_projects_by_language_name(
 /// Example: `dir_name` = "Java/gemini"
 pub fn list_projects_by_dir_name(
     dir_name: &str,
-    test_type: Option<&str>,
+    test_types: &[&str],
 ) -> ToolsetResult<Vec<Project>> {
     let mut projects = Vec::new();
     let mut tfb_path = io::get_tfb_dir()?;
     tfb_path.push(&format!("frameworks/{}/config.toml", dir_name));
-    for path in glob(tfb_path.to_str().unwrap()).unwrap() {
-        let path_buf: &PathBuf = &path.unwrap();
-        let project_name = config::get_project_name_by_config_file(&path_buf)?;
-        let framework = config::get_framework_by_config_file(&path_buf)?;
+    for config in parse_config_files(&tfb_path)? {
         let mut tests = Vec::new();
-        let language = config::get_language_by_config_file(&framework, &path_buf)?;
-        for mut test in config::get_test_implementations_by_config_file(&path_buf)? {
-            if test.tags.is_none()
-                || !test
-                    .tags
-                    .as_ref()
-                    .unwrap()
-                    .contains(&TAG_BROKEN.to_string())
-            {
-                test.specify_test_type(test_type);
+        for mut test in config.tests {
+            if test.specify_test_types(test_types) {
                 tests.push(test);
             }
         }
         if !tests.is_empty() {
             projects.push(Project {
-                name: project_name,
-                framework,
+                name: config.project_name,
+                framework: config.framework,
                 tests,
-                language,
+                language: config.language,
             });
         }
     }
@@ -200,126 +204,450 @@ pub fn list_projects_by_dir_name(
 
 /// Convenience function for calling `metadata::list_projects_by_test_name(None)`.
 pub fn list_all_projects() -> ToolsetResult<Vec<Project>> {
-    list_projects_by_test_name(None, None)
+    list_projects_by_test_name(None, &[])
+}
+
+/// Sets whether an invalid `config.toml` fails listing (see `--strict-config`)
+/// rather than being reported and skipped.
+pub fn set_strict_config(strict_config: bool) {
+    STRICT_CONFIG.store(strict_config, Ordering::SeqCst);
 }
 
 /// Helper method to get the tests to run, specified or not.
-pub fn list_projects_to_run(matches: &ArgMatches) -> Vec<Project> {
+///
+/// Returns a `NoProjectsSelectedError`, describing the filters applied, when
+/// they select no test to run.
+pub fn list_projects_to_run(matches: &ArgMatches) -> ToolsetResult<Vec<Project>> {
     let logger = Logger::default();
+    if let Ok(mut parse_times) = PARSE_TIMES.lock() {
+        *parse_times = ParseTimes::default();
+    }
+    let test_types: Vec<&str> = match matches.values_of(options::args::TYPES) {
+        Some(test_types) => test_types.collect(),
+        None => Vec::new(),
+    };
     let mut projects = Vec::new();
+    let mut filters = Vec::new();
     if let Some(list) = matches.values_of(options::args::TEST_NAMES) {
         let test_names: Vec<&str> = list.collect();
+        filters.push(format!("--test {}", test_names.join(" ")));
+        let several_test_names = test_names.len() > 1;
         for test_name in test_names {
-            match list_projects_by_test_name(
-                Some(String::from(test_name)),
-                matches.value_of(options::args::TYPES),
-            ) {
+            match list_projects_by_test_name(Some(String::from(test_name)), &test_types) {
+                // If none match, `NoProjectsSelectedError` suggests names.
+                Ok(projects_found) if projects_found.is_empty() && several_test_names => {
+                    let suggestions = get_test_name_suggestions(test_name)?;
+                    if suggestions.is_empty() {
+                        logger.error(format!("Found no test named {}", test_name))?;
+                    } else {
+                        logger.error(format!(
+                            "Found no test named {}; did you mean {}?",
+                            test_name,
+                            suggestions.join(", ")
+                        ))?;
+                    }
+                }
                 Ok(mut projects_found) => projects.append(&mut projects_found),
-                Err(e) => logger
-                    .error(format!(
-                        "Error thrown collecting projects for test name: {}; {:?}",
-                        test_name, e
-                    ))
-                    .unwrap(),
+                Err(e @ InvalidConfigsError(_)) => return Err(e),
+                Err(e) => logger.error(format!(
+                    "Error thrown collecting projects for test name: {}; {:?}",
+                    test_name, e
+                ))?,
             };
         }
-        if projects.is_empty() {
-            logger
-                .error(format!(
-                    "Found no project for the supplied test name(s): {}",
-                    matches
-                        .values_of(options::args::TEST_NAMES)
-                        .unwrap()
-                        .collect::<Vec<_>>()
-                        .join(" ")
-                ))
-                .unwrap();
-        }
     } else if let Some(list) = matches.values_of(options::args::TEST_LANGUAGES) {
         let test_languages: Vec<&str> = list.collect();
+        filters.push(format!("--test-lang {}", test_languages.join(" ")));
         for language in test_languages {
-            match list_projects_by_language_name(language, matches.value_of(options::args::TYPES)) {
+            match list_projects_by_language_name(language, &test_types) {
                 Ok(mut projects_found) => projects.append(&mut projects_found),
-                Err(e) => logger
-                    .error(format!(
-                        "Error thrown collecting projects for language name: {}; {:?}",
-                        language, e
-                    ))
-                    .unwrap(),
+                Err(e @ InvalidConfigsError(_)) => return Err(e),
+                Err(e) => logger.error(format!(
+                    "Error thrown collecting projects for language name: {}; {:?}",
+                    language, e
+                ))?,
             }
         }
-        if projects.is_empty() {
-            logger
-                .error(format!(
-                    "Found no project for the supplied language(s): {}",
-                    matches
-                        .values_of(options::args::TEST_LANGUAGES)
-                        .unwrap()
-                        .collect::<Vec<_>>()
-                        .join(" ")
-                ))
-                .unwrap();
-        }
     } else if let Some(list) = matches.values_of(options::args::TEST_DIRS) {
         let test_dirs: Vec<&str> = list.collect();
+        filters.push(format!("--test-dir {}", test_dirs.join(" ")));
         for dir in test_dirs {
-            match list_projects_by_dir_name(dir, matches.value_of(options::args::TYPES)) {
+            match list_projects_by_dir_name(dir, &test_types) {
                 Ok(mut projects_found) => projects.append(&mut projects_found),
-                Err(e) => logger
-                    .error(format!(
-                        "Error thrown collecting projects for directory name: {}; {:?}",
-                        dir, e
-                    ))
-                    .unwrap(),
+                Err(e @ InvalidConfigsError(_)) => return Err(e),
+                Err(e) => logger.error(format!(
+                    "Error thrown collecting projects for directory name: {}; {:?}",
+                    dir, e
+                ))?,
             }
         }
-        if projects.is_empty() {
-            logger
-                .error(format!(
-                    "Found no project for the supplied test dir(s): {}",
-                    matches
-                        .values_of(options::args::TEST_DIRS)
-                        .unwrap()
-                        .collect::<Vec<_>>()
-                        .join(" ")
-                ))
-                .unwrap();
-        }
     } else {
-        match list_all_projects() {
+        match list_projects_by_test_name(None, &test_types) {
             Ok(mut projects_found) => projects.append(&mut projects_found),
-            Err(e) => logger
-                .error(format!("Error thrown collecting all projects: {:?}", e))
-                .unwrap(),
+            Err(e @ InvalidConfigsError(_)) => return Err(e),
+            Err(e) => logger.error(format!("Error thrown collecting all projects: {:?}", e))?,
         };
     }
+    if !test_types.is_empty() {
+        filters.push(format!("--type {}", test_types.join(" ")));
+    }
+    // Broken tests are only worth starting to debug them.
+    if !matches.is_present(options::args::INCLUDE_BROKEN)
+        && matches.value_of(options::args::MODE) != Some(modes::DEBUG)
+    {
+        let mut broken = 0;
+        for project in &mut projects {
+            let tests = project.tests.len();
+            project.tests.retain(|test| !test.is_tagged(TAG_BROKEN));
+            broken += tests - project.tests.len();
+        }
+        if broken > 0 {
+            projects.retain(|project| !project.tests.is_empty());
+            logger.log(format!(
+                "Skipped {} test(s) tagged {}; use --include-broken to run them",
+                broken, TAG_BROKEN
+            ))?;
+            filters.push(format!("(skipped {} tagged {})", broken, TAG_BROKEN));
+        }
+    }
+
+    if projects.is_empty() {
+        let mut suggestions = Vec::new();
+        if let Some(test_names) = matches.values_of(options::args::TEST_NAMES) {
+            for test_name in test_names {
+                for suggestion in get_test_name_suggestions(test_name)? {
+                    if !suggestions.contains(&suggestion) {
+                        suggestions.push(suggestion);
+                    }
+                }
+            }
+        }
+        let filters = if filters.is_empty() {
+            "(none)".to_string()
+        } else {
+            filters.join(" ")
+        };
+        return Err(NoProjectsSelectedError(filters, suggestions));
+    }
+    if let Some(max_tests) = matches.value_of(options::args::MAX_TESTS) {
+        let max_tests = str::parse::<usize>(max_tests).unwrap();
+        if projects.len() > max_tests {
+            logger.log(format!(
+                "Selected the first {} of {} projects (--max-tests)",
+                max_tests,
+                projects.len()
+            ))?;
+            projects.truncate(max_tests);
+        }
+    }
+    if let Ok(parse_times) = PARSE_TIMES.lock() {
+        if parse_times.config_files > 0 {
+            logger.log(format!(
+                "Parsed {} config files in {}ms, {:.1}x as fast as serially",
+                parse_times.config_files,
+                parse_times.elapsed.as_millis(),
+                parse_times.serial.as_secs_f64() / parse_times.elapsed.as_secs_f64().max(1e-9)
+            ))?;
+        }
+    }
 
-    projects
+    Ok(projects)
+}
+
+/// Gets the names of known tests which `test_name` may have been meant as,
+/// closest first; those within a few edits (Levenshtein distance) of it, or
+/// which contain it or are contained by it, ignoring case.
+pub fn get_test_name_suggestions(test_name: &str) -> ToolsetResult<Vec<String>> {
+    let test_name = test_name.to_lowercase();
+    // Allows roughly one typo per three characters.
+    let max_distance = (test_name.chars().count() / 3).max(1);
+    let mut suggestions = Vec::new();
+    for test in list_all_tests()? {
+        let name = test.get_name();
+        let lowercase_name = name.to_lowercase();
+        let distance = get_edit_distance(&test_name, &lowercase_name);
+        if (distance <= max_distance
+            || lowercase_name.contains(&test_name)
+            || test_name.contains(&lowercase_name))
+            && !suggestions
+                .iter()
+                .any(|(_, suggestion)| *suggestion == name)
+        {
+            suggestions.push((distance, name));
+        }
+    }
+    suggestions.sort();
+
+    Ok(suggestions
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, suggestion)| suggestion)
+        .collect())
+}
+
+/// Gets the given `projects` reordered to run one test type at a time; each
+/// `Test` of each `Project` is run once per test type, with only that type,
+/// in turn with every other `Test` having that type.
+///
+/// Example: gemini (json, db) and actix (json, db) are run in the order
+///          gemini (json), actix (json), gemini (db), actix (db).
+pub fn interleave_projects(projects: &[Project]) -> Vec<Project> {
+    let mut test_types: Vec<&str> = Vec::new();
+    for test in projects.iter().flat_map(|project| &project.tests) {
+        for test_type in test.urls.keys() {
+            if !test_types.contains(&test_type.as_str()) {
+                test_types.push(test_type);
+            }
+        }
+    }
+    // Standard test types in their usual order, then any custom ones.
+    test_types.sort_by_key(|test_type| {
+        (
+            TEST_TYPES
+                .iter()
+                .position(|standard| standard == test_type)
+                .unwrap_or(TEST_TYPES.len()),
+            test_type.to_string(),
+        )
+    });
+
+    let mut interleaved = Vec::new();
+    for test_type in test_types {
+        for project in projects {
+            for test in &project.tests {
+                let mut test = test.clone();
+                if test.urls.contains_key(test_type) && test.specify_test_types(&[test_type]) {
+                    interleaved.push(Project {
+                        tests: vec![test],
+                        ..project.clone()
+                    });
+                }
+            }
+        }
+    }
+
+    interleaved
 }
 
 //
 // PRIVATES
 //
 
+/// Gets the glob `Pattern` for the given `test_name`; a name which is not a
+/// valid pattern (e.g. with an unclosed `[`) only matches itself.
+fn get_test_name_pattern(test_name: &str) -> Pattern {
+    Pattern::new(test_name).unwrap_or_else(|_| Pattern::new(&Pattern::escape(test_name)).unwrap())
+}
+
+/// Gets the Levenshtein distance between `a` and `b`; the fewest single
+/// character insertions, deletions, or substitutions changing one into the
+/// other.
+fn get_edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + if a_char == *b_char { 0 } else { 1 };
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
 fn get_test_implementations_by_path(path: &PathBuf) -> ToolsetResult<Vec<Test>> {
     let mut test_implementations = Vec::new();
-    for path in glob(path.to_str().unwrap()).unwrap() {
-        test_implementations
-            .append(config::get_test_implementations_by_config_file(&path.unwrap())?.as_mut());
+    for mut config in parse_config_files(path)? {
+        test_implementations.append(&mut config.tests);
     }
     Ok(test_implementations)
 }
 
+/// A parsed `config.toml`; all but its `tests` make up a `Project`.
+struct ParsedConfig {
+    project_name: String,
+    framework: Framework,
+    language: String,
+    tests: Vec<Test>,
+}
+
+/// The time spent parsing config files since `list_projects_to_run` was
+/// last called, both elapsed and as it would have been spent serially.
+#[derive(Default)]
+struct ParseTimes {
+    config_files: usize,
+    elapsed: Duration,
+    serial: Duration,
+}
+
+/// A `config.toml` which could not be parsed; `line` is only known of those
+/// which are not valid TOML, or not shaped like a config at all.
+struct InvalidConfig {
+    config_file: PathBuf,
+    line: Option<usize>,
+    error: String,
+}
+impl InvalidConfig {
+    fn new(config_file: &Path, error: ToolsetError, whole_file: bool) -> Self {
+        let (line, error) = match error {
+            InvalidConfigError(_, e) => {
+                let line = e
+                    .line_col()
+                    .filter(|_| whole_file)
+                    .map(|(line, _)| line + 1);
+                (line, e.to_string())
+            }
+            e => (None, e.to_string()),
+        };
+
+        Self {
+            config_file: config_file.to_path_buf(),
+            line,
+            error,
+        }
+    }
+}
+impl fmt::Display for InvalidConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.config_file.display(), line, self.error),
+            None => write!(f, "{}: {}", self.config_file.display(), self.error),
+        }
+    }
+}
+
+static STRICT_CONFIG: AtomicBool = AtomicBool::new(false);
+
+lazy_static! {
+    static ref PARSE_TIMES: Mutex<ParseTimes> = Mutex::new(ParseTimes::default());
+    /// The invalid config files already reported, as every listing parses
+    /// them all again.
+    static ref REPORTED_INVALID_CONFIGS: Mutex<HashSet<PathBuf>> = Mutex::new(HashSet::new());
+}
+
+/// Parses each of the `config.toml` files matching the glob `pattern`, in
+/// order; they are divided between a thread per available CPU, as there are
+/// hundreds of them.
+///
+/// Invalid config files are reported together and skipped unless
+/// `--strict-config` was given, in which case they fail the parse.
+fn parse_config_files(pattern: &Path) -> ToolsetResult<Vec<ParsedConfig>> {
+    let (configs, invalid_configs) = parse_all_config_files(pattern);
+    if invalid_configs.is_empty() {
+        Ok(configs)
+    } else if STRICT_CONFIG.load(Ordering::SeqCst) {
+        Err(InvalidConfigsError(
+            invalid_configs.iter().map(ToString::to_string).collect(),
+        ))
+    } else {
+        report_invalid_configs(&invalid_configs)?;
+        Ok(configs)
+    }
+}
+
+/// Logs the given `invalid_configs`, other than those already reported.
+fn report_invalid_configs(invalid_configs: &[InvalidConfig]) -> ToolsetResult<()> {
+    let unreported: Vec<String> = match REPORTED_INVALID_CONFIGS.lock() {
+        Ok(mut reported) => invalid_configs
+            .iter()
+            .filter(|invalid| reported.insert(invalid.config_file.clone()))
+            .map(ToString::to_string)
+            .collect(),
+        Err(_) => invalid_configs.iter().map(ToString::to_string).collect(),
+    };
+    if unreported.is_empty() {
+        return Ok(());
+    }
+
+    Logger::default().error(format!(
+        "{}\nThese are skipped; use --strict-config to fail on them instead",
+        InvalidConfigsError(unreported)
+    ))
+}
+
+/// Parses each of the `config.toml` files matching the glob `pattern`,
+/// returning those which are valid and those which are not, both in order.
+fn parse_all_config_files(pattern: &Path) -> (Vec<ParsedConfig>, Vec<InvalidConfig>) {
+    let started = Instant::now();
+    let config_files: Vec<PathBuf> = glob(pattern.to_str().unwrap())
+        .unwrap()
+        .filter_map(Result::ok)
+        .collect();
+    let threads = thread::available_parallelism()
+        .map_or(1, usize::from)
+        .min(config_files.len())
+        .max(1);
+    let workers: Vec<_> = config_files
+        .chunks(config_files.len().div_ceil(threads).max(1))
+        .map(|config_files| {
+            let config_files = config_files.to_vec();
+            thread::spawn(move || {
+                let started = Instant::now();
+                let configs: Vec<Result<ParsedConfig, InvalidConfig>> =
+                    config_files.iter().map(parse_config_file).collect();
+                (configs, started.elapsed())
+            })
+        })
+        .collect();
+
+    let mut configs = Vec::new();
+    let mut invalid_configs = Vec::new();
+    let mut serial = Duration::default();
+    for worker in workers {
+        let (parsed, elapsed) = worker
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+        serial += elapsed;
+        for config in parsed {
+            match config {
+                Ok(config) => configs.push(config),
+                Err(invalid_config) => invalid_configs.push(invalid_config),
+            }
+        }
+    }
+    if let Ok(mut parse_times) = PARSE_TIMES.lock() {
+        parse_times.config_files += configs.len();
+        parse_times.elapsed += started.elapsed();
+        parse_times.serial += serial;
+    }
+
+    (configs, invalid_configs)
+}
+
+/// Parses the given `config_file`.
+fn parse_config_file(config_file: &PathBuf) -> Result<ParsedConfig, InvalidConfig> {
+    // Only the framework is parsed from the file as a whole; its tests are
+    // parsed table by table, so the lines of their errors are not the file's.
+    let framework = config::get_framework_by_config_file(config_file)
+        .map_err(|e| InvalidConfig::new(config_file, e, true))?;
+    let invalid = |e| InvalidConfig::new(config_file, e, false);
+
+    Ok(ParsedConfig {
+        project_name: config::get_project_name_by_config_file(config_file).map_err(invalid)?,
+        language: config::get_language_by_config_file(&framework, config_file).map_err(invalid)?,
+        tests: config::get_test_implementations_by_config_file(config_file).map_err(invalid)?,
+        framework,
+    })
+}
+
 //
 // TESTS
 //
 
 #[cfg(test)]
 mod tests {
+    use crate::config::Named;
+    use crate::error::ToolsetError::NoProjectsSelectedError;
+    use crate::io::TempDir;
     use crate::metadata::{
-        list_all_frameworks, list_all_projects, list_all_tests, list_tests_by_tag,
-        list_tests_for_framework,
+        get_edit_distance, get_test_name_suggestions, interleave_projects, list_all_frameworks,
+        list_all_projects, list_all_tests, list_config_files, list_projects_by_test_name,
+        list_projects_to_run, list_tests_by_tag, list_tests_for_framework, parse_all_config_files,
+        parse_config_files,
     };
+    use crate::options::parse;
 
     #[test]
     fn it_can_list_all_frameworks() {
@@ -353,6 +681,148 @@ mod tests {
         };
     }
 
+    #[test]
+    fn it_rejects_selecting_no_projects() {
+        let matches = parse().get_matches_from(vec!["tfb", "--test", "gemin", "--type", "json"]);
+        match list_projects_to_run(&matches) {
+            Err(NoProjectsSelectedError(filters, suggestions)) => {
+                assert_eq!(filters, "--test gemin --type json");
+                assert!(suggestions.contains(&"gemini".to_string()));
+            }
+            result => panic!("Expected NoProjectsSelectedError, got {:?}", result),
+        }
+
+        let matches = parse().get_matches_from(vec!["tfb", "--test", "gemini", "--type", "pb"]);
+        assert!(matches!(
+            list_projects_to_run(&matches),
+            Err(NoProjectsSelectedError(..))
+        ));
+    }
+
+    #[test]
+    fn it_can_list_projects_by_test_name_pattern() {
+        let get_test_names = |test_name: &str| {
+            let mut test_names = list_projects_by_test_name(Some(test_name.to_string()), &[])
+                .unwrap()
+                .iter()
+                .flat_map(|project| project.tests.iter().map(|test| test.get_name()))
+                .collect::<Vec<String>>();
+            test_names.sort();
+            test_names
+        };
+        assert_eq!(get_test_names("GEMINI"), vec!["gemini"]);
+        assert_eq!(
+            get_test_names("Gemini-*"),
+            vec!["gemini-mysql", "gemini-postgres"]
+        );
+        assert!(get_test_names("gemini-[").is_empty());
+    }
+
+    #[test]
+    fn it_can_select_multiple_test_types() {
+        let matches = parse().get_matches_from(vec![
+            "tfb",
+            "--test",
+            "gemini*",
+            "--type",
+            "json,db",
+            "--include-broken",
+        ]);
+        let mut test_types = list_projects_to_run(&matches)
+            .unwrap()
+            .iter()
+            .flat_map(|project| project.tests.iter())
+            .map(|test| {
+                let mut test_types = test.urls.keys().cloned().collect::<Vec<String>>();
+                test_types.sort();
+                format!("{}: {}", test.get_name(), test_types.join(","))
+            })
+            .collect::<Vec<String>>();
+        test_types.sort();
+        assert_eq!(
+            test_types,
+            vec!["gemini-mysql: db", "gemini-postgres: db", "gemini: json"]
+        );
+    }
+
+    #[test]
+    fn it_can_skip_broken_tests() {
+        let test_names = |matches| {
+            list_projects_to_run(&matches)
+                .unwrap()
+                .iter()
+                .flat_map(|project| project.tests.iter().map(|test| test.get_name()))
+                .collect::<Vec<String>>()
+        };
+        let skipped = test_names(parse().get_matches_from(vec!["tfb", "--test", "gemini*"]));
+        assert!(skipped.contains(&"gemini".to_string()));
+        assert!(!skipped.contains(&"gemini-mysql".to_string()));
+
+        let included = test_names(parse().get_matches_from(vec![
+            "tfb",
+            "--test",
+            "gemini*",
+            "--include-broken",
+        ]));
+        assert!(included.contains(&"gemini-mysql".to_string()));
+    }
+
+    #[test]
+    fn it_can_limit_the_projects_selected() {
+        let matches = parse().get_matches_from(vec![
+            "tfb",
+            "--test",
+            "gemini",
+            "drogon",
+            "--max-tests",
+            "1",
+        ]);
+        let projects = list_projects_to_run(&matches).unwrap();
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].tests[0].get_name(), "gemini");
+    }
+
+    #[test]
+    fn it_can_interleave_projects() {
+        let matches = parse().get_matches_from(vec![
+            "tfb",
+            "--test",
+            "gemini",
+            "drogon",
+            "--type",
+            "json,plaintext",
+        ]);
+        let interleaved = interleave_projects(&list_projects_to_run(&matches).unwrap())
+            .iter()
+            .map(|project| {
+                let test = &project.tests[0];
+                let test_types = test.urls.keys().cloned().collect::<Vec<String>>();
+                format!("{}: {}", test.get_name(), test_types.join(","))
+            })
+            .collect::<Vec<String>>();
+        assert_eq!(
+            interleaved,
+            vec![
+                "gemini: json",
+                "drogon: json",
+                "gemini: plaintext",
+                "drogon: plaintext"
+            ]
+        );
+    }
+
+    #[test]
+    fn it_can_suggest_test_names() {
+        assert_eq!(get_edit_distance("gemini", "gemini"), 0);
+        assert_eq!(get_edit_distance("gemnii", "gemini"), 2);
+        assert_eq!(get_edit_distance("", "actix"), 5);
+        assert_eq!(get_edit_distance("kitten", "sitting"), 3);
+
+        let suggestions = get_test_name_suggestions("Gemni").unwrap();
+        assert_eq!(suggestions.first(), Some(&"gemini".to_string()));
+        assert!(get_test_name_suggestions("zzzzzzzz").unwrap().is_empty());
+    }
+
     #[test]
     fn it_can_list_all_tests_by_tag() {
         match list_tests_by_tag("Non-Existent Tag") {
@@ -363,4 +833,49 @@ mod tests {
             ),
         };
     }
+
+    #[test]
+    fn it_can_parse_config_files_in_parallel() {
+        let mut tfb_path = crate::io::get_tfb_dir().unwrap();
+        tfb_path.push("frameworks/*/*/config.toml");
+        let configs = parse_config_files(&tfb_path).unwrap();
+        let config_files = list_config_files().unwrap();
+        assert_eq!(configs.len(), config_files.len());
+        for (config, config_file) in configs.iter().zip(config_files) {
+            let project_dir = config_file.parent().unwrap().file_name().unwrap();
+            assert_eq!(config.project_name, project_dir.to_string_lossy());
+        }
+    }
+
+    #[test]
+    fn it_can_skip_invalid_config_files() {
+        let tfb_dir = TempDir::new("invalid-configs");
+        let write_config = |dir: &str, config: &str| {
+            let project_dir = tfb_dir.join("frameworks/Go").join(dir);
+            std::fs::create_dir_all(&project_dir).unwrap();
+            std::fs::write(project_dir.join("config.toml"), config).unwrap();
+        };
+        write_config(
+            "fiber",
+            "[framework]\nname = \"fiber\"\n\n[main]\nurls.json = \"/json\"\napproach = \"Realistic\"\nclassification = \"Micro\"\nplatform = \"Go\"\nwebserver = \"None\"\nos = \"Linux\"\nversus = \"go\"\n",
+        );
+        write_config("gin", "[framework]\nname = \"gin\"\n\n[main\n");
+        write_config(
+            "echo",
+            "[framework]\nname = \"echo\"\n\n[main]\nurls.json = 1\n",
+        );
+
+        let (configs, invalid_configs) =
+            parse_all_config_files(&tfb_dir.join("frameworks/*/*/config.toml"));
+
+        assert_eq!(configs.len(), 1);
+        assert_eq!(configs[0].project_name, "fiber");
+        let invalid_configs: Vec<String> = invalid_configs
+            .iter()
+            .map(|invalid| invalid.to_string())
+            .collect();
+        assert_eq!(invalid_configs.len(), 2);
+        assert!(invalid_configs[0].contains("/echo/config.toml:5: "));
+        assert!(invalid_configs[1].contains("/gin/config.toml:4: "));
+    }
 }
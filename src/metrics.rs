@@ -0,0 +1,161 @@
+use crate::io::Logger;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::Duration;
+
+/// Emits counters, gauges, and timers to a StatsD daemon (which may forward
+/// them to Graphite) over UDP.
+///
+/// Metrics are fire-and-forget; failing to send one never fails a run, and
+/// nothing is sent unless a StatsD address was given.
+#[derive(Debug)]
+pub struct Metrics {
+    socket: Option<UdpSocket>,
+    prefix: String,
+}
+impl Metrics {
+    /// Creates `Metrics` sending to the StatsD daemon at `address` (e.g.
+    /// `localhost:8125`), prefixing every metric name with `prefix`. If no
+    /// socket can be connected to `address`, this is logged via `logger` and
+    /// nothing is sent.
+    pub fn new(address: Option<&str>, prefix: &str, logger: &Logger) -> Self {
+        let socket = address.and_then(|address| match connect(address) {
+            Ok(socket) => Some(socket),
+            Err(error) => {
+                let _ = logger.error(format!(
+                    "Not sending metrics; could not connect to {}: {}",
+                    address, error
+                ));
+                None
+            }
+        });
+
+        Self {
+            socket,
+            prefix: prefix.trim_end_matches('.').to_string(),
+        }
+    }
+
+    /// Increments the counter `name` by one.
+    pub fn increment(&self, name: &str) {
+        self.send(name, "1", "c");
+    }
+
+    /// Sets the gauge `name` to `value`.
+    pub fn gauge(&self, name: &str, value: f32) {
+        self.send(name, &format!("{:.2}", value), "g");
+    }
+
+    /// Records `duration` to the timer `name`.
+    pub fn timing(&self, name: &str, duration: Duration) {
+        self.send(name, &duration.as_millis().to_string(), "ms");
+    }
+}
+
+/// Gets `segment` as a single segment of a metric name, replacing characters
+/// StatsD and Graphite treat specially.
+pub fn get_metric_segment(segment: &str) -> String {
+    segment
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-' => c,
+            _ => '_',
+        })
+        .collect()
+}
+
+//
+// PRIVATES
+//
+
+impl Metrics {
+    fn send(&self, name: &str, value: &str, metric_type: &str) {
+        if let Some(socket) = &self.socket {
+            let _ = socket.send(get_line(&self.prefix, name, value, metric_type).as_bytes());
+        }
+    }
+}
+
+/// Connects a socket to the first of the addresses `address` resolves to
+/// that it can, bound to the unspecified address of the same family.
+fn connect(address: &str) -> std::io::Result<UdpSocket> {
+    let mut last_error = None;
+    for address in address.to_socket_addrs()? {
+        let local_address = match address {
+            SocketAddr::V4(_) => "0.0.0.0:0",
+            SocketAddr::V6(_) => "[::]:0",
+        };
+        match UdpSocket::bind(local_address).and_then(|socket| {
+            socket.connect(address)?;
+            Ok(socket)
+        }) {
+            Ok(socket) => return Ok(socket),
+            Err(error) => last_error = Some(error),
+        }
+    }
+
+    Err(last_error.unwrap_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::NotFound, "no addresses resolved")
+    }))
+}
+
+fn get_line(prefix: &str, name: &str, value: &str, metric_type: &str) -> String {
+    if prefix.is_empty() {
+        format!("{}:{}|{}", name, value, metric_type)
+    } else {
+        format!("{}.{}:{}|{}", prefix, name, value, metric_type)
+    }
+}
+
+//
+// TESTS
+//
+
+#[cfg(test)]
+mod tests {
+    use crate::io::Logger;
+    use crate::metrics::{get_metric_segment, Metrics};
+    use std::net::UdpSocket;
+    use std::time::Duration;
+
+    #[test]
+    fn it_can_send_statsd_lines() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        server
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let address = server.local_addr().unwrap().to_string();
+        let metrics = Metrics::new(Some(&address), "tfb.", &Logger::default());
+
+        metrics.increment("tests.completed");
+        metrics.gauge(
+            &format!("rps.{}.json", get_metric_segment("gemini.mysql")),
+            1234.5,
+        );
+        metrics.timing("phase.build", Duration::from_millis(1500));
+
+        let mut buffer = [0; 128];
+        let mut receive = || {
+            let length = server.recv(&mut buffer).unwrap();
+            String::from_utf8_lossy(&buffer[..length]).to_string()
+        };
+        assert_eq!(receive(), "tfb.tests.completed:1|c");
+        assert_eq!(receive(), "tfb.rps.gemini_mysql.json:1234.50|g");
+        assert_eq!(receive(), "tfb.phase.build:1500|ms");
+    }
+
+    #[test]
+    fn it_can_send_statsd_lines_over_ipv6() {
+        let server = UdpSocket::bind("[::1]:0").unwrap();
+        server
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let address = server.local_addr().unwrap().to_string();
+        let metrics = Metrics::new(Some(&address), "", &Logger::default());
+
+        metrics.increment("tests.completed");
+
+        let mut buffer = [0; 128];
+        let length = server.recv(&mut buffer).unwrap();
+        assert_eq!(&buffer[..length], b"tests.completed:1|c");
+    }
+}
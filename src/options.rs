@@ -1,43 +1,129 @@
 use crate::benchmarker::modes;
+use crate::docker::docker_config::{parse_durations, parse_server_url};
+use crate::docker::resources::ResourceProfile;
+use crate::schedule::{parse_start_at, parse_window};
+use chrono::Local;
 use clap::{App, Arg};
+use uuid::Uuid;
 
 /// All the arguments that the CLI accepts.
 pub mod args {
     pub const AUDIT: &str = "Audit";
     pub const CLEAN: &str = "Clean";
     pub const QUIET: &str = "Quiet";
+    pub const LOG_FORMAT: &str = "Log Format";
     pub const RESULTS_NAME: &str = "Results Name";
     pub const RESULTS_ENVIRONMENT: &str = "Results Environment";
     pub const RESULTS_UPLOAD_URI: &str = "Results Upload URI";
+    pub const RUN_UUID: &str = "Run UUID";
+    pub const RESUME: &str = "Resume";
+    pub const REPORT: &str = "Report";
+    pub const LOAD_GENERATOR: &str = "Load Generator";
+    pub const OTLP_ENDPOINT: &str = "OTLP Endpoint";
+    pub const STATSD_ADDRESS: &str = "StatsD Address";
+    pub const STATSD_PREFIX: &str = "StatsD Prefix";
     pub const PARSE_RESULTS: &str = "Parse Results";
+    pub const COMPARE: &str = "Compare";
+    pub const REGRESSION_THRESHOLD: &str = "Regression Threshold";
+    pub const STATUS: &str = "Status";
     pub const TEST_NAMES: &str = "Test Name(s)";
     pub const TEST_DIRS: &str = "Test Dir(s)";
     pub const TEST_LANGUAGES: &str = "Test Language(s)";
     pub const TAGS: &str = "Tag(s)";
     pub const EXCLUDE: &str = "Exclude";
+    pub const INCLUDE_BROKEN: &str = "Include Broken";
+    pub const STRICT_CONFIG: &str = "Strict Config";
     pub const TYPES: &str = "Type(s)";
+    pub const MAX_TESTS: &str = "Max Tests";
     pub const MODE: &str = "Mode";
     pub const LIST_FRAMEWORKS: &str = "List Frameworks";
     pub const LIST_TESTS: &str = "List Tests";
     pub const LIST_TESTS_WITH_TAG: &str = "List Tests with Tag";
     pub const LIST_TESTS_FOR_FRAMEWORK: &str = "List Tests for Framework";
+    pub const PRINT_COMMANDS: &str = "Print Commands";
+    pub const DRY_RUN: &str = "Dry Run";
     pub const DURATION: &str = "Duration";
+    pub const DURATION_DEFAULT: &str = "15";
+    pub const STALL_TIMEOUT: &str = "Stall Timeout";
+    pub const STARTUP_TIMEOUT: &str = "Startup Timeout";
+    pub const BUILD_TIMEOUT: &str = "Build Timeout";
+    pub const PROFILE: &str = "Profile";
+    pub const SAMPLE_CONNECTIONS: &str = "Sample Connections";
+    pub const COUNT_QUERIES: &str = "Count Queries";
+    pub const CORE_DUMPS: &str = "Core Dumps";
+    pub const MOUNT_SOURCE: &str = "Mount Source";
+    pub const TUNE_MAX_CONCURRENCY: &str = "Tune Max Concurrency";
+    pub const INTERLEAVE: &str = "Interleave";
+    pub const RESTART_AFTER_VERIFICATION: &str = "Restart After Verification";
+    pub const RESTART_BETWEEN_TYPES: &str = "Restart Between Types";
+    pub const CALIBRATE: &str = "Calibrate";
+    pub const BASELINE_INTERVAL: &str = "Baseline Interval";
+    pub const BUILD_JOBS: &str = "Build Jobs";
+    pub const PAUSE_FILE: &str = "Pause File";
+    pub const START_AT: &str = "Start At";
+    pub const SCHEDULE_WINDOWS: &str = "Schedule Window(s)";
+    pub const ADAPTIVE_CV: &str = "Adaptive CV";
+    pub const ADAPTIVE_INTERVAL: &str = "Adaptive Interval";
+    pub const ADAPTIVE_MAX_DURATION: &str = "Adaptive Max Duration";
+    pub const WARMUP_TOLERANCE: &str = "Warmup Tolerance";
+    pub const MAX_WARMUPS: &str = "Max Warmups";
     pub const SERVER_DOCKER_HOST: &str = "Server Docker Host";
     pub const DOCKER_HOST_DEFAULT: &str = "localhost";
     pub const SERVER_HOST: &str = "Server Host";
     pub const SERVER_HOST_DEFAULT: &str = "tfb-server";
+    pub const OVERRIDE_SERVER_URL: &str = "Override Server URL";
     pub const DATABASE_DOCKER_HOST: &str = "Database Docker Host";
     pub const DATABASE_HOST: &str = "Database Host";
     pub const DATABASE_HOST_DEFAULT: &str = "tfb-database";
+    pub const DATABASE_HOSTS: &str = "Database Host(s)";
+    pub const DATABASE_TMPFS: &str = "Database Tmpfs";
+    pub const DATABASE_TMPFS_SIZE: &str = "Database Tmpfs Size";
+    pub const DATABASE_VERSIONS: &str = "Database Version(s)";
+    pub const SHM_SIZE: &str = "Shm Size";
+    pub const TMPFS: &str = "Tmpfs";
     pub const CLIENT_DOCKER_HOST: &str = "Client Docker Host";
     pub const CLIENT_HOST: &str = "Client Host";
     pub const CLIENT_HOST_DEFAULT: &str = "tfb-client";
     pub const CONCURRENCY_LEVELS: &str = "Concurrency Levels";
     pub const PIPELINE_CONCURRENCY_LEVELS: &str = "Pipeline Concurrency Levels";
+    pub const MAX_CONCURRENCY: &str = "Max Concurrency";
+    pub const LEVELS_SUBSET: &str = "Levels Subset";
     pub const QUERY_LEVELS: &str = "Query Levels";
     pub const CACHED_QUERY_LEVELS: &str = "Cached Query Levels";
+    pub const QUERY_LEVELS_SUBSET: &str = "Query Levels Subset";
+    pub const MAX_QUERIES: &str = "Max Queries";
+    pub const WORLD_ROWS: &str = "World Rows";
+    pub const WEBSOCKET_CONNECTIONS: &str = "WebSocket Connections";
+    pub const WEBSOCKET_MESSAGE_RATE: &str = "WebSocket Message Rate";
+    pub const VERIFIER_ENV: &str = "Verifier Env";
     pub const NETWORK_MODE: &str = "Network Mode";
+    pub const SERVER_NETWORK_MODE: &str = "Server Network Mode";
+    pub const DATABASE_NETWORK_MODE: &str = "Database Network Mode";
+    pub const CLIENT_NETWORK_MODE: &str = "Client Network Mode";
+    pub const ISOLATE_NETWORKS: &str = "Isolate Networks";
+    pub const PORT_RETRIES: &str = "Port Retries";
+    pub const VERIFY_RETRIES: &str = "Verify Retries";
+    pub const EXPECTED_LATENCY: &str = "Expected Latency";
+    pub const EXPECTED_BANDWIDTH: &str = "Expected Bandwidth";
+    pub const EXPECTED_DAEMON_PROFILE: &str = "Expected Daemon Profile";
+    pub const MAX_CLOCK_SKEW: &str = "Max Clock Skew";
+    pub const NETEM_DELAY: &str = "Netem Delay";
+    pub const NETEM_JITTER: &str = "Netem Jitter";
+    pub const NETEM_LOSS: &str = "Netem Loss";
+    pub const RESOURCE_PROFILE: &str = "Resource Profile";
     pub const DOCKER_CLEANUP: &str = "Auto-Clean Docker Containers and Images";
+    pub const IMAGE_PREFIX: &str = "Image Prefix";
+    pub const IMAGE_PREFIX_DEFAULT: &str = "tfb.test.";
+}
+
+pub mod load_generators {
+    pub const WRK: &str = "wrk";
+    pub const WRK2: &str = "wrk2";
+}
+
+pub mod log_formats {
+    pub const TEXT: &str = "text";
+    pub const JSON: &str = "json";
 }
 
 pub mod network_modes {
@@ -53,6 +139,12 @@ pub fn parse<'app>() -> App<'app> {
         .version(VERSION)
         .author("Mike Smith <msmith@techempower.com>")
         .about("The toolset for the TechEmpower Framework Benchmarks.")
+        .after_help(
+            "Options may also be given via the TFB_* environment variables shown with them \
+            (e.g. TFB_DURATION=30); arguments on the command line take precedence. A flag's \
+            variable enables it when set to any value, and multiple values are separated by \
+            commas.",
+        )
         // Suite options
         .arg(
             Arg::new(args::AUDIT)
@@ -76,6 +168,17 @@ pub fn parse<'app>() -> App<'app> {
                 .takes_value(false)
                 .short('q')
                 .long("quiet")
+                .multiple_occurrences(true)
+                .env("TFB_QUIET")
+        )
+        .arg(
+            Arg::new(args::LOG_FORMAT)
+                .about("The format of the messages printed to stdout; json prints a JSON record (timestamp, level, test, message) per line, for shipping to a log aggregator")
+                .long("log-format")
+                .takes_value(true)
+                .default_value(log_formats::TEXT)
+                .possible_values(&[log_formats::TEXT, log_formats::JSON])
+                .env("TFB_LOG_FORMAT")
         )
         .arg(
             Arg::new(args::RESULTS_NAME)
@@ -85,6 +188,7 @@ pub fn parse<'app>() -> App<'app> {
                 .takes_value(true)
                 .long("results-name")
                 .default_value("(unspecified, datetime = %Y-%m-%d %H:%M:%S)")
+                .env("TFB_RESULTS_NAME")
         )
         .arg(
             Arg::new(args::RESULTS_ENVIRONMENT)
@@ -92,30 +196,122 @@ pub fn parse<'app>() -> App<'app> {
                 .long("results-environment")
                 .takes_value(true)
                 .default_value("(unspecified, hostname = todo")
+                .env("TFB_RESULTS_ENVIRONMENT")
         )
         .arg(
             Arg::new(args::RESULTS_UPLOAD_URI)
-                .about("A URI where the in-progress results.json file will be POSTed periodically")
+                .about("A URI where the in-progress results.json file will be POSTed after each test type and at the end of the run")
                 .long("results-upload-uri")
+                .takes_value(true)
+                .env("TFB_RESULTS_UPLOAD_URI")
+        )
+        .arg(
+            Arg::new(args::RUN_UUID)
+                .about("The UUID of this run, for systems which register runs before they start; random if not given")
+                .long("run-uuid")
+                .takes_value(true)
+                .validator(Uuid::parse_str)
+                .env("TFB_RUN_UUID")
+        )
+        .arg(
+            Arg::new(args::RESUME)
+                .about("Resumes the interrupted run whose results are in the given directory (e.g. results/20200820150301), skipping the frameworks it completed")
+                .long("resume")
+                .takes_value(true)
+                .env("TFB_RESUME")
+        )
+        .arg(
+            Arg::new(args::REPORT)
+                .about("Also writes a report as <format>=<path>: junit writes the verification summary as JUnit XML, and html renders the results of a benchmark into a self-contained HTML page")
+                .long("report")
+                .takes_value(true)
+                .multiple(true)
+                .use_delimiter(true)
+                .validator(|report| match report.split_once('=') {
+                    Some(("junit", path)) | Some(("html", path)) if !path.is_empty() => Ok(()),
+                    _ => Err(format!("{} is not junit=<path> or html=<path>", report)),
+                })
+                .env("TFB_REPORT")
+        )
+        .arg(
+            Arg::new(args::OTLP_ENDPOINT)
+                .about("The base URL of an OTLP/HTTP collector (e.g. http://localhost:4318) to which a trace of the run is exported")
+                .long("otlp-endpoint")
+                .takes_value(true)
+                .env("TFB_OTLP_ENDPOINT")
+        )
+        .arg(
+            Arg::new(args::STATSD_ADDRESS)
+                .about("The host:port of a StatsD daemon to which run metrics (tests completed, failures, requests/sec, phase durations) are sent")
+                .long("statsd-address")
+                .takes_value(true)
+                .env("TFB_STATSD_ADDRESS")
+        )
+        .arg(
+            Arg::new(args::STATSD_PREFIX)
+                .about("The prefix of every metric sent to --statsd-address")
+                .long("statsd-prefix")
+                .default_value("tfb")
+                .env("TFB_STATSD_PREFIX")
         )
         .arg(
             Arg::new(args::PARSE_RESULTS)
-                .about("Parses the results of the given timestamp and merges that with the latest results")
+                .about("Parses the benchmark logs of the run of the given timestamp (e.g. 20200619191252) and merges them into its results, completing the results of a run which crashed")
                 .long("parse")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::new(args::COMPARE)
+                .about("Compares the results in the second results directory to those in the first, printing the change in the requests/sec and latency of each test type of each framework, and fails if any regressed")
+                .long("compare")
+                .takes_value(true)
+                .number_of_values(2)
+                .value_names(&["BEFORE", "AFTER"])
+        )
+        .arg(
+            Arg::new(args::REGRESSION_THRESHOLD)
+                .about("The percentage by which requests/sec may drop, or latency rise, before --compare flags a regression")
+                .long("regression-threshold")
+                .default_value("5")
+                .validator(|threshold| match str::parse::<f64>(threshold) {
+                    Ok(threshold) if threshold >= 0f64 => Ok(()),
+                    _ => Err(format!("{} is not a non-negative percentage", threshold)),
+                })
+                .env("TFB_REGRESSION_THRESHOLD")
+        )
+        .arg(
+            Arg::new(args::STATUS)
+                .about("Prints the progress of the run whose results are in the given directory, or else of the latest run, including its failures so far")
+                .long("status")
+                .takes_value(true)
+                .min_values(0)
+                .value_name("RESULTS_DIR")
         )
         .arg(
             Arg::new(args::DOCKER_CLEANUP)
                 .about("Automatically remove containers and images after they have exited")
                 .long("rm")
+                .multiple_occurrences(true)
+                .env("TFB_RM")
+        )
+        .arg(
+            Arg::new(args::IMAGE_PREFIX)
+                .about("The prefix of the tag of each test's image, which may include a registry and `{run}` for the run's id (e.g. 'registry.example.com/tfb-{run}/'), so concurrent runs do not overwrite each other's images")
+                .long("image-prefix")
+                .takes_value(true)
+                .default_value(args::IMAGE_PREFIX_DEFAULT)
+                .env("TFB_IMAGE_PREFIX")
         )
         // Test options
         .arg(
             Arg::new(args::TEST_NAMES)
-                .about("Name(s) of the test(s) to run")
+                .about("Name(s) of the test(s) to run; matched ignoring case, and may be glob patterns (e.g. 'actix*')")
                 .long("test")
                 .short('t')
                 .takes_value(true)
                 .multiple(true)
+                .use_delimiter(true)
+                .env("TFB_TEST")
         )
         .arg(
             Arg::new(args::TEST_DIRS)
@@ -124,6 +320,8 @@ pub fn parse<'app>() -> App<'app> {
                 .short('d')
                 .takes_value(true)
                 .multiple(true)
+                .use_delimiter(true)
+                .env("TFB_TEST_DIR")
         )
         .arg(
             Arg::new(args::TEST_LANGUAGES)
@@ -132,6 +330,8 @@ pub fn parse<'app>() -> App<'app> {
                 .short('l')
                 .takes_value(true)
                 .multiple(true)
+                .use_delimiter(true)
+                .env("TFB_TEST_LANG")
         )
         .arg(
             Arg::new(args::TAGS)
@@ -139,6 +339,8 @@ pub fn parse<'app>() -> App<'app> {
                 .long("tag")
                 .takes_value(true)
                 .multiple(true)
+                .use_delimiter(true)
+                .env("TFB_TAG")
         )
         .arg(
             Arg::new(args::EXCLUDE)
@@ -146,6 +348,22 @@ pub fn parse<'app>() -> App<'app> {
                 .long("exclude")
                 .takes_value(true)
                 .multiple(true)
+                .use_delimiter(true)
+                .env("TFB_EXCLUDE")
+        )
+        .arg(
+            Arg::new(args::INCLUDE_BROKEN)
+                .about("Selects tests tagged 'broken', which are otherwise skipped (except in debug mode)")
+                .long("include-broken")
+                .multiple_occurrences(true)
+                .env("TFB_INCLUDE_BROKEN")
+        )
+        .arg(
+            Arg::new(args::STRICT_CONFIG)
+                .about("Fails on any invalid config.toml, rather than reporting and skipping them")
+                .long("strict-config")
+                .multiple_occurrences(true)
+                .env("TFB_STRICT_CONFIG")
         )
         .arg(
             Arg::new(args::TYPES)
@@ -153,6 +371,16 @@ pub fn parse<'app>() -> App<'app> {
                 .long("type")
                 .takes_value(true)
                 .multiple(true)
+                .use_delimiter(true)
+                .env("TFB_TYPE")
+        )
+        .arg(
+            Arg::new(args::MAX_TESTS)
+                .about("The most projects to select, in the order they would otherwise be run; e.g. to verify a representative slice of the tests")
+                .long("max-tests")
+                .takes_value(true)
+                .validator(validate_positive_integer)
+                .env("TFB_MAX_TESTS")
         )
         .arg(
             Arg::new(args::MODE)
@@ -161,7 +389,8 @@ pub fn parse<'app>() -> App<'app> {
                 .long("mode")
                 .short('m')
                 .takes_value(true)
-                .possible_values(&[modes::BENCHMARK, modes::VERIFY, modes::CICD, modes::DEBUG])
+                .possible_values(&[modes::BENCHMARK, modes::VERIFY, modes::CICD, modes::DEBUG, modes::TUNE])
+                .env("TFB_MODE")
         )
         .arg(
             Arg::new(args::LIST_FRAMEWORKS)
@@ -185,48 +414,309 @@ pub fn parse<'app>() -> App<'app> {
                 .long("list-tag")
                 .takes_value(true)
         )
+        .arg(
+            Arg::new(args::PRINT_COMMANDS)
+                .about("Starts the tests and prints the primer, warmup, and benchmark commands for each test type without running them")
+                .long("print-commands")
+        )
+        .arg(
+            Arg::new(args::DRY_RUN)
+                .about("Prints the images which would be built, the containers and networks which would be created, and the benchmark commands which would be requested, without touching Docker")
+                .long("dry-run")
+        )
         // Benchmark Options
         .arg(
             Arg::new(args::DURATION)
-                .about("The duration in seconds for which each benchmark should be measured")
+                .about("The duration in seconds for which each benchmark should be measured; may be given per test type (e.g. 'json=15,plaintext=30'), alongside a bare duration for the others")
                 .long("duration")
-                .default_value("15")
+                .default_value(args::DURATION_DEFAULT)
+                .validator(parse_durations)
+                .env("TFB_DURATION")
+        )
+        .arg(
+            Arg::new(args::STALL_TIMEOUT)
+                .about("Seconds past the expected duration without any benchmarker output after which a benchmark command is considered stalled")
+                .long("stall-timeout")
+                .default_value("30")
+                .validator(str::parse::<u32>)
+                .env("TFB_STALL_TIMEOUT")
+        )
+        .arg(
+            Arg::new(args::STARTUP_TIMEOUT)
+                .about("Seconds to wait for an application server to accept requests after it is started; a test's `startup_timeout` overrides it")
+                .long("startup-timeout")
+                .default_value("60")
+                .validator(str::parse::<u32>)
+                .env("TFB_STARTUP_TIMEOUT")
+        )
+        .arg(
+            Arg::new(args::BUILD_TIMEOUT)
+                .about("Seconds after which building an image is abandoned; a test's `build_timeout` overrides it [default: none]")
+                .long("build-timeout")
+                .takes_value(true)
+                .validator(str::parse::<u32>)
+                .env("TFB_BUILD_TIMEOUT")
+        )
+        .arg(
+            Arg::new(args::PROFILE)
+                .about("Records the application server with perf during the highest concurrency benchmark command and stores the data and a flamegraph in the results dir")
+                .long("profile")
+                .multiple_occurrences(true)
+                .env("TFB_PROFILE")
+        )
+        .arg(
+            Arg::new(args::SAMPLE_CONNECTIONS)
+                .about("Samples the connections open to the database during each benchmark command of the database test types and records their count in the results")
+                .long("sample-connections")
+                .multiple_occurrences(true)
+                .env("TFB_SAMPLE_CONNECTIONS")
+        )
+        .arg(
+            Arg::new(args::COUNT_QUERIES)
+                .about("Counts the statements the database executes during each benchmark command and records the queries per request in the results, flagging tests which make fewer queries than their test type requires")
+                .long("count-queries")
+                .multiple_occurrences(true)
+                .env("TFB_COUNT_QUERIES")
+        )
+        .arg(
+            Arg::new(args::CORE_DUMPS)
+                .about("Collects core dumps from crashing application servers into each test's results dir; requires a local server Docker host and sets the host's core_pattern until the run ends")
+                .long("core-dumps")
+                .multiple_occurrences(true)
+                .env("TFB_CORE_DUMPS")
+        )
+        .arg(
+            Arg::new(args::MOUNT_SOURCE)
+                .about("In debug mode, bind-mounts the framework's directory over the application container's copy at the test's `source_mount`, so edits take effect by restarting the container instead of rebuilding the image; requires a local server Docker host")
+                .long("mount-source")
+                .multiple_occurrences(true)
+                .env("TFB_MOUNT_SOURCE")
+        )
+        .arg(
+            Arg::new(args::ADAPTIVE_CV)
+                .about("Runs each benchmark command in intervals until the coefficient of variation of their requests/sec falls below this threshold (e.g. 0.05)")
+                .long("adaptive-cv")
+                .takes_value(true)
+                .validator(validate_positive_number)
+                .env("TFB_ADAPTIVE_CV")
+        )
+        .arg(
+            Arg::new(args::ADAPTIVE_INTERVAL)
+                .about("The duration in seconds of each interval when --adaptive-cv is given")
+                .long("adaptive-interval")
+                .default_value("5")
+                .validator(validate_positive_integer)
+                .env("TFB_ADAPTIVE_INTERVAL")
+        )
+        .arg(
+            Arg::new(args::ADAPTIVE_MAX_DURATION)
+                .about("The maximum duration in seconds for which each benchmark command is run when --adaptive-cv is given; defaults to three times the duration of its test type")
+                .long("adaptive-max-duration")
+                .takes_value(true)
+                .validator(validate_positive_integer)
+                .env("TFB_ADAPTIVE_MAX_DURATION")
+        )
+        .arg(
+            Arg::new(args::WARMUP_TOLERANCE)
+                .about("Repeats the warmup command until its requests/sec changes by less than this fraction (e.g. 0.05) from the previous warmup, or --max-warmups is reached")
+                .long("warmup-tolerance")
+                .takes_value(true)
+                .validator(validate_positive_number)
+                .env("TFB_WARMUP_TOLERANCE")
+        )
+        .arg(
+            Arg::new(args::MAX_WARMUPS)
+                .about("The most times the warmup command is run when --warmup-tolerance is given")
+                .long("max-warmups")
+                .default_value("5")
+                .validator(validate_positive_integer)
+                .env("TFB_MAX_WARMUPS")
+        )
+        .arg(
+            Arg::new(args::TUNE_MAX_CONCURRENCY)
+                .about("The highest concurrency to try when searching for peak throughput in tune mode")
+                .long("tune-max-concurrency")
+                .default_value("16384")
+                .validator(validate_level)
+                .env("TFB_TUNE_MAX_CONCURRENCY")
+        )
+        .arg(
+            Arg::new(args::INTERLEAVE)
+                .about("Benchmarks one test type of every test at a time (restarting each test per type) rather than every type of one test at a time, to spread time-varying noise across the tests compared")
+                .long("interleave")
+                .multiple_occurrences(true)
+                .env("TFB_INTERLEAVE")
+        )
+        .arg(
+            Arg::new(args::RESTART_AFTER_VERIFICATION)
+                .about("Restarts the application server after the verifier's checks, which some frameworks degrade under, and before benchmarking each test type")
+                .long("restart-after-verification")
+                .multiple_occurrences(true)
+                .env("TFB_RESTART_AFTER_VERIFICATION")
+        )
+        .arg(
+            Arg::new(args::RESTART_BETWEEN_TYPES)
+                .about("Restarts the application server between benchmarking each test type, isolating the effects of one test type (e.g. a memory leak) from the next")
+                .long("restart-between-types")
+                .multiple_occurrences(true)
+                .env("TFB_RESTART_BETWEEN_TYPES")
+        )
+        .arg(
+            Arg::new(args::CALIBRATE)
+                .about("Benchmarks a static file served by nginx before any test and records it in the results, to detect client-side saturation or environment drift across runs")
+                .long("calibrate")
+                .multiple_occurrences(true)
+                .env("TFB_CALIBRATE")
+        )
+        .arg(
+            Arg::new(args::BASELINE_INTERVAL)
+                .about("Benchmarks the static file served by nginx (see --calibrate) before the first test, after every given number of tests, and after the last, recording its drift over the run as a control for the stability of the environment")
+                .long("baseline-interval")
+                .takes_value(true)
+                .validator(str::parse::<u32>)
+                .env("TFB_BASELINE_INTERVAL")
+        )
+        .arg(
+            Arg::new(args::BUILD_JOBS)
+                .about("The number of upcoming tests whose images are built in the background while the current test is benchmarked; the builds share the server's machine with the test being benchmarked")
+                .long("build-jobs")
+                .default_value("0")
+                .validator(str::parse::<usize>)
+                .env("TFB_BUILD_JOBS")
+        )
+        .arg(
+            Arg::new(args::PAUSE_FILE)
+                .about("A file which, while it exists, pauses the run after the current test completes; defaults to `pause` in the run's results dir")
+                .long("pause-file")
+                .takes_value(true)
+                .env("TFB_PAUSE_FILE")
+        )
+        .arg(
+            Arg::new(args::START_AT)
+                .about("The local time (HH:MM, or YYYY-MM-DD HH:MM) at which to start benchmarking; the run waits until then")
+                .long("start-at")
+                .takes_value(true)
+                .validator(|start_at| parse_start_at(start_at, Local::now().naive_local()))
+                .env("TFB_START_AT")
+        )
+        .arg(
+            Arg::new(args::SCHEDULE_WINDOWS)
+                .about("Daily local time window(s) (HH:MM-HH:MM, e.g. 22:00-06:00) during which tests may be benchmarked; outside of them, the run pauses between tests")
+                .long("schedule-window")
+                .takes_value(true)
+                .multiple(true)
+                .use_delimiter(true)
+                .validator(parse_window)
+                .env("TFB_SCHEDULE_WINDOW")
         )
         .arg(
             Arg::new(args::SERVER_DOCKER_HOST)
-                .about("Hostname/IP for the Server Docker daemon")
+                .about("Hostname/IP[:port] (port 2375 by default), tcp:// URL, or unix:///var/run/docker.sock of the Server Docker daemon")
                 .long("server-docker-host")
                 .default_value(args::DOCKER_HOST_DEFAULT)
+                .env("TFB_SERVER_DOCKER_HOST")
         )
         .arg(
             Arg::new(args::DATABASE_DOCKER_HOST)
-                .about("Hostname/IP for the Database Docker daemon")
+                .about("Hostname/IP[:port] (port 2375 by default), tcp:// URL, or unix:///var/run/docker.sock of the Database Docker daemon")
                 .long("database-docker-host")
                 .default_value(args::DOCKER_HOST_DEFAULT)
+                .env("TFB_DATABASE_DOCKER_HOST")
         )
         .arg(
             Arg::new(args::CLIENT_DOCKER_HOST)
-                .about("Hostname/IP for the Client Docker daemon")
+                .about("Hostname/IP[:port] (port 2375 by default), tcp:// URL, or unix:///var/run/docker.sock of the Client Docker daemon")
                 .long("client-docker-host")
                 .default_value(args::DOCKER_HOST_DEFAULT)
+                .env("TFB_CLIENT_DOCKER_HOST")
         )
         .arg(
             Arg::new(args::SERVER_HOST)
                 .about("Hostname/IP for the application server")
                 .long("server-host")
                 .default_value(args::SERVER_HOST_DEFAULT)
+                .env("TFB_SERVER_HOST")
+        )
+        .arg(
+            Arg::new(args::OVERRIDE_SERVER_URL)
+                .about("The [http://]IP:PORT (e.g. a NAT address) at which the client reaches the application server, in place of the server host and the port Docker reports; HTTPS is not supported")
+                .long("override-server-url")
+                .takes_value(true)
+                .validator(parse_server_url)
+                .env("TFB_OVERRIDE_SERVER_URL")
         )
         .arg(
             Arg::new(args::DATABASE_HOST)
                 .about("Hostname/IP for the database server")
                 .long("database-host")
                 .default_value(args::DATABASE_HOST_DEFAULT)
+                .env("TFB_DATABASE_HOST")
+        )
+        .arg(
+            Arg::new(args::DATABASE_HOSTS)
+                .about("List of DATABASE=HOST machines (e.g. postgres=10.0.0.5) on which to start and reach each database in host network mode, rather than the database host; the Docker daemons must then be reached over TCP")
+                .long("database-hosts")
+                .takes_value(true)
+                .multiple(true)
+                .use_delimiter(true)
+                .validator(|host| match host.split_once('=') {
+                    Some((database, host)) if !database.is_empty() && !host.is_empty() => Ok(()),
+                    _ => Err(format!("{} is not DATABASE=HOST", host)),
+                })
+                .env("TFB_DATABASE_HOSTS")
+        )
+        .arg(
+            Arg::new(args::DATABASE_TMPFS)
+                .about("Mounts the database's data directory on tmpfs, removing disk variability from database test results")
+                .long("database-tmpfs")
+                .multiple_occurrences(true)
+                .env("TFB_DATABASE_TMPFS")
+        )
+        .arg(
+            Arg::new(args::DATABASE_TMPFS_SIZE)
+                .about("The size in megabytes of the tmpfs mounted by --database-tmpfs")
+                .long("database-tmpfs-size")
+                .default_value("4096")
+                .validator(validate_positive_integer)
+                .env("TFB_DATABASE_TMPFS_SIZE")
+        )
+        .arg(
+            Arg::new(args::DATABASE_VERSIONS)
+                .about("List of DATABASE=VERSION image versions (e.g. postgres=16) with which to run each database, rather than the latest")
+                .long("database-version")
+                .takes_value(true)
+                .multiple(true)
+                .use_delimiter(true)
+                .validator(|version| match version.split_once('=') {
+                    Some((database, version)) if !database.is_empty() && !version.is_empty() => Ok(()),
+                    _ => Err(format!("{} is not DATABASE=VERSION", version)),
+                })
+                .env("TFB_DATABASE_VERSION")
+        )
+        .arg(
+            Arg::new(args::SHM_SIZE)
+                .about("The size in megabytes of /dev/shm in the application and database containers")
+                .long("shm-size")
+                .takes_value(true)
+                .validator(validate_positive_integer)
+                .env("TFB_SHM_SIZE")
+        )
+        .arg(
+            Arg::new(args::TMPFS)
+                .about("List of tmpfs mounts, as path[:size in megabytes], for the application and database containers")
+                .long("tmpfs")
+                .takes_value(true)
+                .multiple(true)
+                .use_delimiter(true)
+                .validator(validate_tmpfs_mount)
+                .env("TFB_TMPFS")
         )
         .arg(
             Arg::new(args::CLIENT_HOST)
                 .about("Hostname/IP for the client server")
                 .long("client-host")
                 .default_value(args::CLIENT_HOST_DEFAULT)
+                .env("TFB_CLIENT_HOST")
         )
         .arg(
             Arg::new(args::CONCURRENCY_LEVELS)
@@ -235,6 +725,9 @@ pub fn parse<'app>() -> App<'app> {
                 .takes_value(true)
                 .multiple(true)
                 .default_values(&["16", "32", "64", "128", "256", "512"])
+                .use_delimiter(true)
+                .validator(validate_level)
+                .env("TFB_CONCURRENCY_LEVELS")
         )
         .arg(
             Arg::new(args::PIPELINE_CONCURRENCY_LEVELS)
@@ -243,6 +736,27 @@ pub fn parse<'app>() -> App<'app> {
                 .takes_value(true)
                 .multiple(true)
                 .default_values(&["256", "1024", "4096", "16384"])
+                .use_delimiter(true)
+                .validator(validate_level)
+                .env("TFB_PIPELINE_CONCURRENCY_LEVELS")
+        )
+        .arg(
+            Arg::new(args::MAX_CONCURRENCY)
+                .about("Drops the concurrency and pipeline concurrency levels above this, for quick partial sweeps on underpowered hardware")
+                .long("max-concurrency")
+                .takes_value(true)
+                .validator(validate_level)
+                .env("TFB_MAX_CONCURRENCY")
+        )
+        .arg(
+            Arg::new(args::LEVELS_SUBSET)
+                .about("List of the concurrency and pipeline concurrency levels to keep; the others are dropped")
+                .long("levels-subset")
+                .takes_value(true)
+                .multiple(true)
+                .use_delimiter(true)
+                .validator(validate_level)
+                .env("TFB_LEVELS_SUBSET")
         )
         .arg(
             Arg::new(args::QUERY_LEVELS)
@@ -251,6 +765,9 @@ pub fn parse<'app>() -> App<'app> {
                 .takes_value(true)
                 .multiple(true)
                 .default_values(&["1", "5", "10", "15", "20"])
+                .use_delimiter(true)
+                .validator(validate_level)
+                .env("TFB_QUERY_LEVELS")
         )
         .arg(
             Arg::new(args::CACHED_QUERY_LEVELS)
@@ -259,28 +776,389 @@ pub fn parse<'app>() -> App<'app> {
                 .takes_value(true)
                 .multiple(true)
                 .default_values(&["1", "10", "20", "50", "100"])
+                .use_delimiter(true)
+                .validator(validate_level)
+                .env("TFB_CACHED_QUERY_LEVELS")
+        )
+        .arg(
+            Arg::new(args::QUERY_LEVELS_SUBSET)
+                .about("List of the query and cached query levels to keep; the others are dropped")
+                .long("query-levels-subset")
+                .takes_value(true)
+                .multiple(true)
+                .use_delimiter(true)
+                .validator(validate_level)
+                .env("TFB_QUERY_LEVELS_SUBSET")
+        )
+        .arg(
+            Arg::new(args::MAX_QUERIES)
+                .about("The number of queries to which the queries parameter of the query and update tests is clamped")
+                .long("max-queries")
+                .default_value("500")
+                .validator(validate_positive_integer)
+                .env("TFB_MAX_QUERIES")
+        )
+        .arg(
+            Arg::new(args::WORLD_ROWS)
+                .about("The number of rows in the World table of the test databases, for scaled-down or scaled-up datasets")
+                .long("world-rows")
+                .default_value("10000")
+                .validator(validate_positive_integer)
+                .env("TFB_WORLD_ROWS")
+        )
+        .arg(
+            Arg::new(args::WEBSOCKET_CONNECTIONS)
+                .about("List of WebSocket connection counts to benchmark")
+                .long("websocket-connections")
+                .takes_value(true)
+                .multiple(true)
+                .default_values(&["16", "64", "256"])
+                .use_delimiter(true)
+                .validator(validate_level)
+                .env("TFB_WEBSOCKET_CONNECTIONS")
+        )
+        .arg(
+            Arg::new(args::WEBSOCKET_MESSAGE_RATE)
+                .about("Messages per second sent on each WebSocket connection")
+                .long("websocket-message-rate")
+                .default_value("100")
+                .validator(validate_positive_integer)
+                .env("TFB_WEBSOCKET_MESSAGE_RATE")
+        )
+        .arg(
+            Arg::new(args::VERIFIER_ENV)
+                .about("A KEY=VALUE environment variable passed to the verifier, after (and so overriding) the toolset's own; may be repeated, and the VALUE may contain commas")
+                .long("verifier-env")
+                .takes_value(true)
+                .multiple_occurrences(true)
+                .validator(|variable| match variable.split_once('=') {
+                    Some((key, _)) if !key.is_empty() => Ok(()),
+                    _ => Err(format!("{} is not KEY=VALUE", variable)),
+                })
+                .env("TFB_VERIFIER_ENV")
         )
         // Network options
+        .arg(
+            Arg::new(args::LOAD_GENERATOR)
+                .about("The load generator of HTTP test types; wrk2 is asked for commands at a constant throughput and reports latencies corrected for coordinated omission")
+                .long("load-generator")
+                .takes_value(true)
+                .default_value(load_generators::WRK)
+                .possible_values(&[load_generators::WRK, load_generators::WRK2])
+                .env("TFB_LOAD_GENERATOR")
+        )
         .arg(
             Arg::new(args::NETWORK_MODE)
-                .about("The network mode with which Docker should be run")
+                .about("The network mode with which Docker should be run, unless overridden for a role")
                 .long("network-mode")
                 .takes_value(true)
                 .default_value(network_modes::BRIDGE)
                 .possible_values(&[network_modes::BRIDGE, network_modes::HOST])
+                .env("TFB_NETWORK_MODE")
+        )
+        .arg(
+            Arg::new(args::SERVER_NETWORK_MODE)
+                .about("The network mode of the application server containers; defaults to --network-mode")
+                .long("server-network-mode")
+                .takes_value(true)
+                .possible_values(&[network_modes::BRIDGE, network_modes::HOST])
+                .env("TFB_SERVER_NETWORK_MODE")
+        )
+        .arg(
+            Arg::new(args::DATABASE_NETWORK_MODE)
+                .about("The network mode of the database containers; defaults to --network-mode. A bridged database cannot be reached from roles in host mode")
+                .long("database-network-mode")
+                .takes_value(true)
+                .possible_values(&[network_modes::BRIDGE, network_modes::HOST])
+                .env("TFB_DATABASE_NETWORK_MODE")
+        )
+        .arg(
+            Arg::new(args::CLIENT_NETWORK_MODE)
+                .about("The network mode of the verifier and benchmarker containers; defaults to --network-mode")
+                .long("client-network-mode")
+                .takes_value(true)
+                .possible_values(&[network_modes::BRIDGE, network_modes::HOST])
+                .env("TFB_CLIENT_NETWORK_MODE")
+        )
+        .arg(
+            Arg::new(args::ISOLATE_NETWORKS)
+                .about("Runs each test on a fresh bridge network, removed when the test completes, rather than the shared TFBNetwork")
+                .long("isolate-networks")
+                .multiple_occurrences(true)
+                .env("TFB_ISOLATE_NETWORKS")
+        )
+        .arg(
+            Arg::new(args::PORT_RETRIES)
+                .about("Number of times to recreate the application container on another port when starting it fails due to a host port conflict; only in bridge network mode, as Docker binds no host ports for the server in host network mode")
+                .long("port-retries")
+                .default_value("0")
+                .validator(str::parse::<u32>)
+                .env("TFB_PORT_RETRIES")
+        )
+        .arg(
+            Arg::new(args::VERIFY_RETRIES)
+                .about("Number of times to recreate the verifier and verify a test type again when verifying it fails (e.g. during a slow warmup) before recording the failure")
+                .long("verify-retries")
+                .default_value("0")
+                .validator(str::parse::<u32>)
+                .env("TFB_VERIFY_RETRIES")
+        )
+        .arg(
+            Arg::new(args::EXPECTED_LATENCY)
+                .about("The expected latency in milliseconds between hosts in a multi-machine setup; measurements well above it are warned about")
+                .long("expected-latency")
+                .takes_value(true)
+                .validator(validate_positive_number)
+                .env("TFB_EXPECTED_LATENCY")
+        )
+        .arg(
+            Arg::new(args::EXPECTED_BANDWIDTH)
+                .about("The expected bandwidth in Gbps between hosts in a multi-machine setup; measurements well below it are warned about")
+                .long("expected-bandwidth")
+                .takes_value(true)
+                .validator(validate_positive_number)
+                .env("TFB_EXPECTED_BANDWIDTH")
+        )
+        .arg(
+            Arg::new(args::EXPECTED_DAEMON_PROFILE)
+                .about("A TOML file of the storage_driver, cgroup_driver, and live_restore expected of each host's Docker daemon; runs on hosts which deviate from it fail before starting")
+                .long("expected-daemon-profile")
+                .takes_value(true)
+                .env("TFB_EXPECTED_DAEMON_PROFILE")
+        )
+        .arg(
+            Arg::new(args::MAX_CLOCK_SKEW)
+                .about("The maximum skew in milliseconds between the clock of each host's Docker daemon and the toolset's; runs on hosts which exceed it fail before starting (skew is always recorded)")
+                .long("max-clock-skew")
+                .takes_value(true)
+                .validator(|skew| match str::parse::<i64>(skew) {
+                    Ok(skew) if skew >= 0 => Ok(()),
+                    _ => Err(format!("{} is not a non-negative number of milliseconds", skew)),
+                })
+                .env("TFB_MAX_CLOCK_SKEW")
+        )
+        .arg(
+            Arg::new(args::NETEM_DELAY)
+                .about("Delay in milliseconds added via netem to all traffic leaving the application server, to simulate WAN latency; not available with a server in host network mode")
+                .long("netem-delay")
+                .takes_value(true)
+                .validator(str::parse::<u32>)
+                .env("TFB_NETEM_DELAY")
+        )
+        .arg(
+            Arg::new(args::NETEM_JITTER)
+                .about("Jitter in milliseconds applied to the --netem-delay")
+                .long("netem-jitter")
+                .takes_value(true)
+                .validator(str::parse::<u32>)
+                .env("TFB_NETEM_JITTER")
+        )
+        .arg(
+            Arg::new(args::NETEM_LOSS)
+                .about("Percentage of packets leaving the application server dropped via netem, to simulate WAN loss")
+                .long("netem-loss")
+                .takes_value(true)
+                .validator(|loss| match str::parse::<f32>(loss) {
+                    Ok(loss) if (0f32..=100f32).contains(&loss) => Ok(()),
+                    _ => Err(format!("{} is not a percentage", loss)),
+                })
+                .env("TFB_NETEM_LOSS")
+        )
+        .arg(
+            Arg::new(args::RESOURCE_PROFILE)
+                .about("Constrains the CPU and memory of the server, database, and client containers, and shapes the server's network, to emulate a hardware class: citrine, cloud, or the path of a TOML profile")
+                .long("resource-profile")
+                .takes_value(true)
+                .validator(|profile| ResourceProfile::load(profile).map(|_| ()).map_err(|e| e.to_string()))
+                .env("TFB_RESOURCE_PROFILE")
         )
 }
 
+//
+// PRIVATES
+//
+
+/// Validates a concurrency or query level; a positive integer.
+fn validate_level(level: &str) -> Result<(), String> {
+    match str::parse::<u32>(level) {
+        Ok(level) if level > 0 => Ok(()),
+        _ => Err(format!("levels must be positive integers: {}", level)),
+    }
+}
+
+/// Validates a count or size which cannot be zero; a positive integer.
+fn validate_positive_integer(value: &str) -> Result<(), String> {
+    match str::parse::<u32>(value) {
+        Ok(value) if value > 0 => Ok(()),
+        _ => Err(format!("{} is not a positive integer", value)),
+    }
+}
+
+/// Validates a `--tmpfs` mount; an absolute path, optionally followed by its
+/// size in megabytes (e.g. `/tmp:512`).
+fn validate_tmpfs_mount(mount: &str) -> Result<(), String> {
+    let (path, size) = match mount.rsplit_once(':') {
+        Some((path, size)) => (path, Some(size)),
+        None => (mount, None),
+    };
+    if !path.starts_with('/') {
+        return Err(format!("{} is not an absolute path[:size]", mount));
+    }
+
+    size.map_or(Ok(()), validate_positive_integer)
+}
+
+/// Validates a measure of a run which may be fractional; a positive number.
+fn validate_positive_number(value: &str) -> Result<(), String> {
+    match str::parse::<f32>(value) {
+        Ok(value) if value > 0f32 && value.is_finite() => Ok(()),
+        _ => Err(format!("{} is not a positive number", value)),
+    }
+}
+
 //
 // TESTS
 //
 
 #[cfg(test)]
 mod tests {
-    use crate::options::parse;
+    use crate::options::{args, parse};
+    use std::ffi::OsString;
+    use std::sync::{Mutex, MutexGuard};
+
+    lazy_static! {
+        /// Serializes the tests which set environment variables.
+        static ref ENVIRONMENT: Mutex<()> = Mutex::new(());
+    }
+
+    /// Sets the given environment variables until dropped, when those they
+    /// replaced are restored, or they are removed again.
+    struct EnvVars<'a> {
+        replaced: Vec<(&'a str, Option<OsString>)>,
+        _lock: MutexGuard<'static, ()>,
+    }
+    impl<'a> EnvVars<'a> {
+        fn set(vars: &[(&'a str, &str)]) -> Self {
+            let lock = ENVIRONMENT
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            let mut replaced = Vec::new();
+            for (name, value) in vars {
+                replaced.push((*name, std::env::var_os(name)));
+                std::env::set_var(name, value);
+            }
+
+            Self {
+                replaced,
+                _lock: lock,
+            }
+        }
+    }
+    impl Drop for EnvVars<'_> {
+        fn drop(&mut self) {
+            for (name, value) in &self.replaced {
+                match value {
+                    Some(value) => std::env::set_var(name, value),
+                    None => std::env::remove_var(name),
+                }
+            }
+        }
+    }
 
     #[test]
     fn it_can_parse_with_no_program_arguments() {
         parse();
     }
+
+    #[test]
+    fn it_can_default_options_from_the_environment() {
+        // Clap reads the environment as the arguments are built, so the
+        // variables need only be set for as long as that takes.
+        let app = {
+            let _vars = EnvVars::set(&[
+                ("TFB_DURATION", "30"),
+                ("TFB_CONCURRENCY_LEVELS", "8,16"),
+                ("TFB_PROFILE", "1"),
+            ]);
+            parse()
+        };
+        let matches = app.get_matches_from(vec!["tfb", "--duration", "60"]);
+
+        assert_eq!(matches.value_of(args::DURATION), Some("60"));
+        assert_eq!(
+            matches
+                .values_of(args::CONCURRENCY_LEVELS)
+                .unwrap()
+                .collect::<Vec<&str>>(),
+            vec!["8", "16"]
+        );
+        assert!(matches.is_present(args::PROFILE));
+        assert!(!matches.is_present(args::CORE_DUMPS));
+        assert_eq!(matches.value_of(args::QUERY_LEVELS), Some("1"));
+    }
+
+    #[test]
+    fn it_can_reject_invalid_levels() {
+        for levels in &["1,0,5", "1,five", "-1"] {
+            assert!(parse()
+                .try_get_matches_from(vec!["tfb", "--query-levels", levels])
+                .is_err());
+        }
+        assert!(parse()
+            .try_get_matches_from(vec!["tfb", "--query-levels", "1,5,10"])
+            .is_ok());
+    }
+
+    #[test]
+    fn it_can_reject_malformed_option_values() {
+        let invalid = [
+            ("--duration", "json=fifteen"),
+            ("--start-at", "7pm"),
+            ("--schedule-window", "22:00-6"),
+            ("--override-server-url", "https://203.0.113.7:18443"),
+            ("--netem-delay", "5ms"),
+            ("--netem-jitter", "-1"),
+            ("--netem-loss", "101"),
+            ("--database-version", "postgres"),
+            ("--database-hosts", "10.0.0.5"),
+            ("--verifier-env", "ACCEPT"),
+            ("--run-uuid", "6ba7b810-9dad"),
+            ("--stall-timeout", "30s"),
+            ("--startup-timeout", "1m"),
+            ("--build-timeout", "-1"),
+            ("--port-retries", "many"),
+            ("--verify-retries", "1.5"),
+            ("--expected-latency", "0"),
+            ("--expected-bandwidth", "10Gbps"),
+            ("--max-clock-skew", "-5"),
+            ("--tune-max-concurrency", "0"),
+            ("--warmup-tolerance", "5%"),
+            ("--max-warmups", "0"),
+            ("--baseline-interval", "every"),
+            ("--build-jobs", "-2"),
+            ("--adaptive-cv", "-0.05"),
+            ("--adaptive-interval", "0"),
+            ("--adaptive-max-duration", "1.5"),
+            ("--max-concurrency", "none"),
+            ("--max-queries", "0"),
+            ("--world-rows", "10k"),
+            ("--websocket-connections", "16,0"),
+            ("--websocket-message-rate", "fast"),
+            ("--database-tmpfs-size", "4G"),
+            ("--shm-size", "0"),
+            ("--tmpfs", "/tmp:512M"),
+            ("--tmpfs", "tmp"),
+            ("--max-tests", "ten"),
+        ];
+        for (option, value) in &invalid {
+            assert!(
+                parse()
+                    .try_get_matches_from(vec!["tfb", option, value])
+                    .is_err(),
+                "{} {}",
+                option,
+                value
+            );
+        }
+    }
 }
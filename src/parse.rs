@@ -0,0 +1,252 @@
+//! The parse module completes the results of a prior run (see `--parse`) from
+//! the benchmark logs in its results directory; a run which crashed before
+//! writing the results of every test type it benchmarked still leaves their
+//! logs behind.
+
+use crate::config::normalize_name;
+use crate::docker::listener::benchmarker::{BenchmarkResults, Benchmarker};
+use crate::error::ToolsetResult;
+use crate::io::{LogPath, Logger};
+use crate::results::BenchmarkData;
+use curl::easy::Handler;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// Merges the benchmark logs of the run given by `timestamp` into the
+/// `results.json` of its results directory, `results/<timestamp>`.
+pub fn run(timestamp: &str) -> ToolsetResult<()> {
+    let logger = Logger::default();
+    let results_dir = Path::new("results").join(timestamp);
+    let results_file = results_dir.join("results.json");
+    let mut results: Value = serde_json::from_str(&std::fs::read_to_string(&results_file)?)?;
+
+    let merged = merge_benchmark_logs(&mut results, &results_dir)?;
+    for (test, test_type) in &merged {
+        logger.log(format!("Parsed the {} benchmark of {}", test_type, test))?;
+    }
+    std::fs::write(
+        &results_file,
+        format!("{}\n", serde_json::to_string(&results)?),
+    )?;
+
+    logger.log(format!(
+        "Merged {} benchmark log(s) into {}",
+        merged.len(),
+        results_file.to_string_lossy()
+    ))
+}
+
+//
+// PRIVATES
+//
+
+/// Parses the benchmark log of each test type of each test in `results_dir`
+/// for which `results` has no raw data, adding the data to the `rawData` of
+/// the test's framework and, unless it failed, the framework to `succeeded`.
+/// Returns the test and test type of each log merged.
+fn merge_benchmark_logs(
+    results: &mut Value,
+    results_dir: &Path,
+) -> ToolsetResult<Vec<(String, String)>> {
+    // Only the run's default duration is recorded, not those of test types
+    // given their own; see `parse_benchmark_log`.
+    let duration = results["duration"].as_u64().unwrap_or_default() as u32;
+    let mut merged = Vec::new();
+    for test_dir in list_dirs(results_dir)? {
+        let test = test_dir.file_name().unwrap().to_string_lossy().to_string();
+        let framework = match get_framework(results, &test) {
+            Some(framework) => framework,
+            None => continue,
+        };
+        for type_dir in list_dirs(&test_dir.join("benchmark"))? {
+            let test_type = type_dir.file_name().unwrap().to_string_lossy().to_string();
+            let log_file = test_dir.join(LogPath::Benchmark(&test_type).get_path());
+            let recorded = matches!(
+                results["rawData"][&test_type][&framework].as_array(),
+                Some(data) if !data.is_empty()
+            );
+            if !log_file.is_file() || recorded {
+                continue;
+            }
+            let data = parse_benchmark_log(&std::fs::read_to_string(&log_file)?, duration);
+            if data.is_empty() {
+                continue;
+            }
+
+            results["rawData"][&test_type][&framework] = serde_json::to_value(&data)?;
+            let framework = Value::from(framework.clone());
+            let failed = matches!(
+                results["failed"][&test_type].as_array(),
+                Some(failed) if failed.contains(&framework)
+            );
+            let succeeded = &mut results["succeeded"][&test_type];
+            if !succeeded.is_array() {
+                *succeeded = Value::Array(Vec::new());
+            }
+            let succeeded = succeeded.as_array_mut().unwrap();
+            if !failed && !succeeded.contains(&framework) {
+                succeeded.push(framework);
+            }
+            merged.push((test.clone(), test_type));
+        }
+    }
+
+    Ok(merged)
+}
+
+/// Lists the directories in `dir`, in order; none if it does not exist.
+fn list_dirs(dir: &Path) -> ToolsetResult<Vec<PathBuf>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut dirs = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            dirs.push(path);
+        }
+    }
+    dirs.sort();
+
+    Ok(dirs)
+}
+
+/// Gets the key of the framework of the test whose log directory is `test`,
+/// as recorded in the `testMetadata` of `results`.
+fn get_framework(results: &Value, test: &str) -> Option<String> {
+    results["testMetadata"]
+        .as_array()?
+        .iter()
+        .find(|metadata| metadata["name"].as_str().map(normalize_name).as_deref() == Some(test))
+        .and_then(|metadata| metadata["framework"].as_str())
+        .map(normalize_name)
+}
+
+/// Parses the output of each benchmark command in the given benchmark `log`;
+/// those of the primer and warmup commands, and of any command which
+/// completed no requests, are skipped. Each command is recorded as measured
+/// for the duration its load generator reports, as a test type may have been
+/// given a duration of its own; or else for the given `duration`.
+///
+/// Each command's output is preceded by a header between two lines of `-`.
+fn parse_benchmark_log(log: &str, duration: u32) -> Vec<BenchmarkData> {
+    let mut sections: Vec<(String, String)> = Vec::new();
+    let mut in_header = false;
+    for line in log.lines() {
+        if !line.is_empty() && line.chars().all(|c| c == '-') {
+            if !in_header {
+                sections.push(Default::default());
+            }
+            in_header = !in_header;
+        } else if let Some((header, output)) = sections.last_mut() {
+            let section = if in_header { header } else { output };
+            section.push_str(line);
+            section.push('\n');
+        }
+    }
+
+    sections
+        .iter()
+        .filter(|(header, _)| !header.trim_start().starts_with("Running "))
+        .filter_map(|(command, output)| parse_command_output(command, output))
+        .map(|result| {
+            let measured = result.duration.round() as u32;
+            let duration = if measured > 0 { measured } else { duration };
+            BenchmarkData::new(result, duration)
+        })
+        .collect()
+}
+
+/// Parses the `output` of the given benchmark `command`, with the parser of
+/// its load generator.
+fn parse_command_output(command: &str, output: &str) -> Option<BenchmarkResults> {
+    let mut logger = Logger::default();
+    logger.quiet = true;
+    let mut benchmarker = Benchmarker::new(&logger);
+    benchmarker.write(output.as_bytes()).ok()?;
+    let result = match command.split_whitespace().next() {
+        Some("ghz") => benchmarker.parse_ghz_output(),
+        Some("tcpkali") => benchmarker.parse_tcpkali_output(),
+        _ => benchmarker.parse_wrk_output(),
+    }
+    .ok()?;
+
+    if result.total_requests > 0 {
+        Some(result)
+    } else {
+        None
+    }
+}
+
+//
+// TESTS
+//
+
+#[cfg(test)]
+mod tests {
+    use crate::io::TempDir;
+    use crate::parse::merge_benchmark_logs;
+    use serde_json::json;
+
+    const BENCHMARK_LOG: &str = "---------------------------------------------------------
+ Running Primer
+   wrk -d 5 -c 8 http://tfb-server:8080/json
+---------------------------------------------------------
+Running 5s test @ http://tfb-server:8080/json
+  2 threads and 8 connections
+  1000 requests in 5.00s, 140.00KB read
+---------------------------------------------------------
+ wrk -d 15 -c 16 http://tfb-server:8080/json
+---------------------------------------------------------
+Running 15s test @ http://tfb-server:8080/json
+  4 threads and 16 connections
+  Thread Stats   Avg      Stdev     Max   +/- Stdev
+    Latency   210.00us  100.00us   5.00ms   90.00%
+    Req/Sec    19.00k     1.00k   21.00k    70.00%
+  1140000 requests in 15.00s, 160.00MB read
+Requests/sec:  76000.00
+Transfer/sec:     10.67MB
+";
+
+    #[test]
+    fn it_can_merge_benchmark_logs() {
+        let results_dir = TempDir::new("parse");
+        for (test_type, log) in &[
+            ("json", BENCHMARK_LOG.to_string()),
+            // Given a duration of its own via `--duration plaintext=30`.
+            (
+                "plaintext",
+                BENCHMARK_LOG
+                    .replace("json", "plaintext")
+                    .replace("15", "30"),
+            ),
+        ] {
+            let log_dir = results_dir.join("gemini/benchmark").join(test_type);
+            std::fs::create_dir_all(&log_dir).unwrap();
+            std::fs::write(log_dir.join("log.txt"), log).unwrap();
+        }
+        let mut results = json!({
+            "duration": 15,
+            "testMetadata": [{ "name": "gemini", "framework": "Gemini" }],
+            "rawData": {},
+            "succeeded": {},
+            "failed": {},
+        });
+
+        let merged = merge_benchmark_logs(&mut results, &results_dir);
+
+        assert_eq!(
+            merged.unwrap(),
+            vec![
+                ("gemini".to_string(), "json".to_string()),
+                ("gemini".to_string(), "plaintext".to_string())
+            ]
+        );
+        let data = results["rawData"]["json"]["gemini"].as_array().unwrap();
+        assert_eq!(data.len(), 1);
+        assert_eq!(data[0]["totalRequests"], 1_140_000);
+        assert_eq!(data[0]["duration"], 15);
+        assert_eq!(results["succeeded"]["json"], json!(["gemini"]));
+        assert_eq!(results["rawData"]["plaintext"]["gemini"][0]["duration"], 30);
+    }
+}
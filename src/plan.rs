@@ -0,0 +1,300 @@
+//! The plan module describes what a run would do (see `--dry-run`): the
+//! images it would build, the containers and networks it would create, and
+//! the benchmark commands it would request, all without touching Docker. This
+//! is mostly useful to check which tests a combination of filters selects.
+
+use crate::benchmarker::modes;
+use crate::config::{Named, Project, Protocol, Test};
+use crate::docker::container::get_load_generator_image;
+use crate::docker::docker_config::DockerConfig;
+use crate::docker::network::get_isolated_network_name;
+use crate::docker::{BenchmarkCommands, DockerOrchestration};
+use crate::error::ToolsetResult;
+use crate::io::Logger;
+use crate::metadata::interleave_projects;
+use dockurl::network::NetworkMode;
+use dockurl::network::NetworkMode::{Bridge, Host};
+use std::collections::BTreeSet;
+
+/// The port on which applications are assumed to accept requests unless
+/// their config says otherwise; the actual port is only known once the image
+/// is built.
+const DEFAULT_PORT: u16 = 8080;
+
+/// Logs the plan of a run of the given `projects` in `mode`.
+pub fn run(docker_config: &DockerConfig, projects: &[Project], mode: &str) -> ToolsetResult<()> {
+    let logger = Logger::default();
+    for line in get_plan(docker_config, projects, mode) {
+        logger.log(line)?;
+    }
+
+    Ok(())
+}
+
+//
+// PRIVATES
+//
+
+/// Gets the plan of a run of the given `projects` in `mode`, line by line.
+fn get_plan(docker_config: &DockerConfig, projects: &[Project], mode: &str) -> Vec<String> {
+    let mut projects = if docker_config.interleave {
+        interleave_projects(projects)
+    } else {
+        projects.to_vec()
+    };
+    // Debug mode only ever starts the first test.
+    if mode == modes::DEBUG {
+        projects.truncate(1);
+        for project in &mut projects {
+            project.tests.truncate(1);
+        }
+    }
+    let image_prefix = docker_config
+        .image_prefix
+        .replace("{run}", &docker_config.run_uuid);
+    let tests: usize = projects.iter().map(|project| project.tests.len()).sum();
+    let mut plan = vec![format!(
+        "Dry run in {} mode of {} test(s); nothing is built, started, or requested",
+        mode, tests
+    )];
+
+    let mut images = BTreeSet::new();
+    images.insert("techempower/tfb.verifier");
+    if mode == modes::BENCHMARK {
+        for project in &projects {
+            for test in &project.tests {
+                for test_type in test.urls.keys() {
+                    images.insert(get_load_generator_image(
+                        docker_config,
+                        test.get_protocol(test_type),
+                    ));
+                }
+            }
+        }
+    }
+    for image in images {
+        plan.push(format!(
+            "Pull {} on {}",
+            image, docker_config.client_docker_host
+        ));
+    }
+
+    for project in &projects {
+        for test in &project.tests {
+            plan.push(format!("{}:", test.get_name()));
+            plan.append(&mut get_test_plan(
+                docker_config,
+                project,
+                test,
+                &image_prefix,
+                mode,
+            ));
+        }
+    }
+
+    plan
+}
+
+/// Gets the plan of the given `test` in `mode`, whose image is tagged with
+/// `image_prefix`, line by line.
+fn get_test_plan(
+    docker_config: &DockerConfig,
+    project: &Project,
+    test: &Test,
+    image_prefix: &str,
+    mode: &str,
+) -> Vec<String> {
+    let mut plan = Vec::new();
+    let bridged = matches!(docker_config.server_network_mode, Bridge)
+        || matches!(docker_config.database_network_mode, Bridge)
+        || matches!(docker_config.client_network_mode, Bridge);
+    let (server_network, database_network) = if docker_config.isolate_networks && bridged {
+        let network_name = get_isolated_network_name(&docker_config.run_uuid, test);
+        plan.push(format!(
+            "  Create network {} on {}",
+            network_name, docker_config.database_docker_host
+        ));
+        (network_name.clone(), network_name)
+    } else {
+        (
+            docker_config.server_network_id.clone(),
+            docker_config.database_network_id.clone(),
+        )
+    };
+    if let Some(database) = &test.database {
+        let (database_host, database_docker_host) = docker_config
+            .get_database_host_override(database)
+            .unwrap_or_else(|| {
+                (
+                    docker_config.database_host,
+                    docker_config.database_docker_host.clone(),
+                )
+            });
+        plan.push(format!(
+            "  Pull {} and start it as {} on {}, in {}",
+            docker_config.get_database_image(database),
+            database_host,
+            database_docker_host,
+            describe_network(&docker_config.database_network_mode, &database_network)
+        ));
+    }
+    let tag = test.get_tag(image_prefix);
+    plan.push(format!(
+        "  Build {} from frameworks/{}/{}/{} on {}",
+        tag,
+        project.language,
+        project.name,
+        test.get_dockerfile(),
+        docker_config.server_docker_host
+    ));
+    plan.push(format!(
+        "  Start {} as tfb-server on {}, in {}",
+        tag,
+        docker_config.server_docker_host,
+        describe_network(&docker_config.server_network_mode, &server_network)
+    ));
+    if mode == modes::DEBUG {
+        plan.push("  Leave it running".to_string());
+        return plan;
+    }
+
+    let port = test.port.unwrap_or(DEFAULT_PORT);
+    let mut test_types: Vec<(&String, &String)> = test.urls.iter().collect();
+    test_types.sort();
+    for (test_type, url) in test_types {
+        let protocol = test.get_protocol(test_type);
+        plan.push(format!("  {}:", test_type));
+        if protocol == Protocol::Http {
+            plan.push(format!("    Verify http://tfb-server:{}{}", port, url));
+        }
+        match mode {
+            modes::BENCHMARK => plan.append(&mut get_benchmark_plan(
+                docker_config,
+                test,
+                (test_type, url),
+                port,
+            )),
+            modes::TUNE if protocol == Protocol::Http => plan.push(format!(
+                "    Tune the concurrency, from {}",
+                docker_config.concurrency_levels.split(',').next().unwrap()
+            )),
+            _ => {}
+        }
+    }
+
+    plan
+}
+
+/// Gets the plan of benchmarking the given `test_type` of `test`, whose
+/// application accepts requests on `port`, line by line.
+fn get_benchmark_plan(
+    docker_config: &DockerConfig,
+    test: &Test,
+    test_type: (&String, &String),
+    port: u16,
+) -> Vec<String> {
+    let duration = docker_config.get_duration(test_type.0);
+    let orchestration = DockerOrchestration {
+        host_image_id: String::default(),
+        host_source_dir: None,
+        host_container_id: String::default(),
+        host_port: port.to_string(),
+        host_internal_port: port.to_string(),
+        database_name: test.database.clone(),
+        database_host: None,
+        db_container_id: None,
+        db_host_port: None,
+        db_internal_port: None,
+    };
+    let commands = match test.get_protocol(test_type.0) {
+        Protocol::Http => {
+            // The verifier gives the `wrk` commands of HTTP test types.
+            let levels = match test_type.0.as_str() {
+                "plaintext" => &docker_config.pipeline_concurrency_levels,
+                "query" | "update" => &docker_config.query_levels,
+                "cached_query" => &docker_config.cached_query_levels,
+                _ => &docker_config.concurrency_levels,
+            };
+            let mut plan = vec![format!(
+                "    Benchmark for {}s at each of {} with the commands requested of the verifier",
+                duration, levels
+            )];
+            if let Some(command_override) = test.get_command_override(test_type.0) {
+                plan.push(format!("    Override them with {:?}", command_override));
+            }
+            return plan;
+        }
+        Protocol::Grpc => {
+            BenchmarkCommands::ghz(docker_config, &orchestration, test_type.1, duration)
+        }
+        Protocol::Websocket => {
+            BenchmarkCommands::tcpkali(docker_config, &orchestration, test_type.1, duration)
+        }
+    };
+
+    let mut plan = vec![
+        format!("    Prime: {}", commands.primer_command.join(" ")),
+        format!("    Warm up: {}", commands.warmup_command.join(" ")),
+    ];
+    for command in &commands.benchmark_commands {
+        plan.push(format!("    Benchmark: {}", command.join(" ")));
+    }
+
+    plan
+}
+
+/// Describes the network given by `network_id` (in a dry run, its name) in
+/// `network_mode`.
+fn describe_network(network_mode: &NetworkMode, network_id: &str) -> String {
+    match network_mode {
+        Bridge => format!("network {}", network_id),
+        Host => "the host's network".to_string(),
+    }
+}
+
+//
+// TESTS
+//
+
+#[cfg(test)]
+mod tests {
+    use crate::docker::docker_config::DockerConfig;
+    use crate::docker::dry_run::DryRunDockerApi;
+    use crate::metadata::list_projects_by_test_name;
+    use crate::options;
+    use crate::plan::get_plan;
+    use std::sync::Arc;
+
+    #[test]
+    fn it_can_plan_a_run_without_docker() {
+        let matches = options::parse().get_matches_from(vec![
+            "tfb",
+            "--dry-run",
+            "--concurrency-levels",
+            "16,32",
+            "--duration",
+            "15",
+        ]);
+        let docker_config =
+            DockerConfig::with_docker_api(&matches, |_| Arc::new(DryRunDockerApi)).unwrap();
+        let projects =
+            list_projects_by_test_name(Some("gemini-mysql".to_string()), &["db"]).unwrap();
+
+        let plan = get_plan(&docker_config, &projects, "benchmark");
+
+        assert_eq!(
+            plan,
+            vec![
+                "Dry run in benchmark mode of 1 test(s); nothing is built, started, or requested",
+                "Pull techempower/tfb.verifier on localhost:2375",
+                "gemini-mysql:",
+                "  Pull techempower/tfb.database.mysql and start it as tfb-database on localhost:2375, in network TFBNetwork",
+                "  Build tfb.test.gemini-mysql from frameworks/Java/gemini/gemini-mysql.dockerfile on localhost:2375",
+                "  Start tfb.test.gemini-mysql as tfb-server on localhost:2375, in network TFBNetwork",
+                "  db:",
+                "    Verify http://tfb-server:8080/db",
+                "    Benchmark for 15s at each of 16,32 with the commands requested of the verifier",
+            ]
+        );
+    }
+}
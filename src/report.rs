@@ -0,0 +1,201 @@
+//! The report module renders the results of a run (see `--report html=`)
+//! into a self-contained HTML page, with a table and bar chart of the
+//! frameworks benchmarked for each test type, so that a run can be reviewed
+//! without the pipeline behind the TechEmpower website.
+
+use crate::error::ToolsetResult;
+use crate::io::escape_xml;
+use crate::results::{BenchmarkData, Results};
+use chrono::{TimeZone, Utc};
+use std::path::Path;
+
+/// Inlined so that the report can be opened, mailed, or archived by itself.
+const STYLE: &str = "body { font-family: sans-serif; margin: 2em; color: #222; }
+table { border-collapse: collapse; margin-bottom: 1em; }
+th, td { padding: 0.3em 0.8em; text-align: left; border-bottom: 1px solid #ddd; }
+td.number { text-align: right; font-variant-numeric: tabular-nums; }
+td.bar { width: 30em; }
+td.bar div { background: #4a7ebb; height: 1em; }
+p.failed { color: #b22; }";
+
+/// Renders the given `results` into an HTML page at `path`.
+pub fn write_report(results: &Results, path: &Path) -> ToolsetResult<()> {
+    std::fs::write(path, get_report(results))?;
+
+    Ok(())
+}
+
+//
+// PRIVATES
+//
+
+/// The best benchmark command of a framework for a test type.
+struct Entry<'a> {
+    framework: &'a str,
+    requests_per_second: f64,
+    level: Option<u32>,
+    data: &'a BenchmarkData,
+}
+
+/// Gets the HTML report of the given `results`.
+fn get_report(results: &Results) -> String {
+    let name = escape_xml(&results.name);
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>{}</title>\n", name));
+    html.push_str(&format!("<style>\n{}\n</style>\n", STYLE));
+    html.push_str("</head>\n<body>\n");
+    html.push_str(&format!("<h1>{}</h1>\n", name));
+    html.push_str(&format!(
+        "<p>Run {}, started {}; each benchmark was measured for {}s.</p>\n",
+        escape_xml(&results.uuid),
+        Utc.timestamp_millis_opt(results.start_time as i64)
+            .single()
+            .map(|start_time| start_time.format("%Y-%m-%d %H:%M UTC").to_string())
+            .unwrap_or_default(),
+        results.duration
+    ));
+
+    let mut test_types: Vec<&String> = results.raw_data.keys().collect();
+    test_types.sort();
+    for test_type in test_types {
+        html.push_str(&format!("<h2>{}</h2>\n", escape_xml(test_type)));
+        let entries = get_entries(results, test_type);
+        if entries.is_empty() {
+            html.push_str("<p>No framework completed this test type.</p>\n");
+        } else {
+            html.push_str(&get_table(&entries));
+        }
+        if let Some(failed) = results.failed.get(test_type) {
+            if !failed.is_empty() {
+                let mut failed: Vec<String> = failed.iter().map(|f| escape_xml(f)).collect();
+                failed.sort();
+                html.push_str(&format!(
+                    "<p class=\"failed\">Failed: {}</p>\n",
+                    failed.join(", ")
+                ));
+            }
+        }
+    }
+    html.push_str("</body>\n</html>\n");
+
+    html
+}
+
+/// Gets the best benchmark command of each framework of the given
+/// `test_type`, fastest first.
+fn get_entries<'a>(results: &'a Results, test_type: &str) -> Vec<Entry<'a>> {
+    let levels = match test_type {
+        "plaintext" => &results.pipeline_concurrency_levels,
+        "query" | "update" => &results.query_intervals,
+        "cached_query" => &results.cached_query_intervals,
+        _ => &results.concurrency_levels,
+    };
+    let mut entries: Vec<Entry> = results.raw_data[test_type]
+        .iter()
+        .filter_map(|(framework, data)| {
+            data.iter()
+                .enumerate()
+                .filter(|(_, data)| data.duration > 0)
+                .map(|(index, data)| Entry {
+                    framework,
+                    requests_per_second: data.get_requests_per_second(),
+                    level: levels.get(index).copied(),
+                    data,
+                })
+                .max_by(|a, b| a.requests_per_second.total_cmp(&b.requests_per_second))
+        })
+        .collect();
+    entries.sort_by(|a, b| {
+        b.requests_per_second
+            .total_cmp(&a.requests_per_second)
+            .then_with(|| a.framework.cmp(b.framework))
+    });
+
+    entries
+}
+
+/// Gets the table of the given `entries`, each with a bar relative to the
+/// fastest.
+fn get_table(entries: &[Entry]) -> String {
+    let fastest = entries[0].requests_per_second.max(1f64);
+    let mut table = String::from(
+        "<table>\n<tr><th>#</th><th>Framework</th><th>Requests/sec</th><th>Concurrency</th>\
+         <th>Latency (avg)</th><th>Latency (max)</th><th></th></tr>\n",
+    );
+    for (rank, entry) in entries.iter().enumerate() {
+        table.push_str(&format!(
+            "<tr><td class=\"number\">{}</td><td>{}</td><td class=\"number\">{:.0}</td>\
+             <td class=\"number\">{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td>\
+             <td class=\"bar\"><div style=\"width: {:.1}%\"></div></td></tr>\n",
+            rank + 1,
+            escape_xml(entry.framework),
+            entry.requests_per_second,
+            entry
+                .level
+                .map(|level| level.to_string())
+                .unwrap_or_default(),
+            escape_xml(&entry.data.latency_avg),
+            escape_xml(&entry.data.latency_max),
+            entry.requests_per_second / fastest * 100f64
+        ));
+    }
+    table.push_str("</table>\n");
+
+    table
+}
+
+//
+// TESTS
+//
+
+#[cfg(test)]
+mod tests {
+    use crate::report::get_report;
+    use crate::results::{BenchmarkData, Results};
+    use std::collections::HashMap;
+
+    fn get_data(total_requests: u32) -> BenchmarkData {
+        BenchmarkData {
+            latency_avg: "1.00ms".to_string(),
+            latency_max: "9.00ms".to_string(),
+            total_requests,
+            duration: 10,
+            ..BenchmarkData::default()
+        }
+    }
+
+    #[test]
+    fn it_can_render_an_html_report() {
+        let mut json = HashMap::new();
+        json.insert(
+            "gemini".to_string(),
+            vec![get_data(100_000), get_data(400_000)],
+        );
+        json.insert("<actix>".to_string(), vec![get_data(200_000)]);
+        let mut raw_data = HashMap::new();
+        raw_data.insert("json".to_string(), json);
+        let mut failed = HashMap::new();
+        failed.insert("json".to_string(), vec!["nodejs".to_string()]);
+        let results = Results {
+            name: "Round 1".to_string(),
+            duration: 10,
+            concurrency_levels: vec![16, 32],
+            raw_data,
+            failed,
+            ..Results::default()
+        };
+
+        let report = get_report(&results);
+
+        assert!(report.starts_with("<!DOCTYPE html>"));
+        assert!(report.contains("<h1>Round 1</h1>"));
+        assert!(report.contains("<h2>json</h2>"));
+        let gemini = report.find("<td>gemini</td><td class=\"number\">40000</td>");
+        let actix = report.find("<td>&lt;actix&gt;</td><td class=\"number\">20000</td>");
+        assert!(gemini.unwrap() < actix.unwrap());
+        assert!(report.contains("<td class=\"number\">32</td>"));
+        assert!(report.contains("width: 50.0%"));
+        assert!(report.contains("<p class=\"failed\">Failed: nodejs</p>"));
+    }
+}
@@ -1,17 +1,27 @@
-use crate::config::Named;
+use crate::config::{normalize_name, Named, Project, Test};
 use crate::docker::docker_config::DockerConfig;
-use crate::error::ToolsetResult;
+use crate::docker::listener::benchmarker::{BenchmarkResults, SocketErrors};
+use crate::docker::resources::ResourceProfile;
+use crate::error::ToolsetError::{
+    AppServerContainerShutDownError, ContainerPortMappingInspectionError, DockerDaemonError,
+    ExposePortError, FailedBenchmarkCommandRetrievalError, NoResponseFromDockerContainerError,
+    PortConflictError,
+};
+use crate::error::{ToolsetError, ToolsetResult};
 use crate::io::get_tfb_dir;
 use crate::metadata::list_all_projects;
-use rand::Rng;
-use serde::Serialize;
-use std::collections::HashMap;
+use crate::options;
+use clap::ArgMatches;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::path::Path;
 use std::process::Command;
 use std::time::{SystemTime, UNIX_EPOCH};
-use uuid::Uuid;
 
-#[derive(Serialize, Clone, Debug, Default)]
-#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default, rename_all = "camelCase")]
 pub struct Results {
     pub uuid: String,
     pub name: String,
@@ -26,6 +36,14 @@ pub struct Results {
     pub concurrency_levels: Vec<u32>,
     pub pipeline_concurrency_levels: Vec<u32>,
     pub frameworks: Vec<String>,
+    // The test types selected via `--type`; all were run if empty.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub test_types: Vec<String>,
+    pub custom_test_types: Vec<String>,
+    // The database image versions given via `--database-version`; the latest
+    // was run otherwise: `{ "postgres": "16" }`
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub database_versions: HashMap<String, String>,
     // Holdover from legacy, this should be improved in the future but the idea
     // is to support a structure like:
     // `{ "json": { "gemini": { ... } } }`
@@ -42,20 +60,86 @@ pub struct Results {
     // is to support a structure like:
     // `{ "json": [ "gemini" ] }`
     pub failed: HashMap<String, Vec<String>>,
-    // Holdover from legacy; should be updated to better represent intent:
-    // `{ "gemini": "20200810202733" }` - change to `u128` instead of string.
+    // Why each test in `failed` failed, by test type and test:
+    // `{ "json": { "gemini": { "kind": "build", "message": "..." } } }`
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub failures: HashMap<String, HashMap<String, Failure>>,
+    // Only populated with `--count-queries`; the tests which made fewer
+    // queries per request than their test type requires, as though caching:
+    // `{ "db": [ "gemini" ] }`
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub suspected_caching: HashMap<String, Vec<String>>,
+    // Only populated for tests whose containers restarted or failed their
+    // health checks unexpectedly: `{ "gemini": { "restarts": 1, ... } }`
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub container_incidents: HashMap<String, ContainerIncidents>,
+    // Holdover from legacy; the millis since the epoch as a string:
+    // `{ "gemini": "1597090053187" }` - see `completion_times` instead.
     pub completed: HashMap<String, String>,
+    // The time (millis since the epoch) at which each framework last
+    // completed a benchmark, as in `completed`: `{ "gemini": 1597090053187 }`
+    pub completion_times: HashMap<String, u128>,
+    // The time at which each framework completed each test type:
+    // `{ "json": { "gemini": 1597090053187 } }`
+    pub test_type_completion_times: HashMap<String, HashMap<String, u128>>,
+    // The status of each test type of each test run, by language, framework
+    // and test, unlike the legacy maps above which key by the lowercase name
+    // of either the framework or the test (`frameworks` being the legacy list
+    // of framework names):
+    // `{ "Java": { "Gemini": { "gemini-mysql": { "db": "succeeded" } } } }`
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub languages: HashMap<String, HashMap<String, HashMap<String, TestStatuses>>>,
+    // The Docker daemon of each distinct host; see `daemon`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub daemons: Vec<DaemonConfiguration>,
+    // Only populated in multi-machine setups; see `network_check`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub network_checks: Vec<NetworkCheck>,
+    // Only populated with `--calibrate`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub calibration: Option<Calibration>,
+    // Only populated with `--baseline-interval`; see `Baseline`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub baselines: Vec<Baseline>,
+    // Only populated when network shaping was applied for the run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network_shaping: Option<NetworkShaping>,
+    // Only populated with `--resource-profile`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource_profile: Option<ResourceProfile>,
+    // Only populated once a benchmark has completed; the socket errors of
+    // every benchmark command of the run, combined.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub socket_errors: Option<SocketErrorSummary>,
+    // Only populated with `--warmup-tolerance`; the number of warmups run
+    // before requests/sec stabilized: `{ "json": { "gemini": 3 } }`
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub warmups: HashMap<String, HashMap<String, u32>>,
+    // Only populated in `tune` mode; `{ "json": { "gemini": { ... } } }`
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub tuned: HashMap<String, HashMap<String, TunedData>>,
 }
 
+/// The status of each test type of a test, e.g. `{ "db": "succeeded" }`.
+pub type TestStatuses = HashMap<String, String>;
+
+pub const STATUS_SUCCEEDED: &str = "succeeded";
+pub const STATUS_FAILED: &str = "failed";
+
 impl Results {
     pub fn new(docker_config: &DockerConfig) -> ToolsetResult<Self> {
         let mut results = Results::default();
 
         for project in list_all_projects()? {
             for test in &project.tests {
+                for test_type in test.urls.keys() {
+                    if test.is_custom(test_type) && !results.custom_test_types.contains(test_type) {
+                        results.custom_test_types.push(test_type.clone());
+                    }
+                }
                 results.test_metadata.push(MetaData {
                     versus: test.versus.clone(),
-                    project_name: project.name.to_lowercase(),
+                    project_name: normalize_name(&project.name),
                     // Legacy - we no longer support display_name
                     display_name: test.get_name(),
                     name: test.get_name(),
@@ -68,8 +152,8 @@ impl Results {
                     },
                     language: project.language.clone(),
                     os: test.os.clone(),
-                    // ↓ is a holdover from legacy metadata
-                    notes: "".to_string(),
+                    notes: test.notes.clone().unwrap_or_default(),
+                    known_issues: test.known_issues.clone().unwrap_or_default(),
                     tags: if let Some(tags) = &test.tags {
                         tags.clone()
                     } else {
@@ -95,10 +179,7 @@ impl Results {
                 });
             }
         }
-        let mut rng = rand::thread_rng();
-        results.uuid = Uuid::from_u128(rng.gen::<u128>())
-            .to_hyphenated()
-            .to_string();
+        results.uuid = docker_config.run_uuid.clone();
         results.name = docker_config.results_name.to_string();
         results.start_time = SystemTime::now()
             .duration_since(UNIX_EPOCH)
@@ -125,15 +206,130 @@ impl Results {
             .split(',')
             .map(|l| str::parse::<u32>(l).unwrap())
             .collect();
+        results.network_shaping = docker_config.network_shaping.clone();
+        results.resource_profile = docker_config.resource_profile.clone();
+        results.test_types = docker_config
+            .test_types
+            .iter()
+            .map(|test_type| test_type.to_string())
+            .collect();
+        results.database_versions = docker_config.database_versions.iter().cloned().collect();
         results.environment_description = docker_config.results_environment.to_string();
         results.git = Git::default();
 
         Ok(results)
     }
+
+    /// Loads the results written to the `results.json` of `results_dir`, as by
+    /// an interrupted run which is to be resumed.
+    pub fn load(results_dir: &Path) -> ToolsetResult<Self> {
+        let results = std::fs::read_to_string(results_dir.join("results.json"))?;
+
+        Ok(serde_json::from_str(&results)?)
+    }
+
+    /// Records that the `framework` completed its benchmark of `test_type` at
+    /// `time`, in millis since the epoch.
+    pub fn record_completion(&mut self, framework: &str, test_type: &str, time: u128) {
+        self.completed
+            .insert(framework.to_string(), time.to_string());
+        self.completion_times.insert(framework.to_string(), time);
+        self.test_type_completion_times
+            .entry(test_type.to_string())
+            .or_default()
+            .insert(framework.to_string(), time);
+    }
+
+    /// Records the `status` of the given `test_type` of `test`, under its
+    /// language and framework in `languages`.
+    pub fn record_status(&mut self, project: &Project, test: &Test, test_type: &str, status: &str) {
+        self.languages
+            .entry(project.language.clone())
+            .or_default()
+            .entry(project.framework.get_name())
+            .or_default()
+            .entry(test.get_name())
+            .or_default()
+            .insert(test_type.to_string(), status.to_string());
+    }
+
+    /// Returns whether the given `test_type` of `test` was recorded as having
+    /// succeeded; see `record_status`.
+    pub fn has_succeeded(&self, project: &Project, test: &Test, test_type: &str) -> bool {
+        self.languages
+            .get(&project.language)
+            .and_then(|frameworks| frameworks.get(&project.framework.get_name()))
+            .and_then(|tests| tests.get(&test.get_name()))
+            .and_then(|statuses| statuses.get(test_type))
+            .is_some_and(|status| status == STATUS_SUCCEEDED)
+    }
 }
 
+/// What a run was asked to do, written to the `manifest.json` of its
+/// results directory (alongside the layout of that directory) so that the
+/// run is self-describing and can be reproduced.
 #[derive(Serialize, Clone, Debug, Default)]
 #[serde(rename_all = "camelCase")]
+pub struct Manifest {
+    /// The command line, exactly as given.
+    pub invocation: Vec<String>,
+    /// The value(s) of each option given or defaulted, by its long name;
+    /// `true` for flags.
+    pub options: BTreeMap<String, Value>,
+    pub toolset_version: String,
+    /// The names of the tests selected, by project.
+    pub projects: BTreeMap<String, Vec<String>>,
+    /// The digest of the verifier image, once it has been pulled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verifier_digest: Option<String>,
+}
+impl Manifest {
+    pub fn new(matches: &ArgMatches) -> Self {
+        let mut resolved = BTreeMap::new();
+        for arg in options::parse().get_arguments() {
+            if let Some(long) = arg.get_long() {
+                if matches.is_present(arg.get_name()) {
+                    let values: Vec<&str> = match matches.values_of(arg.get_name()) {
+                        Some(values) => values.collect(),
+                        None => Vec::new(),
+                    };
+                    let value = match values.as_slice() {
+                        [] => Value::from(true),
+                        [value] => Value::from(*value),
+                        _ => Value::from(values),
+                    };
+                    resolved.insert(long.to_string(), value);
+                }
+            }
+        }
+
+        Self {
+            invocation: env::args_os()
+                .map(|arg| arg.to_string_lossy().to_string())
+                .collect(),
+            options: resolved,
+            toolset_version: env!("CARGO_PKG_VERSION").to_string(),
+            projects: BTreeMap::new(),
+            verifier_digest: None,
+        }
+    }
+
+    /// Records the tests selected from the given `projects`.
+    pub fn set_projects(&mut self, projects: &[Project]) {
+        self.projects = projects
+            .iter()
+            .map(|project| {
+                (
+                    project.name.clone(),
+                    project.tests.iter().map(|test| test.get_name()).collect(),
+                )
+            })
+            .collect();
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
 pub struct BenchmarkData {
     pub latency_avg: String,
     pub latency_max: String,
@@ -141,9 +337,387 @@ pub struct BenchmarkData {
     pub total_requests: u32,
     pub start_time: u128,
     pub end_time: u128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub messages_per_second: Option<f32>,
+    /// CPU and memory usage of the application server during the command.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container_stats: Option<ContainerStats>,
+    /// CPU, memory, and IO usage of the database during the command, for test
+    /// types which query it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub database_stats: Option<ContainerStats>,
+    /// Connections open to the database during the command.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub database_connections: Option<ConnectionStats>,
+    /// Statements executed by the database per request with `--count-queries`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub queries_per_request: Option<f32>,
+    /// The fewest queries per request the test type allows.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected_queries_per_request: Option<u32>,
+    /// Requests/sec of each interval when run with `--adaptive-cv`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interval_requests_per_second: Option<Vec<f32>>,
+    /// (percentile, latency in milliseconds) pairs of wrk2's detailed latency
+    /// spectrum, the percentile being a fraction; e.g. `[0.999, 2.5]`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_percentiles: Option<Vec<(f64, f64)>>,
+    /// The latency at each percentile from p50 to p99.999, from wrk2's
+    /// spectrum or else wrk's histogram.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_histogram: Option<LatencyHistogram>,
+    /// Whether requests stopped completing partway through the command;
+    /// absent from results recorded before stalls were detected.
+    #[serde(default)]
+    pub stalled: bool,
+    /// The duration in seconds for which the command was to be measured;
+    /// zero in results recorded before durations were configurable.
+    #[serde(default)]
+    pub duration: u32,
+}
+impl BenchmarkData {
+    /// Gets the data recorded of the `result` of a benchmark command which
+    /// was to be measured for `duration` seconds.
+    pub fn new(result: BenchmarkResults, duration: u32) -> Self {
+        Self {
+            latency_avg: result.thread_stats.latency.average,
+            latency_max: result.thread_stats.latency.max,
+            latency_stdev: result.thread_stats.latency.standard_deviation,
+            total_requests: result.total_requests,
+            start_time: result.start_time,
+            end_time: result.end_time,
+            messages_per_second: result.messages_per_second,
+            interval_requests_per_second: result.interval_requests_per_second,
+            latency_percentiles: result.latency_percentiles,
+            latency_histogram: result.latency_histogram,
+            container_stats: result.container_stats,
+            database_stats: result.database_stats,
+            database_connections: result.database_connections,
+            queries_per_request: result.queries_per_request,
+            expected_queries_per_request: result.expected_queries_per_request,
+            stalled: result.stalled,
+            duration,
+        }
+    }
+
+    /// Gets the requests/sec over the duration the command was measured for;
+    /// or, for results recorded without a duration, over the time between
+    /// its start and end.
+    pub fn get_requests_per_second(&self) -> f64 {
+        if self.duration > 0 {
+            self.total_requests as f64 / self.duration as f64
+        } else if self.end_time > self.start_time {
+            let millis = self.end_time - self.start_time;
+            self.total_requests as f64 * 1000f64 / millis as f64
+        } else {
+            0f64
+        }
+    }
+}
+
+/// The latency in milliseconds at each percentile of the full distribution of
+/// a benchmark command's latencies.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct LatencyHistogram {
+    pub p50: f64,
+    pub p75: f64,
+    pub p90: f64,
+    pub p99: f64,
+    #[serde(rename = "p99.9")]
+    pub p99_9: f64,
+    #[serde(rename = "p99.99")]
+    pub p99_99: f64,
+    #[serde(rename = "p99.999")]
+    pub p99_999: f64,
 }
+impl LatencyHistogram {
+    /// Gets the histogram of the given (percentile, latency in milliseconds)
+    /// pairs of a spectrum, the percentile being a fraction, in ascending
+    /// order; each percentile's latency is that of the first pair at or
+    /// beyond it. `None` if the spectrum is empty.
+    pub fn from_spectrum(spectrum: &[(f64, f64)]) -> Option<Self> {
+        let (_, max) = spectrum.last()?;
+        let at = |percentile: f64| {
+            spectrum
+                .iter()
+                // Printed fractions are rounded, e.g. 0.999990 for p99.999.
+                .find(|(fraction, _)| *fraction * 100f64 >= percentile - 1e-6)
+                .map_or(*max, |(_, latency)| *latency)
+        };
 
-#[derive(Serialize, Clone, Debug)]
+        Some(Self {
+            p50: at(50f64),
+            p75: at(75f64),
+            p90: at(90f64),
+            p99: at(99f64),
+            p99_9: at(99.9),
+            p99_99: at(99.99),
+            p99_999: at(99.999),
+        })
+    }
+}
+
+/// The CPU, memory, and IO usage of a container over a benchmark command;
+/// collected from both cgroup v1 and v2 (unified hierarchy) hosts.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ContainerStats {
+    pub cpu_percent_avg: f64,
+    pub cpu_percent_max: f64,
+    pub memory_bytes_avg: u64,
+    pub memory_bytes_max: u64,
+    /// Bytes read from block devices over the command.
+    #[serde(default)]
+    pub io_read_bytes: u64,
+    /// Bytes written to block devices over the command.
+    #[serde(default)]
+    pub io_write_bytes: u64,
+    pub cgroup_version: u8,
+}
+
+/// The number of connections open to the database over a benchmark command,
+/// sampled about once per second.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionStats {
+    pub connections_avg: f32,
+    pub connections_max: u32,
+    pub samples: u32,
+}
+
+/// The unexpected restarts and health check failures (transitions to
+/// unhealthy) of a test's application server and database containers, as
+/// observed via the Docker daemon's events stream.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ContainerIncidents {
+    pub restarts: u32,
+    pub health_flaps: u32,
+}
+
+/// The phase of a test in which it failed.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum FailureKind {
+    /// Its image could not be built.
+    Build,
+    /// Its containers could not be started.
+    Start,
+    /// The verifier found fault with it, so no benchmark commands were given.
+    Verification,
+    /// It could not be benchmarked.
+    Benchmark,
+}
+
+/// Why a test type of a test failed.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Failure {
+    pub kind: FailureKind,
+    pub message: String,
+}
+impl Failure {
+    pub fn new(error: &ToolsetError) -> Self {
+        let kind = match error {
+            DockerDaemonError(operation, _, _) if operation == "build image" => FailureKind::Build,
+            DockerDaemonError(..)
+            | PortConflictError(_)
+            | AppServerContainerShutDownError
+            | NoResponseFromDockerContainerError
+            | ContainerPortMappingInspectionError
+            | ExposePortError => FailureKind::Start,
+            FailedBenchmarkCommandRetrievalError => FailureKind::Verification,
+            _ => FailureKind::Benchmark,
+        };
+
+        Self {
+            kind,
+            message: error.to_string(),
+        }
+    }
+}
+
+/// The configuration of the Docker daemon shared by the given roles.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DaemonConfiguration {
+    pub roles: Vec<String>,
+    pub docker_host: String,
+    pub server_version: String,
+    pub storage_driver: String,
+    pub cgroup_driver: String,
+    pub cgroup_version: String,
+    pub live_restore: bool,
+    /// How far the daemon's clock is ahead of the toolset host's (negative if
+    /// behind), if it reported the time.
+    pub clock_skew_ms: Option<i64>,
+}
+
+/// The connectivity, latency, and bandwidth measured from one host to another
+/// before the run.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkCheck {
+    pub from: String,
+    pub to: String,
+    pub reachable: bool,
+    pub latency_ms: Option<f32>,
+    pub bandwidth_gbps: Option<f32>,
+}
+
+/// The throughput of a static file served by a reference server (nginx),
+/// measured before any test at the highest concurrency level. A reference far
+/// below what the client has achieved before points to client-side saturation
+/// or a change in the environment rather than to the frameworks.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Calibration {
+    pub image: String,
+    pub concurrency: u32,
+    pub requests_per_second: f32,
+    pub latency_avg: String,
+    pub latency_max: String,
+    pub latency_stdev: String,
+    pub total_requests: u32,
+    pub start_time: u128,
+    pub end_time: u128,
+    /// CPU and memory usage of the reference server during the command.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container_stats: Option<ContainerStats>,
+}
+
+/// A calibration (see `Calibration`) measured between tests with
+/// `--baseline-interval`. Drift from the first baseline of the run suggests
+/// that the environment, rather than the frameworks benchmarked since, changed.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Baseline {
+    /// The number of tests run before the baseline was measured.
+    pub tests_run: u32,
+    /// The change in requests/sec since the `first` baseline, in percent.
+    pub drift_percent: f32,
+    pub calibration: Calibration,
+}
+impl Baseline {
+    pub fn new(tests_run: u32, calibration: Calibration, first: Option<&Baseline>) -> Self {
+        let drift_percent = match first {
+            Some(first) if first.calibration.requests_per_second > 0f32 => {
+                (calibration.requests_per_second - first.calibration.requests_per_second)
+                    / first.calibration.requests_per_second
+                    * 100f32
+            }
+            _ => 0f32,
+        };
+
+        Self {
+            tests_run,
+            drift_percent,
+            calibration,
+        }
+    }
+}
+
+/// The socket errors reported by wrk across the benchmark commands of a run;
+/// widespread errors (timeouts in particular) usually indicate a problem with
+/// the environment rather than with the frameworks.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SocketErrorSummary {
+    pub connect: u64,
+    pub read: u64,
+    pub write: u64,
+    pub timeout: u64,
+    pub commands: u32,
+    pub commands_with_errors: u32,
+    /// The tests with any socket errors, by test type:
+    /// `{ "json": [ "gemini" ] }`
+    pub tests: HashMap<String, Vec<String>>,
+}
+impl SocketErrorSummary {
+    /// Adds the socket errors (if any) of a benchmark command of the given
+    /// `test` / `test_type`.
+    pub fn record(&mut self, test: &str, test_type: &str, errors: Option<&SocketErrors>) {
+        self.commands += 1;
+        if let Some(errors) = errors {
+            self.connect += u64::from(errors.connect);
+            self.read += u64::from(errors.read);
+            self.write += u64::from(errors.write);
+            self.timeout += u64::from(errors.timeout);
+            self.commands_with_errors += 1;
+            let tests = self.tests.entry(test_type.to_string()).or_default();
+            if !tests.iter().any(|tested| tested == test) {
+                tests.push(test.to_string());
+            }
+        }
+    }
+
+    /// Returns whether more than half the benchmark commands had socket
+    /// errors.
+    pub fn is_widespread(&self) -> bool {
+        self.commands_with_errors * 2 > self.commands
+    }
+}
+
+/// The `netem` parameters with which the network of each application server
+/// was shaped to simulate a WAN; also read, in snake case, from the
+/// `[network]` of a resource profile.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkShaping {
+    #[serde(alias = "delay_ms")]
+    pub delay_ms: u32,
+    #[serde(alias = "jitter_ms")]
+    pub jitter_ms: u32,
+    #[serde(alias = "loss_percent")]
+    pub loss_percent: f32,
+}
+impl NetworkShaping {
+    /// Gets the `tc` command which applies this shaping to `eth0`, replacing
+    /// any shaping already applied, e.g. before the application restarted.
+    pub fn to_tc_command(&self) -> Vec<String> {
+        let mut command: Vec<String> = ["tc", "qdisc", "replace", "dev", "eth0", "root", "netem"]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+        command.push("delay".to_string());
+        command.push(format!("{}ms", self.delay_ms));
+        if self.jitter_ms > 0 {
+            command.push(format!("{}ms", self.jitter_ms));
+        }
+        if self.loss_percent > 0f32 {
+            command.push("loss".to_string());
+            command.push(format!("{}%", self.loss_percent));
+        }
+
+        command
+    }
+}
+
+/// The concurrency at which a test type reached its peak throughput while
+/// tuning, its latency profile there, and every concurrency measured.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TunedData {
+    pub concurrency: u32,
+    pub requests_per_second: f32,
+    pub latency_avg: String,
+    pub latency_max: String,
+    pub latency_stdev: String,
+    pub latency_50: String,
+    pub latency_75: String,
+    pub latency_90: String,
+    pub latency_99: String,
+    pub probes: Vec<TuningProbe>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TuningProbe {
+    pub concurrency: u32,
+    pub requests_per_second: f32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Git {
     pub commit_id: String,
@@ -213,7 +787,7 @@ impl Default for Git {
     }
 }
 
-#[derive(Serialize, Clone, Debug, Default)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct MetaData {
     pub versus: String,
     pub project_name: String,
@@ -224,6 +798,8 @@ pub struct MetaData {
     pub language: String,
     pub os: String,
     pub notes: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub known_issues: Vec<String>,
     pub tags: Vec<String>,
     pub framework: String,
     pub webserver: String,
@@ -232,3 +808,188 @@ pub struct MetaData {
     pub database_os: String,
     pub approach: String,
 }
+
+//
+// TESTS
+//
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Named;
+    use crate::docker::listener::benchmarker::SocketErrors;
+    use crate::error::ToolsetError::{
+        DockerDaemonError, FailedBenchmarkCommandRetrievalError, NoRequestsCompletedError,
+    };
+    use crate::io::TempDir;
+    use crate::metadata::list_projects_by_test_name;
+    use crate::results::{
+        Baseline, BenchmarkData, Calibration, Failure, FailureKind, NetworkShaping, Results,
+        SocketErrorSummary, STATUS_FAILED, STATUS_SUCCEEDED,
+    };
+
+    #[test]
+    fn it_can_record_statuses_by_language_and_framework() {
+        let projects = list_projects_by_test_name(Some("gemini*".to_string()), &[]).unwrap();
+        let project = &projects[0];
+        let test = |name: &str| {
+            project
+                .tests
+                .iter()
+                .find(|test| test.get_name() == name)
+                .unwrap()
+        };
+        let mut results = Results::default();
+        results.record_status(project, test("gemini"), "json", STATUS_SUCCEEDED);
+        results.record_status(project, test("gemini-mysql"), "db", STATUS_FAILED);
+
+        let results = serde_json::to_value(&results).unwrap();
+        let framework = &results["languages"]["Java"]["Gemini"];
+        assert_eq!(framework["gemini"]["json"], STATUS_SUCCEEDED);
+        assert_eq!(framework["gemini-mysql"]["db"], STATUS_FAILED);
+        assert!(results["frameworks"].is_array());
+    }
+
+    #[test]
+    fn it_can_classify_failures() {
+        let build = Failure::new(&DockerDaemonError(
+            "build image".to_string(),
+            "tfb-server".to_string(),
+            "COPY failed".to_string(),
+        ));
+        let start = Failure::new(&DockerDaemonError(
+            "start container".to_string(),
+            "tfb-server".to_string(),
+            "port is already allocated".to_string(),
+        ));
+
+        assert_eq!(build.kind, FailureKind::Build);
+        assert_eq!(
+            build.message,
+            "Docker daemon on tfb-server failed to build image: COPY failed"
+        );
+        assert_eq!(start.kind, FailureKind::Start);
+        assert_eq!(
+            Failure::new(&FailedBenchmarkCommandRetrievalError).kind,
+            FailureKind::Verification
+        );
+        assert_eq!(
+            Failure::new(&NoRequestsCompletedError(String::new())).kind,
+            FailureKind::Benchmark
+        );
+    }
+
+    #[test]
+    fn it_can_record_completion_times_by_test_type() {
+        let mut results = Results::default();
+        results.record_completion("gemini", "json", 1_597_090_053_187);
+        results.record_completion("gemini", "db", 1_597_090_068_412);
+
+        let results = serde_json::to_value(&results).unwrap();
+        assert_eq!(results["completed"]["gemini"], "1597090068412");
+        assert_eq!(results["completionTimes"]["gemini"], 1_597_090_068_412u64);
+        assert_eq!(
+            results["testTypeCompletionTimes"]["json"]["gemini"],
+            1_597_090_053_187u64
+        );
+        assert_eq!(
+            results["testTypeCompletionTimes"]["db"]["gemini"],
+            1_597_090_068_412u64
+        );
+    }
+
+    #[test]
+    fn it_can_load_results_to_resume() {
+        let results_dir = TempDir::new("resume");
+        let mut results = Results {
+            uuid: "6ba7b810-9dad-11d1-80b4-00c04fd430c8".to_string(),
+            ..Results::default()
+        };
+        results.record_completion("gemini", "json", 1_597_090_053_187);
+        results
+            .failed
+            .insert("db".to_string(), vec!["actix".to_string()]);
+        std::fs::write(
+            results_dir.join("results.json"),
+            serde_json::to_string(&results).unwrap(),
+        )
+        .unwrap();
+
+        let loaded = Results::load(&results_dir);
+
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded.uuid, results.uuid);
+        assert_eq!(loaded.completion_times["gemini"], 1_597_090_053_187);
+        assert!(loaded.completed.contains_key("gemini"));
+        assert_eq!(loaded.failed["db"], vec!["actix".to_string()]);
+    }
+
+    #[test]
+    fn it_can_load_benchmark_data_recorded_by_older_toolsets() {
+        let data: BenchmarkData = serde_json::from_str(
+            r#"{"latencyAvg":"1.2ms","latencyMax":"9ms","latencyStdev":"0.4ms","totalRequests":150000,"startTime":1597090053187,"endTime":1597090068412}"#,
+        )
+        .unwrap();
+
+        assert!(!data.stalled);
+        assert_eq!(data.duration, 0);
+        assert_eq!(data.total_requests, 150_000);
+    }
+
+    #[test]
+    fn it_can_summarize_socket_errors() {
+        let mut summary = SocketErrorSummary::default();
+        let errors = SocketErrors {
+            connect: 0,
+            read: 1,
+            write: 0,
+            timeout: 40,
+        };
+        summary.record("gemini", "json", Some(&errors));
+        summary.record("gemini", "json", Some(&errors));
+        summary.record("actix", "json", None);
+        summary.record("actix", "db", None);
+        assert_eq!(summary.timeout, 80);
+        assert_eq!(summary.commands_with_errors, 2);
+        assert_eq!(summary.tests["json"], vec!["gemini".to_string()]);
+        assert!(!summary.is_widespread());
+
+        summary.record("actix", "db", Some(&errors));
+        assert!(summary.is_widespread());
+    }
+
+    #[test]
+    fn it_can_build_tc_commands() {
+        let delay = NetworkShaping {
+            delay_ms: 5,
+            jitter_ms: 0,
+            loss_percent: 0f32,
+        };
+        let lossy = NetworkShaping {
+            delay_ms: 20,
+            jitter_ms: 2,
+            loss_percent: 0.5,
+        };
+
+        assert_eq!(
+            delay.to_tc_command().join(" "),
+            "tc qdisc replace dev eth0 root netem delay 5ms"
+        );
+        assert_eq!(
+            lossy.to_tc_command().join(" "),
+            "tc qdisc replace dev eth0 root netem delay 20ms 2ms loss 0.5%"
+        );
+    }
+
+    #[test]
+    fn it_can_measure_drift_from_the_first_baseline() {
+        let calibration = |requests_per_second| Calibration {
+            requests_per_second,
+            ..Calibration::default()
+        };
+        let first = Baseline::new(0, calibration(200_000f32), None);
+        let later = Baseline::new(10, calibration(180_000f32), Some(&first));
+        assert_eq!(first.drift_percent, 0f32);
+        assert_eq!(later.tests_run, 10);
+        assert!((later.drift_percent + 10f32).abs() < 0.001);
+    }
+}
@@ -0,0 +1,168 @@
+//! Restricts when benchmarks may run, for labs sharing hardware with other
+//! workloads; a run may be queued to start at a given time and only benchmark
+//! during daily windows (e.g. off-peak hours), pausing between tests outside
+//! of them.
+
+use crate::error::ToolsetError::{InvalidScheduleTimeError, InvalidScheduleWindowError};
+use crate::error::ToolsetResult;
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime};
+
+/// When benchmarks may run, in local time.
+#[derive(Debug, Clone, Default)]
+pub struct Schedule {
+    pub start_at: Option<NaiveDateTime>,
+    pub windows: Vec<ScheduleWindow>,
+}
+impl Schedule {
+    /// Gets how long to wait from `now` until benchmarks may run; zero if they
+    /// may run now.
+    pub fn get_wait(&self, now: NaiveDateTime) -> Duration {
+        let start = match self.start_at {
+            Some(start_at) if start_at > now => start_at,
+            _ => now,
+        };
+        let time = start.time();
+        if self.windows.is_empty() || self.windows.iter().any(|window| window.contains(time)) {
+            return start - now;
+        }
+        let until_window = self
+            .windows
+            .iter()
+            .map(|window| {
+                let until = window.start - time;
+                if until < Duration::zero() {
+                    until + Duration::days(1)
+                } else {
+                    until
+                }
+            })
+            .min()
+            .unwrap_or_else(Duration::zero);
+
+        start - now + until_window
+    }
+}
+
+/// A daily window, which ends the next day if it ends before it starts (e.g.
+/// `22:00-06:00`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScheduleWindow {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+impl ScheduleWindow {
+    /// Returns whether the given `time` of day is within this window.
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+/// Parses the `--start-at` time; either a date and time (e.g.
+/// `2021-01-30 22:00`), or a time of day, which is its next occurrence after
+/// `now`.
+pub fn parse_start_at(start_at: &str, now: NaiveDateTime) -> ToolsetResult<NaiveDateTime> {
+    let start_at = start_at.trim().replace('T', " ");
+    for format in &["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"] {
+        if let Ok(start_at) = NaiveDateTime::parse_from_str(&start_at, format) {
+            return Ok(start_at);
+        }
+    }
+    let time = parse_time(&start_at)?;
+    let today: NaiveDate = now.date();
+    if today.and_time(time) > now {
+        Ok(today.and_time(time))
+    } else {
+        Ok(today.and_time(time) + Duration::days(1))
+    }
+}
+
+/// Parses a `--schedule-window` given as `HH:MM-HH:MM`.
+pub fn parse_window(window: &str) -> ToolsetResult<ScheduleWindow> {
+    match window.split_once('-') {
+        Some((start, end)) => Ok(ScheduleWindow {
+            start: parse_time(start)?,
+            end: parse_time(end)?,
+        }),
+        None => Err(InvalidScheduleWindowError(window.to_string())),
+    }
+}
+
+//
+// PRIVATES
+//
+
+fn parse_time(time: &str) -> ToolsetResult<NaiveTime> {
+    NaiveTime::parse_from_str(time.trim(), "%H:%M")
+        .or_else(|_| NaiveTime::parse_from_str(time.trim(), "%H:%M:%S"))
+        .map_err(|_| InvalidScheduleTimeError(time.to_string()))
+}
+
+//
+// TESTS
+//
+
+#[cfg(test)]
+mod tests {
+    use crate::error::ToolsetError::{InvalidScheduleTimeError, InvalidScheduleWindowError};
+    use crate::schedule::{parse_start_at, parse_window, Schedule};
+    use chrono::{Duration, NaiveDate};
+
+    #[test]
+    fn it_can_wait_for_the_schedule() {
+        let now = NaiveDate::from_ymd_opt(2021, 1, 30)
+            .and_then(|date| date.and_hms_opt(12, 0, 0))
+            .unwrap();
+        let mut schedule = Schedule::default();
+        assert_eq!(schedule.get_wait(now), Duration::zero());
+
+        schedule.windows = vec![
+            parse_window("22:00-06:00").unwrap(),
+            parse_window("13:00-13:30").unwrap(),
+        ];
+        assert_eq!(schedule.get_wait(now), Duration::hours(1));
+        assert_eq!(
+            schedule.get_wait(now + Duration::hours(1)),
+            Duration::zero()
+        );
+        assert_eq!(
+            schedule.get_wait(now + Duration::minutes(90)),
+            Duration::minutes(510)
+        );
+        assert_eq!(
+            schedule.get_wait(now + Duration::hours(15)),
+            Duration::zero()
+        );
+
+        schedule.start_at = Some(parse_start_at("2021-01-31T02:00", now).unwrap());
+        assert_eq!(schedule.get_wait(now), Duration::hours(14));
+        schedule.start_at = Some(parse_start_at("07:00", now).unwrap());
+        assert_eq!(
+            schedule.start_at,
+            NaiveDate::from_ymd_opt(2021, 1, 31).and_then(|date| date.and_hms_opt(7, 0, 0))
+        );
+        assert_eq!(schedule.get_wait(now), Duration::hours(25));
+    }
+
+    #[test]
+    fn it_cannot_parse_malformed_schedules() {
+        let now = NaiveDate::from_ymd_opt(2021, 1, 30)
+            .and_then(|date| date.and_hms_opt(12, 0, 0))
+            .unwrap();
+        assert!(matches!(
+            parse_start_at("7pm", now),
+            Err(InvalidScheduleTimeError(_))
+        ));
+        assert!(matches!(
+            parse_window("22:00"),
+            Err(InvalidScheduleWindowError(_))
+        ));
+        assert!(matches!(
+            parse_window("22:00-25:00"),
+            Err(InvalidScheduleTimeError(_))
+        ));
+    }
+}
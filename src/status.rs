@@ -0,0 +1,343 @@
+//! The status module tracks the progress of a run in the `status.json` of its
+//! results directory, which is rewritten as the run moves from test to test
+//! and phase to phase, so that a headless run can be checked on without
+//! parsing its logs (see `--status`).
+
+use crate::config::{Named, Test};
+use crate::error::ToolsetError::NoRunStatusError;
+use crate::error::ToolsetResult;
+use crate::io::Logger;
+use chrono::{TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Minutes without an update after which an unfinished run is reported as
+/// possibly no longer running.
+const STALE_MINUTES: u128 = 30;
+
+/// What a run is doing at the moment.
+pub mod phases {
+    pub const PREPARING: &str = "preparing";
+    pub const STARTING: &str = "starting";
+    pub const VERIFYING: &str = "verifying";
+    pub const BENCHMARKING: &str = "benchmarking";
+    pub const TUNING: &str = "tuning";
+    pub const FINISHED: &str = "finished";
+}
+
+/// The progress of a run, overall and through its current test.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Status {
+    pub mode: String,
+    /// One of `phases`.
+    pub phase: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub test: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub test_type: Option<String>,
+    pub tests_completed: usize,
+    pub tests_total: usize,
+    /// Of the current test.
+    pub test_types_completed: usize,
+    pub test_types_total: usize,
+    /// Each test type that failed so far, as `<test>/<type>`, or, if the test
+    /// failed to start, as `<test>`.
+    pub failures: Vec<String>,
+    pub started_at: u128,
+    pub updated_at: u128,
+    /// When the run is expected to finish, from the average time taken by
+    /// each test so far; unknown until a test has completed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub eta: Option<u128>,
+}
+impl Status {
+    pub fn new(mode: &str) -> Self {
+        let now = get_timestamp();
+
+        Self {
+            mode: mode.to_string(),
+            phase: phases::PREPARING.to_string(),
+            started_at: now,
+            updated_at: now,
+            ..Self::default()
+        }
+    }
+
+    /// Moves on to starting the given `test`.
+    pub fn start_test(&mut self, test: &Test) {
+        self.set_phase(phases::STARTING, None);
+        self.test = Some(test.get_name());
+        self.test_types_completed = 0;
+        self.test_types_total = test.urls.len();
+    }
+
+    /// Moves on to the given `phase` of `test_type`, if any, of the current
+    /// test.
+    pub fn set_phase(&mut self, phase: &str, test_type: Option<&str>) {
+        self.phase = phase.to_string();
+        self.test_type = test_type.map(|test_type| test_type.to_string());
+        self.updated_at = get_timestamp();
+    }
+
+    /// Records that the current test type completed, and whether it `failed`.
+    pub fn complete_test_type(&mut self, failed: bool) {
+        if failed {
+            if let (Some(test), Some(test_type)) = (&self.test, &self.test_type) {
+                self.failures.push(format!("{}/{}", test, test_type));
+            }
+        }
+        self.test_types_completed += 1;
+        self.updated_at = get_timestamp();
+    }
+
+    /// Records that the current test failed to start.
+    pub fn fail_test(&mut self) {
+        if let Some(test) = &self.test {
+            self.failures.push(test.clone());
+        }
+        self.updated_at = get_timestamp();
+    }
+
+    /// Records that the current test completed, and updates the `eta`.
+    pub fn complete_test(&mut self) {
+        self.test = None;
+        self.test_type = None;
+        self.tests_completed += 1;
+        self.updated_at = get_timestamp();
+        let elapsed = self.updated_at.saturating_sub(self.started_at);
+        self.eta = Some(
+            self.started_at
+                + elapsed * self.tests_total.max(1) as u128 / self.tests_completed as u128,
+        );
+    }
+
+    /// Records that the run finished.
+    pub fn finish(&mut self) {
+        self.set_phase(phases::FINISHED, None);
+        self.test = None;
+        self.eta = None;
+    }
+}
+
+/// Logs the progress, or the outcome, of the run whose results are in
+/// `results_dir`, or else of the latest run in `results`.
+pub fn run(results_dir: Option<&Path>) -> ToolsetResult<()> {
+    let logger = Logger::default();
+    let results_dir = match results_dir {
+        Some(results_dir) => results_dir.to_path_buf(),
+        None => get_latest_results_dir(Path::new("results"))?,
+    };
+    let status = std::fs::read_to_string(results_dir.join("status.json"))
+        .map_err(|_| NoRunStatusError(results_dir.to_string_lossy().to_string()))?;
+    let status: Status = serde_json::from_str(&status)?;
+    // The manifest, if any, gives the command line of the run.
+    let invocation = std::fs::read_to_string(results_dir.join("manifest.json"))
+        .ok()
+        .and_then(|manifest| serde_json::from_str::<Value>(&manifest).ok())
+        .and_then(|manifest| {
+            let invocation = manifest["invocation"].as_array()?.iter();
+            Some(
+                invocation
+                    .filter_map(|arg| arg.as_str())
+                    .collect::<Vec<&str>>()
+                    .join(" "),
+            )
+        });
+
+    logger.log(format!("Run in {}", results_dir.to_string_lossy()))?;
+    if let Some(invocation) = invocation {
+        logger.log(format!("Invoked as: {}", invocation))?;
+    }
+    for line in get_report(&status, get_timestamp()) {
+        logger.log(line)?;
+    }
+
+    Ok(())
+}
+
+//
+// PRIVATES
+//
+
+/// Gets the latest of the results directories in `results` which has a
+/// `status.json`; their names are timestamps, so the greatest is the latest.
+fn get_latest_results_dir(results: &Path) -> ToolsetResult<PathBuf> {
+    let no_run = || NoRunStatusError(results.to_string_lossy().to_string());
+    let mut results_dirs: Vec<PathBuf> = std::fs::read_dir(results)
+        .map_err(|_| no_run())?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.join("status.json").is_file())
+        .collect();
+    results_dirs.sort();
+
+    results_dirs.pop().ok_or_else(no_run)
+}
+
+/// Gets the report of the given `status` as of `now`, line by line.
+fn get_report(status: &Status, now: u128) -> Vec<String> {
+    let format_time = |millis: u128| {
+        Utc.timestamp_millis_opt(millis as i64)
+            .single()
+            .map(|time| time.format("%Y-%m-%d %H:%M UTC").to_string())
+            .unwrap_or_default()
+    };
+    let mut report = vec![format!(
+        "{} test(s) in {} mode, started {}",
+        status.tests_total,
+        status.mode,
+        format_time(status.started_at)
+    )];
+    if status.phase == phases::FINISHED {
+        report.push(format!(
+            "Finished {} of {} test(s) at {}",
+            status.tests_completed,
+            status.tests_total,
+            format_time(status.updated_at)
+        ));
+    } else {
+        let mut current = status.phase.clone();
+        if let Some(test) = &status.test {
+            current.push_str(&format!(" {}", test));
+            if let Some(test_type) = &status.test_type {
+                current.push_str(&format!(" ({})", test_type));
+            }
+            current.push_str(&format!(
+                ", test {} of {}, {} of {} test type(s) done",
+                status.tests_completed + 1,
+                status.tests_total,
+                status.test_types_completed,
+                status.test_types_total
+            ));
+        } else {
+            current.push_str(&format!(
+                ", {} of {} test(s) done",
+                status.tests_completed, status.tests_total
+            ));
+        }
+        report.push(current);
+        if let Some(eta) = status.eta {
+            report.push(format!("Expected to finish at {}", format_time(eta)));
+        }
+        let minutes_since_update = now.saturating_sub(status.updated_at) / 60_000;
+        if minutes_since_update >= STALE_MINUTES {
+            report.push(format!(
+                "Not updated for {} minutes; the run may have stopped",
+                minutes_since_update
+            ));
+        }
+    }
+    if status.failures.is_empty() {
+        report.push("No failures".to_string());
+    } else {
+        report.push(format!(
+            "{} failure(s): {}",
+            status.failures.len(),
+            status.failures.join(", ")
+        ));
+    }
+
+    report
+}
+
+/// Gets the current time in milliseconds since the epoch.
+fn get_timestamp() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis()
+}
+
+//
+// TESTS
+//
+
+#[cfg(test)]
+mod tests {
+    use crate::io::TempDir;
+    use crate::metadata::list_projects_by_test_name;
+    use crate::status::{get_latest_results_dir, get_report, phases, Status};
+
+    #[test]
+    fn it_can_track_the_progress_of_a_run() {
+        let projects = list_projects_by_test_name(Some("gemini-mysql".to_string()), &[]).unwrap();
+        let test = &projects[0].tests[0];
+        let mut status = Status::new("benchmark");
+        status.tests_total = 2;
+
+        status.start_test(test);
+        assert_eq!(status.phase, phases::STARTING);
+        assert_eq!(status.test.as_deref(), Some("gemini-mysql"));
+        assert_eq!(status.test_types_total, 5);
+        status.set_phase(phases::BENCHMARKING, Some("db"));
+        status.complete_test_type(true);
+        status.set_phase(phases::BENCHMARKING, Some("query"));
+        status.complete_test_type(false);
+        assert_eq!(status.test_types_completed, 2);
+        assert_eq!(status.failures, vec!["gemini-mysql/db"]);
+        assert!(status.eta.is_none());
+
+        status.complete_test();
+        assert_eq!(status.tests_completed, 1);
+        assert!(status.test.is_none());
+        assert!(status.eta.unwrap() >= status.updated_at);
+
+        status.finish();
+        assert_eq!(status.phase, phases::FINISHED);
+        assert!(status.eta.is_none());
+    }
+
+    #[test]
+    fn it_can_report_the_progress_of_a_run() {
+        let status = Status {
+            mode: "benchmark".to_string(),
+            phase: phases::BENCHMARKING.to_string(),
+            test: Some("gemini-mysql".to_string()),
+            test_type: Some("query".to_string()),
+            tests_completed: 1,
+            tests_total: 3,
+            test_types_completed: 1,
+            test_types_total: 5,
+            failures: vec!["actix/db".to_string()],
+            started_at: 1_600_000_000_000,
+            updated_at: 1_600_000_600_000,
+            eta: Some(1_600_001_800_000),
+        };
+
+        assert_eq!(
+            get_report(&status, 1_600_000_660_000),
+            vec![
+                "3 test(s) in benchmark mode, started 2020-09-13 12:26 UTC",
+                "benchmarking gemini-mysql (query), test 2 of 3, 1 of 5 test type(s) done",
+                "Expected to finish at 2020-09-13 12:56 UTC",
+                "1 failure(s): actix/db",
+            ]
+        );
+        assert_eq!(
+            get_report(&status, 1_600_002_600_000)[3],
+            "Not updated for 33 minutes; the run may have stopped"
+        );
+    }
+
+    #[test]
+    fn it_can_find_the_latest_run() {
+        let results = TempDir::new("status");
+        for (run, has_status) in &[
+            ("20200101000000", true),
+            ("20200102000000", true),
+            ("20200103000000", false),
+        ] {
+            std::fs::create_dir_all(results.join(run)).unwrap();
+            if *has_status {
+                std::fs::write(results.join(run).join("status.json"), "{}").unwrap();
+            }
+        }
+
+        let latest = get_latest_results_dir(&results);
+
+        assert_eq!(latest.unwrap(), results.join("20200102000000"));
+    }
+}
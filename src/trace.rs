@@ -0,0 +1,293 @@
+use crate::docker::listener::simple::Simple;
+use crate::error::ToolsetError::TraceExportError;
+use crate::error::ToolsetResult;
+use curl::easy::{Easy2, List};
+use rand::Rng;
+use serde::Serialize;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Collects the spans of a run - the root span - and its phases, and exports
+/// them as an OTLP trace when the run completes.
+///
+/// Spans are nested by the order in which they are started; a span started
+/// while another is open is its child. Nothing is recorded unless an OTLP
+/// endpoint was given.
+#[derive(Debug)]
+pub struct Tracer {
+    endpoint: Option<String>,
+    trace_id: String,
+    open_spans: Vec<Span>,
+    spans: Vec<Span>,
+}
+impl Tracer {
+    /// Creates a `Tracer` exporting to the OTLP/HTTP collector at `endpoint`
+    /// (e.g. `http://localhost:4318`) and starts the root span `name`.
+    pub fn new(endpoint: Option<&str>, name: &str) -> Self {
+        let mut tracer = Self {
+            endpoint: endpoint.map(|endpoint| endpoint.trim_end_matches('/').to_string()),
+            trace_id: get_random_id(16),
+            open_spans: Vec::default(),
+            spans: Vec::default(),
+        };
+        tracer.start_span(name);
+
+        tracer
+    }
+
+    /// Starts a span `name` as a child of the innermost open span.
+    pub fn start_span(&mut self, name: &str) {
+        if self.endpoint.is_none() {
+            return;
+        }
+        let parent_span_id = match self.open_spans.last() {
+            Some(parent) => parent.span_id.clone(),
+            None => String::default(),
+        };
+        self.open_spans.push(Span {
+            trace_id: self.trace_id.clone(),
+            span_id: get_random_id(8),
+            parent_span_id,
+            name: name.to_string(),
+            kind: 1,
+            start_time_unix_nano: get_unix_nanos(),
+            end_time_unix_nano: String::default(),
+            attributes: Vec::default(),
+            status: Status::default(),
+        });
+    }
+
+    /// Sets the attribute `key` on the innermost open span.
+    pub fn set_attribute<T: Into<AttributeValue>>(&mut self, key: &str, value: T) {
+        if let Some(span) = self.open_spans.last_mut() {
+            span.attributes.push(Attribute {
+                key: key.to_string(),
+                value: value.into(),
+            });
+        }
+    }
+
+    /// Ends the innermost open span.
+    pub fn end_span(&mut self) {
+        // The root span is only ended by `export`.
+        if self.open_spans.len() > 1 {
+            self.close_span();
+        }
+    }
+
+    /// Ends the innermost open span, marking it as failed if `result` is an
+    /// error.
+    pub fn end_span_with<T>(&mut self, result: &ToolsetResult<T>) {
+        if let (Err(error), Some(span)) = (result, self.open_spans.last_mut()) {
+            span.status = Status {
+                code: 2,
+                message: error.to_string(),
+            };
+        }
+        self.end_span();
+    }
+
+    /// Ends all open spans, including the root span, and sends the trace to
+    /// the OTLP endpoint.
+    pub fn export(&mut self) -> ToolsetResult<()> {
+        while !self.open_spans.is_empty() {
+            self.close_span();
+        }
+        if let Some(endpoint) = &self.endpoint {
+            let body = serde_json::to_vec(&self.get_trace())?;
+
+            let mut headers = List::new();
+            headers.append("Content-Type: application/json")?;
+            let mut easy = Easy2::new(Simple::new());
+            easy.url(&format!("{}/v1/traces", endpoint))?;
+            easy.http_headers(headers)?;
+            easy.post(true)?;
+            easy.post_fields_copy(&body)?;
+            easy.perform()?;
+
+            let code = easy.response_code()?;
+            if !(200..300).contains(&code) {
+                return Err(TraceExportError(endpoint.clone(), code));
+            }
+        }
+        self.spans.clear();
+
+        Ok(())
+    }
+}
+
+/// The value of a span attribute.
+#[derive(Debug, Serialize)]
+pub enum AttributeValue {
+    #[serde(rename = "stringValue")]
+    String(String),
+    #[serde(rename = "doubleValue")]
+    Double(f64),
+    // OTLP/JSON encodes 64-bit integers as strings.
+    #[serde(rename = "intValue")]
+    Int(String),
+}
+impl From<&str> for AttributeValue {
+    fn from(value: &str) -> Self {
+        AttributeValue::String(value.to_string())
+    }
+}
+impl From<String> for AttributeValue {
+    fn from(value: String) -> Self {
+        AttributeValue::String(value)
+    }
+}
+impl From<f32> for AttributeValue {
+    fn from(value: f32) -> Self {
+        AttributeValue::Double(value as f64)
+    }
+}
+impl From<u32> for AttributeValue {
+    fn from(value: u32) -> Self {
+        AttributeValue::Int(value.to_string())
+    }
+}
+
+//
+// PRIVATES
+//
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Trace<'a> {
+    resource_spans: Vec<ResourceSpans<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ResourceSpans<'a> {
+    resource: Resource,
+    scope_spans: Vec<ScopeSpans<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct Resource {
+    attributes: Vec<Attribute>,
+}
+
+#[derive(Debug, Serialize)]
+struct ScopeSpans<'a> {
+    scope: Scope,
+    spans: &'a [Span],
+}
+
+#[derive(Debug, Serialize)]
+struct Scope {
+    name: String,
+    version: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Span {
+    trace_id: String,
+    span_id: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    parent_span_id: String,
+    name: String,
+    kind: u8,
+    start_time_unix_nano: String,
+    end_time_unix_nano: String,
+    attributes: Vec<Attribute>,
+    status: Status,
+}
+
+#[derive(Debug, Serialize)]
+struct Attribute {
+    key: String,
+    value: AttributeValue,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct Status {
+    code: u8,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    message: String,
+}
+
+impl Tracer {
+    fn close_span(&mut self) {
+        if let Some(mut span) = self.open_spans.pop() {
+            span.end_time_unix_nano = get_unix_nanos();
+            self.spans.push(span);
+        }
+    }
+
+    fn get_trace(&self) -> Trace<'_> {
+        Trace {
+            resource_spans: vec![ResourceSpans {
+                resource: Resource {
+                    attributes: vec![Attribute {
+                        key: "service.name".to_string(),
+                        value: "tfb_toolset".into(),
+                    }],
+                },
+                scope_spans: vec![ScopeSpans {
+                    scope: Scope {
+                        name: env!("CARGO_PKG_NAME").to_string(),
+                        version: env!("CARGO_PKG_VERSION").to_string(),
+                    },
+                    spans: &self.spans,
+                }],
+            }],
+        }
+    }
+}
+
+/// Gets a random, hex-encoded id of `length` bytes.
+fn get_random_id(length: usize) -> String {
+    let mut rng = rand::thread_rng();
+    (0..length)
+        .map(|_| format!("{:02x}", rng.gen::<u8>()))
+        .collect()
+}
+
+fn get_unix_nanos() -> String {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos()
+        .to_string()
+}
+
+//
+// TESTS
+//
+
+#[cfg(test)]
+mod tests {
+    use crate::trace::Tracer;
+    use serde_json::Value;
+
+    #[test]
+    fn it_can_nest_spans() {
+        let mut tracer = Tracer::new(Some("http://localhost:4318/"), "run");
+        tracer.start_span("test");
+        tracer.set_attribute("framework", "gemini");
+        tracer.start_span("benchmark");
+        tracer.set_attribute("rps", 1234.5f32);
+        tracer.end_span_with::<()>(&Err(crate::error::ToolsetError::BenchmarkDataParseError));
+        tracer.end_span();
+        while !tracer.open_spans.is_empty() {
+            tracer.close_span();
+        }
+
+        let trace = serde_json::to_value(tracer.get_trace()).unwrap();
+        let spans = &trace["resourceSpans"][0]["scopeSpans"][0]["spans"];
+        let span_id = |index: usize| spans[index]["spanId"].clone();
+        assert_eq!(spans[0]["name"], "benchmark");
+        assert_eq!(spans[0]["parentSpanId"], span_id(1));
+        assert_eq!(spans[0]["status"]["code"], 2);
+        assert_eq!(spans[0]["attributes"][0]["value"]["doubleValue"], 1234.5);
+        assert_eq!(spans[1]["parentSpanId"], span_id(2));
+        assert_eq!(spans[1]["attributes"][0]["value"]["stringValue"], "gemini");
+        assert_eq!(spans[2]["name"], "run");
+        assert_eq!(spans[2]["parentSpanId"], Value::Null);
+        assert_eq!(spans[2]["traceId"].as_str().unwrap().len(), 32);
+        assert_eq!(tracer.endpoint.as_deref(), Some("http://localhost:4318"));
+    }
+}